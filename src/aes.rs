@@ -0,0 +1,478 @@
+//! AES block cipher [Specification][1], with 128, 192 and 256 bits keys.
+//!
+//! Only the raw block cipher is provided, for the constructions built on top
+//! of it in this crate (e.g. the `keywrap` module, with its own feature). It
+//! should not be used directly to encrypt data, as encrypting each block
+//! independently (ECB mode) leaks the equality of blocks.
+//!
+//! The implementation doesn't use any lookup table: the S-box is computed
+//! on the bit planes of the state with the Boyar-Peralta circuit, so that
+//! the execution time and the memory accesses don't depend on the key or the data.
+//!
+//! # Examples
+//!
+//! ```
+//! use cryptoxide::aes::Aes;
+//!
+//! let aes = Aes::new(&[0u8; 16]);
+//! let mut block = [0u8; 16];
+//! aes.encrypt_block(&mut block);
+//! aes.decrypt_block(&mut block);
+//! assert_eq!(block, [0u8; 16]);
+//! ```
+//!
+//! [1]: <https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.197-upd1.pdf>
+
+use crate::util::secure_memset;
+
+/// Size of the AES block in bytes
+pub const BLOCK_LENGTH: usize = 16;
+
+const MAX_ROUNDS: usize = 14;
+
+/// AES cipher with its expanded key
+#[derive(Clone)]
+pub struct Aes {
+    round_keys: [[u8; BLOCK_LENGTH]; MAX_ROUNDS + 1],
+    rounds: usize,
+}
+
+impl Drop for Aes {
+    fn drop(&mut self) {
+        for rk in self.round_keys.iter_mut() {
+            secure_memset(rk, 0);
+        }
+    }
+}
+
+impl Aes {
+    /// Expand the key of an AES-128, AES-192 or AES-256 cipher
+    ///
+    /// The key needs to be 16, 24 or 32 bytes long.
+    #[cfg_attr(feature = "no-panic", deprecated(note = "use try_new"))]
+    pub fn new(key: &[u8]) -> Self {
+        Self::try_new(key).expect("AES key of 16, 24 or 32 bytes")
+    }
+
+    /// Expand the key of an AES cipher, or return `None` if the key is
+    /// not 16, 24 or 32 bytes long
+    pub fn try_new(key: &[u8]) -> Option<Self> {
+        let nk = match key.len() {
+            16 | 24 | 32 => key.len() / 4,
+            _ => return None,
+        };
+        let rounds = nk + 6;
+
+        let mut w = [[0u8; 4]; 4 * (MAX_ROUNDS + 1)];
+        for (i, word) in key.chunks_exact(4).enumerate() {
+            w[i].copy_from_slice(word);
+        }
+        let mut rcon = 1u8;
+        for i in nk..4 * (rounds + 1) {
+            let mut t = w[i - 1];
+            if i % nk == 0 {
+                t = sub_word([t[1], t[2], t[3], t[0]]);
+                t[0] ^= rcon;
+                rcon = xtime(rcon);
+            } else if nk > 6 && i % nk == 4 {
+                t = sub_word(t);
+            }
+            for j in 0..4 {
+                w[i][j] = w[i - nk][j] ^ t[j];
+            }
+        }
+
+        let mut round_keys = [[0u8; BLOCK_LENGTH]; MAX_ROUNDS + 1];
+        for (i, rk) in round_keys.iter_mut().take(rounds + 1).enumerate() {
+            for c in 0..4 {
+                rk[4 * c..4 * c + 4].copy_from_slice(&w[4 * i + c]);
+            }
+        }
+        for word in w.iter_mut() {
+            secure_memset(word, 0);
+        }
+        Some(Aes { round_keys, rounds })
+    }
+
+    /// Size of the key in bytes
+    pub fn key_length(&self) -> usize {
+        4 * (self.rounds - 6)
+    }
+
+    /// Encrypt a block in place
+    pub fn encrypt_block(&self, block: &mut [u8; BLOCK_LENGTH]) {
+        add_round_key(block, &self.round_keys[0]);
+        for round in 1..self.rounds {
+            sub_bytes(block);
+            shift_rows(block);
+            mix_columns(block);
+            add_round_key(block, &self.round_keys[round]);
+        }
+        sub_bytes(block);
+        shift_rows(block);
+        add_round_key(block, &self.round_keys[self.rounds]);
+    }
+
+    /// Decrypt a block in place
+    pub fn decrypt_block(&self, block: &mut [u8; BLOCK_LENGTH]) {
+        add_round_key(block, &self.round_keys[self.rounds]);
+        for round in (1..self.rounds).rev() {
+            inv_shift_rows(block);
+            inv_sub_bytes(block);
+            add_round_key(block, &self.round_keys[round]);
+            inv_mix_columns(block);
+        }
+        inv_shift_rows(block);
+        inv_sub_bytes(block);
+        add_round_key(block, &self.round_keys[0]);
+    }
+}
+
+fn add_round_key(block: &mut [u8; BLOCK_LENGTH], rk: &[u8; BLOCK_LENGTH]) {
+    for (b, k) in block.iter_mut().zip(rk.iter()) {
+        *b ^= k;
+    }
+}
+
+// multiplication by x in GF(2^8), without branch
+#[inline]
+fn xtime(b: u8) -> u8 {
+    (b << 1) ^ (0x1b & 0u8.wrapping_sub(b >> 7))
+}
+
+// the state is stored column by column: byte `r` of column `c` is at `4 * c + r`
+fn shift_rows(s: &mut [u8; BLOCK_LENGTH]) {
+    let t = *s;
+    for c in 0..4 {
+        for r in 1..4 {
+            s[4 * c + r] = t[4 * ((c + r) % 4) + r];
+        }
+    }
+}
+
+fn inv_shift_rows(s: &mut [u8; BLOCK_LENGTH]) {
+    let t = *s;
+    for c in 0..4 {
+        for r in 1..4 {
+            s[4 * ((c + r) % 4) + r] = t[4 * c + r];
+        }
+    }
+}
+
+fn mix_columns(s: &mut [u8; BLOCK_LENGTH]) {
+    for col in s.chunks_exact_mut(4) {
+        let all = col[0] ^ col[1] ^ col[2] ^ col[3];
+        let c0 = col[0];
+        col[0] ^= all ^ xtime(col[0] ^ col[1]);
+        col[1] ^= all ^ xtime(col[1] ^ col[2]);
+        col[2] ^= all ^ xtime(col[2] ^ col[3]);
+        col[3] ^= all ^ xtime(col[3] ^ c0);
+    }
+}
+
+// InvMixColumns is MixColumns preceded by a multiplication by {04}x^2 + {05}
+fn inv_mix_columns(s: &mut [u8; BLOCK_LENGTH]) {
+    for col in s.chunks_exact_mut(4) {
+        let u = xtime(xtime(col[0] ^ col[2]));
+        let v = xtime(xtime(col[1] ^ col[3]));
+        col[0] ^= u;
+        col[1] ^= v;
+        col[2] ^= u;
+        col[3] ^= v;
+    }
+    mix_columns(s);
+}
+
+fn sub_word(w: [u8; 4]) -> [u8; 4] {
+    let mut block = [0u8; BLOCK_LENGTH];
+    block[0..4].copy_from_slice(&w);
+    sub_bytes(&mut block);
+    [block[0], block[1], block[2], block[3]]
+}
+
+fn sub_bytes(s: &mut [u8; BLOCK_LENGTH]) {
+    let mut q = to_planes(s);
+    sbox_planes(&mut q);
+    from_planes(&q, s);
+}
+
+fn inv_sub_bytes(s: &mut [u8; BLOCK_LENGTH]) {
+    let mut q = to_planes(s);
+    inv_affine_planes(&mut q);
+    sbox_planes(&mut q);
+    inv_affine_planes(&mut q);
+    from_planes(&q, s);
+}
+
+// plane `i` holds the bit `i` of every byte of the state, byte `j` at bit `j`
+fn to_planes(s: &[u8; BLOCK_LENGTH]) -> [u16; 8] {
+    let mut q = [0u16; 8];
+    for (i, plane) in q.iter_mut().enumerate() {
+        for (j, b) in s.iter().enumerate() {
+            *plane |= (((*b >> i) & 1) as u16) << j;
+        }
+    }
+    q
+}
+
+fn from_planes(q: &[u16; 8], s: &mut [u8; BLOCK_LENGTH]) {
+    for (j, b) in s.iter_mut().enumerate() {
+        let mut v = 0u8;
+        for (i, plane) in q.iter().enumerate() {
+            v |= (((*plane >> j) & 1) as u8) << i;
+        }
+        *b = v;
+    }
+}
+
+// Inverse of the S-box affine transformation, including the constant: its
+// output on `S(x)` is the inverse of `x` in GF(2^8). As the S-box is the
+// affine transformation of the inverse, the inverse S-box is this
+// transformation, then the S-box, then this transformation again.
+fn inv_affine_planes(q: &mut [u16; 8]) {
+    let y = *q;
+    for i in 0..8 {
+        q[i] = y[(i + 2) % 8] ^ y[(i + 5) % 8] ^ y[(i + 7) % 8];
+    }
+    q[0] = !q[0];
+    q[2] = !q[2];
+}
+
+// S-box circuit of 113 gates from Boyar and Peralta, "A depth-16 circuit for the AES S-box"
+fn sbox_planes(q: &mut [u16; 8]) {
+    let x0 = q[7];
+    let x1 = q[6];
+    let x2 = q[5];
+    let x3 = q[4];
+    let x4 = q[3];
+    let x5 = q[2];
+    let x6 = q[1];
+    let x7 = q[0];
+
+    // top linear transformation
+    let y14 = x3 ^ x5;
+    let y13 = x0 ^ x6;
+    let y9 = x0 ^ x3;
+    let y8 = x0 ^ x5;
+    let t0 = x1 ^ x2;
+    let y1 = t0 ^ x7;
+    let y4 = y1 ^ x3;
+    let y12 = y13 ^ y14;
+    let y2 = y1 ^ x0;
+    let y5 = y1 ^ x6;
+    let y3 = y5 ^ y8;
+    let t1 = x4 ^ y12;
+    let y15 = t1 ^ x5;
+    let y20 = t1 ^ x1;
+    let y6 = y15 ^ x7;
+    let y10 = y15 ^ t0;
+    let y11 = y20 ^ y9;
+    let y7 = x7 ^ y11;
+    let y17 = y10 ^ y11;
+    let y19 = y10 ^ y8;
+    let y16 = t0 ^ y11;
+    let y21 = y13 ^ y16;
+    let y18 = x0 ^ y16;
+
+    // non-linear section
+    let t2 = y12 & y15;
+    let t3 = y3 & y6;
+    let t4 = t3 ^ t2;
+    let t5 = y4 & x7;
+    let t6 = t5 ^ t2;
+    let t7 = y13 & y16;
+    let t8 = y5 & y1;
+    let t9 = t8 ^ t7;
+    let t10 = y2 & y7;
+    let t11 = t10 ^ t7;
+    let t12 = y9 & y11;
+    let t13 = y14 & y17;
+    let t14 = t13 ^ t12;
+    let t15 = y8 & y10;
+    let t16 = t15 ^ t12;
+    let t17 = t4 ^ t14;
+    let t18 = t6 ^ t16;
+    let t19 = t9 ^ t14;
+    let t20 = t11 ^ t16;
+    let t21 = t17 ^ y20;
+    let t22 = t18 ^ y19;
+    let t23 = t19 ^ y21;
+    let t24 = t20 ^ y18;
+
+    let t25 = t21 ^ t22;
+    let t26 = t21 & t23;
+    let t27 = t24 ^ t26;
+    let t28 = t25 & t27;
+    let t29 = t28 ^ t22;
+    let t30 = t23 ^ t24;
+    let t31 = t22 ^ t26;
+    let t32 = t31 & t30;
+    let t33 = t32 ^ t24;
+    let t34 = t23 ^ t33;
+    let t35 = t27 ^ t33;
+    let t36 = t24 & t35;
+    let t37 = t36 ^ t34;
+    let t38 = t27 ^ t36;
+    let t39 = t29 & t38;
+    let t40 = t25 ^ t39;
+
+    let t41 = t40 ^ t37;
+    let t42 = t29 ^ t33;
+    let t43 = t29 ^ t40;
+    let t44 = t33 ^ t37;
+    let t45 = t42 ^ t41;
+    let z0 = t44 & y15;
+    let z1 = t37 & y6;
+    let z2 = t33 & x7;
+    let z3 = t43 & y16;
+    let z4 = t40 & y1;
+    let z5 = t29 & y7;
+    let z6 = t42 & y11;
+    let z7 = t45 & y17;
+    let z8 = t41 & y10;
+    let z9 = t44 & y12;
+    let z10 = t37 & y3;
+    let z11 = t33 & y4;
+    let z12 = t43 & y13;
+    let z13 = t40 & y5;
+    let z14 = t29 & y2;
+    let z15 = t42 & y9;
+    let z16 = t45 & y14;
+    let z17 = t41 & y8;
+
+    // bottom linear transformation
+    let t46 = z15 ^ z16;
+    let t47 = z10 ^ z11;
+    let t48 = z5 ^ z13;
+    let t49 = z9 ^ z10;
+    let t50 = z2 ^ z12;
+    let t51 = z2 ^ z5;
+    let t52 = z7 ^ z8;
+    let t53 = z0 ^ z3;
+    let t54 = z6 ^ z7;
+    let t55 = z16 ^ z17;
+    let t56 = z12 ^ t48;
+    let t57 = t50 ^ t53;
+    let t58 = z4 ^ t46;
+    let t59 = z3 ^ t54;
+    let t60 = t46 ^ t57;
+    let t61 = z14 ^ t57;
+    let t62 = t52 ^ t58;
+    let t63 = t49 ^ t58;
+    let t64 = z4 ^ t59;
+    let t65 = t61 ^ t62;
+    let t66 = z1 ^ t63;
+    let s0 = t59 ^ t63;
+    let s6 = t56 ^ !t62;
+    let s7 = t48 ^ !t60;
+    let t67 = t64 ^ t65;
+    let s3 = t53 ^ t66;
+    let s4 = t51 ^ t66;
+    let s5 = t47 ^ t65;
+    let s1 = t64 ^ !s3;
+    let s2 = t55 ^ !t67;
+
+    q[7] = s0;
+    q[6] = s1;
+    q[5] = s2;
+    q[4] = s3;
+    q[3] = s4;
+    q[2] = s5;
+    q[1] = s6;
+    q[0] = s7;
+}
+
+#[cfg(test)]
+#[cfg_attr(feature = "no-panic", allow(deprecated))]
+mod tests {
+    use super::*;
+
+    fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+        let mut r = 0;
+        while b != 0 {
+            if b & 1 != 0 {
+                r ^= a;
+            }
+            a = xtime(a);
+            b >>= 1;
+        }
+        r
+    }
+
+    // S-box from its definition: inverse in GF(2^8) followed by the affine transformation
+    fn reference_sbox(x: u8) -> u8 {
+        let inv = (1..=255u8).find(|&y| gf_mul(x, y) == 1).unwrap_or(0);
+        inv ^ inv.rotate_left(1)
+            ^ inv.rotate_left(2)
+            ^ inv.rotate_left(3)
+            ^ inv.rotate_left(4)
+            ^ 0x63
+    }
+
+    #[test]
+    fn sbox_matches_definition() {
+        for chunk in 0..16u8 {
+            let mut block = [0u8; 16];
+            for (j, b) in block.iter_mut().enumerate() {
+                *b = chunk * 16 + j as u8;
+            }
+            let input = block;
+            sub_bytes(&mut block);
+            for j in 0..16 {
+                assert_eq!(block[j], reference_sbox(input[j]));
+            }
+            inv_sub_bytes(&mut block);
+            assert_eq!(block, input);
+        }
+    }
+
+    fn check(key: &[u8], plaintext: &[u8; 16], ciphertext: &[u8; 16]) {
+        let aes = Aes::new(key);
+        assert_eq!(aes.key_length(), key.len());
+        let mut block = *plaintext;
+        aes.encrypt_block(&mut block);
+        assert_eq!(&block, ciphertext);
+        aes.decrypt_block(&mut block);
+        assert_eq!(&block, plaintext);
+    }
+
+    #[test]
+    fn fips197_appendix_c() {
+        let plaintext = [
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+            0xee, 0xff,
+        ];
+        let key: [u8; 32] = core::array::from_fn(|i| i as u8);
+        check(
+            &key[0..16],
+            &plaintext,
+            &[
+                0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4,
+                0xc5, 0x5a,
+            ],
+        );
+        check(
+            &key[0..24],
+            &plaintext,
+            &[
+                0xdd, 0xa9, 0x7c, 0xa4, 0x86, 0x4c, 0xdf, 0xe0, 0x6e, 0xaf, 0x70, 0xa0, 0xec, 0x0d,
+                0x71, 0x91,
+            ],
+        );
+        check(
+            &key,
+            &plaintext,
+            &[
+                0x8e, 0xa2, 0xb7, 0xca, 0x51, 0x67, 0x45, 0xbf, 0xea, 0xfc, 0x49, 0x90, 0x4b, 0x49,
+                0x60, 0x89,
+            ],
+        );
+    }
+
+    #[test]
+    fn invalid_key_length() {
+        assert!(Aes::try_new(&[0u8; 15]).is_none());
+        assert!(Aes::try_new(&[0u8; 20]).is_none());
+        assert!(Aes::try_new(&[]).is_none());
+    }
+}
@@ -0,0 +1,350 @@
+//! AES-GCM-SIV nonce misuse-resistant authenticated encryption [RFC 8452][1]
+//!
+//! The tag is computed from the plaintext and the associated data, and is
+//! used as the initial counter of the encryption (a synthetic IV). Reusing a
+//! nonce only reveals whether the same plaintext was encrypted twice with the
+//! same associated data, unlike AES-GCM or ChaCha20-Poly1305 where it breaks
+//! the confidentiality and the authenticity. Random nonces are still
+//! preferable, as this is a safety net and not a replacement for them.
+//!
+//! Since the tag depends on the whole plaintext, the encryption is done in
+//! two passes and there is no incremental interface.
+//!
+//! * key needs to be 16 (AES-128-GCM-SIV) or 32 bytes (AES-256-GCM-SIV)
+//! * nonce needs to be 12 bytes
+//! * the plaintext and the associated data need to be at most 2^36 bytes each
+//!
+//! # Examples
+//!
+//! ```
+//! use cryptoxide::aesgcmsiv::AesGcmSiv;
+//!
+//! let key = [1u8; 32];
+//! let nonce = [2u8; 12];
+//! let cipher = AesGcmSiv::try_new(&key).unwrap();
+//!
+//! let mut ciphertext = [0u8; 11];
+//! let mut tag = [0u8; 16];
+//! cipher
+//!     .encrypt(&nonce, b"header", b"hello world", &mut ciphertext, &mut tag)
+//!     .unwrap();
+//!
+//! let mut plaintext = [0u8; 11];
+//! assert!(cipher.decrypt(&nonce, b"header", &ciphertext, &mut plaintext, &tag));
+//! assert_eq!(&plaintext, b"hello world");
+//! ```
+//!
+//! [1]: <https://datatracker.ietf.org/doc/html/rfc8452>
+
+use crate::aes::{Aes, BLOCK_LENGTH};
+use crate::ghash::Polyval;
+use crate::util::{fixed_time_eq, secure_memset};
+
+/// Size of the nonce in bytes
+pub const NONCE_LENGTH: usize = 12;
+
+/// Size of the tag in bytes
+pub const TAG_LENGTH: usize = 16;
+
+/// Maximum size in bytes of the plaintext and of the associated data, 2^36
+pub const MAX_INPUT_LENGTH: u64 = 1 << 36;
+
+/// Error returned by [`AesGcmSiv::encrypt`] when the plaintext or the
+/// associated data is longer than [`MAX_INPUT_LENGTH`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputTooLong;
+
+impl core::fmt::Display for InputTooLong {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "input longer than 2^36 bytes")
+    }
+}
+
+fn check_lengths(aad_len: usize, input_len: usize) -> Result<(), InputTooLong> {
+    if aad_len as u64 > MAX_INPUT_LENGTH || input_len as u64 > MAX_INPUT_LENGTH {
+        Err(InputTooLong)
+    } else {
+        Ok(())
+    }
+}
+
+/// AES-GCM-SIV cipher with its key-generating key
+#[derive(Clone)]
+pub struct AesGcmSiv {
+    aes: Aes,
+}
+
+impl AesGcmSiv {
+    /// Create a new AES-GCM-SIV cipher from a 16 or 32 bytes key
+    ///
+    /// Panics if the key is of another size, see [`AesGcmSiv::try_new`] for
+    /// a fallible version.
+    #[cfg_attr(feature = "no-panic", deprecated(note = "use try_new"))]
+    pub fn new(key: &[u8]) -> Self {
+        Self::try_new(key).expect("AES-GCM-SIV key of 16 or 32 bytes")
+    }
+
+    /// Create a new AES-GCM-SIV cipher from a 16 or 32 bytes key, or return
+    /// `None` if the key is of another size
+    pub fn try_new(key: &[u8]) -> Option<Self> {
+        match key.len() {
+            16 | 32 => Aes::try_new(key).map(|aes| AesGcmSiv { aes }),
+            _ => None,
+        }
+    }
+
+    // derive the per nonce authentication key and encryption key
+    fn derive_keys(&self, nonce: &[u8; NONCE_LENGTH]) -> (Polyval, Aes) {
+        let mut material = [0u8; 48];
+        let blocks = 2 + self.aes.key_length() / 8;
+        for i in 0..blocks {
+            let mut block = [0u8; BLOCK_LENGTH];
+            block[0..4].copy_from_slice(&(i as u32).to_le_bytes());
+            block[4..16].copy_from_slice(nonce);
+            self.aes.encrypt_block(&mut block);
+            material[8 * i..8 * i + 8].copy_from_slice(&block[0..8]);
+            secure_memset(&mut block, 0);
+        }
+
+        let mut auth_key = [0u8; 16];
+        auth_key.copy_from_slice(&material[0..16]);
+        let polyval = Polyval::new(&auth_key);
+        let aes = Aes::try_new(&material[16..8 * blocks]).unwrap();
+        secure_memset(&mut auth_key, 0);
+        secure_memset(&mut material, 0);
+        (polyval, aes)
+    }
+
+    fn compute_tag(
+        mut polyval: Polyval,
+        aes: &Aes,
+        nonce: &[u8; NONCE_LENGTH],
+        aad: &[u8],
+        plaintext: &[u8],
+    ) -> [u8; TAG_LENGTH] {
+        polyval.input(aad);
+        polyval.pad_zeroes();
+        polyval.input(plaintext);
+        polyval.pad_zeroes();
+        let mut lengths = [0u8; 16];
+        lengths[0..8].copy_from_slice(&((aad.len() as u64) * 8).to_le_bytes());
+        lengths[8..16].copy_from_slice(&((plaintext.len() as u64) * 8).to_le_bytes());
+        polyval.input(&lengths);
+
+        let mut tag = polyval.result();
+        for (t, n) in tag.iter_mut().zip(nonce.iter()) {
+            *t ^= n;
+        }
+        tag[15] &= 0x7f;
+        aes.encrypt_block(&mut tag);
+        tag
+    }
+
+    // AES-CTR with the tag as initial counter block, the counter being its
+    // first 4 bytes in little endian
+    fn ctr(aes: &Aes, tag: &[u8; TAG_LENGTH], input: &[u8], output: &mut [u8]) {
+        let mut counter = *tag;
+        counter[15] |= 0x80;
+        let mut n = u32::from_le_bytes([counter[0], counter[1], counter[2], counter[3]]);
+        for (i, o) in input
+            .chunks(BLOCK_LENGTH)
+            .zip(output.chunks_mut(BLOCK_LENGTH))
+        {
+            let mut keystream = counter;
+            aes.encrypt_block(&mut keystream);
+            for ((o, i), k) in o.iter_mut().zip(i.iter()).zip(keystream.iter()) {
+                *o = i ^ k;
+            }
+            n = n.wrapping_add(1);
+            counter[0..4].copy_from_slice(&n.to_le_bytes());
+        }
+    }
+
+    /// Encrypt the input to the output buffer, and write the tag to `out_tag`
+    ///
+    /// The output needs to be of the same size as the input. Return
+    /// [`InputTooLong`] without encrypting if the input or the associated
+    /// data is longer than [`MAX_INPUT_LENGTH`].
+    pub fn encrypt(
+        &self,
+        nonce: &[u8; NONCE_LENGTH],
+        aad: &[u8],
+        input: &[u8],
+        output: &mut [u8],
+        out_tag: &mut [u8; TAG_LENGTH],
+    ) -> Result<(), InputTooLong> {
+        assert!(input.len() == output.len());
+        check_lengths(aad.len(), input.len())?;
+        let (polyval, aes) = self.derive_keys(nonce);
+        let tag = Self::compute_tag(polyval, &aes, nonce, aad, input);
+        Self::ctr(&aes, &tag, input, output);
+        out_tag.copy_from_slice(&tag);
+        Ok(())
+    }
+
+    /// Decrypt the input to the output buffer, returning whether the tag is valid
+    ///
+    /// The output needs to be of the same size as the input. If the tag
+    /// doesn't match, the output is zeroed instead of holding the decrypted data.
+    /// An input or associated data longer than [`MAX_INPUT_LENGTH`] can't
+    /// have been produced by [`AesGcmSiv::encrypt`], and is rejected as invalid.
+    pub fn decrypt(
+        &self,
+        nonce: &[u8; NONCE_LENGTH],
+        aad: &[u8],
+        input: &[u8],
+        output: &mut [u8],
+        tag: &[u8; TAG_LENGTH],
+    ) -> bool {
+        assert!(input.len() == output.len());
+        if check_lengths(aad.len(), input.len()).is_err() {
+            secure_memset(output, 0);
+            return false;
+        }
+        let (polyval, aes) = self.derive_keys(nonce);
+        Self::ctr(&aes, tag, input, output);
+        let expected = Self::compute_tag(polyval, &aes, nonce, aad, output);
+        if fixed_time_eq(&expected, tag) {
+            true
+        } else {
+            secure_memset(output, 0);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(feature = "no-panic", allow(deprecated))]
+mod tests {
+    use super::{check_lengths, AesGcmSiv, InputTooLong, MAX_INPUT_LENGTH};
+    use std::vec::Vec;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn check(key: &str, nonce: &str, aad: &str, plaintext: &str, result: &str) {
+        let key = hex(key);
+        let mut nonce_bytes = [0u8; 12];
+        nonce_bytes.copy_from_slice(&hex(nonce));
+        let aad = hex(aad);
+        let plaintext = hex(plaintext);
+        let result = hex(result);
+        let (expected_ct, expected_tag) = result.split_at(plaintext.len());
+
+        let cipher = AesGcmSiv::new(&key);
+        let mut ciphertext = vec![0u8; plaintext.len()];
+        let mut tag = [0u8; 16];
+        cipher
+            .encrypt(&nonce_bytes, &aad, &plaintext, &mut ciphertext, &mut tag)
+            .unwrap();
+        assert_eq!(&ciphertext[..], expected_ct);
+        assert_eq!(&tag[..], expected_tag);
+
+        let mut decrypted = vec![0u8; plaintext.len()];
+        assert!(cipher.decrypt(&nonce_bytes, &aad, &ciphertext, &mut decrypted, &tag));
+        assert_eq!(decrypted, plaintext);
+
+        let mut bad_tag = tag;
+        bad_tag[3] ^= 1;
+        assert!(!cipher.decrypt(&nonce_bytes, &aad, &ciphertext, &mut decrypted, &bad_tag));
+        assert!(decrypted.iter().all(|b| *b == 0));
+        if !ciphertext.is_empty() {
+            ciphertext[0] ^= 1;
+            assert!(!cipher.decrypt(&nonce_bytes, &aad, &ciphertext, &mut decrypted, &tag));
+        }
+    }
+
+    #[test]
+    fn rfc8452_aes128() {
+        let key = "01000000000000000000000000000000";
+        let nonce = "030000000000000000000000";
+        check(key, nonce, "", "", "dc20e2d83f25705bb49e439eca56de25");
+        check(
+            key,
+            nonce,
+            "",
+            "0100000000000000",
+            "b5d839330ac7b786578782fff6013b815b287c22493a364c",
+        );
+        check(
+            key,
+            nonce,
+            "01",
+            "020000000000000000000000000000000300000000000000000000000000000004000000000000000000000000000000",
+            "50c8303ea93925d64090d07bd109dfd9515a5a33431019c17d93465999a8b0053201d723120a8562b838cdff25bf9d1e6a8cc3865f76897c2e4b245cf31c51f2",
+        );
+        check(
+            "e66021d5eb8e4f4066d4adb9c33560e4",
+            "f46e44bb3da0015c94f70887",
+            "",
+            "",
+            "a4194b79071b01a87d65f706e3949578",
+        );
+        check(
+            "36864200e0eaf5284d884a0e77d31646",
+            "bae8e37fc83441b16034566b",
+            "46bb91c3c5",
+            "7a806c",
+            "af60eb711bd85bc1e4d3e0a462e074eea428a8",
+        );
+    }
+
+    #[test]
+    fn rfc8452_aes256() {
+        let key = "0100000000000000000000000000000000000000000000000000000000000000";
+        let nonce = "030000000000000000000000";
+        check(key, nonce, "", "", "07f5f4169bbf55a8400cd47ea6fd400f");
+        check(
+            key,
+            nonce,
+            "",
+            "0100000000000000",
+            "c2ef328e5c71c83b843122130f7364b761e0b97427e3df28",
+        );
+        check(
+            key,
+            nonce,
+            "01",
+            "0200000000000000000000000000000003000000000000000000000000000000",
+            "07dad364bfc2b9da89116d7bef6daaaf6f255510aa654f920ac81b94e8bad365aea1bad12702e1965604374aab96dbbc",
+        );
+        // counter wrap around (RFC 8452 appendix C.3)
+        check(
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "000000000000000000000000",
+            "",
+            "000000000000000000000000000000004db923dc793ee6497c76dcc03a98e108",
+            "f3f80f2cf0cb2dd9c5984fcda908456cc537703b5ba70324a6793a7bf218d3eaffffffff000000000000000000000000",
+        );
+    }
+
+    #[test]
+    fn key_length() {
+        assert!(AesGcmSiv::try_new(&[0u8; 16]).is_some());
+        assert!(AesGcmSiv::try_new(&[0u8; 32]).is_some());
+        for len in [0, 8, 15, 24, 33] {
+            assert!(AesGcmSiv::try_new(&vec![0u8; len]).is_none());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_rejects_aes192_key() {
+        AesGcmSiv::new(&[0u8; 24]);
+    }
+
+    // the limit is above the address space of 32 bits targets
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn input_length_limits() {
+        let max = MAX_INPUT_LENGTH as usize;
+        assert_eq!(check_lengths(0, 0), Ok(()));
+        assert_eq!(check_lengths(max, max), Ok(()));
+        assert_eq!(check_lengths(max + 1, 0), Err(InputTooLong));
+        assert_eq!(check_lengths(0, max + 1), Err(InputTooLong));
+    }
+}
@@ -0,0 +1,730 @@
+//! Implements the Argon2 memory hard password hashing function, version 1.3, as specified
+//! in [RFC 9106][1].
+//!
+//! The memory is split in `parallelism` lanes, which are filled independently
+//! between the synchronization points at the end of each quarter of a pass.
+//! With the `std` feature, the lanes are spread over up to
+//! `std::thread::available_parallelism()` threads; otherwise they are filled
+//! one after the other, which gives the same output.
+//!
+//! # Examples
+//!
+//! ```
+//! use cryptoxide::argon2::{argon2, Params, Variant};
+//!
+//! let params = Params::new(Variant::Argon2id, 64, 3, 4).unwrap();
+//! let salt = [0x42u8; 16]; // use a random salt for each password !
+//! let mut out = [0u8; 32];
+//! argon2(&params, b"password", &salt, &[], &[], &mut out).unwrap();
+//! ```
+//!
+//! # References
+//! [1]: <https://www.rfc-editor.org/rfc/rfc9106>
+//!
+
+use alloc::vec;
+use core::convert::TryInto;
+
+use crate::blake2b::{long_hash, Blake2b};
+use crate::digest::Digest;
+use crate::util::secure_memset;
+
+/// Version of Argon2 implemented, 1.3
+pub const VERSION: u32 = 0x13;
+
+/// Minimum size of the salt in bytes
+pub const MIN_SALT_LENGTH: usize = 8;
+
+/// Minimum size of the output in bytes
+pub const MIN_OUTPUT_LENGTH: usize = 4;
+
+/// Maximum number of lanes
+pub const MAX_PARALLELISM: u32 = 0xff_ffff;
+
+// number of slices in a pass, the lanes synchronize at the end of each slice
+const SYNC_POINTS: usize = 4;
+
+// number of 64 bits words in a 1024 bytes block
+const BLOCK_WORDS: usize = 128;
+
+/// The variant of Argon2, deciding how the reference blocks are chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// Data dependent addressing, faster but vulnerable to side channels
+    Argon2d = 0,
+    /// Data independent addressing
+    Argon2i = 1,
+    /// Data independent addressing for the first half of the first pass,
+    /// data dependent for the rest, recommended by RFC 9106
+    Argon2id = 2,
+}
+
+/// Error returned when the parameters of Argon2 are not valid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidParameters;
+
+impl core::fmt::Display for InvalidParameters {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid argon2 parameters")
+    }
+}
+
+/// The Argon2 cost parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    variant: Variant,
+    memory: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+impl Params {
+    /// Create the Argon2 parameters
+    ///
+    /// # Arguments
+    ///
+    /// * variant - The Argon2 variant
+    /// * memory - The memory size in KiB, at least 8 times the parallelism
+    /// * iterations - The number of passes over the memory, at least 1
+    /// * parallelism - The number of lanes, between 1 and `MAX_PARALLELISM`
+    pub fn new(
+        variant: Variant,
+        memory: u32,
+        iterations: u32,
+        parallelism: u32,
+    ) -> Result<Self, InvalidParameters> {
+        if parallelism == 0 || parallelism > MAX_PARALLELISM || iterations == 0 {
+            return Err(InvalidParameters);
+        }
+        if (memory as u64) < 8 * parallelism as u64 {
+            return Err(InvalidParameters);
+        }
+        Ok(Params {
+            variant,
+            memory,
+            iterations,
+            parallelism,
+        })
+    }
+
+    /// The Argon2 variant
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// The memory size in KiB
+    pub fn memory(&self) -> u32 {
+        self.memory
+    }
+
+    /// The number of passes over the memory
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    /// The number of lanes
+    pub fn parallelism(&self) -> u32 {
+        self.parallelism
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Block([u64; BLOCK_WORDS]);
+
+impl Block {
+    const ZERO: Block = Block([0; BLOCK_WORDS]);
+
+    fn xor_with(&mut self, other: &Block) {
+        for (x, y) in self.0.iter_mut().zip(other.0.iter()) {
+            *x ^= *y;
+        }
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Block {
+        let mut block = Block::ZERO;
+        for (w, chunk) in block.0.iter_mut().zip(bytes.chunks_exact(8)) {
+            *w = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        block
+    }
+
+    fn to_bytes(self) -> [u8; 8 * BLOCK_WORDS] {
+        let mut bytes = [0u8; 8 * BLOCK_WORDS];
+        for (chunk, w) in bytes.chunks_exact_mut(8).zip(self.0.iter()) {
+            chunk.copy_from_slice(&w.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+// Blake2b round function with the multiplications added by Argon2
+#[inline(always)]
+fn gb(v: &mut [u64; BLOCK_WORDS], a: usize, b: usize, c: usize, d: usize) {
+    #[inline(always)]
+    fn fblamka(x: u64, y: u64) -> u64 {
+        let m = (x as u32 as u64) * (y as u32 as u64);
+        x.wrapping_add(y).wrapping_add(m.wrapping_mul(2))
+    }
+    v[a] = fblamka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = fblamka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = fblamka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = fblamka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+// Permutation P over the 16 words at the given positions
+#[inline(always)]
+fn permute(v: &mut [u64; BLOCK_WORDS], i: [usize; 16]) {
+    gb(v, i[0], i[4], i[8], i[12]);
+    gb(v, i[1], i[5], i[9], i[13]);
+    gb(v, i[2], i[6], i[10], i[14]);
+    gb(v, i[3], i[7], i[11], i[15]);
+    gb(v, i[0], i[5], i[10], i[15]);
+    gb(v, i[1], i[6], i[11], i[12]);
+    gb(v, i[2], i[7], i[8], i[13]);
+    gb(v, i[3], i[4], i[9], i[14]);
+}
+
+// Compression function G, xored into `next` on the passes after the first one
+fn compress(prev: &Block, reference: &Block, next: &mut Block, with_xor: bool) {
+    let mut r = *prev;
+    r.xor_with(reference);
+    let mut z = r;
+
+    // the block is a 8x8 matrix of 16 bytes registers, P is applied to the rows then the columns
+    for row in 0..8 {
+        let b = 16 * row;
+        permute(
+            &mut z.0,
+            [
+                b,
+                b + 1,
+                b + 2,
+                b + 3,
+                b + 4,
+                b + 5,
+                b + 6,
+                b + 7,
+                b + 8,
+                b + 9,
+                b + 10,
+                b + 11,
+                b + 12,
+                b + 13,
+                b + 14,
+                b + 15,
+            ],
+        );
+    }
+    for col in 0..8 {
+        let b = 2 * col;
+        permute(
+            &mut z.0,
+            [
+                b,
+                b + 1,
+                b + 16,
+                b + 17,
+                b + 32,
+                b + 33,
+                b + 48,
+                b + 49,
+                b + 64,
+                b + 65,
+                b + 80,
+                b + 81,
+                b + 96,
+                b + 97,
+                b + 112,
+                b + 113,
+            ],
+        );
+    }
+
+    z.xor_with(&r);
+    if with_xor {
+        next.xor_with(&z);
+    } else {
+        *next = z;
+    }
+}
+
+// Position of the segment being filled
+#[derive(Clone, Copy)]
+struct Position {
+    pass: u32,
+    lane: u32,
+    slice: usize,
+}
+
+// Memory geometry and parameters shared by all the lanes
+struct Instance {
+    variant: Variant,
+    iterations: u32,
+    lanes: usize,
+    lane_length: usize,
+    segment_length: usize,
+}
+
+impl Instance {
+    fn data_independent(&self, pos: Position) -> bool {
+        match self.variant {
+            Variant::Argon2d => false,
+            Variant::Argon2i => true,
+            Variant::Argon2id => pos.pass == 0 && pos.slice < SYNC_POINTS / 2,
+        }
+    }
+
+    // Column of the reference block in the reference lane, for the block at `index`
+    // of the current segment
+    fn reference_index(&self, pos: Position, index: usize, rand: u32, same_lane: bool) -> usize {
+        // the blocks of the current segment of the other lanes are never referenced,
+        // nor the previous block of the current lane
+        let area = if pos.pass == 0 {
+            if pos.slice == 0 || same_lane {
+                pos.slice * self.segment_length + index - 1
+            } else if index == 0 {
+                pos.slice * self.segment_length - 1
+            } else {
+                pos.slice * self.segment_length
+            }
+        } else if same_lane {
+            self.lane_length - self.segment_length + index - 1
+        } else if index == 0 {
+            self.lane_length - self.segment_length - 1
+        } else {
+            self.lane_length - self.segment_length
+        };
+
+        let x = (rand as u64 * rand as u64) >> 32;
+        let relative = area - 1 - ((area as u64 * x) >> 32) as usize;
+        let start = if pos.pass == 0 || pos.slice == SYNC_POINTS - 1 {
+            0
+        } else {
+            (pos.slice + 1) * self.segment_length
+        };
+        (start + relative) % self.lane_length
+    }
+}
+
+// The memory is stored slice by slice, each slice being the segments of all
+// the lanes one after the other, so that the segments filled concurrently
+// can be borrowed mutably while the other slices are shared.
+struct Memory<'a> {
+    instance: &'a Instance,
+    slice: usize,
+    before: &'a [Block],
+    after: &'a [Block],
+}
+
+impl<'a> Memory<'a> {
+    // Block of `lane` at `column`, which must not be in the current slice
+    fn get(&self, lane: usize, column: usize) -> &'a Block {
+        let seg_len = self.instance.segment_length;
+        let slice = column / seg_len;
+        let offset = lane * seg_len + column % seg_len;
+        if slice < self.slice {
+            &self.before[slice * self.instance.lanes * seg_len + offset]
+        } else {
+            let slice = slice - self.slice - 1;
+            &self.after[slice * self.instance.lanes * seg_len + offset]
+        }
+    }
+}
+
+fn next_addresses(address: &mut Block, input: &mut Block) {
+    input.0[6] += 1;
+    compress(&Block::ZERO, input, address, false);
+    let tmp = *address;
+    compress(&Block::ZERO, &tmp, address, false);
+}
+
+fn fill_segment(instance: &Instance, pos: Position, memory: &Memory, segment: &mut [Block]) {
+    let data_independent = instance.data_independent(pos);
+    let mut address = Block::ZERO;
+    let mut input = Block::ZERO;
+    if data_independent {
+        input.0[0] = pos.pass as u64;
+        input.0[1] = pos.lane as u64;
+        input.0[2] = pos.slice as u64;
+        input.0[3] = (instance.lanes * instance.lane_length) as u64;
+        input.0[4] = instance.iterations as u64;
+        input.0[5] = instance.variant as u64;
+    }
+
+    // the first two blocks of each lane are initialized from H0
+    let start = if pos.pass == 0 && pos.slice == 0 {
+        if data_independent {
+            next_addresses(&mut address, &mut input);
+        }
+        2
+    } else {
+        0
+    };
+
+    let lane = pos.lane as usize;
+    for index in start..instance.segment_length {
+        let prev = if index > 0 {
+            &segment[index - 1]
+        } else if pos.slice > 0 {
+            memory.get(lane, pos.slice * instance.segment_length - 1)
+        } else {
+            memory.get(lane, instance.lane_length - 1)
+        };
+
+        let rand = if data_independent {
+            if index % BLOCK_WORDS == 0 {
+                next_addresses(&mut address, &mut input);
+            }
+            address.0[index % BLOCK_WORDS]
+        } else {
+            prev.0[0]
+        };
+
+        let ref_lane = if pos.pass == 0 && pos.slice == 0 {
+            lane
+        } else {
+            ((rand >> 32) % instance.lanes as u64) as usize
+        };
+        let ref_index = instance.reference_index(pos, index, rand as u32, ref_lane == lane);
+        let reference = if ref_index / instance.segment_length == pos.slice {
+            &segment[ref_index % instance.segment_length]
+        } else {
+            memory.get(ref_lane, ref_index)
+        };
+
+        let mut next = segment[index];
+        compress(prev, reference, &mut next, pos.pass > 0);
+        segment[index] = next;
+    }
+}
+
+fn fill_memory_sequential(instance: &Instance, blocks: &mut [Block]) {
+    let slice_size = instance.lanes * instance.segment_length;
+    for pass in 0..instance.iterations {
+        for slice in 0..SYNC_POINTS {
+            let (before, rest) = blocks.split_at_mut(slice * slice_size);
+            let (current, after) = rest.split_at_mut(slice_size);
+            let memory = Memory {
+                instance,
+                slice,
+                before,
+                after,
+            };
+            for (lane, segment) in current.chunks_mut(instance.segment_length).enumerate() {
+                let pos = Position {
+                    pass,
+                    lane: lane as u32,
+                    slice,
+                };
+                fill_segment(instance, pos, &memory, segment);
+            }
+        }
+    }
+}
+
+// Same as `fill_memory_sequential`, with the lanes of each slice spread over at most
+// `workers` threads, joined at the end of the slice
+#[cfg(any(feature = "std", test))]
+fn fill_memory_threaded(instance: &Instance, blocks: &mut [Block], workers: usize) {
+    let slice_size = instance.lanes * instance.segment_length;
+    let lanes_per_worker = (instance.lanes + workers - 1) / workers;
+    for pass in 0..instance.iterations {
+        for slice in 0..SYNC_POINTS {
+            let (before, rest) = blocks.split_at_mut(slice * slice_size);
+            let (current, after) = rest.split_at_mut(slice_size);
+            let memory = Memory {
+                instance,
+                slice,
+                before,
+                after,
+            };
+            let fill_lanes = |first_lane: usize, segments: &mut [Block]| {
+                for (i, segment) in segments.chunks_mut(instance.segment_length).enumerate() {
+                    let pos = Position {
+                        pass,
+                        lane: (first_lane + i) as u32,
+                        slice,
+                    };
+                    fill_segment(instance, pos, &memory, segment);
+                }
+            };
+            std::thread::scope(|scope| {
+                let mut groups = current
+                    .chunks_mut(lanes_per_worker * instance.segment_length)
+                    .enumerate();
+                // the first group of lanes is filled by the calling thread
+                let (_, first) = groups.next().unwrap();
+                for (group, segments) in groups {
+                    let fill_lanes = &fill_lanes;
+                    scope.spawn(move || fill_lanes(group * lanes_per_worker, segments));
+                }
+                fill_lanes(0, first);
+            });
+        }
+    }
+}
+
+fn fill_memory(instance: &Instance, blocks: &mut [Block]) {
+    #[cfg(feature = "std")]
+    {
+        let workers = std::thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(instance.lanes);
+        if workers > 1 {
+            return fill_memory_threaded(instance, blocks, workers);
+        }
+    }
+    fill_memory_sequential(instance, blocks)
+}
+
+// Compute H0 from the parameters and the inputs
+#[allow(deprecated)]
+fn initial_hash(
+    params: &Params,
+    password: &[u8],
+    salt: &[u8],
+    secret: &[u8],
+    ad: &[u8],
+    out_len: u32,
+) -> [u8; 64] {
+    let mut ctx = Blake2b::new(64);
+    for v in [
+        params.parallelism,
+        out_len,
+        params.memory,
+        params.iterations,
+        VERSION,
+        params.variant as u32,
+    ] {
+        ctx.input(&v.to_le_bytes());
+    }
+    for data in [password, salt, secret, ad] {
+        ctx.input(&(data.len() as u32).to_le_bytes());
+        ctx.input(data);
+    }
+    let mut h0 = [0u8; 64];
+    ctx.result(&mut h0);
+    h0
+}
+
+fn argon2_with<F: FnOnce(&Instance, &mut [Block])>(
+    params: &Params,
+    password: &[u8],
+    salt: &[u8],
+    secret: &[u8],
+    ad: &[u8],
+    out: &mut [u8],
+    fill: F,
+) -> Result<(), InvalidParameters> {
+    if salt.len() < MIN_SALT_LENGTH || out.len() < MIN_OUTPUT_LENGTH {
+        return Err(InvalidParameters);
+    }
+    if [password, salt, secret, ad, out]
+        .iter()
+        .any(|data| data.len() > u32::MAX as usize)
+    {
+        return Err(InvalidParameters);
+    }
+    let out_len = out.len() as u32;
+
+    let lanes = params.parallelism as usize;
+    let segment_length = params.memory as usize / (lanes * SYNC_POINTS);
+    let instance = Instance {
+        variant: params.variant,
+        iterations: params.iterations,
+        lanes,
+        lane_length: segment_length * SYNC_POINTS,
+        segment_length,
+    };
+    let mut blocks = vec![Block::ZERO; lanes * instance.lane_length];
+
+    let mut input = [0u8; 72];
+    input[..64].copy_from_slice(&initial_hash(params, password, salt, secret, ad, out_len));
+    let mut bytes = [0u8; 8 * BLOCK_WORDS];
+    for lane in 0..lanes {
+        input[68..].copy_from_slice(&(lane as u32).to_le_bytes());
+        for column in 0..2 {
+            input[64..68].copy_from_slice(&(column as u32).to_le_bytes());
+            long_hash(&input, bytes.len() as u32, &mut bytes);
+            // the first two blocks of a lane are at the start of its first segment
+            blocks[lane * segment_length + column] = Block::from_bytes(&bytes);
+        }
+    }
+
+    fill(&instance, &mut blocks);
+
+    // xor of the last block of each lane, in the last slice
+    let last_slice = (SYNC_POINTS - 1) * lanes * segment_length;
+    let mut last = Block::ZERO;
+    for lane in 0..lanes {
+        last.xor_with(&blocks[last_slice + (lane + 1) * segment_length - 1]);
+    }
+    long_hash(&last.to_bytes(), out_len, out);
+
+    // Safety: a block is an array of u64 without padding, and u8 has no alignment requirement
+    let bytes = unsafe {
+        core::slice::from_raw_parts_mut(
+            blocks.as_mut_ptr() as *mut u8,
+            blocks.len() * core::mem::size_of::<Block>(),
+        )
+    };
+    secure_memset(bytes, 0);
+    Ok(())
+}
+
+/// Compute the Argon2 hash of a password into `out`
+///
+/// # Arguments
+///
+/// * params - The Argon2 parameters
+/// * password - The password to hash
+/// * salt - The salt, at least `MIN_SALT_LENGTH` bytes
+/// * secret - An optional secret key, or an empty slice
+/// * ad - Optional associated data, or an empty slice
+/// * out - The output buffer, at least `MIN_OUTPUT_LENGTH` bytes
+///
+/// With the `std` feature, the lanes are spread over as many threads as the
+/// available parallelism of the system allows, when the parallelism is more than 1.
+pub fn argon2(
+    params: &Params,
+    password: &[u8],
+    salt: &[u8],
+    secret: &[u8],
+    ad: &[u8],
+    out: &mut [u8],
+) -> Result<(), InvalidParameters> {
+    argon2_with(params, password, salt, secret, ad, out, fill_memory)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn unhex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn hash_with<F: Fn(&Instance, &mut [Block])>(
+        params: &Params,
+        password: &[u8],
+        salt: &[u8],
+        secret: &[u8],
+        ad: &[u8],
+        out_len: usize,
+        fill: F,
+    ) -> Vec<u8> {
+        let mut out = vec![0u8; out_len];
+        argon2_with(params, password, salt, secret, ad, &mut out, fill).unwrap();
+        out
+    }
+
+    // RFC 9106 section 5
+    #[test]
+    fn rfc9106() {
+        let tests = [
+            (
+                Variant::Argon2d,
+                "512b391b6f1162975371d30919734294f868e3be3984f3c1a13a4db9fabe4acb",
+            ),
+            (
+                Variant::Argon2i,
+                "c814d9d1dc7f37aa13f0d77f2494bda1c8de6b016dd388d29952a4c4672b6ce8",
+            ),
+            (
+                Variant::Argon2id,
+                "0d640df58d78766c08c037a34a8b53c9d01ef0452d75b65eb52520e96b01e659",
+            ),
+        ];
+        for (variant, expected) in tests.iter() {
+            let params = Params::new(*variant, 32, 3, 4).unwrap();
+            let mut out = [0u8; 32];
+            argon2(&params, &[1; 32], &[2; 16], &[3; 8], &[4; 12], &mut out).unwrap();
+            assert_eq!(&out[..], &unhex(expected)[..]);
+        }
+    }
+
+    #[test]
+    fn single_lane_and_long_output() {
+        let params = Params::new(Variant::Argon2i, 256, 2, 1).unwrap();
+        let mut out = [0u8; 32];
+        argon2(&params, b"password", b"somesalt", &[], &[], &mut out).unwrap();
+        assert_eq!(
+            &out[..],
+            &unhex("89e9029f4637b295beb027056a7336c414fadd43f6b208645281cb214a56452f")[..]
+        );
+
+        let params = Params::new(Variant::Argon2id, 64, 2, 1).unwrap();
+        let mut out = [0u8; 100];
+        argon2(&params, b"password", b"somesalt", &[], &[], &mut out).unwrap();
+        let expected = unhex(
+            "7712f6cfaea89a90b11559e10e234f92f892db147d4c3b6e628a51836a20dcd0\
+             7537028d562157088d11c966eced97430f53e747196cd7d99ddfb21b159e05ae\
+             131bd627e4a4b3452d5800c3351986221ec89db7698fcf4f91a1f5f4b73ef5e6\
+             92c2fbc1",
+        );
+        assert_eq!(&out[..], &expected[..]);
+    }
+
+    #[test]
+    fn memory_rounded_down() {
+        // 37 KiB over 3 lanes uses 36 blocks
+        let params = Params::new(Variant::Argon2id, 37, 1, 3).unwrap();
+        let mut out = [0u8; 4];
+        argon2(&params, b"pw", b"saltsalt", &[], &[], &mut out).unwrap();
+        assert_eq!(&out[..], &unhex("5cdea1e1")[..]);
+    }
+
+    #[test]
+    fn threaded_matches_sequential() {
+        for variant in [Variant::Argon2d, Variant::Argon2i, Variant::Argon2id].iter() {
+            for lanes in [4, 5] {
+                let params = Params::new(*variant, 1024, 3, lanes).unwrap();
+                let hash = |fill: &dyn Fn(&Instance, &mut [Block])| {
+                    hash_with(&params, b"password", b"somesalt", b"key", b"data", 32, fill)
+                };
+                let sequential = hash(&fill_memory_sequential);
+                for workers in 1..=lanes as usize {
+                    let threaded =
+                        hash(&|instance, blocks| fill_memory_threaded(instance, blocks, workers));
+                    assert_eq!(sequential, threaded);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_parameters() {
+        assert_eq!(
+            Params::new(Variant::Argon2id, 64, 0, 1),
+            Err(InvalidParameters)
+        );
+        assert_eq!(
+            Params::new(Variant::Argon2id, 64, 1, 0),
+            Err(InvalidParameters)
+        );
+        assert_eq!(
+            Params::new(Variant::Argon2id, 31, 1, 4),
+            Err(InvalidParameters)
+        );
+        assert!(Params::new(Variant::Argon2id, 32, 1, 4).is_ok());
+
+        let params = Params::new(Variant::Argon2id, 8, 1, 1).unwrap();
+        let mut out = [0u8; 4];
+        assert_eq!(
+            argon2(&params, b"pw", b"short", &[], &[], &mut out),
+            Err(InvalidParameters)
+        );
+        assert_eq!(
+            argon2(&params, b"pw", b"saltsalt", &[], &[], &mut out[..3]),
+            Err(InvalidParameters)
+        );
+    }
+}
@@ -0,0 +1,285 @@
+//! Implements the bcrypt password hashing function, using the `$2b$` modular crypt format.
+//!
+//! bcrypt is based on the expensive key schedule of the Blowfish cipher (eksblowfish),
+//! and is mostly provided for compatibility with existing password databases.
+//! For new applications, consider using a memory hard function like scrypt instead.
+//!
+//! Only the first 72 bytes of the password are used by bcrypt.
+//!
+//! # Examples
+//!
+//! ```
+//! use cryptoxide::bcrypt;
+//!
+//! let salt = [0x42u8; 16]; // use a random salt for each password !
+//! let hashed = bcrypt::hash(6, &salt, b"password");
+//! assert!(bcrypt::verify(&hashed, b"password"));
+//! assert!(!bcrypt::verify(&hashed, b"Password"));
+//! ```
+//!
+//! # References
+//! [1]: <https://www.usenix.org/legacy/events/usenix99/provos/provos.pdf>
+//!
+
+use alloc::string::String;
+
+use crate::blowfish::Blowfish;
+use crate::util::fixed_time_eq;
+
+/// Size of the salt in bytes
+pub const SALT_LENGTH: usize = 16;
+
+/// Size of the raw output of the bcrypt function in bytes
+pub const OUTPUT_LENGTH: usize = 24;
+
+/// Minimum cost parameter
+pub const MIN_COST: u32 = 4;
+
+/// Maximum cost parameter
+pub const MAX_COST: u32 = 31;
+
+/// Maximum length of the password used by bcrypt, including the terminating NUL byte
+const PASSWORD_MAX_LENGTH: usize = 72;
+
+// length of the bcrypt output stored in the modular crypt format
+const ENCODED_OUTPUT_LENGTH: usize = 23;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"./ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+fn setup(cost: u32, salt: &[u8], key: &[u8]) -> Blowfish {
+    let mut state = Blowfish::init_state();
+    state.salted_expand_key(salt, key);
+    for _ in 0..(1u64 << cost) {
+        state.expand_key(key);
+        state.expand_key(salt);
+    }
+    state
+}
+
+/// Raw bcrypt function, using the password as is
+///
+/// The password must be between 1 and 72 bytes long, and is expected to
+/// contain the NUL terminator when following the usual bcrypt convention.
+/// Most users should use [`hash`] and [`verify`] instead.
+pub fn bcrypt(
+    cost: u32,
+    salt: &[u8; SALT_LENGTH],
+    password: &[u8],
+    output: &mut [u8; OUTPUT_LENGTH],
+) {
+    assert!(
+        (MIN_COST..=MAX_COST).contains(&cost),
+        "cost should be between {} and {}",
+        MIN_COST,
+        MAX_COST
+    );
+    assert!(!password.is_empty() && password.len() <= PASSWORD_MAX_LENGTH);
+
+    let state = setup(cost, salt, password);
+    let mut ctext = [
+        0x4f727068, 0x65616e42, 0x65686f6c, 0x64657253, 0x63727944, 0x6f756274,
+    ];
+    for i in (0..6).step_by(2) {
+        for _ in 0..64 {
+            let (l, r) = state.encrypt(ctext[i], ctext[i + 1]);
+            ctext[i] = l;
+            ctext[i + 1] = r;
+        }
+    }
+    for (o, c) in output.chunks_mut(4).zip(ctext.iter()) {
+        o.copy_from_slice(&c.to_be_bytes());
+    }
+}
+
+fn base64_encode(input: &[u8], out: &mut String) {
+    for chunk in input.chunks(3) {
+        let b0 = chunk[0] as usize;
+        let b1 = *chunk.get(1).unwrap_or(&0) as usize;
+        let b2 = *chunk.get(2).unwrap_or(&0) as usize;
+        let sextets = [
+            b0 >> 2,
+            ((b0 & 3) << 4) | (b1 >> 4),
+            ((b1 & 0xf) << 2) | (b2 >> 6),
+            b2 & 0x3f,
+        ];
+        for s in sextets.iter().take(chunk.len() + 1) {
+            out.push(BASE64_ALPHABET[*s] as char);
+        }
+    }
+}
+
+// decode exactly out.len() bytes, returning None if the input is not of the matching length or invalid
+fn base64_decode(input: &[u8], out: &mut [u8]) -> Option<()> {
+    if input.len() != (out.len() * 4 + 2) / 3 {
+        return None;
+    }
+    let mut acc = 0u32;
+    let mut bits = 0;
+    let mut pos = 0;
+    for c in input {
+        let v = BASE64_ALPHABET.iter().position(|a| a == c)? as u32;
+        acc = (acc << 6) | v;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out[pos] = (acc >> bits) as u8;
+            pos += 1;
+        }
+    }
+    Some(())
+}
+
+// bcrypt of the NUL terminated password truncated to 72 bytes
+fn bcrypt_password(cost: u32, salt: &[u8; SALT_LENGTH], password: &[u8]) -> [u8; OUTPUT_LENGTH] {
+    let mut key = [0u8; PASSWORD_MAX_LENGTH];
+    let len = core::cmp::min(password.len(), PASSWORD_MAX_LENGTH);
+    key[..len].copy_from_slice(&password[..len]);
+    let key_len = core::cmp::min(password.len() + 1, PASSWORD_MAX_LENGTH);
+
+    let mut output = [0u8; OUTPUT_LENGTH];
+    bcrypt(cost, salt, &key[..key_len], &mut output);
+    output
+}
+
+/// Hash a password with the given cost and salt, returning the hash
+/// in the `$2b$` modular crypt format, e.g. `$2b$10$` followed by 53 characters.
+///
+/// The cost is the base-2 logarithm of the number of iterations and need
+/// to be between 4 and 31, and the salt should be randomly generated for
+/// every password.
+pub fn hash(cost: u32, salt: &[u8; SALT_LENGTH], password: &[u8]) -> String {
+    let output = bcrypt_password(cost, salt, password);
+
+    let mut s = String::with_capacity(60);
+    s.push_str("$2b$");
+    s.push((b'0' + (cost / 10) as u8) as char);
+    s.push((b'0' + (cost % 10) as u8) as char);
+    s.push('$');
+    base64_encode(salt, &mut s);
+    base64_encode(&output[..ENCODED_OUTPUT_LENGTH], &mut s);
+    s
+}
+
+/// Verify a password against a hash in the modular crypt format
+///
+/// The `$2a$`, `$2b$` and `$2y$` variants are accepted; any malformed hash
+/// is reported as a verification failure.
+pub fn verify(hash: &str, password: &[u8]) -> bool {
+    let h = hash.as_bytes();
+    if h.len() != 60 || h[0] != b'$' || h[1] != b'2' || h[3] != b'$' || h[6] != b'$' {
+        return false;
+    }
+    match h[2] {
+        b'a' | b'b' | b'y' => {}
+        _ => return false,
+    }
+    if !h[4].is_ascii_digit() || !h[5].is_ascii_digit() {
+        return false;
+    }
+    let cost = ((h[4] - b'0') * 10 + (h[5] - b'0')) as u32;
+    if !(MIN_COST..=MAX_COST).contains(&cost) {
+        return false;
+    }
+
+    let mut salt = [0u8; SALT_LENGTH];
+    let mut expected = [0u8; ENCODED_OUTPUT_LENGTH];
+    if base64_decode(&h[7..29], &mut salt).is_none()
+        || base64_decode(&h[29..60], &mut expected).is_none()
+    {
+        return false;
+    }
+
+    let output = bcrypt_password(cost, &salt, password);
+    fixed_time_eq(&output[..ENCODED_OUTPUT_LENGTH], &expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hash, verify};
+
+    // test vectors from the openwall crypt_blowfish test suite
+    #[test]
+    fn openwall_vectors() {
+        let vectors: [(&str, &[u8]); 4] = [
+            (
+                "$2a$05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW",
+                b"U*U",
+            ),
+            (
+                "$2a$05$CCCCCCCCCCCCCCCCCCCCC.VGOzA784oUp/Z0DY336zx7pLYAy0lwK",
+                b"U*U*",
+            ),
+            (
+                "$2a$05$XXXXXXXXXXXXXXXXXXXXXOAcXxm9kjPGEMsLznoKqmqw7tc8WCx4a",
+                b"U*U*U",
+            ),
+            (
+                "$2a$05$CCCCCCCCCCCCCCCCCCCCC.7uG0VCzI2bS7j6ymqJi9CdcdxiRTWNy",
+                b"",
+            ),
+        ];
+        for (h, password) in vectors.iter() {
+            assert!(verify(h, password));
+            assert!(!verify(h, b"U*U*U*"));
+        }
+    }
+
+    #[test]
+    fn hash_costs() {
+        let salt = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+        let vectors: [(u32, &[u8], &str); 3] = [
+            (
+                4,
+                b"password",
+                "$2b$04$..CA.uOD/eaGAOmJB.yMBubqEtzdkvfegxfotQ8UAMQWLlq7JbHJW",
+            ),
+            (
+                6,
+                b"correct horse battery staple",
+                "$2b$06$..CA.uOD/eaGAOmJB.yMBun3iZ2iqdV8kghRZLsYVPC.CZ1Ub9qya",
+            ),
+            (
+                8,
+                "été".as_bytes(),
+                "$2b$08$..CA.uOD/eaGAOmJB.yMBugfYNX3WcrO7B93YXKWKIIgX7ihWjsEq",
+            ),
+        ];
+        for (cost, password, expected) in vectors.iter() {
+            let h = hash(*cost, &salt, password);
+            assert_eq!(&h, expected);
+            assert!(verify(&h, password));
+        }
+    }
+
+    #[test]
+    fn long_password_truncation() {
+        let salt = [0x5a; 16];
+        let p71 = [b'a'; 71];
+        let p72 = [b'a'; 72];
+        let p100 = [b'a'; 100];
+        let h72 = hash(4, &salt, &p72);
+        assert!(verify(&h72, &p100));
+        assert!(!verify(&h72, &p71));
+        assert_eq!(
+            hash(
+                4,
+                &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+                &p72
+            ),
+            "$2b$04$..CA.uOD/eaGAOmJB.yMBuIbwKfymVuMfASrmYBUytUgog5Nvtojy"
+        );
+    }
+
+    #[test]
+    fn malformed_hashes() {
+        let h = "$2b$04$..CA.uOD/eaGAOmJB.yMBubqEtzdkvfegxfotQ8UAMQWLlq7JbHJW";
+        assert!(verify(h, b"password"));
+        assert!(!verify(&h[..59], b"password"));
+        assert!(!verify(&h.replace("$2b$", "$2x$"), b"password"));
+        assert!(!verify(&h.replace("$04$", "$03$"), b"password"));
+        assert!(!verify(&h.replace("$04$", "$4a$"), b"password"));
+        assert!(!verify(&h.replace("JbHJW", "JbHJ!"), b"password"));
+        assert!(!verify("", b"password"));
+    }
+}
@@ -739,10 +739,10 @@ impl EngineB {
         let t = self.t.as_ptr() as *const __m128i;
 
         let f = unsafe {
-            if last == LastBlock::Yes {
-                _mm_set_epi64x(0, -1i64)
-            } else {
-                _mm_set1_epi64x(0)
+            match last {
+                LastBlock::No => _mm_set1_epi64x(0),
+                LastBlock::Yes => _mm_set_epi64x(0, -1i64),
+                LastBlock::YesLastNode => _mm_set1_epi64x(-1i64),
             }
         };
 
@@ -792,10 +792,12 @@ impl EngineS {
         let h = self.h.as_mut_ptr() as *mut __m128i;
         let iv = s::IV.as_ptr() as *const __m128i;
         let t = unsafe {
-            if last == LastBlock::Yes {
-                _mm_set_epi32(0, -1i32, self.t[1] as i32, self.t[0] as i32)
-            } else {
-                _mm_set_epi32(0, 0, self.t[1] as i32, self.t[0] as i32)
+            match last {
+                LastBlock::No => _mm_set_epi32(0, 0, self.t[1] as i32, self.t[0] as i32),
+                LastBlock::Yes => _mm_set_epi32(0, -1i32, self.t[1] as i32, self.t[0] as i32),
+                LastBlock::YesLastNode => {
+                    _mm_set_epi32(-1i32, -1i32, self.t[1] as i32, self.t[0] as i32)
+                }
             }
         };
 
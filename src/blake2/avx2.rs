@@ -359,10 +359,12 @@ impl EngineB {
         let h = self.h.as_mut_ptr() as *mut __m256i;
         let iv = b::IV.as_ptr() as *const __m256i;
         let t_and_f = unsafe {
-            if last == LastBlock::Yes {
-                _mm256_set_epi64x(0, -1i64, self.t[1] as i64, self.t[0] as i64)
-            } else {
-                _mm256_set_epi64x(0, 0, self.t[1] as i64, self.t[0] as i64)
+            match last {
+                LastBlock::No => _mm256_set_epi64x(0, 0, self.t[1] as i64, self.t[0] as i64),
+                LastBlock::Yes => _mm256_set_epi64x(0, -1i64, self.t[1] as i64, self.t[0] as i64),
+                LastBlock::YesLastNode => {
+                    _mm256_set_epi64x(-1i64, -1i64, self.t[1] as i64, self.t[0] as i64)
+                }
             }
         };
 
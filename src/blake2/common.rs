@@ -63,6 +63,9 @@ pub const SIGMA: [[usize; 16]; 12] = [
 pub enum LastBlock {
     /// To use when this is the last block to process, otherwise use [`LastBlock::No`]
     Yes,
+    /// To use instead of [`LastBlock::Yes`] for the last block of the last node
+    /// of a level in tree hashing mode, which also sets the last node flag
+    YesLastNode,
     /// To use for all block to compress except the last one
     No,
 }
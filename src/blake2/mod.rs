@@ -9,27 +9,137 @@ mod reference;
 
 pub use common::LastBlock;
 
-#[cfg(all(target_arch = "x86_64", target_feature = "avx"))]
+#[cfg(all(
+    not(feature = "force-portable"),
+    target_arch = "x86_64",
+    target_feature = "avx"
+))]
 mod avx;
 
-#[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+#[cfg(all(
+    not(feature = "force-portable"),
+    target_arch = "x86_64",
+    target_feature = "avx2"
+))]
 mod avx2;
 
 mod implementation {
-    #[cfg(not(all(target_arch = "x86_64", target_feature = "avx")))]
+    use crate::cpu::Backend;
+
+    #[cfg(not(all(
+        not(feature = "force-portable"),
+        target_arch = "x86_64",
+        target_feature = "avx"
+    )))]
     pub use super::reference::{EngineB, EngineS};
+    #[cfg(not(all(
+        not(feature = "force-portable"),
+        target_arch = "x86_64",
+        target_feature = "avx"
+    )))]
+    pub(crate) const BACKEND_B: Backend = Backend::Portable;
+    #[cfg(not(all(
+        not(feature = "force-portable"),
+        target_arch = "x86_64",
+        target_feature = "avx"
+    )))]
+    pub(crate) const BACKEND_S: Backend = Backend::Portable;
 
-    #[cfg(all(target_arch = "x86_64", target_feature = "avx"))]
+    #[cfg(all(
+        not(feature = "force-portable"),
+        target_arch = "x86_64",
+        target_feature = "avx"
+    ))]
     pub use super::avx::EngineS;
+    #[cfg(all(
+        not(feature = "force-portable"),
+        target_arch = "x86_64",
+        target_feature = "avx"
+    ))]
+    pub(crate) const BACKEND_S: Backend = Backend::Avx;
 
     #[cfg(all(
+        not(feature = "force-portable"),
         target_arch = "x86_64",
         all(target_feature = "avx", not(target_feature = "avx2"))
     ))]
     pub use super::avx::EngineB;
+    #[cfg(all(
+        not(feature = "force-portable"),
+        target_arch = "x86_64",
+        all(target_feature = "avx", not(target_feature = "avx2"))
+    ))]
+    pub(crate) const BACKEND_B: Backend = Backend::Avx;
 
-    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    #[cfg(all(
+        not(feature = "force-portable"),
+        target_arch = "x86_64",
+        target_feature = "avx2"
+    ))]
     pub use super::avx2::EngineB;
+    #[cfg(all(
+        not(feature = "force-portable"),
+        target_arch = "x86_64",
+        target_feature = "avx2"
+    ))]
+    pub(crate) const BACKEND_B: Backend = Backend::Avx2;
 }
 
 pub use implementation::{EngineB, EngineS};
+pub(crate) use implementation::{BACKEND_B, BACKEND_S};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(seed: u8, len: usize) -> [u8; 128] {
+        let mut buf = [0u8; 128];
+        for (i, b) in buf.iter_mut().enumerate() {
+            *b = seed.wrapping_mul(31).wrapping_add(i as u8);
+        }
+        for b in buf[len..].iter_mut() {
+            *b = 0;
+        }
+        buf
+    }
+
+    #[test]
+    fn selected_engine_b_matches_reference() {
+        let mut selected = EngineB::new(64, 0);
+        let mut portable = reference::EngineB::new(64, 0);
+        for seed in 0..4 {
+            let last = if seed == 3 {
+                LastBlock::Yes
+            } else {
+                LastBlock::No
+            };
+            let len = if seed == 3 { 77 } else { 128 };
+            let buf = block(seed, len);
+            selected.increment_counter(len as u64);
+            portable.increment_counter(len as u64);
+            selected.compress(&buf, last.clone());
+            portable.compress(&buf, last);
+        }
+        assert_eq!(selected.h, portable.h);
+    }
+
+    #[test]
+    fn selected_engine_s_matches_reference() {
+        let mut selected = EngineS::new(32, 0);
+        let mut portable = reference::EngineS::new(32, 0);
+        for seed in 0..4 {
+            let last = if seed == 3 {
+                LastBlock::Yes
+            } else {
+                LastBlock::No
+            };
+            let len = if seed == 3 { 41 } else { 64 };
+            let buf = block(seed, len);
+            selected.increment_counter(len as u32);
+            portable.increment_counter(len as u32);
+            selected.compress(&buf[..64], last.clone());
+            portable.compress(&buf[..64], last);
+        }
+        assert_eq!(selected.h, portable.h);
+    }
+}
@@ -39,9 +39,12 @@ macro_rules! compressbody {
 
         vs[12] ^= $engine.t[0];
         vs[13] ^= $engine.t[1];
-        if $last == LastBlock::Yes {
+        if $last != LastBlock::No {
             vs[14] = !vs[14];
         }
+        if $last == LastBlock::YesLastNode {
+            vs[15] = !vs[15];
+        }
 
         round!($conmod, 0, vs, ms);
         round!($conmod, 1, vs, ms);
@@ -27,15 +27,27 @@
 //! ```
 //!
 //!
+//! Deriving subkeys from a master key:
+//!
+//! ```
+//! use cryptoxide::blake2b::kdf_derive;
+//!
+//! let master_key = [0u8; 32];
+//! let mut subkey = [0u8; 32];
+//! kdf_derive(&master_key, 1, b"Examples", &mut subkey);
+//! ```
+//!
 //! [1]: <https://eprint.iacr.org/2013/322.pdf>
 
 use crate::blake2::{EngineB as Engine, LastBlock};
 use crate::cryptoutil::{copy_memory, write_u64v_le};
-use crate::digest::Digest;
-use crate::mac::{Mac, MacResult};
+use crate::digest::{assert_output_size, Digest};
+use crate::mac::Mac;
 use crate::util::secure_memset;
-use alloc::vec::Vec;
-use core::iter::repeat;
+use core::convert::TryInto;
+
+/// Size of the personalization string of the parameter block
+pub const PERSONAL_LENGTH: usize = 16;
 
 /// Blake2b Context
 #[derive(Clone)]
@@ -44,24 +56,87 @@ pub struct Blake2b {
     buf: [u8; Engine::BLOCK_BYTES],
     buflen: usize,
     digest_length: u8,
-    computed: bool, // whether the final digest has been computed
+    computed: bool,  // whether the final digest has been computed
+    last_node: bool, // whether this is the last node of a tree level
+    // key given at construction, to restore the keyed state on reset
+    key: [u8; Engine::MAX_KEYLEN],
+    keylen: u8,
+    // parameter block words differing from the sequential mode defaults
+    // (salt, personalization, tree parameters), kept on reset
+    params: [u64; 8],
+    // last node flag set by the parameters, restored on reset
+    params_last_node: bool,
+}
+
+impl Drop for Blake2b {
+    fn drop(&mut self) {
+        secure_memset(&mut self.key[..], 0);
+    }
 }
 
 impl Blake2b {
     /// Create a new Blake2b context with a specific output size in bytes
     ///
     /// the size need to be between 0 (non included) and 64 bytes (included)
+    #[cfg_attr(feature = "no-panic", deprecated(note = "use try_new"))]
     pub fn new(outlen: usize) -> Self {
         assert!(outlen > 0 && outlen <= Engine::MAX_OUTLEN);
-        Self::new_keyed(outlen, &[])
+        Self::init(outlen, &[])
     }
 
     /// Similar to `new` but also takes a variable size key
     /// to tweak the context initialization
+    #[cfg_attr(feature = "no-panic", deprecated(note = "use try_new_keyed"))]
     pub fn new_keyed(outlen: usize, key: &[u8]) -> Self {
         assert!(outlen > 0 && outlen <= Engine::MAX_OUTLEN);
         assert!(key.len() <= Engine::MAX_KEYLEN);
+        Self::init(outlen, key)
+    }
+
+    /// Create a new Blake2b context with a specific output size in bytes,
+    /// or return an error if the size is not between 1 and 64 bytes (included)
+    pub fn try_new(outlen: usize) -> Result<Self, InvalidOutputLen> {
+        Self::try_new_keyed(outlen, &[])
+    }
+
+    /// Similar to `try_new` but also takes a variable size key, returning
+    /// an error if the key is longer than 64 bytes
+    pub fn try_new_keyed(outlen: usize, key: &[u8]) -> Result<Self, InvalidOutputLen> {
+        if outlen == 0 || outlen > Engine::MAX_OUTLEN || key.len() > Engine::MAX_KEYLEN {
+            return Err(InvalidOutputLen);
+        }
+        Ok(Self::init(outlen, key))
+    }
+
+    /// Similar to `try_new_keyed` but also sets the 16 bytes personalization
+    /// string of the parameter block, for domain separation
+    ///
+    /// The personalization is kept when the context is reset.
+    pub fn try_new_personalized(
+        outlen: usize,
+        key: &[u8],
+        personal: &[u8; PERSONAL_LENGTH],
+    ) -> Result<Self, InvalidOutputLen> {
+        let mut ctx = Self::try_new_keyed(outlen, key)?;
+        ctx.set_personal(personal);
+        Ok(ctx)
+    }
+
+    // parameter block words 6 and 7 are the personalization
+    fn set_personal(&mut self, personal: &[u8; PERSONAL_LENGTH]) {
+        self.params[6] = u64::from_le_bytes(personal[0..8].try_into().unwrap());
+        self.params[7] = u64::from_le_bytes(personal[8..16].try_into().unwrap());
+        self.apply_params();
+    }
 
+    fn apply_params(&mut self) {
+        for (h, p) in self.eng.h.iter_mut().zip(self.params.iter()) {
+            *h ^= p;
+        }
+    }
+
+    // the output size and key length need to have been checked
+    fn init(outlen: usize, key: &[u8]) -> Self {
         let mut buf = [0u8; Engine::BLOCK_BYTES];
 
         let eng = Engine::new(outlen, key.len());
@@ -72,12 +147,20 @@ impl Blake2b {
             0
         };
 
+        let mut stored_key = [0u8; Engine::MAX_KEYLEN];
+        stored_key[0..key.len()].copy_from_slice(key);
+
         Blake2b {
             eng,
             buf,
             buflen,
             digest_length: outlen as u8,
             computed: false,
+            last_node: false,
+            key: stored_key,
+            keylen: key.len() as u8,
+            params: [0; 8],
+            params_last_node: false,
         }
     }
 
@@ -112,8 +195,12 @@ impl Blake2b {
         if !self.computed {
             self.eng.increment_counter(self.buflen as u64);
             secure_memset(&mut self.buf[self.buflen..], 0);
-            self.eng
-                .compress(&self.buf[0..Engine::BLOCK_BYTES], LastBlock::Yes);
+            let last = if self.last_node {
+                LastBlock::YesLastNode
+            } else {
+                LastBlock::Yes
+            };
+            self.eng.compress(&self.buf[0..Engine::BLOCK_BYTES], last);
 
             write_u64v_le(&mut self.buf[0..64], &self.eng.h);
             self.computed = true;
@@ -121,42 +208,593 @@ impl Blake2b {
         copy_memory(&self.buf[0..out.len()], out);
     }
 
-    /// Reset the context to the state after calling `new`
-    pub fn reset(&mut self) {
-        self.eng.reset(self.digest_length as usize, 0);
-        self.computed = false;
-        self.buflen = 0;
-        secure_memset(&mut self.buf[..], 0);
+    /// Append data to the hash and return the context, for chaining
+    ///
+    /// ```
+    /// use cryptoxide::blake2b::Blake2b;
+    ///
+    /// let digest: [u8; 32] = Blake2b::new(32).chain(b"hello ").chain(b"world").finalize_array();
+    /// ```
+    pub fn chain(mut self, data: &[u8]) -> Self {
+        self.update(data);
+        self
     }
 
-    pub fn reset_with_key(&mut self, key: &[u8]) {
-        assert!(key.len() <= Engine::MAX_KEYLEN);
+    /// Consume the context and return the hash as an array
+    ///
+    /// The size `N` of the array needs to be the output size of the context,
+    /// otherwise this panics. This is [`Digest::finalize_fixed`], without
+    /// having to import the trait.
+    pub fn finalize_array<const N: usize>(mut self) -> [u8; N] {
+        Digest::finalize_fixed(&mut self)
+    }
+
+    /// Obtain the result of the Mac computation as an array, without allocation
+    ///
+    /// The size `N` of the array needs to be the output size of the context,
+    /// otherwise this panics. This is [`Digest::finalize_fixed`], without
+    /// having to import the trait.
+    /// As with `Mac::raw_result`, the code should be compared in constant time.
+    pub fn result_array<const N: usize>(&mut self) -> [u8; N] {
+        Digest::finalize_fixed(self)
+    }
 
-        self.eng.reset(self.digest_length as usize, key.len());
+    /// Mark the context as hashing the last node of a level in tree hashing mode
+    ///
+    /// This sets the last node finalization flag `f[1]` used when compressing
+    /// the final block, as required by the BLAKE2 specification for the
+    /// rightmost node of each level of a tree, and for the root node.
+    /// It needs to be set before the result is computed.
+    pub fn set_last_node(&mut self, last: bool) {
+        self.last_node = last;
+    }
+
+    /// Reset the context to the state after calling `new` or `new_keyed`,
+    /// keeping the key and the personalization of the context if any, or the
+    /// parameters of [`Blake2bParams`]
+    pub fn reset(&mut self) {
+        let keylen = self.keylen as usize;
+        self.eng.reset(self.digest_length as usize, keylen);
+        self.apply_params();
         self.computed = false;
+        self.last_node = self.params_last_node;
         secure_memset(&mut self.buf[..], 0);
 
-        if !key.is_empty() {
-            self.buf[0..key.len()].copy_from_slice(key);
+        if keylen > 0 {
+            self.buf[0..keylen].copy_from_slice(&self.key[0..keylen]);
             self.buflen = Engine::BLOCK_BYTES;
         } else {
-            self.buf = [0; Engine::BLOCK_BYTES];
             self.buflen = 0;
         }
     }
 
+    /// Reset the context with a new key, which is kept by the next resets
+    pub fn reset_with_key(&mut self, key: &[u8]) {
+        assert!(key.len() <= Engine::MAX_KEYLEN);
+
+        secure_memset(&mut self.key[..], 0);
+        self.key[0..key.len()].copy_from_slice(key);
+        self.keylen = key.len() as u8;
+        self.reset();
+    }
+
+    #[cfg_attr(feature = "no-panic", deprecated(note = "use try_new_keyed"))]
     pub fn blake2b(out: &mut [u8], input: &[u8], key: &[u8]) {
-        let mut hasher: Blake2b = if !key.is_empty() {
-            Blake2b::new_keyed(out.len(), key)
-        } else {
-            Blake2b::new(out.len())
-        };
+        assert!(!out.is_empty() && out.len() <= Engine::MAX_OUTLEN);
+        assert!(key.len() <= Engine::MAX_KEYLEN);
+        Self::oneshot(out, input, key)
+    }
 
+    fn oneshot(out: &mut [u8], input: &[u8], key: &[u8]) {
+        let mut hasher = Blake2b::init(out.len(), key);
         hasher.update(input);
         hasher.finalize(out);
     }
 }
 
+/// Derive a subkey from a master key, a context and a subkey identifier
+///
+/// This is keyed Blake2b of an empty input, with the 8 bytes context used as
+/// personalization and the little endian subkey identifier as salt, compatible
+/// with libsodium's `crypto_kdf_derive_from_key`.
+///
+/// The size of the subkey need to be between 16 and 64 bytes (included)
+pub fn kdf_derive(master_key: &[u8; 32], subkey_id: u64, context: &[u8; 8], out: &mut [u8]) {
+    assert!(out.len() >= 16 && out.len() <= Engine::MAX_OUTLEN);
+
+    let mut ctx = Blake2b::init(out.len(), master_key);
+    // parameter block words 4 and 5 are the salt, 6 and 7 the personalization
+    ctx.eng.h[4] ^= subkey_id;
+    ctx.eng.h[6] ^= u64::from_le_bytes(*context);
+    ctx.finalize(out);
+}
+
+/// Unkeyed Blake2b with a 16 bytes personalization string, as used for domain
+/// separation by protocols like Zcash (e.g. `ZcashPrevoutHash` with a 32 bytes output)
+///
+/// The size of `out` need to be between 1 and 64 bytes (included)
+pub fn personalized(personal: &[u8; PERSONAL_LENGTH], data: &[u8], out: &mut [u8]) {
+    assert!(!out.is_empty() && out.len() <= Engine::MAX_OUTLEN);
+    let mut ctx = Blake2b::init(out.len(), &[]);
+    ctx.set_personal(personal);
+    ctx.update(data);
+    ctx.finalize(out);
+}
+
+/// Parameter block of Blake2b, for the hashing modes other than the default
+/// sequential one: salt, personalization, and the tree hashing parameters
+///
+/// The defaults are the ones of [`Blake2b::new`] with a 64 bytes output: no key,
+/// fanout and maximal depth of 1, leaf length, node offset, node depth and
+/// inner length of 0, salt and personalization of zeros, and the last node
+/// flag cleared. Each method changes one parameter, and [`Blake2bParams::to_state`]
+/// creates the context, which keeps the parameters when it is reset.
+///
+/// ```
+/// use cryptoxide::blake2b::Blake2bParams;
+///
+/// let digest: [u8; 32] = Blake2bParams::new()
+///     .hash_length(32)
+///     .personal(b"ZcashPrevoutHash")
+///     .to_state()
+///     .unwrap()
+///     .chain(b"data")
+///     .finalize_array();
+/// ```
+#[derive(Clone)]
+pub struct Blake2bParams<'a> {
+    hash_length: usize,
+    key: &'a [u8],
+    salt: [u8; 16],
+    personal: [u8; PERSONAL_LENGTH],
+    fanout: u8,
+    max_depth: u8,
+    leaf_length: u32,
+    node_offset: u64,
+    node_depth: u8,
+    inner_length: u8,
+    last_node: bool,
+}
+
+impl<'a> Blake2bParams<'a> {
+    /// Parameters of the sequential mode, with a 64 bytes output
+    pub fn new() -> Self {
+        Blake2bParams {
+            hash_length: Engine::MAX_OUTLEN,
+            key: &[],
+            salt: [0; 16],
+            personal: [0; PERSONAL_LENGTH],
+            fanout: 1,
+            max_depth: 1,
+            leaf_length: 0,
+            node_offset: 0,
+            node_depth: 0,
+            inner_length: 0,
+            last_node: false,
+        }
+    }
+
+    /// Set the output size in bytes, between 1 and 64 (included)
+    pub fn hash_length(mut self, hash_length: usize) -> Self {
+        self.hash_length = hash_length;
+        self
+    }
+
+    /// Set the key, of up to 64 bytes
+    pub fn key(mut self, key: &'a [u8]) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// Set the 16 bytes salt
+    pub fn salt(mut self, salt: &[u8; 16]) -> Self {
+        self.salt = *salt;
+        self
+    }
+
+    /// Set the 16 bytes personalization string
+    pub fn personal(mut self, personal: &[u8; PERSONAL_LENGTH]) -> Self {
+        self.personal = *personal;
+        self
+    }
+
+    /// Set the fanout, 0 meaning unlimited
+    pub fn fanout(mut self, fanout: u8) -> Self {
+        self.fanout = fanout;
+        self
+    }
+
+    /// Set the maximal depth of the tree, between 1 and 255
+    pub fn max_depth(mut self, max_depth: u8) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Set the leaf maximal byte length, 0 meaning unlimited
+    pub fn leaf_length(mut self, leaf_length: u32) -> Self {
+        self.leaf_length = leaf_length;
+        self
+    }
+
+    /// Set the offset of the node in its level of the tree
+    pub fn node_offset(mut self, node_offset: u64) -> Self {
+        self.node_offset = node_offset;
+        self
+    }
+
+    /// Set the depth of the node, 0 for the leaves
+    pub fn node_depth(mut self, node_depth: u8) -> Self {
+        self.node_depth = node_depth;
+        self
+    }
+
+    /// Set the inner hash byte length, up to 64
+    pub fn inner_length(mut self, inner_length: u8) -> Self {
+        self.inner_length = inner_length;
+        self
+    }
+
+    /// Set whether the node is the last one of its level, see [`Blake2b::set_last_node`]
+    pub fn last_node(mut self, last_node: bool) -> Self {
+        self.last_node = last_node;
+        self
+    }
+
+    /// Create a context with those parameters, or return `None` if the output
+    /// size, the key length, the maximal depth or the inner length is invalid
+    pub fn to_state(&self) -> Option<Blake2b> {
+        if self.max_depth == 0 || self.inner_length as usize > Engine::MAX_OUTLEN {
+            return None;
+        }
+        let mut ctx = Blake2b::try_new_keyed(self.hash_length, self.key).ok()?;
+        ctx.params[0] = ((self.fanout ^ 1) as u64) << 16
+            ^ ((self.max_depth ^ 1) as u64) << 24
+            ^ (self.leaf_length as u64) << 32;
+        ctx.params[1] = self.node_offset;
+        ctx.params[2] = self.node_depth as u64 ^ (self.inner_length as u64) << 8;
+        ctx.params[4] = u64::from_le_bytes(self.salt[0..8].try_into().unwrap());
+        ctx.params[5] = u64::from_le_bytes(self.salt[8..16].try_into().unwrap());
+        ctx.set_personal(&self.personal);
+        ctx.params_last_node = self.last_node;
+        ctx.last_node = self.last_node;
+        Some(ctx)
+    }
+}
+
+impl<'a> Default for Blake2bParams<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Blake2b-256 context for the Zcash digests personalized by a 12 bytes tag
+/// and a consensus branch id
+///
+/// This is the hash of the signature digests of [ZIP 143][1] and [ZIP 243][2]
+/// (tag `ZcashSigHash`), and of the transaction identifier of [ZIP 244][3]
+/// (tag `ZcashTxHash_`). The parameters are the ones of [`Blake2bParams::new`]
+/// except for:
+///
+/// * a hash length of 32 bytes,
+/// * the personalization, being the tag followed by the consensus branch id
+///   in little endian, e.g. `0x76b809bb` for Sapling or `0xc2d6d0b4` for NU5.
+///
+/// The other digests of those ZIPs use a full 16 bytes personalization and
+/// no branch id, and are computed with [`personalized`].
+///
+/// [1]: <https://zips.z.cash/zip-0143>
+/// [2]: <https://zips.z.cash/zip-0243>
+/// [3]: <https://zips.z.cash/zip-0244>
+pub fn zcash(tag: &[u8; 12], consensus_branch_id: u32) -> Blake2b {
+    let mut personal = [0u8; PERSONAL_LENGTH];
+    personal[0..12].copy_from_slice(tag);
+    personal[12..16].copy_from_slice(&consensus_branch_id.to_le_bytes());
+    Blake2bParams::new()
+        .hash_length(32)
+        .personal(&personal)
+        .to_state()
+        .expect("valid Blake2b-256 parameters")
+}
+
+/// Variable length hash function H' of Argon2, as specified in RFC 9106
+///
+/// Up to 64 bytes, this is Blake2b of the little endian 32 bits output length
+/// followed by the input. Longer outputs are made of the first 32 bytes of each
+/// hash of a chain of Blake2b-512 hashes, followed by a final hash of the
+/// remaining length.
+///
+/// `out` needs to be `out_len` bytes long.
+pub fn long_hash(input: &[u8], out_len: u32, out: &mut [u8]) {
+    assert!(out_len > 0 && out.len() == out_len as usize);
+
+    let mut ctx = Blake2b::init(core::cmp::min(out.len(), Engine::MAX_OUTLEN), &[]);
+    ctx.update(&out_len.to_le_bytes());
+    ctx.update(input);
+    if out.len() <= Engine::MAX_OUTLEN {
+        ctx.finalize(out);
+        return;
+    }
+
+    let r = (out.len() + 31) / 32 - 2;
+    let mut v = [0u8; 64];
+    ctx.finalize(&mut v);
+    out[..32].copy_from_slice(&v[..32]);
+    for i in 1..r {
+        let prev = v;
+        Blake2b::oneshot(&mut v, &prev, &[]);
+        out[32 * i..32 * (i + 1)].copy_from_slice(&v[..32]);
+    }
+    Blake2b::oneshot(&mut out[32 * r..], &v, &[]);
+}
+
+/// Error returned when the requested output size of Blake2b is not between 1 and 64 bytes,
+/// or the key is longer than 64 bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidOutputLen;
+
+impl core::fmt::Display for InvalidOutputLen {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "invalid blake2b length, the output needs 1 to {} bytes and the key at most {}",
+            Engine::MAX_OUTLEN,
+            Engine::MAX_KEYLEN
+        )
+    }
+}
+
+/// Blake2b Context with an output size chosen at runtime
+///
+/// Unlike `Blake2b::new`, which panics on an invalid output size, the
+/// construction is fallible, which is convenient when the size comes
+/// from the outside, e.g. from a protocol message or generic code.
+#[derive(Clone)]
+pub struct Blake2bVar(Blake2b);
+
+impl Blake2bVar {
+    /// Create a new Blake2b context with a specific output size in bytes,
+    /// or an error if the size is not between 1 and 64 bytes (included)
+    pub fn new(outlen: usize) -> Result<Self, InvalidOutputLen> {
+        if outlen == 0 || outlen > Engine::MAX_OUTLEN {
+            return Err(InvalidOutputLen);
+        }
+        Ok(Blake2bVar(Blake2b::init(outlen, &[])))
+    }
+
+    /// Size of the output in bytes
+    pub fn output_size(&self) -> usize {
+        self.0.digest_length as usize
+    }
+}
+
+impl Digest for Blake2bVar {
+    fn input(&mut self, msg: &[u8]) {
+        self.0.update(msg);
+    }
+    fn reset(&mut self) {
+        self.0.reset();
+    }
+    fn result(&mut self, out: &mut [u8]) {
+        assert_output_size(out, self.output_size());
+        self.0.finalize(out);
+    }
+    fn output_bits(&self) -> usize {
+        8 * self.output_size()
+    }
+    fn block_size(&self) -> usize {
+        Engine::BLOCK_BYTES
+    }
+}
+
+/// Blake2b context with the multi-part API of libsodium's `crypto_generichash`
+///
+/// The output length (1 to 64 bytes) and the optional key (up to 64 bytes)
+/// are chosen at runtime, and the result is the same as the one of
+/// `crypto_generichash_init`, `crypto_generichash_update` and
+/// `crypto_generichash_final` with the same parameters. An empty key is the
+/// same as no key.
+///
+/// ```
+/// use cryptoxide::blake2b::GenericHash;
+///
+/// let mut state = GenericHash::init(Some(b"secret key"), 32).unwrap();
+/// state.update(b"hello ");
+/// state.update(b"world");
+/// let mut out = [0u8; 32];
+/// state.final_into(&mut out).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct GenericHash(Blake2b);
+
+impl GenericHash {
+    /// Create a new context, or return an error if the output length is not
+    /// between 1 and 64 bytes, or the key longer than 64 bytes
+    pub fn init(key: Option<&[u8]>, out_len: usize) -> Result<Self, InvalidOutputLen> {
+        Blake2b::try_new_keyed(out_len, key.unwrap_or(&[])).map(GenericHash)
+    }
+
+    /// Process more data
+    pub fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    /// Size of the output in bytes
+    pub fn output_size(&self) -> usize {
+        self.0.digest_length as usize
+    }
+
+    /// Write the hash to `out`, which needs to be of the output length given to
+    /// [`GenericHash::init`]
+    pub fn final_into(mut self, out: &mut [u8]) -> Result<(), InvalidOutputLen> {
+        if out.len() != self.output_size() {
+            return Err(InvalidOutputLen);
+        }
+        self.0.finalize(out);
+        Ok(())
+    }
+}
+
+/// Output length of [`Blake2xb`] for an output of unknown length
+pub const XOF_UNKNOWN_LENGTH: u32 = 0xffff_ffff;
+
+/// Blake2Xb extendable output function context
+///
+/// Blake2Xb, from the [BLAKE2X specification][1], hashes the input with
+/// Blake2b-512, and expands the hash into an output of a length fixed at
+/// construction, between 1 and 2^32-2 bytes. With [`Blake2xb::new_unknown`],
+/// the output length is not committed to, and up to 2^32 blocks of 64 bytes
+/// can be read.
+///
+/// The output length is a parameter of the hash: the outputs for different
+/// lengths are unrelated, and an output of unknown length is not the prefix
+/// of the output of a known length.
+///
+/// ```
+/// use cryptoxide::blake2b::Blake2xb;
+///
+/// let mut context = Blake2xb::new_unknown();
+/// context.input(b"hello world");
+/// let mut reader = context.finalize();
+/// let mut key_material = [0u8; 100];
+/// reader.squeeze(&mut key_material[..40]).unwrap();
+/// reader.squeeze(&mut key_material[40..]).unwrap();
+/// ```
+///
+/// [1]: <https://www.blake2.net/blake2x.pdf>
+#[derive(Clone)]
+pub struct Blake2xb {
+    root: Blake2b,
+    xof_length: u32,
+}
+
+impl Blake2xb {
+    /// Create a new Blake2Xb context for an output of `xof_length` bytes
+    ///
+    /// The length need to be at least 1 byte. A length of [`XOF_UNKNOWN_LENGTH`]
+    /// is the same as [`Blake2xb::new_unknown`].
+    pub fn new(xof_length: u32) -> Self {
+        Self::new_keyed(xof_length, &[])
+    }
+
+    /// Similar to `new` but also takes a key of up to 64 bytes
+    pub fn new_keyed(xof_length: u32, key: &[u8]) -> Self {
+        assert!(xof_length > 0);
+        assert!(key.len() <= Engine::MAX_KEYLEN);
+        let mut root = Blake2b::init(Engine::MAX_OUTLEN, key);
+        // the XOF length is in the upper half of the parameter block word 1
+        root.eng.h[1] ^= (xof_length as u64) << 32;
+        Blake2xb { root, xof_length }
+    }
+
+    /// Create a new Blake2Xb context for an output of unknown length
+    pub fn new_unknown() -> Self {
+        Self::new(XOF_UNKNOWN_LENGTH)
+    }
+
+    /// Similar to `new_unknown` but also takes a key of up to 64 bytes
+    pub fn new_unknown_keyed(key: &[u8]) -> Self {
+        Self::new_keyed(XOF_UNKNOWN_LENGTH, key)
+    }
+
+    /// Append data to the hash
+    pub fn input(&mut self, data: &[u8]) {
+        self.root.update(data);
+    }
+
+    /// Finish hashing the input, and return a reader of the output
+    pub fn finalize(mut self) -> Blake2xbReader {
+        let mut h0 = [0u8; 64];
+        self.root.finalize(&mut h0);
+        let limit = if self.xof_length == XOF_UNKNOWN_LENGTH {
+            (Engine::MAX_OUTLEN as u64) << 32
+        } else {
+            self.xof_length as u64
+        };
+        Blake2xbReader {
+            h0,
+            xof_length: self.xof_length,
+            block: [0; 64],
+            read: 0,
+            limit,
+        }
+    }
+}
+
+/// Error returned when reading more output than the Blake2Xb output length,
+/// or than 2^32 blocks for an output of unknown length
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XofLimitReached;
+
+impl core::fmt::Display for XofLimitReached {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "blake2xb output length exceeded")
+    }
+}
+
+/// Reader of the output of [`Blake2xb`]
+#[derive(Clone)]
+pub struct Blake2xbReader {
+    h0: [u8; 64],
+    xof_length: u32,
+    // current output block, the node at offset `read / 64`
+    block: [u8; 64],
+    read: u64,
+    limit: u64,
+}
+
+impl Drop for Blake2xbReader {
+    fn drop(&mut self) {
+        secure_memset(&mut self.h0[..], 0);
+        secure_memset(&mut self.block[..], 0);
+    }
+}
+
+impl Blake2xbReader {
+    // compute the output block at the given node offset
+    fn output_block(&mut self, node_offset: u32) {
+        let outlen = if self.xof_length == XOF_UNKNOWN_LENGTH {
+            Engine::MAX_OUTLEN
+        } else {
+            core::cmp::min(
+                Engine::MAX_OUTLEN as u64,
+                self.limit - (node_offset as u64) * 64,
+            ) as usize
+        };
+        let mut eng = Engine::new(outlen, 0);
+        // fanout and depth 0, leaf length 64, node offset, XOF length,
+        // node depth 0 and inner length 64
+        eng.h[0] ^= 0x01010000 ^ ((Engine::MAX_OUTLEN as u64) << 32);
+        eng.h[1] ^= node_offset as u64 ^ ((self.xof_length as u64) << 32);
+        eng.h[2] ^= (Engine::MAX_OUTLEN as u64) << 8;
+
+        let mut buf = [0u8; Engine::BLOCK_BYTES];
+        buf[0..64].copy_from_slice(&self.h0);
+        eng.increment_counter(64);
+        eng.compress(&buf, LastBlock::Yes);
+        write_u64v_le(&mut self.block, &eng.h);
+        secure_memset(&mut buf[..], 0);
+    }
+
+    /// Read the next bytes of the output
+    ///
+    /// Return an error, without reading anything, if this would read past
+    /// the output length.
+    pub fn squeeze(&mut self, out: &mut [u8]) -> Result<(), XofLimitReached> {
+        if out.len() as u64 > self.limit - self.read {
+            return Err(XofLimitReached);
+        }
+        for byte in out.iter_mut() {
+            let offset = (self.read % 64) as usize;
+            if offset == 0 {
+                self.output_block((self.read / 64) as u32);
+            }
+            *byte = self.block[offset];
+            self.read += 1;
+        }
+        Ok(())
+    }
+}
+
 impl Digest for Blake2b {
     fn input(&mut self, msg: &[u8]) {
         self.update(msg);
@@ -165,6 +803,7 @@ impl Digest for Blake2b {
         Blake2b::reset(self);
     }
     fn result(&mut self, out: &mut [u8]) {
+        assert_output_size(out, self.digest_length as usize);
         self.finalize(out);
     }
     fn output_bits(&self) -> usize {
@@ -194,15 +833,6 @@ impl Mac for Blake2b {
         Blake2b::reset(self);
     }
 
-    /**
-     * Obtain the result of a Mac computation as a MacResult.
-     */
-    fn result(&mut self) -> MacResult {
-        let mut mac: Vec<u8> = repeat(0).take(self.digest_length as usize).collect();
-        self.raw_result(&mut mac);
-        MacResult::new_from_owned(mac)
-    }
-
     /**
      * Obtain the result of a Mac computation as [u8]. This method should be used very carefully
      * since incorrect use of the Mac code could result in permitting a timing attack which defeats
@@ -221,8 +851,20 @@ impl Mac for Blake2b {
 }
 
 #[cfg(test)]
+#[cfg_attr(feature = "no-panic", allow(deprecated))]
 mod hash_tests {
-    use super::Blake2b;
+    use super::{
+        kdf_derive, long_hash, personalized, zcash, Blake2b, Blake2bParams, Blake2bVar, Blake2xb,
+        GenericHash, InvalidOutputLen, XofLimitReached,
+    };
+    use crate::digest::Digest;
+    use alloc::vec::Vec;
+
+    fn unhex(s: &str) -> Vec<u8> {
+        (0..s.len() / 2)
+            .map(|i| u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap())
+            .collect()
+    }
 
     #[test]
     fn test_vector() {
@@ -237,14 +879,594 @@ mod hash_tests {
         ];
         assert_eq!(&out[..], &expected[..])
     }
+
+    // keyed with 00 01 .. 3f, input 00 01 .. ff
+    fn blake2xb_case(mut context: Blake2xb, expected: &str) {
+        let expected = unhex(expected);
+        let data: Vec<u8> = (0..=255u8).collect();
+        context.input(&data);
+        let reader = context.finalize();
+
+        let mut out = vec![0u8; expected.len()];
+        reader.clone().squeeze(&mut out).unwrap();
+        assert_eq!(out, expected);
+
+        for chunk_size in [1, 7, 64, 100].iter() {
+            let mut reader = reader.clone();
+            let mut out = vec![0u8; expected.len()];
+            for chunk in out.chunks_mut(*chunk_size) {
+                reader.squeeze(chunk).unwrap();
+            }
+            assert_eq!(out, expected);
+        }
+    }
+
+    #[test]
+    fn test_blake2xb_unknown() {
+        let key: Vec<u8> = (0..64u8).collect();
+        blake2xb_case(Blake2xb::new_unknown_keyed(&key), UNKNOWN_EXPECTED);
+    }
+
+    #[test]
+    fn test_blake2xb_known() {
+        let key: Vec<u8> = (0..64u8).collect();
+        blake2xb_case(Blake2xb::new_keyed(1000, &key), KNOWN_EXPECTED);
+
+        let mut context = Blake2xb::new(100);
+        context.input(b"abc");
+        let mut reader = context.finalize();
+        let mut out = [0u8; 100];
+        reader.squeeze(&mut out[..90]).unwrap();
+        assert_eq!(reader.squeeze(&mut out[90..]), Ok(()));
+        assert_eq!(&out[..], &unhex(SHORT_EXPECTED)[..]);
+        assert_eq!(reader.squeeze(&mut out[..1]), Err(XofLimitReached));
+    }
+
+    #[test]
+    fn test_blake2xb_limit() {
+        let mut reader = Blake2xb::new_unknown().finalize();
+        // skip to the last block
+        reader.read = (64 << 32) - 64;
+        let mut out = [0u8; 65];
+        assert_eq!(reader.squeeze(&mut out), Err(XofLimitReached));
+        assert_eq!(reader.squeeze(&mut out[..64]), Ok(()));
+        assert_eq!(reader.squeeze(&mut out[..1]), Err(XofLimitReached));
+    }
+
+    const UNKNOWN_EXPECTED: &str =
+        "3dbba8516da76bf7330055c66ea36cf1005e92714262b24d9710f51d9e126406\
+         e1bcd6497059f9331f1091c3634b695428d475ed432f987040575520a1c29f5e\
+         6ee7189d601a409f996ba04b5414b1b04b28f2214d3cc6ade59074b61611f98c\
+         cdaf795204290e4960df8600eee8879c691db8e8e43ee098dafa6338fd96e4e3\
+         4a20675eb999c3eb5b6d2ab248a60396143ee813ab9b16a8d248f64c6b63da0f\
+         ea25b69c1da8f7acf4de3bfa5f9bd2470db71f800cafb87a7f9cec0c3cbe9d2a\
+         bd8323a3f956179c80c7360960fa33e5f59ed3c5f7c268e54772f9d1e89c26e8\
+         33adf6dd9756a42e949ab6901b61dbf0e6c7619870fb21af70d3562a56bdea31\
+         0d111ca2e9e818d9a3120c46baffaeaa055bd3437968ea17721c5d937f1fb0f8\
+         9e9952e2fc10ad8d96a46d7abc5c34529f8d34be3222ba9a198ea4db95382068\
+         0fbc1816532110155c0c080bf31fcf6d0a4fec73908ba2c0d6912637b11313c3\
+         b86668be8df0474486cfcf1b5973c4e0cccf199a43acafcf6a08abc1c6e6e9aa\
+         f4a3a27f1f80efcf5a5cff777e0e4e92416c42a1baffcda1e08f3d88ef7150fb\
+         ed4a42e4d68738e2de71347d72676debd41f6915eb8f69172d5253dfffe936d4\
+         69a38da52a962b938f1e9f952e5e2dace53ea52ff4eda2c762748b98896a12ff\
+         7df025bec78ab5af6a6845008d5f0d7cc9a3db7431cfc5252341db4a47abe8ab\
+         36200dfce73d79620038523e49e97be8659314bdbde5c818f893157ea92ad35e\
+         742b9540d01712407b4c031a0bc9e0cad08a1061d8563ba369baeeab3601d903\
+         efc36085db650029383265fae0fc0d11f6142ebf4ff1614c5f222e7de30f351e\
+         39b5deeb05d5ef4ea380aef4fd4057d7bcc7483a421cab6652ccfcbfb40bb4c7\
+         044f4289967e17262efd642cbe7d09e0be02f6c99d0a77cba6834a9a64ee68dc\
+         ca186c332b0402e06b73e6a5225ea285bf95f7b43efd9d8aaa759b9e7168122c\
+         21701e957c20a4b13997ff777484ff07f912b94dd768142a822e798fc3cefa7b\
+         ca4fba016a1e6b201d397f981675a515386cbe04a317b5cd701ef622faf6d39a\
+         3ff317d04aed7bff53dcfd9a30e1a84b36583740e0a9231728ecc7e801ea66cc\
+         43425f0adb04a26ac8f8bb85089bbf4fdd93819737b7c33c687f52d7dd086ee5\
+         ccdfe0d5fdb16c8c62105bde0b69998af00836039c5be0af393ec42d0fcc5fc2\
+         6751b7e3c9596ee34fb03a74dffa521346674592ad728c65fead7888d6537073\
+         27569e339a2949f7e35610fefc7c014081479d886448c14d20edfa06729230a4\
+         80b68c2f621666fff3144fb29f9d6b756421869c5579c3614bf1218b4bba997c\
+         107fe1f06bd403439ca5a315dfcb86ebcab965ca3962cb5a1b94a6d531822ee6\
+         e651fe28d03ac383";
+
+    const KNOWN_EXPECTED: &str = "3b728beee4037cdf570b8702cef84e636759675d96749b10cae46244645e8908\
+         1e72648c194aeddc76e1d7f4ef6487d3df1974c8f769f6f08844a2ab2fb87bda\
+         57db5961e41211a19de65fc569504536e24124cd2b4ee1c3269d06fd4063800a\
+         19c1dceea63c0013f6d7bf9be42729754e2e9b910c6eb01f82eb8787b786e608\
+         6d6dd1f306d14edf96c571e01460f3d00395b2274b955074a71ab28f04d38e36\
+         3fe1822936a7d026f8c1cafc3a257bc64904bc4087c59db948958d47d2cdeaad\
+         3d09c52130b9a0f762df9d697a4da70b39ef31e0d877f36ee07da514b1aa0bb2\
+         a1bf7c23a43344200953353aa26ba65dea771cd206db86b733022e1464b270d0\
+         9b6e72b29092f78a86a421f7c20bdcb9992346a0ad1437ed9e16c0b034a71b8a\
+         784c7ec59ffaa7c19e2dfc51201c83c1cffe4b38de7e5614f02c783190bfe009\
+         e7387c77fa6413399611d60b0347461de438e11b34c8e0e880c414eb7aadf879\
+         5907ae86a67133439ceb6a25811d1dbcdaa20ec15d5e82ed72b55a8fc01b4f0b\
+         830aff8858d04ccebd43aa88a056a4bf954d3f30922a06b39634c6f150164962\
+         585e3e3ac2e0fb1b44d00d84817fbc639d1801d06c9e247ba7509b0502faaeef\
+         aef5d7f3c983617aec672193f92fcba271fd9e6bad1aaddf512d80822fda9ffc\
+         de7293403b7918271dcfdaa0d09bc4fcb89feb9548568770e9ffa73f7035e16f\
+         3d09e5998d012259ed8347bede3a3c68b38d38e41d8dac1be3b46cf127c5fb7e\
+         efd70bacc1574b30b75b3db42475cf115aec4a96122770cf704c132c0e4f0d34\
+         ef02486d4ad9eddeab1591e40d437ae596778c984007b4ff637633df39345a2f\
+         5a984fc3db3fb3ef4f657b23934fecdf082137eedd4cfda4611ae1e818ccceae\
+         4e669794ac3ebc5e09b22a08d65dc84ec7f30cc9c94b6b87f0888053567bb561\
+         0d5ca79dbda4ce7ea82d01414c6a5ae8fe848b3935276035995a15c1a91d98dd\
+         aec506edf4bacf431b88e7182160474ecc4a9f31df9b30d425541855ab7c1a78\
+         b9eb7b776fd72f23db3aa8608ab3b4731412b0c310f46b44e2357f9fe00846ee\
+         827934eea38a786906a73c7334aeee49982017cc9ac7056ae8613dda6dd56293\
+         798ec2c3b671e850795e4011d8b3af7c8e08d9c71b36860c653618dc4dd911e5\
+         bbf0bf6fa04d2a9cfb9efb585949db55c8ada69bd15a634c9a03e049b91bbee9\
+         e662854c68bad9014b4eb99d5330239cb8e224c41409e56ca0afab7c6bdc41e2\
+         36c254d732fcbd939d5f9de648e3301fedf75975104fcf75ad61a8cde9452032\
+         ea01066513ba002a9760e70f4ffdc459e0fae0206685582ba3087571f29fd29f\
+         3be018f5340643d7d33e739402eb41166e069ded36bbbafe710dd290aec29387\
+         5455b2c57cc90c34";
+
+    const SHORT_EXPECTED: &str = "e0f82b71c07860b65be612d2633becc46596a6c12a8772b561adec35721b7a5c\
+         44a7e075e8a3bc8c4fc8390a197be2085b4aa4385c207f24e46415defc659afd\
+         73bacb288080b10849aeea386c60cd3fa04c9bcbfeebaed6e98634d696b9d5bd\
+         ef0ad2c5";
+
+    #[test]
+    fn test_try_new() {
+        assert_eq!(Blake2b::try_new(0).err(), Some(InvalidOutputLen));
+        assert_eq!(Blake2b::try_new(65).err(), Some(InvalidOutputLen));
+        assert_eq!(
+            Blake2b::try_new_keyed(32, &[0u8; 65]).err(),
+            Some(InvalidOutputLen)
+        );
+
+        let mut expected = [0u8; 32];
+        let mut out = [0u8; 32];
+        Blake2b::new_keyed(32, b"key")
+            .chain(b"abc")
+            .finalize(&mut expected);
+        Blake2b::try_new_keyed(32, b"key")
+            .unwrap()
+            .chain(b"abc")
+            .finalize(&mut out);
+        assert_eq!(out, expected);
+
+        let digest: [u8; 64] = Blake2b::try_new(64).unwrap().chain(b"abc").finalize_array();
+        let expected: [u8; 64] = Blake2b::new(64).chain(b"abc").finalize_array();
+        assert_eq!(digest, expected);
+    }
+
+    #[test]
+    fn test_var_output_len() {
+        assert_eq!(Blake2bVar::new(0).err(), Some(InvalidOutputLen));
+        assert_eq!(Blake2bVar::new(65).err(), Some(InvalidOutputLen));
+
+        let mut ctx = Blake2bVar::new(64).unwrap();
+        assert_eq!(ctx.output_size(), 64);
+        assert_eq!(ctx.output_bits(), 512);
+        ctx.input(b"abc");
+        let mut out = [0u8; 64];
+        ctx.result(&mut out);
+        let mut expected = [0u8; 64];
+        Blake2b::blake2b(&mut expected, b"abc", &[]);
+        assert_eq!(&out[..], &expected[..]);
+
+        // python hashlib.blake2b(data, digest_size=1)
+        let mut ctx = Blake2bVar::new(1).unwrap();
+        let mut out = [0u8; 1];
+        ctx.result(&mut out);
+        assert_eq!(out, [0x2e]);
+
+        let mut ctx = Blake2bVar::new(1).unwrap();
+        ctx.input(b"abc");
+        ctx.result(&mut out);
+        assert_eq!(out, [0x6b]);
+    }
+
+    #[test]
+    fn test_kdf_derive() {
+        // master key 00..1f and context "KDF test", as libsodium's crypto_kdf_derive_from_key
+        let mut master_key = [0u8; 32];
+        for (i, b) in master_key.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        let context = b"KDF test";
+
+        let mut subkey = [0u8; 32];
+        kdf_derive(&master_key, 0, context, &mut subkey);
+        assert_eq!(
+            subkey,
+            [
+                0xc1, 0x3f, 0xcc, 0x2e, 0x6c, 0xd0, 0xcd, 0x0f, 0x82, 0xd9, 0x3b, 0x16, 0x3a, 0x56,
+                0x96, 0xc5, 0x10, 0x53, 0x78, 0xf8, 0xc6, 0x29, 0xd3, 0x6b, 0xaf, 0x3a, 0xe0, 0x23,
+                0x9d, 0xe9, 0xc2, 0x80
+            ]
+        );
+        kdf_derive(&master_key, 1, context, &mut subkey);
+        assert_eq!(
+            subkey,
+            [
+                0x13, 0xfe, 0xa5, 0x2b, 0xb8, 0xcb, 0xa0, 0x63, 0xf3, 0xed, 0x93, 0xde, 0x27, 0xed,
+                0x07, 0xe0, 0x6d, 0x8c, 0x63, 0x67, 0x47, 0x4e, 0x6a, 0xe4, 0xc9, 0x28, 0x29, 0x13,
+                0xac, 0x3c, 0x3a, 0x03
+            ]
+        );
+
+        let mut subkey = [0u8; 64];
+        kdf_derive(&master_key, 0x0123456789abcdef, context, &mut subkey);
+        assert_eq!(
+            &subkey[..],
+            &[
+                0x9b, 0x61, 0x48, 0xd2, 0xa1, 0xe3, 0x98, 0xc2, 0x4f, 0x46, 0x12, 0x68, 0xf4, 0x9e,
+                0x9b, 0x32, 0xc5, 0xae, 0x9f, 0x66, 0x1d, 0x28, 0xc0, 0x28, 0x6a, 0xf4, 0x99, 0x58,
+                0xba, 0x49, 0x3f, 0x39, 0x40, 0xec, 0xda, 0xc3, 0xd4, 0xa4, 0xc7, 0xd7, 0x87, 0x7e,
+                0xa1, 0x13, 0x51, 0x93, 0x61, 0x92, 0xc2, 0xcf, 0x88, 0x4a, 0x10, 0xcf, 0xe2, 0x8f,
+                0x55, 0xaa, 0x22, 0x65, 0xf0, 0xbf, 0x8d, 0xe6
+            ][..]
+        );
+
+        let mut subkey = [0u8; 16];
+        kdf_derive(&master_key, 2, context, &mut subkey);
+        assert_eq!(
+            subkey,
+            [
+                0xb8, 0xed, 0xd6, 0x3d, 0xf4, 0x0e, 0xb4, 0x50, 0x7b, 0xfb, 0x7a, 0x74, 0x62, 0xc9,
+                0x8a, 0xbf
+            ]
+        );
+    }
+
+    // cross checked with Python's hashlib.blake2b(person=...), these are not
+    // published ZIP 143 test vectors
+    #[test]
+    fn test_personalized() {
+        // hashPrevouts input of ZIP 143 for a made up outpoint: txid 00..1f, index 1
+        let mut prevout = [0u8; 36];
+        for (i, b) in prevout[..32].iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        prevout[32] = 1;
+        let expected = unhex("d2c4b0c2d29107efdd00a08008b726109c0d533e5b7f9e60b79afda2df245ab1");
+        let mut out = [0u8; 32];
+        personalized(b"ZcashPrevoutHash", &prevout, &mut out);
+        assert_eq!(&out[..], &expected[..]);
+
+        let mut ctx = Blake2b::try_new_personalized(32, &[], b"ZcashPrevoutHash").unwrap();
+        ctx.input(&prevout);
+        ctx.result(&mut out);
+        assert_eq!(&out[..], &expected[..]);
+        Digest::reset(&mut ctx);
+        ctx.input(&prevout);
+        ctx.result(&mut out);
+        assert_eq!(&out[..], &expected[..]);
+
+        personalized(b"ZcashSequencHash", &[], &mut out);
+        assert_eq!(
+            &out[..],
+            &unhex("a5f25f01959361ee6eb56a7401210ee268226f6ce764a4f10b7f29e54db37272")[..]
+        );
+
+        let expected = unhex(
+            "1e9300996d708c74f6fd84502ddf4079eab964a7942fc28d6879feaae1bcdc5e\
+             d3acf70be83379b2120a22c58b81b5b6a43bd322cffc59099d05ee732b361d3b",
+        );
+        let mut mac = Blake2b::try_new_personalized(64, b"key", b"ZcashPrevoutHash").unwrap();
+        for _ in 0..2 {
+            crate::mac::Mac::input(&mut mac, b"abc");
+            let mut code = [0u8; 64];
+            crate::mac::Mac::raw_result(&mut mac, &mut code);
+            assert_eq!(&code[..], &expected[..]);
+            crate::mac::Mac::reset(&mut mac);
+        }
+    }
+
+    // cross checked with Python's hashlib.blake2b and the same parameters
+    #[test]
+    fn test_params() {
+        let mut salt = [0u8; 16];
+        for (i, b) in salt.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        let params = Blake2bParams::new()
+            .hash_length(32)
+            .key(b"tree key")
+            .salt(&salt)
+            .personal(b"personalization!")
+            .fanout(2)
+            .max_depth(3)
+            .leaf_length(4096)
+            .node_offset(5)
+            .node_depth(1)
+            .inner_length(64)
+            .last_node(true);
+        let expected = unhex("904f73a2c6b42e8a34f0369670da6b585a621f058a84a20b8dfb6c2ebe35e746");
+        let mut ctx = params.to_state().unwrap();
+        let mut out = [0u8; 32];
+        for _ in 0..2 {
+            ctx.input(b"abc");
+            ctx.result(&mut out);
+            assert_eq!(&out[..], &expected[..]);
+            Digest::reset(&mut ctx);
+        }
+
+        let digest: [u8; 32] = Blake2bParams::new()
+            .hash_length(32)
+            .fanout(0)
+            .max_depth(255)
+            .node_offset(0x0102030405060708)
+            .to_state()
+            .unwrap()
+            .chain(b"abc")
+            .finalize_array();
+        assert_eq!(
+            &digest[..],
+            &unhex("1526672d5170ea636b89c7bdb7ec67f9d551dd04a7b7708b418cfbb7e9c2f040")[..]
+        );
+
+        // the defaults are the sequential mode
+        let digest: [u8; 64] = Blake2bParams::new()
+            .to_state()
+            .unwrap()
+            .chain(b"abc")
+            .finalize_array();
+        let expected: [u8; 64] = Blake2b::new(64).chain(b"abc").finalize_array();
+        assert_eq!(digest, expected);
+        let digest: [u8; 64] = Blake2bParams::default()
+            .to_state()
+            .unwrap()
+            .chain(b"abc")
+            .finalize_array();
+        assert_eq!(digest, expected);
+
+        assert!(Blake2bParams::new().hash_length(0).to_state().is_none());
+        assert!(Blake2bParams::new().hash_length(65).to_state().is_none());
+        assert!(Blake2bParams::new().key(&[0; 65]).to_state().is_none());
+        assert!(Blake2bParams::new().max_depth(0).to_state().is_none());
+        assert!(Blake2bParams::new().inner_length(65).to_state().is_none());
+    }
+
+    // signature digest following the ZIP 243 layout, for a made up Sapling v4
+    // transaction spending one transparent P2PKH input to one P2PKH output
+    // with SIGHASH_ALL. This is not one of the published ZIP 243 test vectors:
+    // the expected value is hashlib.blake2b over the same preimage, so this
+    // checks the personalization with the consensus branch id of `zcash`,
+    // not the transaction encoding.
+    #[test]
+    fn test_zcash_sighash() {
+        let mut prevout = [0u8; 36];
+        for (i, b) in prevout[..32].iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        prevout[32] = 1;
+        let sequence = 0xffff_fffeu32.to_le_bytes();
+        let mut outputs = Vec::new();
+        outputs.extend_from_slice(&50000u64.to_le_bytes());
+        outputs.extend_from_slice(&[25, 0x76, 0xa9, 0x14]);
+        outputs.extend((0..20).map(|i| i as u8));
+        outputs.extend_from_slice(&[0x88, 0xac]);
+
+        let mut hash_prevouts = [0u8; 32];
+        let mut hash_sequence = [0u8; 32];
+        let mut hash_outputs = [0u8; 32];
+        personalized(b"ZcashPrevoutHash", &prevout, &mut hash_prevouts);
+        personalized(b"ZcashSequencHash", &sequence, &mut hash_sequence);
+        personalized(b"ZcashOutputsHash", &outputs, &mut hash_outputs);
+
+        let mut ctx = zcash(b"ZcashSigHash", 0x76b809bb);
+        ctx.input(&0x80000004u32.to_le_bytes()); // header, overwintered v4
+        ctx.input(&0x892f2085u32.to_le_bytes()); // version group id
+        ctx.input(&hash_prevouts);
+        ctx.input(&hash_sequence);
+        ctx.input(&hash_outputs);
+        ctx.input(&[0u8; 96]); // no joinsplits, spends nor outputs
+        ctx.input(&0u32.to_le_bytes()); // lock time
+        ctx.input(&500000u32.to_le_bytes()); // expiry height
+        ctx.input(&0u64.to_le_bytes()); // value balance
+        ctx.input(&1u32.to_le_bytes()); // SIGHASH_ALL
+        ctx.input(&prevout);
+        ctx.input(&[25, 0x76, 0xa9, 0x14]); // script code
+        ctx.input(&[0xaa; 20]);
+        ctx.input(&[0x88, 0xac]);
+        ctx.input(&100000u64.to_le_bytes()); // amount
+        ctx.input(&sequence);
+        let sighash: [u8; 32] = ctx.finalize_array();
+        assert_eq!(
+            &sighash[..],
+            &unhex("7ce4a3077682e992ea952cf4c992f543bd3f45ac8e5d896e9c5a6bed6e449b90")[..]
+        );
+    }
+
+    #[test]
+    fn test_long_hash() {
+        let mut out = [0u8; 32];
+        long_hash(b"long hash input", 32, &mut out);
+        assert_eq!(
+            &out[..],
+            &[
+                0x16, 0x0c, 0x78, 0x35, 0xf0, 0xaa, 0x98, 0xb0, 0x7d, 0x1a, 0xe3, 0xa3, 0xf4, 0xea,
+                0xd9, 0xba, 0xf1, 0x13, 0xaf, 0x1b, 0x8c, 0x21, 0x2f, 0x3e, 0x99, 0x4e, 0x9c, 0x7f,
+                0xe2, 0x6b, 0x3d, 0xcc,
+            ][..]
+        );
+        let mut out = [0u8; 65];
+        long_hash(b"long hash input", 65, &mut out);
+        assert_eq!(
+            &out[..],
+            &[
+                0xf1, 0x2d, 0x2e, 0xf5, 0x32, 0x33, 0xf6, 0x93, 0xa9, 0x99, 0xc8, 0xeb, 0x42, 0x22,
+                0xee, 0x2b, 0x18, 0x83, 0x84, 0x93, 0x73, 0x69, 0x02, 0x70, 0x97, 0x38, 0xc5, 0xe8,
+                0x46, 0x60, 0x0c, 0x52, 0x10, 0xf7, 0x84, 0xec, 0xd5, 0x98, 0x96, 0xd5, 0x99, 0x8d,
+                0x89, 0x86, 0xf8, 0xc1, 0xde, 0x22, 0xf4, 0x5d, 0x07, 0x4a, 0x4d, 0xcb, 0x12, 0x4b,
+                0x05, 0xb8, 0x59, 0x89, 0x03, 0x4f, 0xde, 0x38, 0x36,
+            ][..]
+        );
+        let mut out = [0u8; 100];
+        long_hash(b"long hash input", 100, &mut out);
+        assert_eq!(
+            &out[..],
+            &[
+                0x2f, 0x41, 0x38, 0xbf, 0x2b, 0x28, 0x98, 0xfa, 0x63, 0xc6, 0x8f, 0xfe, 0x2a, 0x24,
+                0xf0, 0x35, 0x44, 0x3f, 0x0a, 0x4a, 0xff, 0x2a, 0x61, 0x6e, 0xb1, 0x78, 0x4d, 0x64,
+                0x88, 0x75, 0x62, 0x5c, 0x4b, 0xdc, 0xe3, 0x95, 0xda, 0xa8, 0x84, 0xa9, 0xbd, 0xaa,
+                0x8c, 0xa1, 0xc8, 0x26, 0x96, 0x42, 0xeb, 0xcd, 0x6f, 0xe1, 0x34, 0x31, 0x39, 0x8f,
+                0x89, 0x83, 0xe9, 0x57, 0xad, 0xc5, 0x74, 0x1c, 0x37, 0x04, 0xa4, 0xba, 0x0b, 0xa9,
+                0x94, 0xca, 0xb9, 0xab, 0x8c, 0xf1, 0x28, 0x94, 0x46, 0x8a, 0xd4, 0x66, 0x5d, 0x1c,
+                0xa7, 0x5c, 0x18, 0x14, 0xb3, 0x14, 0xca, 0xb2, 0x7b, 0x98, 0x14, 0x8c, 0x55, 0xc4,
+                0x48, 0x7a,
+            ][..]
+        );
+
+        // the output of up to 64 bytes is a single Blake2b with the length prefix
+        let mut out = [0u8; 64];
+        long_hash(b"abc", 64, &mut out);
+        let mut expected = [0u8; 64];
+        Blake2b::blake2b(&mut expected, &[64, 0, 0, 0, b'a', b'b', b'c'], &[]);
+        assert_eq!(&out[..], &expected[..]);
+    }
+
+    // node of a tree of fanout 2 and depth 2, with 4096 bytes leaves and 64 bytes inner hashes
+    fn tree_node(node_offset: u64, node_depth: u64, last: bool) -> Blake2b {
+        let mut ctx = Blake2b::new(64);
+        ctx.eng.h[0] ^= (0x01010000 ^ 0x02020000) ^ (4096 << 32);
+        ctx.eng.h[1] ^= node_offset;
+        ctx.eng.h[2] ^= node_depth ^ (64 << 8);
+        ctx.set_last_node(last);
+        ctx
+    }
+
+    #[test]
+    fn test_chain() {
+        let mut ctx = Blake2b::new(32);
+        ctx.input(b"hello ");
+        ctx.input(b"world");
+        let mut expected = [0u8; 32];
+        ctx.result(&mut expected);
+
+        let digest: [u8; 32] = Blake2b::new(32)
+            .chain(b"hello ")
+            .chain(b"world")
+            .finalize_array();
+        assert_eq!(digest, expected);
+
+        let digest: [u8; 64] = Blake2b::new(64).chain(&[]).finalize_array();
+        let mut expected = [0u8; 64];
+        Blake2b::blake2b(&mut expected, &[], &[]);
+        assert_eq!(digest, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_finalize_array_wrong_size() {
+        let _: [u8; 64] = Blake2b::new(32).chain(b"abc").finalize_array();
+    }
+
+    #[test]
+    fn test_last_node() {
+        let mut out = [0u8; 32];
+        let mut ctx = Blake2b::new(32);
+        ctx.set_last_node(true);
+        ctx.input(b"abc");
+        ctx.result(&mut out);
+        let expected = [
+            0xd1, 0xfa, 0x7f, 0x9f, 0x13, 0xe3, 0x98, 0xef, 0x51, 0xa5, 0x57, 0x40, 0x53, 0x54,
+            0xe2, 0xb5, 0x89, 0x8f, 0x29, 0xed, 0x99, 0x64, 0xf6, 0x31, 0x19, 0x61, 0x4f, 0xb3,
+            0x43, 0xbd, 0x20, 0xb3,
+        ];
+        assert_eq!(out, expected);
+
+        // the flag is cleared by reset
+        ctx.reset();
+        ctx.input(b"abc");
+        ctx.result(&mut out);
+        assert_ne!(out, expected);
+    }
+
+    #[test]
+    fn test_generic_hash() {
+        // keyed Blake2b of the bytes 0..len with the key 0..64, computed with
+        // python's hashlib.blake2b and checked against the multi-part
+        // crypto_generichash of libsodium 1.0.18, with the same splits
+        let key: Vec<u8> = (0..64).collect();
+        let vectors = [
+            (0, "2ae6b02df83c8d5a85eb7ab86fe3a11f"),
+            (1, "1040a52fb904de3fe8ce0bb55a6f3b6264"),
+            (
+                64,
+                "14bad0182e14c6199f3fd5e5003e5c853cec105a0f70749867131ffad60c47",
+            ),
+            (
+                65,
+                "88feea78f5d2fcb7f9cf27953214b14bb1e6d2c1cfde07404ac8dbe02812ab9b",
+            ),
+            (200, "3062d119dcb83127970108ee465de20670c8e8e9"),
+        ];
+        for (len, expected) in vectors.iter() {
+            let input: Vec<u8> = (0..*len).map(|i| i as u8).collect();
+            let expected = unhex(expected);
+            for split in [0, len / 3, *len].iter() {
+                let mut state = GenericHash::init(Some(&key), expected.len()).unwrap();
+                assert_eq!(state.output_size(), expected.len());
+                state.update(&input[..*split]);
+                state.update(&input[*split..]);
+                let mut out = vec![0u8; expected.len()];
+                state.final_into(&mut out).unwrap();
+                assert_eq!(out, expected);
+            }
+        }
+
+        let mut state = GenericHash::init(None, 7).unwrap();
+        state.update(b"hello world");
+        let mut out = [0u8; 7];
+        state.clone().final_into(&mut out).unwrap();
+        assert_eq!(&out[..], &unhex("44c30cb552f8e0")[..]);
+        let mut empty_key = GenericHash::init(Some(&[]), 7).unwrap();
+        empty_key.update(b"hello world");
+        let mut out2 = [0u8; 7];
+        empty_key.final_into(&mut out2).unwrap();
+        assert_eq!(out, out2);
+        assert_eq!(state.final_into(&mut [0u8; 8]), Err(InvalidOutputLen));
+
+        assert_eq!(GenericHash::init(None, 0).err(), Some(InvalidOutputLen));
+        assert_eq!(GenericHash::init(None, 65).err(), Some(InvalidOutputLen));
+        assert_eq!(
+            GenericHash::init(Some(&[0u8; 65]), 32).err(),
+            Some(InvalidOutputLen)
+        );
+        assert!(GenericHash::init(Some(&key), 64).is_ok());
+    }
+
+    #[test]
+    fn test_two_leaves_tree() {
+        let mut leaves = [0u8; 128];
+        let mut left = tree_node(0, 0, false);
+        left.input(b"left leaf");
+        left.result(&mut leaves[..64]);
+        let mut right = tree_node(1, 0, true);
+        right.input(b"right leaf");
+        right.result(&mut leaves[64..]);
+
+        let mut root = tree_node(0, 1, true);
+        root.input(&leaves);
+        let mut out = [0u8; 64];
+        root.result(&mut out);
+        let expected = [
+            0x1b, 0x4b, 0x74, 0xc8, 0x44, 0x00, 0x2c, 0xda, 0x85, 0x93, 0x90, 0x0a, 0x25, 0x89,
+            0x1b, 0x93, 0x94, 0x3a, 0xd6, 0x82, 0xce, 0x46, 0x5d, 0x36, 0x8b, 0x93, 0x08, 0x6c,
+            0x07, 0x7c, 0xf3, 0x9b, 0x16, 0x0a, 0xc0, 0x7e, 0xba, 0x12, 0xf0, 0x3d, 0x60, 0x3e,
+            0xa7, 0x82, 0x28, 0xad, 0x66, 0x3d, 0xc8, 0xfa, 0x21, 0xb8, 0xc5, 0x70, 0x67, 0x56,
+            0x04, 0xd2, 0x9c, 0xb9, 0xd3, 0xbe, 0x09, 0x72,
+        ];
+        assert_eq!(out, expected);
+    }
 }
 
 #[cfg(test)]
+#[cfg_attr(feature = "no-panic", allow(deprecated))]
 mod mac_tests {
     use super::Blake2b;
     use crate::mac::Mac;
     use std::vec::Vec;
 
+    fn mac(m: &mut Blake2b) -> Vec<u8> {
+        let mut code = vec![0u8; m.output_bytes()];
+        m.raw_result(&mut code);
+        code
+    }
+
     #[test]
     fn test_reset_with_key_same_as_new_keyed_if_empty() {
         const KEY: &[u8] = &[];
@@ -252,14 +1474,47 @@ mod mac_tests {
         let mut m = Blake2b::new_keyed(32, &KEY);
         m.input(&INPUT);
 
-        let mac1 = m.result();
+        let mac1 = mac(&mut m);
 
         m.reset_with_key(&KEY);
         m.input(&INPUT);
 
-        let mac2 = m.result();
+        let mac2 = mac(&mut m);
+
+        assert_eq!(mac1, mac2);
+    }
+
+    #[test]
+    fn test_reset_keeps_key() {
+        let key: Vec<u8> = (0..32).collect();
+        let mut m = Blake2b::new_keyed(32, &key);
+        m.input(b"message");
+        let mac1 = mac(&mut m);
+
+        m.reset();
+        m.input(b"message");
+        let mac2 = mac(&mut m);
+        assert_eq!(mac1, mac2);
 
-        assert_eq!(mac1.code(), mac2.code());
+        // same as Digest::reset
+        crate::digest::Digest::reset(&mut m);
+        crate::digest::Digest::input(&mut m, b"message");
+        assert_eq!(mac(&mut m), mac1);
+
+        // the new key is kept on reset
+        m.reset_with_key(b"other key");
+        m.input(b"message");
+        let mac3 = mac(&mut m);
+        assert_ne!(mac3, mac1);
+        m.reset();
+        m.input(b"message");
+        assert_eq!(mac(&mut m), mac3);
+
+        let mut expected = [0u8; 32];
+        Blake2b::new_keyed(32, b"other key")
+            .chain(b"message")
+            .finalize(&mut expected);
+        assert_eq!(mac3, expected);
     }
 
     #[test]
@@ -274,11 +1529,26 @@ mod mac_tests {
             0x08, 0xf6, 0xa3, 0x58, 0xa9, 0xe0, 0x7d, 0x35, 0x3b, 0xd3, 0x1c, 0x41, 0x70, 0x15,
             0x62, 0xac, 0xd5, 0x39, 0x4e, 0xee, 0x73, 0xae,
         ];
-        assert_eq!(m.result().code().to_vec(), expected.to_vec());
+        assert_eq!(mac(&mut m), expected);
+    }
+
+    #[test]
+    fn test_result_array() {
+        let key: Vec<u8> = (0..64).collect();
+        let mut m = Blake2b::new_keyed(32, &key[..]);
+        m.input(&[1, 2, 4, 8]);
+        let mut expected = [0u8; 32];
+        m.raw_result(&mut expected);
+        assert_eq!(m.result_array::<32>(), expected);
+
+        let mut m = Blake2b::new_keyed(32, &key[..]);
+        m.input(&[1, 2, 4, 8]);
+        assert_eq!(m.result_array::<32>(), expected);
     }
 }
 
 #[cfg(all(test, feature = "with-bench"))]
+#[cfg_attr(feature = "no-panic", allow(deprecated))]
 mod bench {
     use test::Bencher;
 
@@ -26,16 +26,39 @@
 //! let mac = context.result();
 //! ```
 //!
+//! Verifying a Blake2s-256 MAC in constant time:
+//!
+//! ```
+//! use cryptoxide::{mac::Mac, blake2s::Blake2s};
+//!
+//! let key = [0u8; 32];
+//! let mut context = Blake2s::new_keyed(32, &key);
+//! context.input(b"hello world");
+//! let mut code = [0u8; 32];
+//! context.raw_result(&mut code);
+//!
+//! let mut context = Blake2s::new_keyed(32, &key);
+//! context.input(b"hello world");
+//! assert!(context.verify(&code));
+//! ```
+//!
+//!
+//! Short 128 bits MAC with a 16 bytes key:
+//!
+//! ```
+//! use cryptoxide::blake2s;
+//!
+//! let key = [0u8; 16];
+//! let tag: [u8; 16] = blake2s::mac128(&key, b"hello world");
+//! ```
 //!
 //! [1]: <https://eprint.iacr.org/2013/322.pdf>
 
 use crate::blake2::{EngineS as Engine, LastBlock};
 use crate::cryptoutil::{copy_memory, write_u32v_le};
-use crate::digest::Digest;
-use crate::mac::{Mac, MacResult};
-use crate::util::secure_memset;
-use alloc::vec::Vec;
-use core::iter::repeat;
+use crate::digest::{assert_output_size, Digest};
+use crate::mac::Mac;
+use crate::util::{fixed_time_eq, secure_memset};
 
 /// Blake2s Context
 #[derive(Clone)]
@@ -129,6 +152,7 @@ impl Blake2s {
         secure_memset(&mut self.buf[..], 0);
     }
 
+    /// Reset the context to the state after calling `new_keyed` with the given key
     pub fn reset_with_key(&mut self, key: &[u8]) {
         assert!(key.len() <= Engine::MAX_KEYLEN);
 
@@ -144,6 +168,26 @@ impl Blake2s {
         }
     }
 
+    /// Obtain the result of the Mac computation as an array, without allocation
+    ///
+    /// The size `N` of the array needs to be the output size of the context,
+    /// otherwise this panics. This is [`Digest::finalize_fixed`], without
+    /// having to import the trait.
+    /// As with `Mac::raw_result`, the code should be compared in constant time.
+    pub fn result_array<const N: usize>(&mut self) -> [u8; N] {
+        Digest::finalize_fixed(self)
+    }
+
+    /// Finalize the MAC computation and compare the result with the expected code in constant time
+    ///
+    /// Return false if the code is not of the output size of the context
+    pub fn verify(&mut self, code: &[u8]) -> bool {
+        let mut mac = [0u8; Engine::MAX_OUTLEN];
+        let outlen = self.digest_length as usize;
+        self.finalize(&mut mac[0..outlen]);
+        code.len() == outlen && fixed_time_eq(&mac[0..outlen], code)
+    }
+
     pub fn blake2s(out: &mut [u8], input: &[u8], key: &[u8]) {
         let mut hasher: Blake2s = if !key.is_empty() {
             Blake2s::new_keyed(out.len(), key)
@@ -156,6 +200,17 @@ impl Blake2s {
     }
 }
 
+/// Compute the 128 bits keyed Blake2s MAC of `data`
+///
+/// This is Blake2s with an output length of 16 bytes set in the parameter
+/// block, which differs from the first 16 bytes of a 32 bytes Blake2s output.
+/// The key can be up to 32 bytes long.
+pub fn mac128(key: &[u8], data: &[u8]) -> [u8; 16] {
+    let mut ctx = Blake2s::new_keyed(16, key);
+    ctx.update(data);
+    ctx.result_array()
+}
+
 impl Digest for Blake2s {
     fn input(&mut self, msg: &[u8]) {
         self.update(msg);
@@ -164,6 +219,7 @@ impl Digest for Blake2s {
         Blake2s::reset(self);
     }
     fn result(&mut self, out: &mut [u8]) {
+        assert_output_size(out, self.digest_length as usize);
         self.finalize(out);
     }
     fn output_bits(&self) -> usize {
@@ -193,15 +249,6 @@ impl Mac for Blake2s {
         Blake2s::reset(self);
     }
 
-    /**
-     * Obtain the result of a Mac computation as a MacResult.
-     */
-    fn result(&mut self) -> MacResult {
-        let mut mac: Vec<u8> = repeat(0).take(self.digest_length as usize).collect();
-        self.raw_result(&mut mac);
-        MacResult::new_from_owned(mac)
-    }
-
     /**
      * Obtain the result of a Mac computation as [u8]. This method should be used very carefully
      * since incorrect use of the Mac code could result in permitting a timing attack which defeats
@@ -330,7 +377,69 @@ mod mac_tests {
             0x01, 0x9c, 0x7e, 0xf9, 0x76, 0x2b, 0x4f, 0xf1, 0xad, 0x7e, 0x05, 0x5b, 0xa8, 0xc8,
             0x82, 0x9e, 0xe2, 0x49,
         ];
-        assert_eq!(m.result().code().to_vec(), expected.to_vec());
+        let mut out = [0u8; 32];
+        m.raw_result(&mut out);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_blake2s_mac_official_vector() {
+        // keyed test vector of the empty input from the Blake2 reference distribution
+        let key: Vec<u8> = (0..32).collect();
+        let expected = [
+            0x48, 0xa8, 0x99, 0x7d, 0xa4, 0x07, 0x87, 0x6b, 0x3d, 0x79, 0xc0, 0xd9, 0x23, 0x25,
+            0xad, 0x3b, 0x89, 0xcb, 0xb7, 0x54, 0xd8, 0x6a, 0xb7, 0x1a, 0xee, 0x04, 0x7a, 0xd3,
+            0x45, 0xfd, 0x2c, 0x49,
+        ];
+
+        let mut m = Blake2s::new_keyed(32, &key[..]);
+        assert_eq!(m.output_bytes(), 32);
+        let mut out = [0u8; 32];
+        m.raw_result(&mut out);
+        assert_eq!(out, expected);
+
+        m.reset_with_key(&key[..]);
+        m.input(&[]);
+        m.raw_result(&mut out);
+        assert_eq!(out, expected);
+
+        m.reset_with_key(&key[..]);
+        assert!(m.verify(&expected));
+
+        let mut wrong = expected;
+        wrong[31] ^= 1;
+        m.reset_with_key(&key[..]);
+        assert!(!m.verify(&wrong));
+        m.reset_with_key(&key[..]);
+        assert!(!m.verify(&expected[0..16]));
+        m.reset_with_key(&[]);
+        assert!(!m.verify(&expected));
+    }
+
+    #[test]
+    fn test_blake2s_mac128() {
+        // keyed Blake2s with a 16 bytes output, computed with python's hashlib.blake2s
+        let key: Vec<u8> = (0..16).collect();
+        let input: Vec<u8> = (0..255).collect();
+        let expected = [
+            0xb2, 0x95, 0x37, 0x95, 0x12, 0x3b, 0xd6, 0xb8, 0x90, 0xef, 0x3b, 0x6f, 0xa8, 0xb9,
+            0xba, 0xe2,
+        ];
+        assert_eq!(super::mac128(&key, &input), expected);
+
+        let mut m = Blake2s::new_keyed(16, &key);
+        assert_eq!(m.output_bytes(), 16);
+        m.input(&input[0..100]);
+        m.input(&input[100..]);
+        assert_eq!(m.result_array::<16>(), expected);
+
+        // the 32 bytes key of the reference test vectors, over the empty input
+        let key: Vec<u8> = (0..32).collect();
+        let expected = [
+            0x95, 0x36, 0xf9, 0xb2, 0x67, 0x65, 0x57, 0x43, 0xde, 0xe9, 0x7b, 0x8a, 0x67, 0x0f,
+            0x9f, 0x53,
+        ];
+        assert_eq!(super::mac128(&key, &[]), expected);
     }
 }
 
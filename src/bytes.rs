@@ -0,0 +1,51 @@
+//! Byte order helpers to read and write integers from and to byte slices
+//!
+//! These are the helpers used internally by the primitives of this crate,
+//! exposed for building custom constructions on top of them.
+//!
+//! All the functions panic if the sizes of the slices don't match exactly
+//! the number of bytes of the integers read or written.
+//!
+//! # Examples
+//!
+//! Writing and reading back a single integer:
+//!
+//! ```
+//! use cryptoxide::bytes::{read_u32_be, read_u32_le, write_u32_be, write_u32_le};
+//!
+//! let mut buf = [0u8; 4];
+//! write_u32_be(&mut buf, 0x01020304);
+//! assert_eq!(buf, [1, 2, 3, 4]);
+//! assert_eq!(read_u32_be(&buf), 0x01020304);
+//!
+//! write_u32_le(&mut buf, 0x01020304);
+//! assert_eq!(buf, [4, 3, 2, 1]);
+//! assert_eq!(read_u32_le(&buf), 0x01020304);
+//! ```
+//!
+//! Writing and reading back arrays of integers:
+//!
+//! ```
+//! use cryptoxide::bytes::{read_u64v_le, write_u64v_le, read_u32v_be, write_u32v_be};
+//!
+//! let words = [0x0102030405060708u64, 0x1112131415161718];
+//! let mut buf = [0u8; 16];
+//! write_u64v_le(&mut buf, &words);
+//! assert_eq!(buf[0..8], [8, 7, 6, 5, 4, 3, 2, 1]);
+//! let mut read = [0u64; 2];
+//! read_u64v_le(&mut read, &buf);
+//! assert_eq!(read, words);
+//!
+//! let words = [0xdeadbeefu32, 0x01234567, 0x89abcdef, 0];
+//! write_u32v_be(&mut buf, &words);
+//! assert_eq!(buf[0..4], [0xde, 0xad, 0xbe, 0xef]);
+//! let mut read = [0u32; 4];
+//! read_u32v_be(&mut read, &buf);
+//! assert_eq!(read, words);
+//! ```
+
+pub use crate::cryptoutil::{
+    copy_memory, read_u32_be, read_u32_le, read_u32v_be, read_u32v_le, read_u64v_be, read_u64v_le,
+    write_u128_be, write_u32_be, write_u32_le, write_u32v_be, write_u32v_le, write_u64_be,
+    write_u64_le, write_u64v_be, write_u64v_le,
+};
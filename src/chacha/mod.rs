@@ -1,23 +1,41 @@
+use crate::cpu::Backend;
+
 #[cfg(not(all(
+    not(feature = "force-portable"),
     any(target_arch = "x86", target_arch = "x86_64"),
     any(target_feature = "sse2", target_feature = "avx2")
 )))]
 mod reference;
 
 #[cfg(not(all(
+    not(feature = "force-portable"),
     any(target_arch = "x86", target_arch = "x86_64"),
     any(target_feature = "sse2", target_feature = "avx2")
 )))]
 pub(crate) type ChaChaEngine = reference::State;
+#[cfg(not(all(
+    not(feature = "force-portable"),
+    any(target_arch = "x86", target_arch = "x86_64"),
+    any(target_feature = "sse2", target_feature = "avx2")
+)))]
+pub(crate) const BACKEND: Backend = Backend::Portable;
 
 #[cfg(all(
+    not(feature = "force-portable"),
     any(target_arch = "x86", target_arch = "x86_64"),
     target_feature = "sse2",
 ))]
 mod sse2;
 
 #[cfg(all(
+    not(feature = "force-portable"),
     any(target_arch = "x86", target_arch = "x86_64"),
     target_feature = "sse2",
 ))]
 pub(crate) type ChaChaEngine = sse2::State;
+#[cfg(all(
+    not(feature = "force-portable"),
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse2",
+))]
+pub(crate) const BACKEND: Backend = Backend::Sse2;
@@ -172,7 +172,11 @@ impl State {
 
     #[inline]
     pub(crate) fn increment(&mut self) {
-        self.d = self.d + u32x4(1, 0, 0, 0);
+        let (a, overflowed) = self.d.0.overflowing_add(1);
+        self.d.0 = a;
+        if overflowed {
+            self.d.1 = self.d.1.wrapping_add(1);
+        }
     }
 
     #[inline]
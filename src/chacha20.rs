@@ -3,8 +3,12 @@
 //! Implementation of [ChaCha spec](https://cr.yp.to/chacha/chacha-20080128.pdf),
 //! which is a fast and lean stream cipher.
 //!
-//! Along with the standard ChaCha20, there is support for the
-//! XChaCha20 variant with extended nonce.
+//! [`ChaCha20::new`] takes a 16 or 32 bytes key, and either a 12 bytes nonce
+//! with a 32 bits block counter as specified by RFC 8439, or a 8 bytes nonce
+//! with a 64 bits block counter as in the original design, also available as
+//! [`ChaCha20::new_djb`]. [`ChaCha20::new_xchacha20`] is the XChaCha20 variant
+//! with a 24 bytes nonce, large enough to be picked at random, which derives
+//! a subkey from the first 16 bytes of the nonce with [`hchacha20`].
 //!
 //! Note that with stream cipher, there's only one operation [`ChaCha20::process`]
 //! instead of the typical encrypt and decrypt.
@@ -58,6 +62,19 @@ impl ChaCha20 {
         }
     }
 
+    /// Create a new ChaCha20 context for the original variant of the cipher,
+    /// with a 64 bits nonce and a 64 bits block counter
+    ///
+    /// This is the variant used by protocols predating RFC8439, which
+    /// changed the nonce to 96 bits and the counter to 32 bits. The counter
+    /// allows to process up to 2^64 blocks of 64 bytes with the same nonce.
+    ///
+    /// This is the same as calling [`ChaCha20::new`] with a 32 bytes key
+    /// and a 8 bytes nonce.
+    pub fn new_djb(key: &[u8; 32], nonce: &[u8; 8]) -> ChaCha20 {
+        ChaCha20::new(key, nonce)
+    }
+
     /// Create a new XChaCha20 context.
     ///
     /// Key must be 32 bytes and the nonce 24 bytes.
@@ -65,38 +82,19 @@ impl ChaCha20 {
         assert!(key.len() == 32);
         assert!(nonce.len() == 24);
 
-        // HChaCha20 produces a 256-bit output block starting from a 512 bit
-        // input block where (x0,x1,...,x15) where
-        //
-        //  * (x0, x1, x2, x3) is the ChaCha20 constant.
-        //  * (x4, x5, ... x11) is a 256 bit key.
-        //  * (x12, x13, x14, x15) is a 128 bit nonce.
-        let mut xchacha20 = ChaCha20 {
-            state: ChaChaState::init(key, &nonce[0..16]),
-            output: [0u8; 64],
-            offset: 64,
-        };
-
         // Use HChaCha to derive the subkey, and initialize a ChaCha20 instance
         // with the subkey and the remaining 8 bytes of the nonce.
-        let mut new_key = [0; 32];
-        xchacha20.hchacha20(&mut new_key);
-        xchacha20.state = ChaChaState::init(&new_key, &nonce[16..24]);
-
-        xchacha20
-    }
-
-    fn hchacha20(&mut self, out: &mut [u8; 32]) {
-        let mut state = self.state.clone();
+        let mut key32 = [0; 32];
+        key32.copy_from_slice(key);
+        let mut nonce16 = [0; 16];
+        nonce16.copy_from_slice(&nonce[0..16]);
+        let new_key = hchacha20(&key32, &nonce16);
 
-        // Apply r/2 iterations of the same "double-round" function,
-        // obtaining (z0, z1, ... z15) = doubleround r/2 (x0, x1, ... x15).
-        state.round20();
-
-        // HChaCha20 then outputs the 256-bit block (z0, z1, z2, z3, z12, z13,
-        // z14, z15).  These correspond to the constant and input positions in
-        // the ChaCha matrix.
-        state.output_ad_bytes(out)
+        ChaCha20 {
+            state: ChaChaState::init(&new_key, &nonce[16..24]),
+            output: [0u8; 64],
+            offset: 64,
+        }
     }
 
     // put the the next 64 keystream bytes into self.output
@@ -160,12 +158,41 @@ impl ChaCha20 {
     }
 }
 
+/// HChaCha20 function, deriving a 32 bytes subkey from a key and a 16 bytes nonce
+///
+/// The 20 rounds of ChaCha are applied to the key and the nonce, and the
+/// first and last rows of the state are returned, without the feed-forward
+/// addition of the input. This is how XChaCha20 derives its subkey.
+///
+/// HChaCha20 is a PRF keyed by `key`, not a hash function: its output is
+/// only pseudorandom for a secret and uniformly random key.
+pub fn hchacha20(key: &[u8; 32], nonce: &[u8; 16]) -> [u8; 32] {
+    // HChaCha20 produces a 256-bit output block starting from a 512 bit
+    // input block where (x0,x1,...,x15) where
+    //
+    //  * (x0, x1, x2, x3) is the ChaCha20 constant.
+    //  * (x4, x5, ... x11) is a 256 bit key.
+    //  * (x12, x13, x14, x15) is a 128 bit nonce.
+    let mut state = ChaChaState::init(key, nonce);
+
+    // Apply r/2 iterations of the same "double-round" function,
+    // obtaining (z0, z1, ... z15) = doubleround r/2 (x0, x1, ... x15).
+    state.round20();
+
+    // HChaCha20 then outputs the 256-bit block (z0, z1, z2, z3, z12, z13,
+    // z14, z15).  These correspond to the constant and input positions in
+    // the ChaCha matrix.
+    let mut out = [0u8; 32];
+    state.output_ad_bytes(&mut out);
+    out
+}
+
 #[cfg(test)]
 mod test {
     use std::iter::repeat;
     use std::vec::Vec;
 
-    use super::ChaCha20;
+    use super::{hchacha20, ChaCha20, ChaChaState};
 
     #[test]
     fn test_chacha20_256_tls_vectors() {
@@ -277,6 +304,76 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_chacha20_djb() {
+        // last vector of test_chacha20_256_tls_vectors
+        let key = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b,
+            0x1c, 0x1d, 0x1e, 0x1f,
+        ];
+        let nonce = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07];
+        let mut stream = [0u8; 16];
+        ChaCha20::new_djb(&key, &nonce).process_mut(&mut stream);
+        assert_eq!(
+            stream,
+            [
+                0xf7, 0x98, 0xa1, 0x89, 0xf1, 0x95, 0xe6, 0x69, 0x82, 0x10, 0x5f, 0xfb, 0x64, 0x0b,
+                0xb7, 0x75
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chacha20_djb_counter_carry() {
+        let key = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b,
+            0x1c, 0x1d, 0x1e, 0x1f,
+        ];
+        // block counter of 2^32 - 1, followed by the nonce
+        let counter_nonce = [
+            0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05,
+            0x06, 0x07,
+        ];
+        let mut c = ChaCha20 {
+            state: ChaChaState::init(&key, &counter_nonce),
+            output: [0u8; 64],
+            offset: 64,
+        };
+        let mut stream = [0u8; 128];
+        c.process_mut(&mut stream);
+        // the block of counter 2^32 with a 64 bits counter
+        let expected = [
+            0x2f, 0xca, 0xb2, 0xc0, 0x9a, 0x96, 0x05, 0x45, 0xc6, 0xf5, 0x7e, 0x92, 0x69, 0xeb,
+            0xc2, 0x2b, 0x4e, 0xd1, 0x27, 0x82, 0xe6, 0x6d, 0xc4, 0xcb, 0x61, 0x25, 0x36, 0xf5,
+            0xcd, 0xbe, 0xd4, 0xbc, 0xba, 0x16, 0xaf, 0x8a, 0x92, 0x14, 0x0b, 0xf4, 0xde, 0xd4,
+            0x80, 0x8a, 0xf8, 0xee, 0xe8, 0x2b, 0xd0, 0xf1, 0x8f, 0xbb, 0x64, 0xf0, 0x73, 0xc2,
+            0xa5, 0x47, 0xbc, 0x23, 0x72, 0x52, 0x8f, 0x36,
+        ];
+        assert_eq!(&stream[64..], &expected[..]);
+    }
+
+    #[test]
+    fn test_hchacha20() {
+        // draft-irtf-cfrg-xchacha-03 section 2.2.1
+        let key = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b,
+            0x1c, 0x1d, 0x1e, 0x1f,
+        ];
+        let nonce = [
+            0x00, 0x00, 0x00, 0x09, 0x00, 0x00, 0x00, 0x4a, 0x00, 0x00, 0x00, 0x00, 0x31, 0x41,
+            0x59, 0x27,
+        ];
+        let expected = [
+            0x82, 0x41, 0x3b, 0x42, 0x27, 0xb2, 0x7b, 0xfe, 0xd3, 0x0e, 0x42, 0x50, 0x8a, 0x87,
+            0x7d, 0x73, 0xa0, 0xf9, 0xe4, 0xd5, 0x8a, 0x74, 0xa8, 0x53, 0xc1, 0x2e, 0xc4, 0x13,
+            0x26, 0xd3, 0xec, 0xdc,
+        ];
+        assert_eq!(hchacha20(&key, &nonce), expected);
+    }
+
     #[test]
     fn test_xchacha20_basic() {
         // There aren't any convenient test vectors for XChaCha/20,
@@ -2,10 +2,13 @@
 //!
 //! the specification of chacha20poly1305 is available at [RFC8439][1] and it follows general principle related to [AEAD][2].
 //!
-//! This module provides 2 interfaces:
+//! This module provides 3 interfaces:
 //!
 //! * the one shot interface [`ChaCha20Poly1305`]
-//! * the incremental interfaces, using [`Context`], [`ContextEncryption`] and [`ContextDecryption`]
+//! * the incremental interfaces, using [`Context`], [`ContextEncryption`] and [`ContextDecryption`],
+//!   also created directly with [`ChaCha20Poly1305::encryptor`] and [`ChaCha20Poly1305::decryptor`]
+//! * the allocating functions returning the ciphertext and the tag separately,
+//!   [`seal_detached`] and [`open_detached`] (requires the `alloc` feature)
 //!
 //! The incremental interfaces should be used when you are streaming data or that
 //! you need more control over the memory usage, as the one-shot interface
@@ -69,9 +72,15 @@
 
 use crate::chacha20::ChaCha20;
 use crate::cryptoutil::write_u64_le;
-use crate::mac::Mac;
+use crate::mac::OneTimeMac;
 use crate::poly1305::Poly1305;
 use crate::util::fixed_time_eq;
+#[cfg(feature = "alloc")]
+use crate::util::secure_memset;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::array::TryFromSliceError;
+use core::convert::TryFrom;
 
 /// Chacha20Poly1305 Incremental Context for Authenticated Data (AAD)
 ///
@@ -107,6 +116,7 @@ pub struct Context {
     mac: Poly1305,
     aad_len: u64,
     data_len: u64,
+    data_started: bool,
 }
 
 /// ChaCha20Poly1305 Incremental Context for encryption
@@ -129,6 +139,40 @@ impl PartialEq for Tag {
 
 impl Eq for Tag {}
 
+impl From<[u8; 16]> for Tag {
+    fn from(bytes: [u8; 16]) -> Self {
+        Tag(bytes)
+    }
+}
+
+impl TryFrom<&[u8]> for Tag {
+    type Error = TryFromSliceError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        <[u8; 16]>::try_from(bytes).map(Tag)
+    }
+}
+
+impl AsRef<[u8]> for Tag {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Error returned when adding authenticated data to a streaming context
+/// that has already processed some data
+///
+/// The authenticated data precedes the encrypted data in the tag computation,
+/// so it cannot be added anymore once the encryption or decryption has started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AadAfterData;
+
+impl core::fmt::Display for AadAfterData {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "authenticated data added after the encrypted data")
+    }
+}
+
 impl Context {
     /// Create a new context given the key and nonce.
     ///
@@ -142,21 +186,53 @@ impl Context {
     pub fn new(key: &[u8], nonce: &[u8]) -> Self {
         assert!(key.len() == 16 || key.len() == 32);
         assert!(nonce.len() == 8 || nonce.len() == 12);
-        let mut cipher = ChaCha20::new(key, nonce);
+        Self::from_cipher(ChaCha20::new(key, nonce))
+    }
+
+    /// Create a new XChaCha20-Poly1305 context given the key and the 24 bytes extended nonce.
+    ///
+    /// The extended nonce is large enough to be chosen at random for each message,
+    /// see [`random_nonce`](crate::xchacha20::random_nonce). This follows
+    /// draft-irtf-cfrg-xchacha, with the cipher created by [`ChaCha20::new_xchacha20`].
+    pub fn new_xchacha20(key: &[u8; 32], nonce: &[u8; 24]) -> Self {
+        Self::from_cipher(ChaCha20::new_xchacha20(key, nonce))
+    }
+
+    fn from_cipher(mut cipher: ChaCha20) -> Self {
         let mut mac_key = [0u8; 64];
         let zero_key = [0u8; 64];
         cipher.process(&zero_key, &mut mac_key);
 
-        let mac = Poly1305::new(&mac_key[..32]);
+        let mut poly_key = [0u8; 32];
+        poly_key.copy_from_slice(&mac_key[..32]);
+        let mac = Poly1305::new(poly_key);
         Context {
             cipher: cipher,
             mac: mac,
             aad_len: 0,
             data_len: 0,
+            data_started: false,
+        }
+    }
+
+    // close the authenticated data part, on the first data processed
+    fn start_data(&mut self) {
+        if !self.data_started {
+            pad16(&mut self.mac, self.aad_len);
+            self.data_started = true;
+        }
+    }
+
+    fn update_aad(&mut self, aad: &[u8]) -> Result<(), AadAfterData> {
+        if self.data_started {
+            return Err(AadAfterData);
         }
+        self.add_data(aad);
+        Ok(())
     }
 
     fn add_encrypted(&mut self, encrypted: &[u8]) {
+        self.start_data();
         self.mac.input(encrypted);
         self.data_len += encrypted.len() as u64;
     }
@@ -169,30 +245,42 @@ impl Context {
         self.mac.input(aad);
     }
 
-    /// Finish authenticated part and move to the encryption phase
-    pub fn to_encryption(mut self) -> ContextEncryption {
-        pad16(&mut self.mac, self.aad_len);
+    /// Move to the encryption phase
+    ///
+    /// More authenticated data can still be added with
+    /// [`ContextEncryption::update_aad`] until the first data is encrypted.
+    pub fn to_encryption(self) -> ContextEncryption {
         ContextEncryption(self)
     }
 
-    /// Finish authenticated part and move to the decryption phase
-    pub fn to_decryption(mut self) -> ContextDecryption {
-        pad16(&mut self.mac, self.aad_len);
+    /// Move to the decryption phase
+    ///
+    /// More authenticated data can still be added with
+    /// [`ContextDecryption::update_aad`] until the first data is decrypted.
+    pub fn to_decryption(self) -> ContextDecryption {
         ContextDecryption(self)
     }
 }
 
-fn finalize_raw(inner: &mut Context) -> [u8; 16] {
+fn finalize_raw(mut inner: Context) -> [u8; 16] {
     let mut len_buf = [0u8; 16];
+    inner.start_data();
     pad16(&mut inner.mac, inner.data_len);
     write_u64_le(&mut len_buf[0..8], inner.aad_len);
     write_u64_le(&mut len_buf[8..16], inner.data_len);
     inner.mac.input(&len_buf);
-    inner.mac.raw_result(&mut len_buf);
-    len_buf
+    OneTimeMac::finalize(inner.mac)
 }
 
 impl ContextEncryption {
+    /// Add authenticated data, before any data is encrypted
+    ///
+    /// Returns an error, without changing the context, if the encryption
+    /// has already started.
+    pub fn update_aad(&mut self, aad: &[u8]) -> Result<(), AadAfterData> {
+        self.0.update_aad(aad)
+    }
+
     /// Encrypt input in place
     pub fn encrypt_mut(&mut self, buf: &mut [u8]) {
         self.0.cipher.process_mut(buf);
@@ -214,8 +302,8 @@ impl ContextEncryption {
 
     /// Finalize the encryption context and return the tag
     #[must_use]
-    pub fn finalize(mut self) -> Tag {
-        let tag = finalize_raw(&mut self.0);
+    pub fn finalize(self) -> Tag {
+        let tag = finalize_raw(self.0);
         Tag(tag)
     }
 }
@@ -228,6 +316,14 @@ pub enum DecryptionResult {
 }
 
 impl ContextDecryption {
+    /// Add authenticated data, before any data is decrypted
+    ///
+    /// Returns an error, without changing the context, if the decryption
+    /// has already started.
+    pub fn update_aad(&mut self, aad: &[u8]) -> Result<(), AadAfterData> {
+        self.0.update_aad(aad)
+    }
+
     /// Decrypt input in place
     pub fn decrypt_mut(&mut self, buf: &mut [u8]) {
         self.0.add_encrypted(buf);
@@ -247,8 +343,8 @@ impl ContextDecryption {
     /// Finalize the decryption context and check that the tag match the expected value
     ///
     #[must_use = "if the result is not checked, then the data will not be verified against tempering"]
-    pub fn finalize(mut self, expected_tag: &Tag) -> DecryptionResult {
-        let got_tag = Tag(finalize_raw(&mut self.0));
+    pub fn finalize(self, expected_tag: &Tag) -> DecryptionResult {
+        let got_tag = Tag(finalize_raw(self.0));
         if &got_tag == expected_tag {
             DecryptionResult::Match
         } else {
@@ -287,6 +383,63 @@ impl ChaCha20Poly1305 {
         }
     }
 
+    /// Create a new XChaCha20-Poly1305, with a 32 bytes key and a 24 bytes extended nonce
+    ///
+    /// ```
+    /// use cryptoxide::xchacha20::random_nonce;
+    /// use cryptoxide::chacha20poly1305::ChaCha20Poly1305;
+    ///
+    /// let key = [1u8; 32];
+    /// // use the operating system RNG here, this is only for example !
+    /// let nonce = random_nonce(|buf| buf.fill(0x42));
+    /// let mut out = [0u8; 5];
+    /// let mut tag = [0u8; 16];
+    /// ChaCha20Poly1305::new_xchacha20(&key, &nonce, b"").encrypt(b"hello", &mut out, &mut tag);
+    /// ```
+    pub fn new_xchacha20(key: &[u8; 32], nonce: &[u8; 24], aad: &[u8]) -> ChaCha20Poly1305 {
+        let mut context = Context::new_xchacha20(key, nonce);
+        context.add_data(aad);
+        ChaCha20Poly1305 {
+            context: context,
+            finished: false,
+        }
+    }
+
+    /// Create an incremental encryption context, to encrypt a message chunk by chunk
+    ///
+    /// This is the same as creating a [`Context`], adding the authenticated data
+    /// and calling [`Context::to_encryption`]. The ciphertext and the tag are the
+    /// same as the one-shot [`ChaCha20Poly1305::encrypt`] of the whole message.
+    ///
+    /// ```
+    /// use cryptoxide::chacha20poly1305::ChaCha20Poly1305;
+    ///
+    /// let key = [1u8; 32];
+    /// let nonce = [2u8; 12];
+    /// let mut out = [0u8; 11];
+    ///
+    /// let mut encryptor = ChaCha20Poly1305::encryptor(&key, &nonce, b"header");
+    /// encryptor.encrypt(b"hello", &mut out[0..5]);
+    /// encryptor.encrypt(b" world", &mut out[5..11]);
+    /// let tag = encryptor.finalize();
+    /// ```
+    pub fn encryptor(key: &[u8], nonce: &[u8], aad: &[u8]) -> ContextEncryption {
+        let mut context = Context::new(key, nonce);
+        context.add_data(aad);
+        context.to_encryption()
+    }
+
+    /// Create an incremental decryption context, to decrypt a message chunk by chunk
+    ///
+    /// The tag is checked in constant time by [`ContextDecryption::finalize`],
+    /// once all the chunks have been decrypted: the decrypted data must not be
+    /// used before the tag has been found to match.
+    pub fn decryptor(key: &[u8], nonce: &[u8], aad: &[u8]) -> ContextDecryption {
+        let mut context = Context::new(key, nonce);
+        context.add_data(aad);
+        context.to_decryption()
+    }
+
     /// Encrypt input buffer to output buffer, and write an authenticated tag to out_tag.
     ///
     /// Output buffer need to be the same size as the input buffer
@@ -375,9 +528,117 @@ impl ChaCha20Poly1305 {
     }
 }
 
+#[cfg(feature = "alloc")]
+fn seal_with(context: Context, plaintext: &[u8]) -> (Vec<u8>, [u8; 16]) {
+    let mut ciphertext = plaintext.to_vec();
+    let mut ctx = context.to_encryption();
+    ctx.encrypt_mut(&mut ciphertext);
+    let Tag(tag) = ctx.finalize();
+    (ciphertext, tag)
+}
+
+#[cfg(feature = "alloc")]
+fn open_with(context: Context, ciphertext: &[u8], tag: &[u8; 16]) -> Option<Vec<u8>> {
+    let mut plaintext = ciphertext.to_vec();
+    let mut ctx = context.to_decryption();
+    ctx.decrypt_mut(&mut plaintext);
+    match ctx.finalize(&Tag(*tag)) {
+        DecryptionResult::Match => Some(plaintext),
+        DecryptionResult::MisMatch => {
+            secure_memset(&mut plaintext, 0);
+            None
+        }
+    }
+}
+
+/// Encrypt the plaintext, returning the ciphertext and the tag separately
+///
+/// The ciphertext and the tag are the same as the ones of
+/// [`ChaCha20Poly1305::encrypt`], for wire formats storing the tag apart from
+/// the ciphertext, like libsodium's `crypto_aead_chacha20poly1305_ietf_encrypt_detached`.
+///
+/// * key needs to be 16 or 32 bytes
+/// * nonce needs to be 8 or 12 bytes
+///
+/// ```
+/// use cryptoxide::chacha20poly1305::{open_detached, seal_detached};
+///
+/// let key = [1u8; 32];
+/// let nonce = [2u8; 12];
+/// let (ciphertext, tag) = seal_detached(&key, &nonce, b"header", b"hello world");
+/// let plaintext = open_detached(&key, &nonce, b"header", &ciphertext, &tag);
+/// assert_eq!(plaintext.as_deref(), Some(&b"hello world"[..]));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn seal_detached(
+    key: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> (Vec<u8>, [u8; 16]) {
+    let mut context = Context::new(key, nonce);
+    context.add_data(aad);
+    seal_with(context, plaintext)
+}
+
+/// Decrypt a ciphertext with a separate tag, as produced by [`seal_detached`]
+///
+/// Returns `None` if the tag doesn't match, in which case no decrypted data
+/// is returned.
+///
+/// * key needs to be 16 or 32 bytes
+/// * nonce needs to be 8 or 12 bytes
+#[cfg(feature = "alloc")]
+pub fn open_detached(
+    key: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8; 16],
+) -> Option<Vec<u8>> {
+    let mut context = Context::new(key, nonce);
+    context.add_data(aad);
+    open_with(context, ciphertext, tag)
+}
+
+/// XChaCha20-Poly1305 version of [`seal_detached`], with a 24 bytes extended nonce
+///
+/// Same as libsodium's `crypto_aead_xchacha20poly1305_ietf_encrypt_detached`.
+#[cfg(feature = "alloc")]
+pub fn xchacha20_seal_detached(
+    key: &[u8; 32],
+    nonce: &[u8; 24],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> (Vec<u8>, [u8; 16]) {
+    let mut context = Context::new_xchacha20(key, nonce);
+    context.add_data(aad);
+    seal_with(context, plaintext)
+}
+
+/// XChaCha20-Poly1305 version of [`open_detached`], with a 24 bytes extended nonce
+#[cfg(feature = "alloc")]
+pub fn xchacha20_open_detached(
+    key: &[u8; 32],
+    nonce: &[u8; 24],
+    aad: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8; 16],
+) -> Option<Vec<u8>> {
+    let mut context = Context::new_xchacha20(key, nonce);
+    context.add_data(aad);
+    open_with(context, ciphertext, tag)
+}
+
 #[cfg(test)]
 mod test {
-    use super::ChaCha20Poly1305;
+    use super::{
+        open_detached, seal_detached, xchacha20_open_detached, xchacha20_seal_detached,
+        AadAfterData, ChaCha20Poly1305, Context, DecryptionResult, Tag,
+    };
+    use crate::chacha20::ChaCha20;
+    use crate::xchacha20::random_nonce;
+    use core::convert::TryFrom;
     use std::vec::Vec;
 
     struct TestVector {
@@ -521,6 +782,230 @@ mod test {
             test_vector(&tv)
         }
     }
+
+    #[test]
+    fn xchacha20poly1305() {
+        // draft-irtf-cfrg-xchacha-03 section A.3.1
+        let key: [u8; 32] = core::array::from_fn(|i| 0x80 + i as u8);
+        let nonce: [u8; 24] = core::array::from_fn(|i| 0x40 + i as u8);
+        let aad = [
+            0x50, 0x51, 0x52, 0x53, 0xc0, 0xc1, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc7,
+        ];
+        let plain_text = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+        let cipher_text = [
+            0xbd, 0x6d, 0x17, 0x9d, 0x3e, 0x83, 0xd4, 0x3b, 0x95, 0x76, 0x57, 0x94, 0x93, 0xc0,
+            0xe9, 0x39, 0x57, 0x2a, 0x17, 0x00, 0x25, 0x2b, 0xfa, 0xcc, 0xbe, 0xd2, 0x90, 0x2c,
+            0x21, 0x39, 0x6c, 0xbb, 0x73, 0x1c, 0x7f, 0x1b, 0x0b, 0x4a, 0xa6, 0x44, 0x0b, 0xf3,
+            0xa8, 0x2f, 0x4e, 0xda, 0x7e, 0x39, 0xae, 0x64, 0xc6, 0x70, 0x8c, 0x54, 0xc2, 0x16,
+            0xcb, 0x96, 0xb7, 0x2e, 0x12, 0x13, 0xb4, 0x52, 0x2f, 0x8c, 0x9b, 0xa4, 0x0d, 0xb5,
+            0xd9, 0x45, 0xb1, 0x1b, 0x69, 0xb9, 0x82, 0xc1, 0xbb, 0x9e, 0x3f, 0x3f, 0xac, 0x2b,
+            0xc3, 0x69, 0x48, 0x8f, 0x76, 0xb2, 0x38, 0x35, 0x65, 0xd3, 0xff, 0xf9, 0x21, 0xf9,
+            0x66, 0x4c, 0x97, 0x63, 0x7d, 0xa9, 0x76, 0x88, 0x12, 0xf6, 0x15, 0xc6, 0x8b, 0x13,
+            0xb5, 0x2e,
+        ];
+        let tag = [
+            0xc0, 0x87, 0x59, 0x24, 0xc1, 0xc7, 0x98, 0x79, 0x47, 0xde, 0xaf, 0xd8, 0x78, 0x0a,
+            0xcf, 0x49,
+        ];
+
+        let mut output = [0u8; 114];
+        let mut out_tag = [0u8; 16];
+        ChaCha20Poly1305::new_xchacha20(&key, &nonce, &aad).encrypt(
+            plain_text,
+            &mut output,
+            &mut out_tag,
+        );
+        assert_eq!(&output[..], &cipher_text[..]);
+        assert_eq!(out_tag, tag);
+
+        let mut decrypted = [0u8; 114];
+        assert!(ChaCha20Poly1305::new_xchacha20(&key, &nonce, &aad).decrypt(
+            &cipher_text,
+            &mut decrypted,
+            &tag
+        ));
+        assert_eq!(&decrypted[..], &plain_text[..]);
+    }
+
+    #[test]
+    fn xchacha20poly1305_random_nonce() {
+        // deterministic stand-in for a random number generator
+        let mut rng = ChaCha20::new(&[7u8; 32], &[0u8; 12]);
+        let nonce1 = random_nonce(|buf| rng.process_mut(buf));
+        let nonce2 = random_nonce(|buf| rng.process_mut(buf));
+        assert_ne!(nonce1, nonce2);
+
+        let key = [0x42u8; 32];
+        let plain = b"sealed with a random nonce";
+        let mut sealed = [0u8; 26];
+        let mut tag = [0u8; 16];
+        ChaCha20Poly1305::new_xchacha20(&key, &nonce1, b"aad").encrypt(
+            plain,
+            &mut sealed,
+            &mut tag,
+        );
+
+        let mut opened = [0u8; 26];
+        assert!(
+            ChaCha20Poly1305::new_xchacha20(&key, &nonce1, b"aad").decrypt(
+                &sealed,
+                &mut opened,
+                &tag
+            )
+        );
+        assert_eq!(&opened, plain);
+        assert!(
+            !ChaCha20Poly1305::new_xchacha20(&key, &nonce2, b"aad").decrypt(
+                &sealed,
+                &mut opened,
+                &tag
+            )
+        );
+    }
+
+    #[test]
+    fn streaming() {
+        let key = [0x42u8; 32];
+        let nonce = [0x24u8; 12];
+        let aad = b"streamed header";
+        let plain: Vec<u8> = (0..1000u32).map(|i| (i * 7) as u8).collect();
+
+        let mut expected = vec![0u8; plain.len()];
+        let mut expected_tag = [0u8; 16];
+        ChaCha20Poly1305::new(&key, &nonce, aad).encrypt(&plain, &mut expected, &mut expected_tag);
+
+        for chunk_size in [1, 15, 16, 64, 100, 1000].iter() {
+            let mut encryptor = ChaCha20Poly1305::encryptor(&key, &nonce, aad);
+            let mut cipher = vec![0u8; plain.len()];
+            for (input, output) in plain
+                .chunks(*chunk_size)
+                .zip(cipher.chunks_mut(*chunk_size))
+            {
+                encryptor.encrypt(input, output);
+            }
+            let tag = encryptor.finalize();
+            assert_eq!(cipher, expected);
+            assert_eq!(tag.0, expected_tag);
+
+            let mut decryptor = ChaCha20Poly1305::decryptor(&key, &nonce, aad);
+            let mut decrypted = cipher.clone();
+            for chunk in decrypted.chunks_mut(*chunk_size) {
+                decryptor.decrypt_mut(chunk);
+            }
+            assert_eq!(decryptor.finalize(&tag), DecryptionResult::Match);
+            assert_eq!(decrypted, plain);
+        }
+
+        // tampered chunk
+        let mut tampered = expected.clone();
+        tampered[500] ^= 1;
+        let mut decryptor = ChaCha20Poly1305::decryptor(&key, &nonce, aad);
+        let mut output = vec![0u8; tampered.len()];
+        for (input, output) in tampered.chunks(64).zip(output.chunks_mut(64)) {
+            decryptor.decrypt(input, output);
+        }
+        assert_eq!(
+            decryptor.finalize(&Tag(expected_tag)),
+            DecryptionResult::MisMatch
+        );
+    }
+
+    #[test]
+    fn tag_conversions() {
+        let tag = Tag::from([3u8; 16]);
+        assert_eq!(tag, Tag([3u8; 16]));
+        assert_eq!(tag.as_ref(), &[3u8; 16][..]);
+        assert_eq!(Tag::try_from(&[3u8; 16][..]).unwrap(), tag);
+        assert!(Tag::try_from(&[3u8; 15][..]).is_err());
+        assert!(Tag::try_from(&[3u8; 17][..]).is_err());
+    }
+
+    #[test]
+    fn streaming_aad() {
+        let key = [0x42u8; 32];
+        let nonce = [0x24u8; 12];
+        let aad = b"streamed header, in several parts";
+        let plain = b"streamed data";
+
+        let mut expected = [0u8; 13];
+        let mut expected_tag = [0u8; 16];
+        ChaCha20Poly1305::new(&key, &nonce, aad).encrypt(plain, &mut expected, &mut expected_tag);
+
+        let mut encryptor = ChaCha20Poly1305::encryptor(&key, &nonce, &aad[0..9]);
+        encryptor.update_aad(&aad[9..20]).unwrap();
+        encryptor.update_aad(&aad[20..]).unwrap();
+        let mut cipher = [0u8; 13];
+        encryptor.encrypt(&plain[0..4], &mut cipher[0..4]);
+        assert_eq!(encryptor.update_aad(b"late"), Err(AadAfterData));
+        encryptor.encrypt(&plain[4..], &mut cipher[4..]);
+        let tag = encryptor.finalize();
+        assert_eq!(cipher, expected);
+        assert_eq!(tag.0, expected_tag);
+
+        let mut decryptor = Context::new(&key, &nonce).to_decryption();
+        decryptor.update_aad(&aad[0..20]).unwrap();
+        decryptor.update_aad(&aad[20..]).unwrap();
+        decryptor.decrypt_mut(&mut cipher[0..10]);
+        assert_eq!(decryptor.update_aad(b"late"), Err(AadAfterData));
+        decryptor.decrypt_mut(&mut cipher[10..]);
+        assert_eq!(decryptor.finalize(&tag), DecryptionResult::Match);
+        assert_eq!(&cipher, plain);
+
+        // the authenticated data only, without any encrypted data
+        let mut expected_tag = [0u8; 16];
+        ChaCha20Poly1305::new(&key, &nonce, aad).encrypt(&[], &mut [], &mut expected_tag);
+        let mut encryptor = ChaCha20Poly1305::encryptor(&key, &nonce, &[]);
+        encryptor.update_aad(aad).unwrap();
+        assert_eq!(encryptor.finalize().0, expected_tag);
+    }
+
+    #[test]
+    fn detached_matches_combined() {
+        let key = [0x42u8; 32];
+        let nonce = [0x24u8; 12];
+        let aad = b"header";
+        let plain: Vec<u8> = (0..100u32).map(|i| (i * 3) as u8).collect();
+
+        let mut combined = vec![0u8; plain.len() + 16];
+        let (cipher, tag) = combined.split_at_mut(plain.len());
+        ChaCha20Poly1305::new(&key, &nonce, aad).encrypt(&plain, cipher, tag);
+
+        let (ciphertext, tag) = seal_detached(&key, &nonce, aad, &plain);
+        assert_eq!(&combined[..plain.len()], &ciphertext[..]);
+        assert_eq!(&combined[plain.len()..], &tag[..]);
+        assert_eq!(
+            open_detached(&key, &nonce, aad, &ciphertext, &tag),
+            Some(plain.clone())
+        );
+
+        let mut bad_tag = tag;
+        bad_tag[0] ^= 1;
+        assert_eq!(
+            open_detached(&key, &nonce, aad, &ciphertext, &bad_tag),
+            None
+        );
+        assert_eq!(
+            open_detached(&key, &nonce, b"other", &ciphertext, &tag),
+            None
+        );
+
+        let xnonce = [0x07u8; 24];
+        let mut combined = vec![0u8; plain.len() + 16];
+        let (cipher, tag) = combined.split_at_mut(plain.len());
+        ChaCha20Poly1305::new_xchacha20(&key, &xnonce, aad).encrypt(&plain, cipher, tag);
+
+        let (ciphertext, tag) = xchacha20_seal_detached(&key, &xnonce, aad, &plain);
+        assert_eq!(&combined[..plain.len()], &ciphertext[..]);
+        assert_eq!(&combined[plain.len()..], &tag[..]);
+        assert_eq!(
+            xchacha20_open_detached(&key, &xnonce, aad, &ciphertext, &tag),
+            Some(plain)
+        );
+        assert_eq!(
+            xchacha20_open_detached(&key, &xnonce, aad, &ciphertext[1..], &tag),
+            None
+        );
+    }
 }
 
 #[cfg(all(test, feature = "with-bench"))]
@@ -0,0 +1,119 @@
+//! Report the implementations selected for the algorithms with SIMD variants
+//!
+//! The implementations are selected at compile time from the target features
+//! enabled for the build, e.g. with `-C target-cpu=native` or
+//! `-C target-feature=+avx2`; there is no runtime detection. Enabling the
+//! `force-portable` cargo feature always selects the portable implementations,
+//! which is useful to rule out a SIMD specific issue or to compare both.
+//!
+//! ```
+//! use cryptoxide::cpu::{self, Backend};
+//!
+//! if cfg!(feature = "force-portable") {
+//!     assert_eq!(cpu::blake2b_backend(), Backend::Portable);
+//! }
+//! ```
+
+/// Implementation of an algorithm selected for the build
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// Portable implementation, available on every target
+    Portable,
+    /// x86 SSE2 implementation
+    Sse2,
+    /// x86 SSE4.1 implementation
+    Sse41,
+    /// x86 SSE4.2 implementation
+    Sse42,
+    /// x86 AVX implementation
+    Avx,
+    /// x86 AVX2 implementation
+    Avx2,
+    /// x86 carry-less multiplication implementation
+    Pclmulqdq,
+}
+
+/// Implementation used by [`Blake2b`](crate::blake2b::Blake2b)
+#[cfg(feature = "blake2")]
+pub const fn blake2b_backend() -> Backend {
+    crate::blake2::BACKEND_B
+}
+
+/// Implementation used by [`Blake2s`](crate::blake2s::Blake2s)
+#[cfg(feature = "blake2")]
+pub const fn blake2s_backend() -> Backend {
+    crate::blake2::BACKEND_S
+}
+
+/// Implementation used by [`Sha256`](crate::sha2::Sha256) and
+/// [`Sha224`](crate::sha2::Sha224)
+#[cfg(feature = "sha2")]
+pub const fn sha256_backend() -> Backend {
+    crate::sha2::impl256::BACKEND
+}
+
+/// Implementation used by [`ChaCha20`](crate::chacha20::ChaCha20) and the
+/// ciphers built on it
+#[cfg(feature = "chacha")]
+pub const fn chacha_backend() -> Backend {
+    crate::chacha::BACKEND
+}
+
+/// Implementation used by [`ghash`](crate::ghash)
+#[cfg(feature = "ghash")]
+pub const fn ghash_backend() -> Backend {
+    crate::ghash::BACKEND
+}
+
+/// Implementation used by [`Crc32c`](crate::crc::Crc32c)
+#[cfg(feature = "crc")]
+pub const fn crc32c_backend() -> Backend {
+    crate::crc::BACKEND
+}
+
+#[cfg(all(
+    test,
+    feature = "blake2",
+    feature = "sha2",
+    feature = "chacha",
+    feature = "ghash",
+    feature = "crc"
+))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selected_backends() {
+        let backends = [
+            blake2b_backend(),
+            blake2s_backend(),
+            sha256_backend(),
+            chacha_backend(),
+            ghash_backend(),
+            crc32c_backend(),
+        ];
+        if cfg!(any(
+            feature = "force-portable",
+            not(any(target_arch = "x86", target_arch = "x86_64"))
+        )) {
+            assert!(backends.iter().all(|b| *b == Backend::Portable));
+        } else {
+            assert_eq!(
+                chacha_backend() == Backend::Sse2,
+                cfg!(target_feature = "sse2")
+            );
+            assert_eq!(
+                blake2b_backend() == Backend::Portable,
+                cfg!(not(all(target_arch = "x86_64", target_feature = "avx")))
+            );
+            assert_eq!(
+                ghash_backend() == Backend::Pclmulqdq,
+                cfg!(target_feature = "pclmulqdq")
+            );
+            assert_eq!(
+                crc32c_backend() == Backend::Sse42,
+                cfg!(target_feature = "sse4.2")
+            );
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! CRC-32 checksums
+//!
+//! Implementation of the CRC-32 (IEEE 802.3) and CRC-32C (Castagnoli)
+//! cyclic redundancy checks, in their usual reflected form with an
+//! initial value and a final xor of `0xffffffff`.
+//!
+//! These checksums only detect accidental corruption of the data, e.g. in
+//! transport framing: they are not cryptographic, and anyone can modify the
+//! data and its checksum accordingly. Use a MAC to authenticate data.
+//!
+//! When the `sse4.2` target feature is enabled, CRC-32C uses the
+//! `crc32` instruction.
+//!
+//! ```
+//! use cryptoxide::crc::{crc32, Crc32};
+//!
+//! let mut context = Crc32::new();
+//! context.input(b"123");
+//! context.input(b"456789");
+//! assert_eq!(context.result(), 0xcbf43926);
+//! assert_eq!(crc32(b"123456789"), 0xcbf43926);
+//! ```
+
+#[cfg(all(
+    not(feature = "force-portable"),
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse4.2"
+))]
+mod sse42;
+
+use crate::cpu::Backend;
+
+/// Reversed polynomial of CRC-32 (IEEE)
+const POLY_IEEE: u32 = 0xedb88320;
+
+/// Reversed polynomial of CRC-32C (Castagnoli)
+#[cfg(not(all(
+    not(feature = "force-portable"),
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse4.2"
+)))]
+const POLY_CASTAGNOLI: u32 = 0x82f63b78;
+
+const fn make_table(poly: u32) -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut j = 0;
+        while j < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ poly
+            } else {
+                crc >> 1
+            };
+            j += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+static TABLE_IEEE: [u32; 256] = make_table(POLY_IEEE);
+
+#[cfg(not(all(
+    not(feature = "force-portable"),
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse4.2"
+)))]
+static TABLE_CASTAGNOLI: [u32; 256] = make_table(POLY_CASTAGNOLI);
+
+fn update_table(table: &[u32; 256], mut crc: u32, data: &[u8]) -> u32 {
+    for &b in data {
+        crc = table[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    crc
+}
+
+fn update_ieee(crc: u32, data: &[u8]) -> u32 {
+    update_table(&TABLE_IEEE, crc, data)
+}
+
+#[cfg(not(all(
+    not(feature = "force-portable"),
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse4.2"
+)))]
+fn update_castagnoli(crc: u32, data: &[u8]) -> u32 {
+    update_table(&TABLE_CASTAGNOLI, crc, data)
+}
+#[cfg(not(all(
+    not(feature = "force-portable"),
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse4.2"
+)))]
+pub(crate) const BACKEND: Backend = Backend::Portable;
+
+#[cfg(all(
+    not(feature = "force-portable"),
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse4.2"
+))]
+use sse42::update_castagnoli;
+#[cfg(all(
+    not(feature = "force-portable"),
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse4.2"
+))]
+pub(crate) const BACKEND: Backend = Backend::Sse42;
+
+macro_rules! crc_impl {
+    ($name:ident, $update:ident, $doc:expr) => {
+        #[doc = $doc]
+        #[derive(Clone)]
+        pub struct $name {
+            crc: u32,
+        }
+
+        impl $name {
+            /// Create a new context
+            pub const fn new() -> Self {
+                $name { crc: 0xffffffff }
+            }
+
+            /// Process more data
+            pub fn input(&mut self, data: &[u8]) {
+                self.crc = $update(self.crc, data);
+            }
+
+            /// Return the checksum of the data processed so far, without changing the context
+            pub fn result(&self) -> u32 {
+                self.crc ^ 0xffffffff
+            }
+
+            /// Reset the context to its initial state
+            pub fn reset(&mut self) {
+                self.crc = 0xffffffff;
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+    };
+}
+
+crc_impl!(Crc32, update_ieee, "CRC-32 (IEEE) context");
+crc_impl!(Crc32c, update_castagnoli, "CRC-32C (Castagnoli) context");
+
+/// Compute the CRC-32 (IEEE) checksum of the data
+pub fn crc32(data: &[u8]) -> u32 {
+    update_ieee(0xffffffff, data) ^ 0xffffffff
+}
+
+/// Compute the CRC-32C (Castagnoli) checksum of the data
+pub fn crc32c(data: &[u8]) -> u32 {
+    update_castagnoli(0xffffffff, data) ^ 0xffffffff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn crc32_vectors() {
+        let tests: [(&[u8], u32); 5] = [
+            (b"", 0x00000000),
+            (b"a", 0xe8b7be43),
+            (b"123456789", 0xcbf43926),
+            (b"The quick brown fox jumps over the lazy dog", 0x414fa339),
+            (&[0u8; 32], 0x190a55ad),
+        ];
+        for &(data, expected) in tests.iter() {
+            assert_eq!(crc32(data), expected);
+        }
+    }
+
+    // from RFC 3720 appendix B.4 and the usual check value
+    #[test]
+    fn crc32c_vectors() {
+        let ascending: Vec<u8> = (0..32).collect();
+        let descending: Vec<u8> = (0..32).rev().collect();
+        let tests: [(&[u8], u32); 6] = [
+            (b"", 0x00000000),
+            (b"123456789", 0xe3069283),
+            (&[0u8; 32], 0x8a9136aa),
+            (&[0xffu8; 32], 0x62a8ab43),
+            (&ascending, 0x46dd794e),
+            (&descending, 0x113fdb5c),
+        ];
+        for &(data, expected) in tests.iter() {
+            assert_eq!(crc32c(data), expected);
+        }
+    }
+
+    #[test]
+    fn incremental() {
+        let data: Vec<u8> = (0..100u32).map(|i| (i * 7) as u8).collect();
+        for split in 0..data.len() {
+            let mut c = Crc32::new();
+            c.input(&data[..split]);
+            c.input(&data[split..]);
+            assert_eq!(c.result(), crc32(&data));
+
+            let mut c = Crc32c::new();
+            c.input(&data[..split]);
+            c.input(&data[split..]);
+            assert_eq!(c.result(), crc32c(&data));
+        }
+
+        let mut c = Crc32c::new();
+        c.input(b"garbage");
+        c.reset();
+        c.input(b"123456789");
+        assert_eq!(c.result(), 0xe3069283);
+    }
+}
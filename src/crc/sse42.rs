@@ -0,0 +1,39 @@
+//! CRC-32C using the SSE4.2 `crc32` instruction
+
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+/// Update the reflected CRC-32C state `crc` with `data`
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn update_castagnoli(crc: u32, data: &[u8]) -> u32 {
+    let mut crc = crc as u64;
+    let mut chunks = data.chunks_exact(8);
+    for chunk in &mut chunks {
+        let mut w = [0u8; 8];
+        w.copy_from_slice(chunk);
+        crc = unsafe { _mm_crc32_u64(crc, u64::from_le_bytes(w)) };
+    }
+    let mut crc = crc as u32;
+    for &b in chunks.remainder() {
+        crc = unsafe { _mm_crc32_u8(crc, b) };
+    }
+    crc
+}
+
+/// Update the reflected CRC-32C state `crc` with `data`
+#[cfg(target_arch = "x86")]
+pub(crate) fn update_castagnoli(mut crc: u32, data: &[u8]) -> u32 {
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut w = [0u8; 4];
+        w.copy_from_slice(chunk);
+        crc = unsafe { _mm_crc32_u32(crc, u32::from_le_bytes(w)) };
+    }
+    for &b in chunks.remainder() {
+        crc = unsafe { _mm_crc32_u8(crc, b) };
+    }
+    crc
+}
@@ -10,11 +10,12 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use crate::digest::Digest;
 use core::{mem::size_of, ptr};
 
 macro_rules! write_type {
     ($C: ident, $T: ident, $F: ident) => {
-        /// Write a $T into a vector, which must be of the correct size. The value is written using $F for endianness
+        #[doc = concat!("Write a `", stringify!($T), "` into a slice, which must be of the correct size. The value is written using `", stringify!($F), "` for endianness")]
         pub fn $C(dst: &mut [u8], input: $T) {
             const SZ: usize = size_of::<$T>();
             assert!(dst.len() == SZ);
@@ -36,7 +37,7 @@ write_type!(write_u32_le, u32, to_le_bytes);
 
 macro_rules! write_array_type {
     ($C: ident, $T: ident, $F: ident) => {
-        /// Write a $T into a vector, which must be of the correct size. The value is written using $F for endianness
+        #[doc = concat!("Write an array of `", stringify!($T), "` into a slice, which must be of the correct size. The values are written using `", stringify!($F), "` for endianness")]
         pub fn $C(dst: &mut [u8], input: &[$T]) {
             const SZ: usize = size_of::<$T>();
             assert!(dst.len() == SZ * input.len());
@@ -59,7 +60,7 @@ write_array_type!(write_u32v_be, u32, to_be_bytes);
 
 macro_rules! read_array_type {
     ($C: ident, $T: ident, $F: ident) => {
-        /// Read an array of bytes into an array of $T. The values are read with $F for endianness.
+        #[doc = concat!("Read a slice of bytes into an array of `", stringify!($T), "`, which must be of the correct size. The values are read with `", stringify!($F), "` for endianness.")]
         pub fn $C(dst: &mut [$T], input: &[u8]) {
             const SZ: usize = size_of::<$T>();
             assert!(dst.len() * SZ == input.len());
@@ -95,17 +96,13 @@ pub fn read_u32_le(input: &[u8]) -> u32 {
     u32::from_le_bytes(tmp)
 }
 
-/*
 /// Read the value of a vector of bytes as a u32 value in big-endian format.
 pub fn read_u32_be(input: &[u8]) -> u32 {
     assert!(input.len() == 4);
-    unsafe {
-        let mut tmp: u32 = mem::uninitialized();
-        ptr::copy_nonoverlapping(input.get_unchecked(0), &mut tmp as *mut _ as *mut u8, 4);
-        u32::from_be(tmp)
-    }
+    let mut tmp = [0u8; 4];
+    tmp.copy_from_slice(input);
+    u32::from_be_bytes(tmp)
 }
-*/
 
 /// XOR plaintext and keystream, storing the result in dst.
 pub fn xor_keystream(dst: &mut [u8], plaintext: &[u8], keystream: &[u8]) {
@@ -152,6 +149,76 @@ pub fn zero(dst: &mut [u8]) {
     }
 }
 
+/// Copy `src` into `dst` if `choice` is `0xFF`, leave `dst` unchanged if `choice` is 0
+///
+/// The copy is done with a mask instead of a branch, so that the memory accesses
+/// and the execution time don't depend on the secret `choice`. Any other value of
+/// `choice` copies the bits of `src` selected by the mask. Panics if the slices
+/// are not of the same length.
+pub fn conditional_copy(dst: &mut [u8], src: &[u8], choice: u8) {
+    assert_eq!(dst.len(), src.len());
+    for (d, s) in dst.iter_mut().zip(src.iter()) {
+        *d ^= (*d ^ *s) & choice;
+    }
+}
+
+/// Reverse the byte order of each `word_size` bytes word of a digest, in place
+///
+/// Digests are serialized with big endian words (SHA-1, SHA-2) or little endian
+/// words (Blake2). Some protocols expect another byte order, e.g. the words of
+/// a SHA-256 digest in little endian, or Bitcoin which displays transaction and
+/// block hashes as the whole 32 bytes digest reversed (a `word_size` of 32).
+///
+/// The length of `out` needs to be a multiple of `word_size`, which can't be zero.
+pub fn reverse_digest_endianness(out: &mut [u8], word_size: usize) {
+    assert!(word_size > 0);
+    assert_eq!(out.len() % word_size, 0);
+    for word in out.chunks_exact_mut(word_size) {
+        word.reverse();
+    }
+}
+
+/// Input multiple byte strings into a digest, without ambiguity on their boundaries
+///
+/// Each part is preceded by its length in bytes, encoded as a fixed 8 bytes
+/// big endian integer, so that e.g. `["ab", "c"]` and `["a", "bc"]` are hashed
+/// differently. The framing of `[x1, .., xn]` is:
+///
+/// `be64(len(x1)) || x1 || .. || be64(len(xn)) || xn`
+///
+/// Note that the number of parts is not encoded: the parts list is only
+/// unambiguous when the number of parts is fixed by the usage.
+pub fn absorb_framed<D: Digest>(d: &mut D, parts: &[&[u8]]) {
+    for part in parts.iter() {
+        d.input(&(part.len() as u64).to_be_bytes());
+        d.input(part);
+    }
+}
+
+/// Read all the data from a reader, feeding it in chunks to a function,
+/// and return the number of bytes read
+///
+/// Reads interrupted by a signal are retried.
+#[cfg(feature = "std")]
+pub fn read_chunks<R: std::io::Read + ?Sized, F: FnMut(&[u8])>(
+    r: &mut R,
+    mut func: F,
+) -> std::io::Result<u64> {
+    let mut buf = [0u8; 8192];
+    let mut total = 0u64;
+    loop {
+        match r.read(&mut buf) {
+            Ok(0) => return Ok(total),
+            Ok(n) => {
+                func(&buf[..n]);
+                total += n as u64;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
 /// A fixed size buffer of N bytes useful for cryptographic operations.
 #[derive(Clone)]
 pub(crate) struct FixedBuffer<const N: usize> {
@@ -250,10 +317,19 @@ impl<const N: usize> FixedBuffer<N> {
 #[cfg(test)]
 pub mod test {
     use std::iter::repeat;
+    use std::string::String;
     use std::vec::Vec;
 
     use crate::digest::Digest;
 
+    /// Hexadecimal result of the digest, like `Digest::result_str` which is
+    /// only available with the `alloc` feature
+    pub fn result_hex<D: Digest + ?Sized>(digest: &mut D) -> String {
+        let mut out = vec![0u8; digest.output_bytes()];
+        digest.result(&mut out);
+        out.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
     /// Feed 1,000,000 'a's into the digest with varying input sizes and check that the result is
     /// correct.
     pub fn test_digest_1million_random<D: Digest>(
@@ -278,8 +354,87 @@ pub mod test {
             count += size;
         }
 
-        let result_str = digest.result_str();
+        let result_str = result_hex(digest);
 
         assert!(expected == &result_str[..]);
     }
+
+    #[test]
+    fn conditional_copy() {
+        use super::conditional_copy;
+
+        let src: Vec<u8> = (0..40).collect();
+        let mut dst = [0xa5u8; 40];
+        conditional_copy(&mut dst, &src, 0);
+        assert_eq!(dst, [0xa5u8; 40]);
+        conditional_copy(&mut dst, &src, 0xff);
+        assert_eq!(&dst[..], &src[..]);
+    }
+
+    #[test]
+    fn reverse_digest_endianness() {
+        use super::reverse_digest_endianness;
+
+        let mut digest = [0u8; 32];
+        for (i, b) in digest.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        let original = digest;
+
+        reverse_digest_endianness(&mut digest, 4);
+        assert_eq!(&digest[..8], &[3, 2, 1, 0, 7, 6, 5, 4]);
+        assert_eq!(&digest[28..], &[31, 30, 29, 28]);
+        reverse_digest_endianness(&mut digest, 4);
+        assert_eq!(digest, original);
+
+        reverse_digest_endianness(&mut digest, 32);
+        assert_eq!(digest[0], 31);
+        assert_eq!(digest[31], 0);
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn absorb_framed_unambiguous() {
+        use super::absorb_framed;
+        use super::test::result_hex;
+        use crate::sha2::Sha256;
+
+        let framed = |parts: &[&[u8]]| {
+            let mut d = Sha256::new();
+            absorb_framed(&mut d, parts);
+            result_hex(&mut d)
+        };
+
+        assert_ne!(framed(&[b"ab", b"c"]), framed(&[b"a", b"bc"]));
+        assert_ne!(framed(&[b"abc", b""]), framed(&[b"", b"abc"]));
+        assert_ne!(framed(&[b"abc"]), framed(&[b"abc", b""]));
+
+        // the framing is the 8 bytes big endian length before each part
+        let mut d = Sha256::new();
+        d.input(&[0, 0, 0, 0, 0, 0, 0, 2]);
+        d.input(b"ab");
+        d.input(&[0, 0, 0, 0, 0, 0, 0, 1]);
+        d.input(b"c");
+        assert_eq!(framed(&[b"ab", b"c"]), result_hex(&mut d));
+    }
+
+    #[cfg(all(feature = "std", feature = "sha2"))]
+    #[test]
+    fn digest_input_reader() {
+        use crate::sha2::Sha256;
+        use std::io::Cursor;
+
+        let data: Vec<u8> = (0..20000u32).map(|i| i as u8).collect();
+        let mut d = Sha256::new();
+        d.input(&data);
+        let expected = d.result_str();
+
+        let mut d = Sha256::new();
+        assert_eq!(d.input_reader(&mut Cursor::new(&data)).unwrap(), 20000);
+        assert_eq!(d.result_str(), expected);
+
+        let mut d = Sha256::new();
+        assert_eq!(d.input_reader(&mut Cursor::new(&[])).unwrap(), 0);
+        assert_eq!(d.result_str(), Sha256::new().result_str());
+    }
 }
@@ -0,0 +1,534 @@
+//! CTR-DRBG deterministic random bit generator over AES-256 [NIST SP 800-90A][1]
+//!
+//! This is the alternative to the HMAC-DRBG (used by [`rfc6979`](crate::rfc6979))
+//! that some compliance regimes require. The generator is seeded by the caller
+//! with entropy from a proper source, and then expands it with AES-256 in
+//! counter mode, updating its internal key after each request.
+//!
+//! Both variants of the specification are supported:
+//!
+//! * with the derivation function ([`CtrDrbg::instantiate`]), the entropy,
+//!   nonce, personalization string and additional inputs can be of any length,
+//!   and are compressed with the Block_Cipher_df function.
+//! * without the derivation function ([`CtrDrbg::instantiate_no_df`]), the
+//!   entropy needs to be full entropy of exactly [`SEED_LENGTH`] bytes, and
+//!   the other inputs are at most [`SEED_LENGTH`] bytes.
+//!
+//! Prediction resistance is left to the caller, by calling [`CtrDrbg::reseed`]
+//! with fresh entropy before [`CtrDrbg::generate`].
+//!
+//! # Examples
+//!
+//! ```
+//! use cryptoxide::ctrdrbg::CtrDrbg;
+//!
+//! let entropy = [0x42u8; 32];
+//! let nonce = [0x24u8; 16];
+//! let mut drbg = CtrDrbg::instantiate(&entropy, &nonce, b"my application").unwrap();
+//!
+//! let mut out = [0u8; 64];
+//! drbg.generate(&[], &mut out).unwrap();
+//! ```
+//!
+//! [1]: <https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-90Ar1.pdf>
+
+use crate::aes::{Aes, BLOCK_LENGTH};
+use crate::util::secure_memset;
+
+const KEY_LENGTH: usize = 32;
+
+/// Size in bytes of the seed, being the key and the counter block
+pub const SEED_LENGTH: usize = KEY_LENGTH + BLOCK_LENGTH;
+
+/// Minimum size in bytes of the entropy, for the security strength of 256 bits
+pub const MIN_ENTROPY_LENGTH: usize = 32;
+
+/// Maximum number of bytes returned by a single call to [`CtrDrbg::generate`]
+pub const MAX_REQUEST_LENGTH: usize = 1 << 16;
+
+/// Number of calls to [`CtrDrbg::generate`] allowed before a reseed is required
+pub const RESEED_INTERVAL: u64 = 1 << 48;
+
+/// CTR-DRBG error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// An input is too short or too long for the chosen variant
+    InvalidLength,
+    /// More than [`MAX_REQUEST_LENGTH`] bytes requested at once
+    RequestTooLarge,
+    /// The generator has been used [`RESEED_INTERVAL`] times since it was last seeded
+    ReseedRequired,
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::InvalidLength => write!(f, "invalid CTR-DRBG input length"),
+            Error::RequestTooLarge => write!(f, "CTR-DRBG request too large"),
+            Error::ReseedRequired => write!(f, "CTR-DRBG reseed required"),
+        }
+    }
+}
+
+/// CTR-DRBG state, using AES-256
+#[derive(Clone)]
+pub struct CtrDrbg {
+    aes: Aes,
+    v: [u8; BLOCK_LENGTH],
+    reseed_counter: u64,
+    derivation: bool,
+}
+
+impl Drop for CtrDrbg {
+    fn drop(&mut self) {
+        secure_memset(&mut self.v, 0);
+    }
+}
+
+// V = (V + 1) mod 2^128
+fn increment(v: &mut [u8; BLOCK_LENGTH]) {
+    for b in v.iter_mut().rev() {
+        *b = b.wrapping_add(1);
+        if *b != 0 {
+            break;
+        }
+    }
+}
+
+fn aes256(key: &[u8]) -> Aes {
+    Aes::try_new(key).expect("AES-256 key of 32 bytes")
+}
+
+// BCC function chained over the concatenation of `inputs`, the total length
+// of which is a multiple of the block size
+struct Bcc<'a> {
+    aes: &'a Aes,
+    chaining: [u8; BLOCK_LENGTH],
+    pos: usize,
+}
+
+impl<'a> Bcc<'a> {
+    fn input(&mut self, data: &[u8]) {
+        for b in data.iter() {
+            self.chaining[self.pos] ^= b;
+            self.pos += 1;
+            if self.pos == BLOCK_LENGTH {
+                self.aes.encrypt_block(&mut self.chaining);
+                self.pos = 0;
+            }
+        }
+    }
+}
+
+// Block_Cipher_df of section 10.3.2, returning SEED_LENGTH bytes
+fn derivation_function(inputs: &[&[u8]]) -> [u8; SEED_LENGTH] {
+    let len: usize = inputs.iter().map(|i| i.len()).sum();
+    // S = L || N || input_string || 0x80, padded with zeros to a block boundary
+    let s_len = 4 + 4 + len + 1;
+    let padding = (BLOCK_LENGTH - s_len % BLOCK_LENGTH) % BLOCK_LENGTH;
+
+    let mut k = [0u8; KEY_LENGTH];
+    for (i, b) in k.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    let aes = aes256(&k);
+
+    let mut temp = [0u8; SEED_LENGTH + BLOCK_LENGTH];
+    for (i, out) in temp.chunks_mut(BLOCK_LENGTH).enumerate() {
+        let mut bcc = Bcc {
+            aes: &aes,
+            chaining: [0u8; BLOCK_LENGTH],
+            pos: 0,
+        };
+        // IV = i || 0^(outlen - 32)
+        bcc.input(&(i as u32).to_be_bytes());
+        bcc.input(&[0u8; BLOCK_LENGTH - 4]);
+        bcc.input(&(len as u32).to_be_bytes());
+        bcc.input(&(SEED_LENGTH as u32).to_be_bytes());
+        for input in inputs.iter() {
+            bcc.input(input);
+        }
+        bcc.input(&[0x80]);
+        bcc.input(&[0u8; BLOCK_LENGTH][..padding]);
+        out.copy_from_slice(&bcc.chaining);
+        secure_memset(&mut bcc.chaining, 0);
+    }
+
+    let aes = aes256(&temp[0..KEY_LENGTH]);
+    let mut x = [0u8; BLOCK_LENGTH];
+    x.copy_from_slice(&temp[KEY_LENGTH..SEED_LENGTH]);
+    let mut seed = [0u8; SEED_LENGTH];
+    for out in seed.chunks_mut(BLOCK_LENGTH) {
+        aes.encrypt_block(&mut x);
+        out.copy_from_slice(&x);
+    }
+    secure_memset(&mut x, 0);
+    secure_memset(&mut temp, 0);
+    seed
+}
+
+// without derivation function, the input is padded with zeros to SEED_LENGTH
+fn pad(input: &[u8]) -> Result<[u8; SEED_LENGTH], Error> {
+    if input.len() > SEED_LENGTH {
+        return Err(Error::InvalidLength);
+    }
+    let mut out = [0u8; SEED_LENGTH];
+    out[..input.len()].copy_from_slice(input);
+    Ok(out)
+}
+
+impl CtrDrbg {
+    /// Instantiate the generator with the derivation function
+    ///
+    /// `entropy` needs to be at least [`MIN_ENTROPY_LENGTH`] bytes. The `nonce`
+    /// should be at least half of that, and the personalization string can be
+    /// empty.
+    pub fn instantiate(
+        entropy: &[u8],
+        nonce: &[u8],
+        personalization: &[u8],
+    ) -> Result<Self, Error> {
+        if entropy.len() < MIN_ENTROPY_LENGTH {
+            return Err(Error::InvalidLength);
+        }
+        let mut seed = derivation_function(&[entropy, nonce, personalization]);
+        let drbg = Self::from_seed(&seed, true);
+        secure_memset(&mut seed, 0);
+        Ok(drbg)
+    }
+
+    /// Instantiate the generator without the derivation function
+    ///
+    /// `entropy` needs to be full entropy, and the personalization string at
+    /// most [`SEED_LENGTH`] bytes.
+    pub fn instantiate_no_df(
+        entropy: &[u8; SEED_LENGTH],
+        personalization: &[u8],
+    ) -> Result<Self, Error> {
+        let mut seed = pad(personalization)?;
+        for (s, e) in seed.iter_mut().zip(entropy.iter()) {
+            *s ^= e;
+        }
+        let drbg = Self::from_seed(&seed, false);
+        secure_memset(&mut seed, 0);
+        Ok(drbg)
+    }
+
+    fn from_seed(seed: &[u8; SEED_LENGTH], derivation: bool) -> Self {
+        let mut drbg = CtrDrbg {
+            aes: aes256(&[0u8; KEY_LENGTH]),
+            v: [0u8; BLOCK_LENGTH],
+            reseed_counter: 1,
+            derivation,
+        };
+        drbg.update(seed);
+        drbg
+    }
+
+    // CTR_DRBG_Update of section 10.2.1.2
+    fn update(&mut self, provided_data: &[u8; SEED_LENGTH]) {
+        let mut temp = [0u8; SEED_LENGTH];
+        for (out, data) in temp
+            .chunks_mut(BLOCK_LENGTH)
+            .zip(provided_data.chunks(BLOCK_LENGTH))
+        {
+            increment(&mut self.v);
+            let mut block = self.v;
+            self.aes.encrypt_block(&mut block);
+            for ((o, b), d) in out.iter_mut().zip(block.iter()).zip(data.iter()) {
+                *o = b ^ d;
+            }
+        }
+        self.aes = aes256(&temp[0..KEY_LENGTH]);
+        self.v.copy_from_slice(&temp[KEY_LENGTH..]);
+        secure_memset(&mut temp, 0);
+    }
+
+    // the additional input as SEED_LENGTH bytes
+    fn additional_input(&self, additional: &[u8]) -> Result<[u8; SEED_LENGTH], Error> {
+        if self.derivation {
+            Ok(derivation_function(&[additional]))
+        } else {
+            pad(additional)
+        }
+    }
+
+    /// Reseed the generator with fresh entropy and optional additional input
+    ///
+    /// The entropy has the same length requirements as when instantiating:
+    /// at least [`MIN_ENTROPY_LENGTH`] bytes with the derivation function,
+    /// exactly [`SEED_LENGTH`] bytes without.
+    pub fn reseed(&mut self, entropy: &[u8], additional: &[u8]) -> Result<(), Error> {
+        if self.derivation && entropy.len() < MIN_ENTROPY_LENGTH {
+            return Err(Error::InvalidLength);
+        }
+        let mut seed = if self.derivation {
+            derivation_function(&[entropy, additional])
+        } else {
+            if entropy.len() != SEED_LENGTH {
+                return Err(Error::InvalidLength);
+            }
+            let mut seed = pad(additional)?;
+            for (s, e) in seed.iter_mut().zip(entropy.iter()) {
+                *s ^= e;
+            }
+            seed
+        };
+        self.update(&seed);
+        self.reseed_counter = 1;
+        secure_memset(&mut seed, 0);
+        Ok(())
+    }
+
+    /// Fill `out` with pseudo random bytes, with optional additional input
+    ///
+    /// At most [`MAX_REQUEST_LENGTH`] bytes can be requested at once.
+    pub fn generate(&mut self, additional: &[u8], out: &mut [u8]) -> Result<(), Error> {
+        if out.len() > MAX_REQUEST_LENGTH {
+            return Err(Error::RequestTooLarge);
+        }
+        if self.reseed_counter > RESEED_INTERVAL {
+            return Err(Error::ReseedRequired);
+        }
+        let mut additional = if additional.is_empty() {
+            [0u8; SEED_LENGTH]
+        } else {
+            let additional = self.additional_input(additional)?;
+            self.update(&additional);
+            additional
+        };
+
+        for chunk in out.chunks_mut(BLOCK_LENGTH) {
+            increment(&mut self.v);
+            let mut block = self.v;
+            self.aes.encrypt_block(&mut block);
+            chunk.copy_from_slice(&block[..chunk.len()]);
+            secure_memset(&mut block, 0);
+        }
+        self.update(&additional);
+        self.reseed_counter += 1;
+        secure_memset(&mut additional, 0);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    struct Vector {
+        entropy: &'static str,
+        nonce: &'static str,
+        personalization: &'static str,
+        reseed: Option<(&'static str, &'static str)>,
+        reseed_between: bool,
+        additional: [&'static str; 2],
+        returned: &'static str,
+    }
+
+    // CAVP flow: instantiate, optionally reseed before the first or the
+    // second request, generate twice and compare the second output
+    fn check(v: &Vector, derivation: bool) {
+        let mut drbg = if derivation {
+            CtrDrbg::instantiate(&hex(v.entropy), &hex(v.nonce), &hex(v.personalization)).unwrap()
+        } else {
+            let mut entropy = [0u8; SEED_LENGTH];
+            entropy.copy_from_slice(&hex(v.entropy));
+            CtrDrbg::instantiate_no_df(&entropy, &hex(v.personalization)).unwrap()
+        };
+        let mut out = vec![0u8; v.returned.len() / 2];
+        for (i, additional) in v.additional.iter().enumerate() {
+            if let Some((entropy, reseed_additional)) = v.reseed {
+                if (i == 1) == v.reseed_between {
+                    drbg.reseed(&hex(entropy), &hex(reseed_additional)).unwrap();
+                }
+            }
+            drbg.generate(&hex(additional), &mut out).unwrap();
+        }
+        assert_eq!(out, hex(v.returned));
+    }
+
+    // the CAVS 14.3 entry and the first entries of the AES-256 blocks without
+    // prediction resistance of the CAVP CTR_DRBG.rsp file, as vendored by the
+    // mbed TLS test suite (test_suite_ctr_drbg.data), where the nonce and the
+    // personalization string are concatenated. The later entries reseed
+    // between the two requests.
+    #[test]
+    fn with_derivation_function() {
+        let check = |v| check(v, true);
+        check(&Vector {
+            entropy: "2d4c9f46b981c6a0b2b5d8c69391e569ff13851437ebc0fc00d616340252fed5",
+            nonce: "0bf814b411f65ec4866be1abb59d3c32",
+            personalization: "",
+            reseed: Some((
+                "93500fae4fa32b86033b7a7bac9d37e710dcc67ca266bc8607d665937766d207",
+                "",
+            )),
+            reseed_between: false,
+            additional: ["", ""],
+            returned: "322dd28670e75c0ea638f3cb68d6a9d6e50ddfd052b772a7b1d78263a7b8978b6740c2b65a9550c3a76325866fa97e16d74006bc96f26249b9f0a90d076f08e5",
+        });
+        check(&Vector {
+            entropy: "5a194d5e2b31581454def675fb7958fec7db873e5689fc9d03217c68d8033820",
+            nonce: "1b54b8ff0642bff521f15c1c0b665f3f",
+            personalization: "",
+            reseed: Some((
+                "f9e65e04d856f3a9c44a4cbdc1d00846f5983d771c1b137e4e0f9d8ef409f92e",
+                "",
+            )),
+            reseed_between: true,
+            additional: ["", ""],
+            returned: "a054303d8a7ea9889d903e077c6f218f",
+        });
+        check(&Vector {
+            entropy: "f84d395b1734eac4600dbc36f6b1e1599bc7f2608dc8ecb3a55369d7b1b122a0",
+            nonce: "176200bb44808b5400b24e1b5f56cf73",
+            personalization: "",
+            reseed: Some((
+                "9f5ac9c16d9a2be37d2ff70a9bba732fc3785b23ff4ade3c8404da3f09f95a8f",
+                "0626ae19763c5313b627a8d65cf1cfba46dfd6773242738b9b81fde8d566ade1",
+            )),
+            reseed_between: true,
+            additional: [
+                "aef28c9169e9af74c73432d4aa6f5dff9ea4a53433de2ecb9bf380a8868c86e1",
+                "63c160ed6a6c1fffd0586f52fa488a9055533930b36d4fa5ea3467cda9ffe198",
+            ],
+            returned: "e8f91633725d786081625fb99336a993",
+        });
+        check(&Vector {
+            entropy: "7f88c3805ae0857c5cbb085a5d6259d26fb3a88dfe7084172ec959066f26296a",
+            nonce: "cd7a1981c1b7079c1c38f5aeee86db22",
+            personalization: "207cb9faed8c576b1724ca7817aa6abfb26c42a019eb4c2f4064f0587ea2b952",
+            reseed: Some((
+                "800953ce19a24785b6acef451c4ce4c2dfb565cbe057f21b054a28633afbdd97",
+                "",
+            )),
+            reseed_between: true,
+            additional: ["", ""],
+            returned: "76c1cdb0b95af271b52ac3b0c9289146",
+        });
+        check(&Vector {
+            entropy: "a53e371017439193591e475087aaddd5c1c386cdca0ddb68e002d80fdc401a47",
+            nonce: "a94da55afdc50ce51c9a3b8a4c448440",
+            personalization: "8b52a24a93c34ea71e1ca705eb829ba65de4d4e07fa3d86b37845ff1c7d5f6d2",
+            reseed: Some((
+                "dd40e5987b2716731568d276bf0c6715757903d3dede914642ddd467c879c81e",
+                "7fd81fbd2ab51c115d834e99f65ca54020ed388ed59ee07593fe125e5d73fb75",
+            )),
+            reseed_between: true,
+            additional: [
+                "20f422edf85ca16a01cfbe5f8d6c947fae12a857db2aa9bfc7b36581808d0d46",
+                "cd2cff14693e4c9efdfe260de986004930bab1c65057772a62392c3b74ebc90d",
+            ],
+            returned: "4f78beb94d978ce9d097feadfafd355e",
+        });
+    }
+
+    // COUNT 0 of the AES-256 no df block of the CAVP CTR_DRBG.rsp file
+    // without reseed, and the first entries of each block with reseed as
+    // vendored by BoringSSL (crypto/fipsmodule/rand/ctrdrbg_vectors.txt)
+    #[test]
+    fn without_derivation_function() {
+        let check = |v| check(v, false);
+        check(&Vector {
+            entropy: "df5d73faa468649edda33b5cca79b0b05600419ccb7a879ddfec9db32ee494e5531b51de16a30f769262474c73bec010",
+            nonce: "",
+            personalization: "",
+            reseed: None,
+            reseed_between: false,
+            additional: ["", ""],
+            returned: "d1c07cd95af8a7f11012c84ce48bb8cb87189e99d40fccb1771c619bdf82ab2280b1dc2f2581f39164f7ac0c510494b3a43c41b7db17514c87b107ae793e01c5",
+        });
+        check(&Vector {
+            entropy: "e4bc23c5089a19d86f4119cb3fa08c0a4991e0a1def17e101e4c14d9c323460a7c2fb58e0b086c6c57b55f56cae25bad",
+            nonce: "",
+            personalization: "",
+            reseed: Some((
+                "fd85a836bba85019881e8c6bad23c9061adc75477659acaea8e4a01dfe07a1832dad1c136f59d70f8653a5dc118663d6",
+                "",
+            )),
+            reseed_between: false,
+            additional: ["", ""],
+            returned: "b2cb8905c05e5950ca31895096be29ea3d5a3b82b269495554eb80fe07de43e193b9e7c3ece73b80e062b1c1f68202fbb1c52a040ea2478864295282234aaada",
+        });
+        check(&Vector {
+            entropy: "99903165903fea49c2db26ed675e44cc14cb2c1f28b836b203240b02771e831146ffc4335373bb344688c5c950670291",
+            nonce: "",
+            personalization: "",
+            reseed: Some((
+                "b4ee99fa9e0eddaf4a3612013cd636c4af69177b43eebb3c58a305b9979b68b5cc820504f6c029aad78a5d29c66e84a0",
+                "2d8c5c28b05696e74774eb69a10f01c5fabc62691ddf7848a8004bb5eeb4d2c5febe1aa01f4d557b23d7e9a0e4e90655",
+            )),
+            reseed_between: false,
+            additional: [
+                    "0dc9cde42ac6e856f01a55f219c614de90c659260948db5053d414bab0ec2e13e995120c3eb5aafc25dc4bdcef8ace24",
+                    "711be6c035013189f362211889248ca8a3268e63a7eb26836d915810a680ac4a33cd1180811a31a0f44f08db3dd64f91",
+                ],
+            returned: "11c7a0326ea737baa7a993d510fafee5374e7bbe17ef0e3e29f50fa68aac2124b017d449768491cac06d136d691a4e80785739f9aaedf311bba752a3268cc531",
+        });
+        check(&Vector {
+            entropy: "ffad10100025a879672ff50374b286712f457dd01441d76ac1a1cd15c7390dd93179a2f5920d198bf34a1b76fbc21289",
+            nonce: "",
+            personalization: "1d2be6f25e88fa30c4ef42e4d54efd957dec231fa00143ca47580be666a8c143a916c90b3819a0a7ea914e3c9a2e7a3f",
+            reseed: Some((
+                "6c1a089cae313363bc76a780139eb4f2f2048b1f6b07896c5c412bff0385440fc43b73facbb79e3a252fa01fe17ab391",
+                "",
+            )),
+            reseed_between: false,
+            additional: ["", ""],
+            returned: "e053c7d4bd9099ef6a99f190a5fd80219437d642006672338da6e0fe73ca4d24ffa51151bfbdac78d8a2f6255046edf57a04626e9977139c6933274299f3bdff",
+        });
+        check(&Vector {
+            entropy: "ae7ebe062971f5eb32e5b21444750785de816595ad2cbe80a209c8f8ab04b5468166de8c6ae522d8f10b56386a3b424f",
+            nonce: "",
+            personalization: "55860dae57fcac297087c137efb796878a75868f6e7681114e9b73ed0c67e3c62bfc9f5d77e8caa59bcdb223f4ffd247",
+            reseed: Some((
+                "a42407931bfeca70e6ee5dd197021a129525051c07468e8b25587c5ad50abe9204e882fe847b8fd47cf7b4360e5aa034",
+                "ee4c88d1eb05f4853663eada501d2fc4b4984b283a88db579af2113031e03d9bc570de943dd168918f3ba8065581fea7",
+            )),
+            reseed_between: false,
+            additional: [
+                    "4b4b03ef19b0f259dca2b3ee3ae4cd86c3895a784b3d8eee043a2003c08289f8fffdad141e6b1ab2174d8d5d79c1e581",
+                    "3062b33f116b46e20fe3c354726ae9b2a3a4c51922c8107863cb86f1f0bdad7554075659d91c371e2b11b1e8106a1ed5",
+                ],
+            returned: "0d270518baeafac160ff1cb28c11ef68712c764c0c01674e6c9ca2cc9c7e0e8accfd3c753635ee070081eee7628af6187fbc2854b3c204461a796cf3f3fcb092",
+        });
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            CtrDrbg::instantiate(&[0u8; 31], &[0u8; 16], &[]).err(),
+            Some(Error::InvalidLength)
+        );
+        assert_eq!(
+            CtrDrbg::instantiate_no_df(&[0u8; SEED_LENGTH], &[0u8; 49]).err(),
+            Some(Error::InvalidLength)
+        );
+
+        let mut drbg = CtrDrbg::instantiate_no_df(&[0u8; SEED_LENGTH], &[]).unwrap();
+        assert_eq!(drbg.reseed(&[0u8; 32], &[]), Err(Error::InvalidLength));
+        let mut out = vec![0u8; MAX_REQUEST_LENGTH + 1];
+        assert_eq!(drbg.generate(&[], &mut out), Err(Error::RequestTooLarge));
+        assert_eq!(
+            drbg.generate(&[0u8; 49], &mut out[..16]),
+            Err(Error::InvalidLength)
+        );
+
+        drbg.reseed_counter = RESEED_INTERVAL + 1;
+        assert_eq!(
+            drbg.generate(&[], &mut out[..16]),
+            Err(Error::ReseedRequired)
+        );
+        drbg.reseed(&[1u8; SEED_LENGTH], &[]).unwrap();
+        assert_eq!(drbg.generate(&[], &mut out[..16]), Ok(()));
+    }
+}
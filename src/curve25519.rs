@@ -23,11 +23,40 @@
 //! let public = curve25519(&secret, &other_point);
 //! ```
 //!
+//! # Group operations
+//!
+//! The points of the twisted Edwards form of the curve used by Ed25519 are
+//! available for protocols built on top of it, for example to aggregate
+//! public keys. Those are low level primitives: most operations are not
+//! constant time, and no check is done on the order of the points.
+//!
+//! Adding two multiples of the base point:
+//!
+//! ```
+//! use cryptoxide::curve25519::{ge_scalarmult_base, GeP3};
+//!
+//! let mut a = [0u8; 32];
+//! let mut b = [0u8; 32];
+//! let mut sum = [0u8; 32];
+//! a[0] = 2;
+//! b[0] = 3;
+//! sum[0] = 5;
+//!
+//! let pa = ge_scalarmult_base(&a).to_bytes();
+//! let pb = ge_scalarmult_base(&b).to_bytes();
+//!
+//! let pa = GeP3::from_bytes(&pa).unwrap();
+//! let pb = GeP3::from_bytes(&pb).unwrap();
+//! let r = (&pa + &pb.to_cached()).to_p3();
+//! assert_eq!(r.to_bytes(), ge_scalarmult_base(&sum).to_bytes());
+//! ```
+//!
 //! [1]: <https://cr.yp.to/ecdh/curve25519-20060209.pdf>
 //! [2]: <https://en.wikipedia.org/wiki/Curve25519>
 
 use crate::util::fixed_time_eq;
 use core::cmp::{min, Eq, Ordering, PartialEq};
+use core::convert::TryFrom;
 use core::ops::{Add, Mul, Sub};
 
 /*
@@ -38,14 +67,19 @@ t[0]+2^26 t[1]+2^51 t[2]+2^77 t[3]+2^102 t[4]+...+2^230 t[9].
 Bounds on each t[i] vary depending on context.
 */
 
+/// Element of the field of integers modulo p = 2^255 - 19
+///
+/// The canonical encoding of a field element is its 32 bytes little endian
+/// representation in `[0, p)`, as produced by [`Fe::to_bytes`] and accepted by
+/// [`Fe::from_canonical_bytes`].
 #[derive(Clone)]
-pub(crate) struct Fe(pub [i32; 10]);
+pub struct Fe(pub(crate) [i32; 10]);
 
 impl PartialEq for Fe {
     fn eq(&self, other: &Fe) -> bool {
         let &Fe(self_elems) = self;
         let &Fe(other_elems) = other;
-        self_elems.to_vec() == other_elems.to_vec()
+        self_elems == other_elems
     }
 }
 impl Eq for Fe {}
@@ -452,8 +486,14 @@ impl Mul for &Fe {
 }
 
 impl Fe {
+    /// Decode a field element from its 32 bytes little endian representation,
+    /// without validation
+    ///
+    /// Bit 255 is ignored, and values in `[p, 2^255)` are reduced modulo p,
+    /// as done for the u-coordinates of X25519 and the y-coordinates of
+    /// Ed25519. Use [`Fe::from_canonical_bytes`] to reject those encodings.
     #[rustfmt::skip]
-    pub(crate) fn from_bytes(s: &[u8]) -> Fe {
+    pub fn from_bytes(s: &[u8; 32]) -> Fe {
         let mut h0 = load_4i(&s[0..4]);
         let mut h1 = load_3i(&s[4..7]) << 6;
         let mut h2 = load_3i(&s[7..10]) << 5;
@@ -506,8 +546,10 @@ impl Fe {
       so floor(2^(-255)(h + 19 2^(-25) h9 + 2^(-1))) = q.
     */
 
+    /// Encode the field element in canonical form: 32 bytes little endian,
+    /// fully reduced modulo p, with bit 255 cleared
     #[rustfmt::skip]
-    pub(crate) fn to_bytes(&self) -> [u8; 32] {
+    pub fn to_bytes(&self) -> [u8; 32] {
         let &Fe(es) = self;
         let mut h0 = es[0];
         let mut h1 = es[1];
@@ -591,6 +633,19 @@ impl Fe {
         ]
     }
 
+    /// Decode a field element, only if it is in canonical form
+    ///
+    /// Returns `None` if bit 255 is set or the value is not smaller than p.
+    /// The check is done in constant time.
+    pub fn from_canonical_bytes(s: &[u8; 32]) -> Option<Fe> {
+        let fe = Fe::from_bytes(s);
+        if fixed_time_eq(&fe.to_bytes(), s) {
+            Some(fe)
+        } else {
+            None
+        }
+    }
+
     pub(crate) fn maybe_swap_with(&mut self, other: &mut Fe, do_swap: i32) {
         let &mut Fe(f) = self;
         let &mut Fe(g) = other;
@@ -661,62 +716,18 @@ impl Fe {
         ]);
     }
 
-    pub(crate) fn maybe_set(&mut self, other: &Fe, do_swap: i32) {
-        let &mut Fe(f) = self;
-        let &Fe(g) = other;
-        let f0 = f[0];
-        let f1 = f[1];
-        let f2 = f[2];
-        let f3 = f[3];
-        let f4 = f[4];
-        let f5 = f[5];
-        let f6 = f[6];
-        let f7 = f[7];
-        let f8 = f[8];
-        let f9 = f[9];
-        let g0 = g[0];
-        let g1 = g[1];
-        let g2 = g[2];
-        let g3 = g[3];
-        let g4 = g[4];
-        let g5 = g[5];
-        let g6 = g[6];
-        let g7 = g[7];
-        let g8 = g[8];
-        let g9 = g[9];
-        let mut x0 = f0 ^ g0;
-        let mut x1 = f1 ^ g1;
-        let mut x2 = f2 ^ g2;
-        let mut x3 = f3 ^ g3;
-        let mut x4 = f4 ^ g4;
-        let mut x5 = f5 ^ g5;
-        let mut x6 = f6 ^ g6;
-        let mut x7 = f7 ^ g7;
-        let mut x8 = f8 ^ g8;
-        let mut x9 = f9 ^ g9;
-        let b = -do_swap;
-        x0 &= b;
-        x1 &= b;
-        x2 &= b;
-        x3 &= b;
-        x4 &= b;
-        x5 &= b;
-        x6 &= b;
-        x7 &= b;
-        x8 &= b;
-        x9 &= b;
-        *self = Fe([
-            f0 ^ x0,
-            f1 ^ x1,
-            f2 ^ x2,
-            f3 ^ x3,
-            f4 ^ x4,
-            f5 ^ x5,
-            f6 ^ x6,
-            f7 ^ x7,
-            f8 ^ x8,
-            f9 ^ x9,
-        ]);
+    /// Set the element to `other` if `choice` is 0xFF, keep it unchanged if `choice` is 0
+    ///
+    /// The choice is applied as a bitmask on every limb, without any branch
+    /// or memory access depending on its value. Any other value of `choice`
+    /// gives an unspecified result.
+    pub(crate) fn conditional_assign(&mut self, other: &Fe, choice: u8) {
+        let mask = (choice as i8) as i32;
+        let Fe(f) = self;
+        let Fe(g) = other;
+        for (fi, gi) in f.iter_mut().zip(g.iter()) {
+            *fi ^= (*fi ^ *gi) & mask;
+        }
     }
 
     /*
@@ -1028,7 +1039,21 @@ impl Fe {
             h5 as i32, h6 as i32, h7 as i32, h8 as i32, h9 as i32])
     }
 
+    /// Compute the inverse of the field element, or zero for zero
+    ///
+    /// This is Fermat's inversion, see [`Fe::pow_p_minus_2`]
     pub(crate) fn invert(&self) -> Fe {
+        self.pow_p_minus_2()
+    }
+
+    /// Raise the field element to the power p-2 = 2^255 - 21
+    ///
+    /// By Fermat's little theorem this is the inverse of any non zero element,
+    /// and zero for zero. The computation is a fixed addition chain of
+    /// 254 squarings and 11 multiplications, independent of the value of
+    /// the element: there is no secret-dependent branch or memory access,
+    /// so it runs in constant time.
+    pub fn pow_p_minus_2(&self) -> Fe {
         let z1 = self.clone();
 
         /* qhasm: z2 = z1^2^1 */
@@ -1147,38 +1172,51 @@ impl Fe {
     }
 }
 
+/// Point of the Ed25519 curve in projective coordinates `(X:Y:Z)`
 #[derive(Clone)]
-pub(crate) struct GeP2 {
+pub struct GeP2 {
     x: Fe,
     y: Fe,
     z: Fe,
 }
 
+/// Point of the Ed25519 curve in extended coordinates `(X:Y:Z:T)`,
+/// with `XY = ZT`
+///
+/// This is the representation used for group operations: points are added
+/// to a `GeP3` in the [`GeCached`] or [`GePrecomp`] form, giving a
+/// [`GeP1P1`] to convert back.
 #[derive(Clone)]
-pub(crate) struct GeP3 {
+pub struct GeP3 {
     x: Fe,
     y: Fe,
     z: Fe,
     t: Fe,
 }
 
+/// Point of the Ed25519 curve in completed coordinates `((X:Z), (Y:T))`,
+/// the result of additions and doublings
 #[derive(Clone)]
-pub(crate) struct GeP1P1 {
+pub struct GeP1P1 {
     x: Fe,
     y: Fe,
     z: Fe,
     t: Fe,
 }
 
+/// Affine point of the Ed25519 curve precomputed for additions,
+/// as `(y+x, y-x, 2dxy)`
 #[derive(Clone)]
-pub(crate) struct GePrecomp {
+pub struct GePrecomp {
     y_plus_x: Fe,
     y_minus_x: Fe,
     xy2d: Fe,
 }
 
+/// Point of the Ed25519 curve precomputed for additions,
+/// as `(Y+X, Y-X, Z, 2dT)`
 #[derive(Clone)]
-pub(crate) struct GeCached {
+pub struct GeCached {
     y_plus_x: Fe,
     y_minus_x: Fe,
     z: Fe,
@@ -1186,7 +1224,8 @@ pub(crate) struct GeCached {
 }
 
 impl GeP1P1 {
-    pub(crate) fn to_p2(&self) -> GeP2 {
+    /// Convert to projective coordinates
+    pub fn to_p2(&self) -> GeP2 {
         GeP2 {
             x: &self.x * &self.t,
             y: &self.y * &self.z,
@@ -1194,7 +1233,8 @@ impl GeP1P1 {
         }
     }
 
-    fn to_p3(&self) -> GeP3 {
+    /// Convert to extended coordinates
+    pub fn to_p3(&self) -> GeP3 {
         GeP3 {
             x: &self.x * &self.t,
             y: &self.y * &self.z,
@@ -1213,7 +1253,8 @@ impl GeP2 {
         }
     }
 
-    pub(crate) fn to_bytes(&self) -> [u8; 32] {
+    /// Encode the point in the 32 bytes compressed form
+    pub fn to_bytes(&self) -> [u8; 32] {
         let recip = self.z.invert();
         let x = &self.x * &recip;
         let y = &self.y * &recip;
@@ -1279,27 +1320,16 @@ impl GeP2 {
     where a = a[0]+256*a[1]+...+256^31 a[31].
     and b = b[0]+256*b[1]+...+256^31 b[31].
     B is the Ed25519 base point (x,4/5) with x positive.
+    The odd multiples of A are computed in advance by `GeP3::odd_multiples`.
     */
-    pub(crate) fn double_scalarmult_vartime(
+    pub(crate) fn double_scalarmult_vartime_precomp(
         a_scalar: &[u8],
-        a_point: GeP3,
+        ai: &[GeCached; 8],
         b_scalar: &[u8],
     ) -> GeP2 {
         let aslide = GeP2::slide(a_scalar);
         let bslide = GeP2::slide(b_scalar);
 
-        let a1 = a_point.to_cached();
-        let a2 = a_point.dbl().to_p3();
-        let a3 = (&a2 + &a1).to_p3().to_cached();
-        let a5 = (&a2 + &a3).to_p3().to_cached();
-        let a7 = (&a2 + &a5).to_p3().to_cached();
-        let a9 = (&a2 + &a7).to_p3().to_cached();
-        let a11 = (&a2 + &a9).to_p3().to_cached();
-        let a13 = (&a2 + &a11).to_p3().to_cached();
-        let a15 = (&a2 + &a13).to_p3().to_cached();
-
-        let ai: [GeCached; 8] = [a1, a3, a5, a7, a9, a11, a13, a15];
-
         let mut r = GeP2::zero();
 
         let mut i: usize = 255;
@@ -1338,7 +1368,41 @@ impl GeP2 {
 }
 
 impl GeP3 {
-    pub(crate) fn from_bytes_negate_vartime(s: &[u8]) -> Option<GeP3> {
+    // the odd multiples A, 3A, 5A, .., 15A of the point, as used by the
+    // sliding window of `GeP2::double_scalarmult_vartime_precomp`
+    pub(crate) fn odd_multiples(&self) -> [GeCached; 8] {
+        let a1 = self.to_cached();
+        let a2 = self.dbl().to_p3();
+        let a3 = (&a2 + &a1).to_p3().to_cached();
+        let a5 = (&a2 + &a3).to_p3().to_cached();
+        let a7 = (&a2 + &a5).to_p3().to_cached();
+        let a9 = (&a2 + &a7).to_p3().to_cached();
+        let a11 = (&a2 + &a9).to_p3().to_cached();
+        let a13 = (&a2 + &a11).to_p3().to_cached();
+        let a15 = (&a2 + &a13).to_p3().to_cached();
+        [a1, a3, a5, a7, a9, a11, a13, a15]
+    }
+
+    /// Decode a point from its 32 bytes compressed form, in variable time
+    ///
+    /// Return `None` if the bytes are not the encoding of a point of the curve.
+    /// Non canonical encodings, with a y coordinate not reduced modulo p,
+    /// are accepted.
+    pub fn from_bytes(s: &[u8; 32]) -> Option<GeP3> {
+        GeP3::from_bytes_negate_vartime(s).map(|p| GeP3 {
+            x: p.x.neg(),
+            y: p.y,
+            z: p.z,
+            t: p.t.neg(),
+        })
+    }
+
+    /// Decode the negation of a point from its 32 bytes compressed form,
+    /// in variable time
+    ///
+    /// This is [`GeP3::from_bytes`] followed by a negation, as used by the
+    /// signature verification.
+    pub fn from_bytes_negate_vartime(s: &[u8; 32]) -> Option<GeP3> {
         let y = Fe::from_bytes(s);
         let z = FE_ONE.clone();
         let y_squared = y.square();
@@ -1382,7 +1446,8 @@ impl GeP3 {
         }
     }
 
-    pub(crate) fn to_cached(&self) -> GeCached {
+    /// Convert to the cached form, to add the point to other points
+    pub fn to_cached(&self) -> GeCached {
         GeCached {
             y_plus_x: &self.y + &self.x,
             y_minus_x: &self.y - &self.x,
@@ -1404,7 +1469,40 @@ impl GeP3 {
         self.to_p2().dbl()
     }
 
-    pub(crate) fn to_bytes(&self) -> [u8; 32] {
+    /// Convert to the precomputed affine form, to add the point to other points
+    ///
+    /// This needs a field inversion, and is only worth it compared to
+    /// [`GeP3::to_cached`] if the point is added many times.
+    pub fn to_precomp(&self) -> GePrecomp {
+        let recip = self.z.invert();
+        let x = &self.x * &recip;
+        let y = &self.y * &recip;
+        GePrecomp {
+            y_plus_x: &y + &x,
+            y_minus_x: &y - &x,
+            xy2d: &(&x * &y) * &FE_D2,
+        }
+    }
+
+    // small order points are the ones of the torsion subgroup, killed by the cofactor 8
+    pub(crate) fn is_small_order(&self) -> bool {
+        let p8 = self.dbl().to_p3().dbl().to_p3().dbl().to_p2();
+        !p8.x.is_nonzero()
+    }
+
+    /// Check whether the point is in the prime order subgroup generated by
+    /// the base point, i.e. whether multiplying it by l gives the identity
+    ///
+    /// This is done in variable time, and is meant to reject the points with a
+    /// torsion component received from other parties, which
+    /// [`GeP3::from_bytes`] accepts.
+    pub fn is_torsion_free(&self) -> bool {
+        let p = GeP2::double_scalarmult_vartime_precomp(&L, &self.odd_multiples(), &[0; 32]);
+        !p.x.is_nonzero() && !(&p.y - &p.z).is_nonzero()
+    }
+
+    /// Encode the point in the 32 bytes compressed form
+    pub fn to_bytes(&self) -> [u8; 32] {
         let recip = self.z.invert();
         let x = &self.x * &recip;
         let y = &self.y * &recip;
@@ -1547,12 +1645,13 @@ impl Sub<&GePrecomp> for &GeP3 {
     }
 }
 
-fn equal(b: u8, c: u8) -> i32 {
+// 0xFF if b == c, 0 otherwise, in constant time
+fn equal(b: u8, c: u8) -> u8 {
     let x = b ^ c; /* 0: yes; 1..255: no */
     let mut y = x as u32; /* 0: yes; 1..255: no */
     y = y.wrapping_sub(1); /* 4294967295: yes; 0..254: no */
     y >>= 31; /* 1: yes; 0: no */
-    y as i32
+    0u8.wrapping_sub(y as u8)
 }
 
 impl GePrecomp {
@@ -1564,30 +1663,32 @@ impl GePrecomp {
         }
     }
 
-    pub(crate) fn maybe_set(&mut self, other: &GePrecomp, do_swap: i32) {
-        self.y_plus_x.maybe_set(&other.y_plus_x, do_swap);
-        self.y_minus_x.maybe_set(&other.y_minus_x, do_swap);
-        self.xy2d.maybe_set(&other.xy2d, do_swap);
+    /// Set the point to `other` if `choice` is 0xFF, keep it unchanged if `choice` is 0,
+    /// in constant time
+    pub(crate) fn conditional_assign(&mut self, other: &GePrecomp, choice: u8) {
+        self.y_plus_x.conditional_assign(&other.y_plus_x, choice);
+        self.y_minus_x.conditional_assign(&other.y_minus_x, choice);
+        self.xy2d.conditional_assign(&other.xy2d, choice);
     }
 
+    // b B 256^pos for a signed digit b in [-8, 8], from the row `pos` of the comb table
+    //
+    // The secret digit is only used through masks: every entry of the row is read
+    // and conditionally assigned, and the negation is computed then conditionally
+    // assigned, so neither the memory accesses nor the branches depend on b.
     pub(crate) fn select(pos: usize, b: i8) -> GePrecomp {
         let bnegative = (b as u8) >> 7;
         let babs: u8 = (b - (((-(bnegative as i8)) & b) << 1)) as u8;
         let mut t = GePrecomp::zero();
-        t.maybe_set(&GE_PRECOMP_BASE[pos][0], equal(babs, 1));
-        t.maybe_set(&GE_PRECOMP_BASE[pos][1], equal(babs, 2));
-        t.maybe_set(&GE_PRECOMP_BASE[pos][2], equal(babs, 3));
-        t.maybe_set(&GE_PRECOMP_BASE[pos][3], equal(babs, 4));
-        t.maybe_set(&GE_PRECOMP_BASE[pos][4], equal(babs, 5));
-        t.maybe_set(&GE_PRECOMP_BASE[pos][5], equal(babs, 6));
-        t.maybe_set(&GE_PRECOMP_BASE[pos][6], equal(babs, 7));
-        t.maybe_set(&GE_PRECOMP_BASE[pos][7], equal(babs, 8));
+        for (k, p) in GE_PRECOMP_BASE[pos].iter().enumerate() {
+            t.conditional_assign(p, equal(babs, k as u8 + 1));
+        }
         let minus_t = GePrecomp {
             y_plus_x: t.y_minus_x.clone(),
             y_minus_x: t.y_plus_x.clone(),
             xy2d: t.xy2d.neg(),
         };
-        t.maybe_set(&minus_t, bnegative as i32);
+        t.conditional_assign(&minus_t, 0u8.wrapping_sub(bnegative));
         t
     }
 }
@@ -1599,8 +1700,23 @@ B is the Ed25519 base point (x,4/5) with x positive.
 
 Preconditions:
   a[31] <= 127
+
+The scalar is recoded in 64 signed radix-16 digits e[i] in [-8,8], so that
+  a * B = sum(e[2j] 256^j B) + 16 * sum(e[2j+1] 256^j B)
+and every term is looked up in the static comb table GE_PRECOMP_BASE,
+where GE_PRECOMP_BASE[j][k] = (k+1) 256^j B. The lookup always scans the
+whole row and the sign is applied with a conditional negation, so the
+memory access pattern doesn't depend on the scalar, and only 4 doublings
+are needed overall.
 */
-pub(crate) fn ge_scalarmult_base(a: &[u8]) -> GeP3 {
+/// Multiply the Ed25519 base point by a 32 bytes little endian scalar, in constant time
+///
+/// The most significant bit of the scalar must be cleared, otherwise the
+/// result is wrong: this holds for any scalar reduced modulo l (see
+/// [`scalar_reduce`]) as well as for a clamped Ed25519 secret scalar.
+/// This is checked in debug builds only.
+pub fn ge_scalarmult_base(a: &[u8; 32]) -> GeP3 {
+    debug_assert!(a[31] <= 127, "scalar most significant bit must be cleared");
     let mut es: [i8; 64] = [0; 64];
     let mut r: GeP1P1;
     let mut s: GeP2;
@@ -2244,10 +2360,190 @@ pub(crate) fn sc_muladd(s: &mut[u8], a: &[u8], b: &[u8], c: &[u8]) {
     s[31] = (s11 >> 17) as u8;
 }
 
+/// Canonical encoding of the Ed25519 base point, the point of y = 4/5 with x positive
+pub const BASEPOINT_BYTES: [u8; 32] = [
+    0x58, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+    0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+];
+
+/// Canonical encoding of the identity element of the Ed25519 group, the point (0, 1)
+pub const IDENTITY_BYTES: [u8; 32] = [
+    0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+/// Order of the base point, l = 2^252 + 27742317777372353535851937790883648493,
+/// in little endian
+pub const L: [u8; 32] = [
+    0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+];
+
+/// Check that a little endian scalar is in canonical form, i.e. `0 <= s < l`
+/// where l is the order of the base point.
+///
+/// The comparison is done in constant time.
+pub fn scalar_is_canonical(s: &[u8; 32]) -> bool {
+    // c is set when s is found smaller than L, n is set as long as all the
+    // bytes compared so far (from the most significant) are equal.
+    let mut c: u8 = 0;
+    let mut n: u8 = 1;
+
+    for i in (0..32).rev() {
+        c |= ((((s[i] as i32) - (L[i] as i32)) >> 8) as u8) & n;
+        n &= ((((s[i] ^ L[i]) as i32) - 1) >> 8) as u8;
+    }
+
+    c != 0
+}
+
+/// Reduce a 64 bytes little endian value modulo l, the order of the base point
+///
+/// This is how Ed25519 turns the output of SHA-512 into a scalar for the
+/// nonce and the challenge, and can be used for hash-to-scalar in other
+/// constructions on the curve. The reduction is done in constant time, and
+/// the result is a canonical scalar.
+pub fn scalar_from_bytes_wide(bytes: &[u8; 64]) -> [u8; 32] {
+    let mut s = *bytes;
+    sc_reduce(&mut s);
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&s[0..32]);
+    out
+}
+
+/// Reduce a 32 bytes little endian value modulo l, the order of the base point
+///
+/// This brings an encoding that is not canonical, as checked by
+/// [`scalar_is_canonical`], into range. The reduction is done in constant time.
+/// Two scalars can then be compared in constant time with
+/// [`fixed_time_eq`].
+pub fn scalar_reduce(bytes: &[u8; 32]) -> [u8; 32] {
+    let mut wide = [0u8; 64];
+    wide[..32].copy_from_slice(bytes);
+    scalar_from_bytes_wide(&wide)
+}
+
+/// Scalar modulo l, the order of the base point, in canonical form
+///
+/// The canonical encoding is the 32 bytes little endian representation in
+/// `[0, l)`, as checked by [`scalar_is_canonical`]. Equality is checked in
+/// constant time, and the value is not shown by `Debug` as scalars are
+/// usually secret.
+#[derive(Clone)]
+pub struct Scalar([u8; 32]);
+
+impl PartialEq for Scalar {
+    fn eq(&self, other: &Self) -> bool {
+        fixed_time_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Scalar {}
+
+impl core::fmt::Debug for Scalar {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Scalar([redacted])")
+    }
+}
+
+/// Error returned when converting bytes which are not reduced modulo l to a [`Scalar`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonCanonicalScalar;
+
+impl core::fmt::Display for NonCanonicalScalar {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "non canonical scalar encoding")
+    }
+}
+
+impl TryFrom<[u8; 32]> for Scalar {
+    type Error = NonCanonicalScalar;
+
+    fn try_from(bytes: [u8; 32]) -> Result<Self, Self::Error> {
+        Scalar::from_canonical_bytes(&bytes).ok_or(NonCanonicalScalar)
+    }
+}
+
+impl AsRef<[u8]> for Scalar {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Scalar {
+    /// Decode a scalar, only if it is in canonical form
+    pub fn from_canonical_bytes(bytes: &[u8; 32]) -> Option<Scalar> {
+        if scalar_is_canonical(bytes) {
+            Some(Scalar(*bytes))
+        } else {
+            None
+        }
+    }
+
+    /// Decode a scalar, reducing it modulo l
+    pub fn from_bytes_mod_order(bytes: &[u8; 32]) -> Scalar {
+        Scalar(scalar_reduce(bytes))
+    }
+
+    /// Decode a scalar from 64 bytes, reducing it modulo l
+    pub fn from_bytes_mod_order_wide(bytes: &[u8; 64]) -> Scalar {
+        Scalar(scalar_from_bytes_wide(bytes))
+    }
+
+    /// Encode the scalar in canonical form
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+
+    /// Canonical encoding of the scalar
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+/// Clamp a 32 bytes X25519 secret scalar in place, as specified in RFC 7748
+///
+/// The 3 low bits are cleared to make the scalar a multiple of the cofactor 8,
+/// bit 255 is cleared and bit 254 is set, so that the scalar is in
+/// [2^254, 2^255) and the Montgomery ladder has a fixed number of steps.
+pub fn clamp_x25519_scalar(scalar: &mut [u8]) {
+    assert!(scalar.len() == 32);
+    scalar[0] &= 248;
+    scalar[31] &= 127;
+    scalar[31] |= 64;
+}
+
+/// Clamp the 32 bytes Ed25519 secret scalar in place, as specified in RFC 8032
+///
+/// The ed25519 reference implementation masks the last byte with 63 rather
+/// than 127, also clearing bit 254 before setting it, so the result is bit for
+/// bit the same as [`clamp_x25519_scalar`]: this is what allows an Ed25519
+/// secret key to be reused for X25519. It is kept as a separate function as
+/// the two specifications define the clamping independently.
+pub fn clamp_ed25519_scalar(scalar: &mut [u8]) {
+    assert!(scalar.len() == 32);
+    scalar[0] &= 248;
+    scalar[31] &= 63;
+    scalar[31] |= 64;
+}
+
 /// Computes a shared secret from the curve25519 private key (n) and public
 /// key (p)
 pub fn curve25519(n: &[u8], p: &[u8]) -> [u8; 32] {
     let mut e = [0u8; 32];
+    for (d, s) in e.iter_mut().zip(n.iter()) {
+        *d = *s;
+    }
+    clamp_x25519_scalar(&mut e);
+    montgomery_ladder(&e, <&[u8; 32]>::try_from(&p[0..32]).unwrap())
+}
+
+/// Montgomery ladder computing the u-coordinate of the multiplication of
+/// the point of u-coordinate `p` by the scalar `e`, taken as is
+///
+/// All the 256 bits of the scalar are used, and the most significant bit of
+/// `p` is ignored. Points on the twist are handled by the same formulas.
+pub(crate) fn montgomery_ladder(e: &[u8; 32], p: &[u8; 32]) -> [u8; 32] {
     let mut x2;
     let mut z2;
     let mut x3;
@@ -2255,12 +2551,6 @@ pub fn curve25519(n: &[u8], p: &[u8]) -> [u8; 32] {
     let mut swap: i32;
     let mut b: i32;
 
-    for (d, s) in e.iter_mut().zip(n.iter()) {
-        *d = *s;
-    }
-    e[0] &= 248;
-    e[31] &= 127;
-    e[31] |= 64;
     let x1 = Fe::from_bytes(p);
     x2 = FE_ONE.clone();
     z2 = FE_ZERO.clone();
@@ -2268,8 +2558,8 @@ pub fn curve25519(n: &[u8], p: &[u8]) -> [u8; 32] {
     z3 = FE_ONE.clone();
 
     swap = 0;
-    // pos starts at 254 and goes down to 0
-    for pos in (0usize..255).rev() {
+    // pos starts at 255 and goes down to 0
+    for pos in (0usize..256).rev() {
         b = (e[pos / 8] >> (pos & 7)) as i32;
         b &= 1;
         swap ^= b;
@@ -2307,6 +2597,101 @@ pub fn curve25519(n: &[u8], p: &[u8]) -> [u8; 32] {
     (z2.invert() * x2).to_bytes()
 }
 
+/// Encodings of the points of small order of Curve25519 as X25519 public keys
+///
+/// These are the u-coordinates 0, 1, the two points of order 8, p - 1, and
+/// their non canonical encodings by adding p or 2p, that fit in 32 bytes.
+const X25519_SMALL_ORDER: [[u8; 32]; 12] = [
+    [
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00,
+    ],
+    [
+        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00,
+    ],
+    [
+        0xe0, 0xeb, 0x7a, 0x7c, 0x3b, 0x41, 0xb8, 0xae, 0x16, 0x56, 0xe3, 0xfa, 0xf1, 0x9f, 0xc4,
+        0x6a, 0xda, 0x09, 0x8d, 0xeb, 0x9c, 0x32, 0xb1, 0xfd, 0x86, 0x62, 0x05, 0x16, 0x5f, 0x49,
+        0xb8, 0x00,
+    ],
+    [
+        0x5f, 0x9c, 0x95, 0xbc, 0xa3, 0x50, 0x8c, 0x24, 0xb1, 0xd0, 0xb1, 0x55, 0x9c, 0x83, 0xef,
+        0x5b, 0x04, 0x44, 0x5c, 0xc4, 0x58, 0x1c, 0x8e, 0x86, 0xd8, 0x22, 0x4e, 0xdd, 0xd0, 0x9f,
+        0x11, 0x57,
+    ],
+    [
+        0xec, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0x7f,
+    ],
+    [
+        0xed, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0x7f,
+    ],
+    [
+        0xee, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0x7f,
+    ],
+    [
+        0xcd, 0xeb, 0x7a, 0x7c, 0x3b, 0x41, 0xb8, 0xae, 0x16, 0x56, 0xe3, 0xfa, 0xf1, 0x9f, 0xc4,
+        0x6a, 0xda, 0x09, 0x8d, 0xeb, 0x9c, 0x32, 0xb1, 0xfd, 0x86, 0x62, 0x05, 0x16, 0x5f, 0x49,
+        0xb8, 0x80,
+    ],
+    [
+        0x4c, 0x9c, 0x95, 0xbc, 0xa3, 0x50, 0x8c, 0x24, 0xb1, 0xd0, 0xb1, 0x55, 0x9c, 0x83, 0xef,
+        0x5b, 0x04, 0x44, 0x5c, 0xc4, 0x58, 0x1c, 0x8e, 0x86, 0xd8, 0x22, 0x4e, 0xdd, 0xd0, 0x9f,
+        0x11, 0xd7,
+    ],
+    [
+        0xd9, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff,
+    ],
+    [
+        0xda, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff,
+    ],
+    [
+        0xdb, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff,
+    ],
+];
+
+/// Check whether a X25519 public key is one of the points of small order
+///
+/// The shared secret computed from such a public key is independent of the
+/// secret key, e.g. all zeros, so protocols relying on the contribution of
+/// both parties to the shared secret should reject them. The encodings with
+/// the most significant bit set are checked as is, for the peers that don't
+/// ignore this bit, and with the bit cleared as done by [`curve25519`].
+///
+/// The comparison is done in constant time.
+pub fn x25519_public_key_is_weak(public_key: &[u8; 32]) -> bool {
+    let mut masked = *public_key;
+    masked[31] &= 0x7f;
+    let mut weak = 0u8;
+    for point in X25519_SMALL_ORDER.iter() {
+        let mut d_raw = 0u8;
+        let mut d_masked = 0u8;
+        for i in 0..32 {
+            d_raw |= public_key[i] ^ point[i];
+            d_masked |= masked[i] ^ point[i];
+        }
+        // 1 if any of the differences is 0
+        weak |= (((d_raw as u16).wrapping_sub(1) >> 8) as u8
+            | ((d_masked as u16).wrapping_sub(1) >> 8) as u8)
+            & 1;
+    }
+    weak != 0
+}
+
 /// Derives a public key from a private key
 pub fn curve25519_base(x: &[u8]) -> [u8; 32] {
     let mut base: [u8; 32] = [0; 32];
@@ -2316,24 +2701,96 @@ pub fn curve25519_base(x: &[u8]) -> [u8; 32] {
 
 #[cfg(test)]
 mod tests {
-    use super::{curve25519_base, Fe};
-    use alloc::vec::Vec;
+    use super::{
+        clamp_ed25519_scalar, clamp_x25519_scalar, curve25519, curve25519_base, equal,
+        ge_scalarmult_base, scalar_from_bytes_wide, scalar_is_canonical, scalar_reduce,
+        x25519_public_key_is_weak, Fe, GeP2, GeP3, GePrecomp, NonCanonicalScalar, Scalar,
+        BASEPOINT_BYTES, FE_ONE, FE_ZERO, GE_PRECOMP_BASE, IDENTITY_BYTES, L, X25519_SMALL_ORDER,
+    };
+    use crate::util::fixed_time_eq;
+    use core::convert::TryFrom;
 
     #[test]
     fn from_to_bytes_preserves() {
         for i in 0..50 {
-            let mut e: Vec<u8> = (0u32..32)
-                .map(|idx| (idx * (1289 + i * 761)) as u8)
-                .collect();
+            let mut e = [0u8; 32];
+            for (idx, b) in e.iter_mut().enumerate() {
+                *b = (idx as u32 * (1289 + i * 761)) as u8;
+            }
             e[0] &= 248;
             e[31] &= 127;
             e[31] |= 64;
-            let fe = Fe::from_bytes(e.as_ref());
+            let fe = Fe::from_bytes(&e);
             let e_preserved = fe.to_bytes();
-            assert!(e == e_preserved.to_vec());
+            assert!(e == e_preserved);
         }
     }
 
+    #[test]
+    fn fe_canonical_bytes() {
+        for i in 0..50u32 {
+            let mut e = [0u8; 32];
+            for (idx, b) in e.iter_mut().enumerate() {
+                *b = ((idx as u32) * (1289 + i * 761)) as u8;
+            }
+            e[31] &= 127;
+            let fe = Fe::from_canonical_bytes(&e).unwrap();
+            assert_eq!(fe.to_bytes(), e);
+        }
+
+        // p - 1 is the largest canonical encoding
+        let mut p_minus_one = [0xffu8; 32];
+        p_minus_one[0] = 0xec;
+        p_minus_one[31] = 0x7f;
+        assert!(Fe::from_canonical_bytes(&p_minus_one).is_some());
+
+        // p, p + 1, ..., 2^255 - 1 are the non canonical encodings of 0, 1, ..., 18
+        for k in 0..19u8 {
+            let mut e = p_minus_one;
+            e[0] = 0xed + k;
+            assert!(Fe::from_canonical_bytes(&e).is_none());
+            let mut reduced = [0u8; 32];
+            reduced[0] = k;
+            assert_eq!(Fe::from_bytes(&e).to_bytes(), reduced);
+        }
+
+        // bit 255 set
+        let mut e = [0u8; 32];
+        e[0] = 9;
+        e[31] = 0x80;
+        assert!(Fe::from_canonical_bytes(&e).is_none());
+        e[31] = 0;
+        assert!(Fe::from_canonical_bytes(&e) == Some(Fe::from_bytes(&e)));
+    }
+
+    #[test]
+    fn scalar_canonical_bytes() {
+        let s = scalar_from_bytes_wide(&[0x5a; 64]);
+        let scalar = Scalar::from_canonical_bytes(&s).unwrap();
+        assert_eq!(scalar.to_bytes(), s);
+        assert_eq!(scalar, Scalar::from_bytes_mod_order_wide(&[0x5a; 64]));
+
+        assert!(Scalar::from_canonical_bytes(&L).is_none());
+        assert_eq!(Scalar::from_bytes_mod_order(&L).as_bytes(), &[0u8; 32]);
+
+        let mut l_minus_one = L;
+        l_minus_one[0] -= 1;
+        assert_eq!(
+            Scalar::from_canonical_bytes(&l_minus_one),
+            Some(Scalar::from_bytes_mod_order(&l_minus_one))
+        );
+        assert!(Scalar::from_canonical_bytes(&[0xff; 32]).is_none());
+    }
+
+    #[test]
+    fn scalar_conversions() {
+        let s = scalar_from_bytes_wide(&[0x5a; 64]);
+        let scalar = Scalar::try_from(s).unwrap();
+        assert_eq!(scalar.as_ref(), &s[..]);
+        assert_eq!(Scalar::try_from(L), Err(NonCanonicalScalar));
+        assert_eq!(std::format!("{:?}", scalar), "Scalar([redacted])");
+    }
+
     #[test]
     fn swap_test() {
         let f_initial = Fe([10, 20, 30, 40, 50, 60, 70, 80, 90, 100]);
@@ -2361,13 +2818,14 @@ mod tests {
         type Item = Fe;
 
         fn next(&mut self) -> Option<Fe> {
-            let mut e: Vec<u8> = (0..32)
-                .map(|idx| (idx * (1289 + self.which * 761)) as u8)
-                .collect();
+            let mut e = [0u8; 32];
+            for (idx, b) in e.iter_mut().enumerate() {
+                *b = (idx as u32 * (1289 + self.which * 761)) as u8;
+            }
             e[0] &= 248;
             e[31] &= 127;
             e[31] |= 64;
-            Some(Fe::from_bytes(e.as_ref()))
+            Some(Fe::from_bytes(&e))
         }
     }
 
@@ -2396,6 +2854,99 @@ mod tests {
         }
     }
 
+    #[test]
+    fn pow_p_minus_2_is_inverse() {
+        for x in CurveGen::new(1).take(40) {
+            let y = x.pow_p_minus_2();
+            assert!(y == x.invert());
+            assert_eq!((&x * &y).to_bytes(), FE_ONE.to_bytes());
+        }
+        assert_eq!(FE_ZERO.pow_p_minus_2().to_bytes(), [0; 32]);
+    }
+
+    #[test]
+    fn clamping() {
+        let mut all_ones = [0xff; 32];
+        clamp_x25519_scalar(&mut all_ones);
+        let mut expected = [0xff; 32];
+        expected[0] = 0xf8;
+        expected[31] = 0x7f;
+        assert_eq!(all_ones, expected);
+
+        let mut zeros = [0; 32];
+        clamp_x25519_scalar(&mut zeros);
+        let mut expected = [0; 32];
+        expected[31] = 0x40;
+        assert_eq!(zeros, expected);
+
+        let mut all_ones = [0xff; 32];
+        clamp_ed25519_scalar(&mut all_ones);
+        let mut expected = [0xff; 32];
+        expected[0] = 0xf8;
+        expected[31] = 0x7f;
+        assert_eq!(all_ones, expected);
+
+        let mut zeros = [0; 32];
+        clamp_ed25519_scalar(&mut zeros);
+        let mut expected = [0; 32];
+        expected[31] = 0x40;
+        assert_eq!(zeros, expected);
+
+        // both clampings give the same result on any input
+        for b in 0..=255u8 {
+            let mut x = [b; 32];
+            let mut e = [b; 32];
+            clamp_x25519_scalar(&mut x);
+            clamp_ed25519_scalar(&mut e);
+            assert_eq!(x, e);
+            assert_eq!(x[0] & 7, 0);
+            assert_eq!(x[31] & 0xc0, 0x40);
+            assert_eq!(&x[1..31], &[b; 30][..]);
+        }
+    }
+
+    #[test]
+    fn conditional_assign() {
+        for (x, y) in CurveGen::new(1).zip(CurveGen::new(2)).take(40) {
+            let mut z = x.clone();
+            z.conditional_assign(&y, 0);
+            assert!(z == x);
+            z.conditional_assign(&y, 0xff);
+            assert!(z == y);
+            z.conditional_assign(&x, 0xff);
+            assert!(z == x);
+        }
+    }
+
+    #[test]
+    fn equal_mask() {
+        for b in 0..=255u8 {
+            assert_eq!(equal(b, b), 0xff);
+            assert_eq!(equal(b, b.wrapping_add(1)), 0);
+            assert_eq!(equal(b, b ^ 0x80), 0);
+        }
+    }
+
+    #[test]
+    fn precomp_select() {
+        for (pos, row) in GE_PRECOMP_BASE.iter().enumerate() {
+            let zero = GePrecomp::select(pos, 0);
+            assert!(zero.y_plus_x == FE_ONE && zero.y_minus_x == FE_ONE && zero.xy2d == FE_ZERO);
+            for b in 1..=8i8 {
+                let expected = &row[b as usize - 1];
+                let t = GePrecomp::select(pos, b);
+                assert!(t.y_plus_x == expected.y_plus_x);
+                assert!(t.y_minus_x == expected.y_minus_x);
+                assert!(t.xy2d == expected.xy2d);
+
+                let t = GePrecomp::select(pos, -b);
+                assert!(t.y_plus_x == expected.y_minus_x);
+                assert!(t.y_minus_x == expected.y_plus_x);
+                assert!(t.xy2d == expected.xy2d.neg());
+            }
+        }
+    }
+
     #[test]
     fn square_by_mul() {
         for x in CurveGen::new(1).take(40) {
@@ -2418,6 +2969,266 @@ mod tests {
         ];
         assert_eq!(pk.to_vec(), correct.to_vec());
     }
+
+    #[test]
+    fn scalarmult_base_matches_vartime() {
+        // any valid point works as the unused second point
+        let point = ge_scalarmult_base(&[1; 32]).to_bytes();
+        for i in 0..16u32 {
+            let mut scalar = [0u8; 32];
+            for (idx, b) in scalar.iter_mut().enumerate() {
+                *b = (idx as u32 * (1289 + i * 761)) as u8;
+            }
+            scalar[31] &= 127;
+            let a = GeP3::from_bytes_negate_vartime(&point).unwrap();
+            let vartime =
+                GeP2::double_scalarmult_vartime_precomp(&[0; 32], &a.odd_multiples(), &scalar);
+            assert_eq!(ge_scalarmult_base(&scalar).to_bytes(), vartime.to_bytes());
+        }
+    }
+
+    #[test]
+    fn scalar_canonical_boundaries() {
+        let l_minus_1 = {
+            let mut s = L;
+            s[0] -= 1;
+            s
+        };
+        let l_plus_1 = {
+            let mut s = L;
+            s[0] += 1;
+            s
+        };
+        assert!(scalar_is_canonical(&[0; 32]));
+        assert!(scalar_is_canonical(&l_minus_1));
+        assert!(!scalar_is_canonical(&L));
+        assert!(!scalar_is_canonical(&l_plus_1));
+        assert!(!scalar_is_canonical(&[0xff; 32]));
+
+        // 2^252 - 1, 2^252, 2^252 + 1 are below l, 2^253 is above
+        let mut p252_minus_1 = [0xff; 32];
+        p252_minus_1[31] = 0x0f;
+        let mut p252 = [0; 32];
+        p252[31] = 0x10;
+        let mut p252_plus_1 = p252;
+        p252_plus_1[0] = 1;
+        let mut p253 = [0; 32];
+        p253[31] = 0x20;
+        assert!(scalar_is_canonical(&p252_minus_1));
+        assert!(scalar_is_canonical(&p252));
+        assert!(scalar_is_canonical(&p252_plus_1));
+        assert!(!scalar_is_canonical(&p253));
+    }
+
+    #[test]
+    fn constants() {
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        assert_eq!(ge_scalarmult_base(&one).to_bytes(), BASEPOINT_BYTES);
+        assert_eq!(ge_scalarmult_base(&[0u8; 32]).to_bytes(), IDENTITY_BYTES);
+
+        // l B is the identity, and (l - 1) B the negation of B
+        assert_eq!(ge_scalarmult_base(&L).to_bytes(), IDENTITY_BYTES);
+        let mut l_minus_one = L;
+        l_minus_one[0] -= 1;
+        let p = ge_scalarmult_base(&l_minus_one);
+        let b = GeP3::from_bytes(&BASEPOINT_BYTES).unwrap();
+        assert_eq!((&p + &b.to_cached()).to_p3().to_bytes(), IDENTITY_BYTES);
+    }
+
+    #[test]
+    fn x25519_weak_public_keys() {
+        for point in X25519_SMALL_ORDER.iter() {
+            assert!(x25519_public_key_is_weak(point));
+            // the shared secret doesn't depend on the secret key
+            let mut masked = *point;
+            masked[31] &= 0x7f;
+            if masked == *point {
+                assert_eq!(curve25519(&[0x42; 32], point), [0u8; 32]);
+            }
+        }
+
+        // ignored most significant bit
+        let mut zero_msb = [0u8; 32];
+        zero_msb[31] = 0x80;
+        assert!(x25519_public_key_is_weak(&zero_msb));
+        let mut one_msb = zero_msb;
+        one_msb[0] = 1;
+        assert!(x25519_public_key_is_weak(&one_msb));
+
+        let mut basepoint = [0u8; 32];
+        basepoint[0] = 9;
+        assert!(!x25519_public_key_is_weak(&basepoint));
+        for i in 0..16u8 {
+            let public = curve25519_base(&[i; 32]);
+            assert!(!x25519_public_key_is_weak(&public));
+        }
+        let mut two = [0u8; 32];
+        two[0] = 2;
+        assert!(!x25519_public_key_is_weak(&two));
+    }
+
+    #[test]
+    fn scalar_wide_reduction() {
+        let mut wide = [0u8; 64];
+        wide[..32].copy_from_slice(&L);
+        assert_eq!(scalar_from_bytes_wide(&wide), [0u8; 32]);
+
+        wide[0] += 5;
+        let mut five = [0u8; 32];
+        five[0] = 5;
+        assert_eq!(scalar_from_bytes_wide(&wide), five);
+
+        // (2^512 - 1) mod l
+        let s = scalar_from_bytes_wide(&[0xff; 64]);
+        assert_eq!(
+            s,
+            [
+                0x00, 0x0f, 0x9c, 0x44, 0xe3, 0x11, 0x06, 0xa4, 0x47, 0x93, 0x85, 0x68, 0xa7, 0x1b,
+                0x0e, 0xd0, 0x65, 0xbe, 0xf5, 0x17, 0xd2, 0x73, 0xec, 0xce, 0x3d, 0x9a, 0x30, 0x7c,
+                0x1b, 0x41, 0x99, 0x03,
+            ]
+        );
+        assert!(scalar_is_canonical(&s));
+    }
+
+    #[test]
+    fn scalar_reduction_boundaries() {
+        let mut l_minus_1 = L;
+        l_minus_1[0] -= 1;
+        let mut l_plus_1 = L;
+        l_plus_1[0] += 1;
+        let mut one = [0u8; 32];
+        one[0] = 1;
+
+        assert_eq!(scalar_reduce(&[0; 32]), [0; 32]);
+        assert_eq!(scalar_reduce(&l_minus_1), l_minus_1);
+        assert_eq!(scalar_reduce(&L), [0; 32]);
+        assert_eq!(scalar_reduce(&l_plus_1), one);
+
+        // (2^256 - 1) mod l
+        let s = scalar_reduce(&[0xff; 32]);
+        assert_eq!(
+            s,
+            [
+                0x1c, 0x95, 0x98, 0x8d, 0x74, 0x31, 0xec, 0xd6, 0x70, 0xcf, 0x7d, 0x73, 0xf4, 0x5b,
+                0xef, 0xc6, 0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                0xff, 0xff, 0xff, 0x0f,
+            ]
+        );
+        assert!(scalar_is_canonical(&s));
+        assert!(fixed_time_eq(&scalar_reduce(&s), &s));
+    }
+
+    #[test]
+    fn torsion_free() {
+        fn unhex(s: &str) -> [u8; 32] {
+            let mut out = [0u8; 32];
+            for (i, b) in out.iter_mut().enumerate() {
+                *b = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+            }
+            out
+        }
+
+        // the 8 points of the torsion subgroup, the identity first
+        let torsion = [
+            "0100000000000000000000000000000000000000000000000000000000000000",
+            "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac037a",
+            "0000000000000000000000000000000000000000000000000000000000000080",
+            "26e8958fc2b227b045c3f489f2ef98f0d5dfac05d3c63339b13802886d53fc05",
+            "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            "26e8958fc2b227b045c3f489f2ef98f0d5dfac05d3c63339b13802886d53fc85",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac03fa",
+        ];
+        let b = GeP3::from_bytes(&BASEPOINT_BYTES).unwrap();
+        assert!(b.is_torsion_free());
+        assert!(ge_scalarmult_base(&[0x42; 32]).is_torsion_free());
+
+        for (i, t) in torsion.iter().enumerate() {
+            let t = GeP3::from_bytes(&unhex(t)).unwrap();
+            assert!(t.is_small_order());
+            // only the identity is in the prime order subgroup
+            assert_eq!(t.is_torsion_free(), i == 0);
+
+            let mixed = (&b + &t.to_cached()).to_p3();
+            assert_eq!(mixed.is_torsion_free(), i == 0);
+        }
+    }
+
+    #[test]
+    fn point_addition() {
+        let scalar = |n: u8| {
+            let mut s = [0u8; 32];
+            s[0] = n;
+            s
+        };
+        let p3 = ge_scalarmult_base(&scalar(3));
+        let p7 = ge_scalarmult_base(&scalar(7));
+        let p10 = ge_scalarmult_base(&scalar(10)).to_bytes();
+
+        let decoded = GeP3::from_bytes(&p3.to_bytes()).unwrap();
+        assert_eq!(decoded.to_bytes(), p3.to_bytes());
+        let negated = GeP3::from_bytes_negate_vartime(&p3.to_bytes()).unwrap();
+        assert_eq!((&p3 + &negated.to_cached()).to_p3().to_bytes(), scalar(1));
+
+        assert_eq!((&p3 + &p7.to_cached()).to_p3().to_bytes(), p10);
+        assert_eq!((&p3 + &p7.to_precomp()).to_p3().to_bytes(), p10);
+        assert_eq!((&p3 + &p7.to_precomp()).to_p2().to_bytes(), p10);
+        assert_eq!(
+            (&ge_scalarmult_base(&scalar(10)) - &p7.to_precomp())
+                .to_p3()
+                .to_bytes(),
+            p3.to_bytes()
+        );
+
+        // y = 2 is not the coordinate of a point of the curve
+        assert!(GeP3::from_bytes(&scalar(2)).is_none());
+    }
+
+    // variable time comparison of little endian numbers
+    fn lt_reference(a: &[u8; 32], b: &[u8; 32]) -> bool {
+        a.iter().rev().lt(b.iter().rev())
+    }
+
+    #[test]
+    fn scalar_canonical_random() {
+        // xorshift64* generator, good enough to cover the comparison paths
+        let mut state = 0x2545f4914f6cdd1du64;
+        let mut next = || {
+            state ^= state >> 12;
+            state ^= state << 25;
+            state ^= state >> 27;
+            state.wrapping_mul(0x2545f4914f6cdd1d)
+        };
+
+        for i in 0..1_000_000 {
+            let mut s = [0u8; 32];
+            for chunk in s.chunks_mut(8) {
+                chunk.copy_from_slice(&next().to_le_bytes());
+            }
+            match i % 3 {
+                // uniformly random, mostly above l
+                0 => {}
+                // in the 2^252 range, sharing the most significant bytes of l
+                1 => {
+                    let shared = (next() % 33) as usize;
+                    s[32 - shared..].copy_from_slice(&L[32 - shared..]);
+                    s[31] &= 0x1f;
+                }
+                // l with a few bytes changed by a small amount
+                _ => {
+                    s = L;
+                    for _ in 0..(next() % 3) {
+                        let r = next();
+                        let j = (r % 32) as usize;
+                        s[j] = s[j].wrapping_add((r >> 8) as u8 % 3).wrapping_sub(1);
+                    }
+                }
+            }
+            assert_eq!(scalar_is_canonical(&s), lt_reference(&s, &L), "{:?}", s);
+        }
+    }
 }
 
 #[rustfmt::skip]
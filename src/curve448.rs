@@ -0,0 +1,768 @@
+//! Curve448 elliptic curve diffie hellman (X448)
+//!
+//! Curve448 elliptic curve specified in [1], also known as the
+//! "Goldilocks" curve [2], giving a ~224 bits security level.
+//!
+//! # Example
+//!
+//! Creating a curve448 point from a secret:
+//!
+//! ```
+//! use cryptoxide::curve448::x448_base;
+//!
+//! let secret = [7u8; 56];
+//! let public = x448_base(&secret);
+//! ```
+//!
+//! Doing a ECDH on curve448 using a curve point 'other_point' and a specific secret:
+//!
+//! ```
+//! use cryptoxide::curve448::{x448, x448_base};
+//!
+//! # let other_point = x448_base(&[3u8; 56]);
+//! let secret = [7u8; 56];
+//! let shared = x448(&secret, &other_point);
+//! ```
+//!
+//! [`curve448`] and [`curve448_base`] are the same functions taking slices.
+//!
+//! [1]: <https://tools.ietf.org/html/rfc7748>
+//! [2]: <https://eprint.iacr.org/2015/625.pdf>
+
+use crate::util::fixed_time_eq;
+use core::convert::TryFrom;
+use core::ops::{Add, Mul, Sub};
+
+/*
+fe means field element.
+Here the field is \Z/(2^448-2^224-1).
+An element t, entries t[0]...t[7], represents the integer
+t[0]+2^56 t[1]+2^112 t[2]+...+2^392 t[7].
+Limbs are kept below 2^57 between operations.
+*/
+
+#[derive(Clone)]
+pub(crate) struct Fe(pub [u64; 8]);
+
+const MASK56: u64 = (1 << 56) - 1;
+
+static FE_ZERO: Fe = Fe([0, 0, 0, 0, 0, 0, 0, 0]);
+static FE_ONE: Fe = Fe([1, 0, 0, 0, 0, 0, 0, 0]);
+static FE_D: Fe = Fe([
+    0xffffffffff6756,
+    0xffffffffffffff,
+    0xffffffffffffff,
+    0xffffffffffffff,
+    0xfffffffffffffe,
+    0xffffffffffffff,
+    0xffffffffffffff,
+    0xffffffffffffff,
+]);
+// 2*p, added before subtracting to keep limbs positive
+static FE_2P: Fe = Fe([
+    0x1fffffffffffffe,
+    0x1fffffffffffffe,
+    0x1fffffffffffffe,
+    0x1fffffffffffffe,
+    0x1fffffffffffffc,
+    0x1fffffffffffffe,
+    0x1fffffffffffffe,
+    0x1fffffffffffffe,
+]);
+static FE_P: [u64; 8] = [
+    0xffffffffffffff,
+    0xffffffffffffff,
+    0xffffffffffffff,
+    0xffffffffffffff,
+    0xfffffffffffffe,
+    0xffffffffffffff,
+    0xffffffffffffff,
+    0xffffffffffffff,
+];
+
+// p - 2, in little endian
+static P_MINUS_2: [u8; 56] = [
+    0xfd, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+];
+
+// (p - 3) / 4, in little endian
+static P_MINUS_3_DIV_4: [u8; 56] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xbf, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x3f,
+];
+
+// Propagate the carries and fold the bits above 2^448 back,
+// using 2^448 = 2^224 + 1 (mod p)
+fn carry(mut h: [u128; 8]) -> Fe {
+    for _ in 0..2 {
+        for i in 0..7 {
+            h[i + 1] += h[i] >> 56;
+            h[i] &= MASK56 as u128;
+        }
+        let top = h[7] >> 56;
+        h[7] &= MASK56 as u128;
+        h[0] += top;
+        h[4] += top;
+    }
+    Fe([
+        h[0] as u64,
+        h[1] as u64,
+        h[2] as u64,
+        h[3] as u64,
+        h[4] as u64,
+        h[5] as u64,
+        h[6] as u64,
+        h[7] as u64,
+    ])
+}
+
+impl Add for &Fe {
+    type Output = Fe;
+
+    fn add(self, rhs: &Fe) -> Fe {
+        let mut h = [0u128; 8];
+        for (i, hi) in h.iter_mut().enumerate() {
+            *hi = (self.0[i] + rhs.0[i]) as u128;
+        }
+        carry(h)
+    }
+}
+
+impl Sub for &Fe {
+    type Output = Fe;
+
+    fn sub(self, rhs: &Fe) -> Fe {
+        let mut h = [0u128; 8];
+        for (i, hi) in h.iter_mut().enumerate() {
+            *hi = (self.0[i] + FE_2P.0[i] - rhs.0[i]) as u128;
+        }
+        carry(h)
+    }
+}
+
+impl Mul for &Fe {
+    type Output = Fe;
+
+    fn mul(self, rhs: &Fe) -> Fe {
+        let a = &self.0;
+        let b = &rhs.0;
+        let mut c = [0u128; 15];
+        for i in 0..8 {
+            for j in 0..8 {
+                c[i + j] += (a[i] as u128) * (b[j] as u128);
+            }
+        }
+        // c[k] 2^(56k) = c[k] 2^(56(k-8)) (2^224 + 1) for k >= 8
+        for k in (8..15).rev() {
+            c[k - 8] += c[k];
+            c[k - 4] += c[k];
+        }
+        let mut h = [0u128; 8];
+        h.copy_from_slice(&c[0..8]);
+        // limbs are too big for a single pass of carry
+        for i in 0..7 {
+            h[i + 1] += h[i] >> 56;
+            h[i] &= MASK56 as u128;
+        }
+        carry(h)
+    }
+}
+
+impl Add for Fe {
+    type Output = Fe;
+    fn add(self, rhs: Fe) -> Fe {
+        &self + &rhs
+    }
+}
+
+impl Sub for Fe {
+    type Output = Fe;
+    fn sub(self, rhs: Fe) -> Fe {
+        &self - &rhs
+    }
+}
+
+impl Mul for Fe {
+    type Output = Fe;
+    fn mul(self, rhs: Fe) -> Fe {
+        &self * &rhs
+    }
+}
+
+impl Fe {
+    /// Load 56 bytes in little endian, the value doesn't need to be reduced
+    pub(crate) fn from_bytes(s: &[u8]) -> Fe {
+        let mut h = [0u64; 8];
+        for (i, hi) in h.iter_mut().enumerate() {
+            for j in 0..7 {
+                *hi |= (s[i * 7 + j] as u64) << (8 * j);
+            }
+        }
+        Fe(h)
+    }
+
+    /// Serialize the fully reduced value in 56 bytes little endian
+    pub(crate) fn to_bytes(&self) -> [u8; 56] {
+        let mut h = self.0;
+        // after the third pass there cannot be any carry left, and h < 2^448
+        for _ in 0..3 {
+            for i in 0..7 {
+                h[i + 1] += h[i] >> 56;
+                h[i] &= MASK56;
+            }
+            let top = h[7] >> 56;
+            h[7] &= MASK56;
+            h[0] += top;
+            h[4] += top;
+        }
+
+        // subtract p if h >= p
+        let mut t = [0u64; 8];
+        let mut borrow = 0u64;
+        for i in 0..8 {
+            let v = h[i].wrapping_sub(FE_P[i]).wrapping_sub(borrow);
+            borrow = v >> 63;
+            t[i] = v & MASK56;
+        }
+        let mask = borrow.wrapping_sub(1);
+        for i in 0..8 {
+            h[i] = (t[i] & mask) | (h[i] & !mask);
+        }
+
+        let mut out = [0u8; 56];
+        for i in 0..8 {
+            for j in 0..7 {
+                out[i * 7 + j] = (h[i] >> (8 * j)) as u8;
+            }
+        }
+        out
+    }
+
+    pub(crate) fn square(&self) -> Fe {
+        self * self
+    }
+
+    pub(crate) fn neg(&self) -> Fe {
+        &FE_ZERO - self
+    }
+
+    pub(crate) fn mul_small(&self, n: u32) -> Fe {
+        let mut h = [0u128; 8];
+        for (i, hi) in h.iter_mut().enumerate() {
+            *hi = (self.0[i] as u128) * (n as u128);
+        }
+        carry(h)
+    }
+
+    // exponentiation by a public exponent
+    fn pow(&self, e: &[u8; 56]) -> Fe {
+        let mut r = FE_ONE.clone();
+        for i in (0..448).rev() {
+            r = r.square();
+            if (e[i / 8] >> (i & 7)) & 1 == 1 {
+                r = &r * self;
+            }
+        }
+        r
+    }
+
+    pub(crate) fn invert(&self) -> Fe {
+        self.pow(&P_MINUS_2)
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        fixed_time_eq(&self.to_bytes(), &[0u8; 56])
+    }
+
+    pub(crate) fn is_negative(&self) -> bool {
+        (self.to_bytes()[0] & 1) != 0
+    }
+
+    /// Swap self and other if do_swap is 1, leave them untouched if 0
+    pub(crate) fn maybe_swap_with(&mut self, other: &mut Fe, do_swap: u64) {
+        let mask = 0u64.wrapping_sub(do_swap);
+        for i in 0..8 {
+            let x = (self.0[i] ^ other.0[i]) & mask;
+            self.0[i] ^= x;
+            other.0[i] ^= x;
+        }
+    }
+
+    /// Set self to other if do_set is 1, leave it untouched if 0
+    pub(crate) fn maybe_set(&mut self, other: &Fe, do_set: u64) {
+        let mask = 0u64.wrapping_sub(do_set);
+        for i in 0..8 {
+            self.0[i] ^= (self.0[i] ^ other.0[i]) & mask;
+        }
+    }
+}
+
+/// Point on the Ed448 Edwards curve x^2 + y^2 = 1 + d x^2 y^2 with d = -39081,
+/// in projective coordinates (X:Y:Z) with x = X/Z and y = Y/Z
+#[derive(Clone)]
+pub(crate) struct Point {
+    x: Fe,
+    y: Fe,
+    z: Fe,
+}
+
+static BASE_X: Fe = Fe([
+    0x26a82bc70cc05e,
+    0x80e18b00938e26,
+    0xf72ab66511433b,
+    0xa3d3a46412ae1a,
+    0x0f1767ea6de324,
+    0x36da9e14657047,
+    0xed221d15a622bf,
+    0x4f1970c66bed0d,
+]);
+static BASE_Y: Fe = Fe([
+    0x08795bf230fa14,
+    0x132c4ed7c8ad98,
+    0x1ce67c39c4fdbd,
+    0x05a0c2d73ad3ff,
+    0xa3984087789c1e,
+    0xc7624bea73736c,
+    0x248876203756c9,
+    0x693f46716eb6bc,
+]);
+
+impl Point {
+    pub(crate) fn identity() -> Point {
+        Point {
+            x: FE_ZERO.clone(),
+            y: FE_ONE.clone(),
+            z: FE_ONE.clone(),
+        }
+    }
+
+    pub(crate) fn base() -> Point {
+        Point {
+            x: BASE_X.clone(),
+            y: BASE_Y.clone(),
+            z: FE_ONE.clone(),
+        }
+    }
+
+    /// Decode a point from its 57 bytes encoding
+    pub(crate) fn from_bytes_vartime(s: &[u8]) -> Option<Point> {
+        if s[56] & 0x7f != 0 {
+            return None;
+        }
+        let y = Fe::from_bytes(&s[0..56]);
+        // non canonical encoding of y
+        if y.to_bytes()[..] != s[0..56] {
+            return None;
+        }
+        let x_sign = s[56] >> 7;
+
+        // x^2 = (y^2 - 1) / (d y^2 - 1) = u / v
+        let yy = y.square();
+        let u = &yy - &FE_ONE;
+        let v = &(&yy * &FE_D) - &FE_ONE;
+
+        // x = (u/v)^((p+1)/4) = u^3 v (u^5 v^3)^((p-3)/4)
+        let u2 = u.square();
+        let u3 = &u2 * &u;
+        let u5 = &u3 * &u2;
+        let v3 = &v.square() * &v;
+        let mut x = &(&u3 * &v) * &(&u5 * &v3).pow(&P_MINUS_3_DIV_4);
+
+        let check = &(&x.square() * &v) - &u;
+        if !check.is_zero() {
+            return None;
+        }
+        if x.is_zero() && x_sign == 1 {
+            return None;
+        }
+        if x.is_negative() != (x_sign == 1) {
+            x = x.neg();
+        }
+        Some(Point {
+            x: x,
+            y: y,
+            z: FE_ONE.clone(),
+        })
+    }
+
+    pub(crate) fn to_bytes(&self) -> [u8; 57] {
+        let recip = self.z.invert();
+        let x = &self.x * &recip;
+        let y = &self.y * &recip;
+        let mut bs = [0u8; 57];
+        bs[0..56].copy_from_slice(&y.to_bytes());
+        bs[56] = (if x.is_negative() { 1 } else { 0 }) << 7;
+        bs
+    }
+
+    pub(crate) fn dbl(&self) -> Point {
+        let b = (&self.x + &self.y).square();
+        let c = self.x.square();
+        let d = self.y.square();
+        let e = &c + &d;
+        let h = self.z.square();
+        let j = &e - &(&h + &h);
+        Point {
+            x: &(&b - &e) * &j,
+            y: &e * &(&c - &d),
+            z: &e * &j,
+        }
+    }
+
+    fn maybe_set(&mut self, other: &Point, do_set: u64) {
+        self.x.maybe_set(&other.x, do_set);
+        self.y.maybe_set(&other.y, do_set);
+        self.z.maybe_set(&other.z, do_set);
+    }
+
+    /// Multiply the point by a 57 bytes little endian scalar, in constant time
+    pub(crate) fn scalarmult(&self, scalar: &[u8]) -> Point {
+        let mut q = Point::identity();
+        for i in (0..456).rev() {
+            q = q.dbl();
+            let t = &q + self;
+            q.maybe_set(&t, ((scalar[i / 8] >> (i & 7)) & 1) as u64);
+        }
+        q
+    }
+}
+
+impl Add for &Point {
+    type Output = Point;
+
+    // complete addition formula, valid for doubling and the identity too
+    fn add(self, rhs: &Point) -> Point {
+        let a = &self.z * &rhs.z;
+        let b = a.square();
+        let c = &self.x * &rhs.x;
+        let d = &self.y * &rhs.y;
+        let e = &(&c * &d) * &FE_D;
+        let f = &b - &e;
+        let g = &b + &e;
+        let h = &(&self.x + &self.y) * &(&rhs.x + &rhs.y);
+        Point {
+            x: &(&a * &f) * &(&(&h - &c) - &d),
+            y: &(&a * &g) * &(&d - &c),
+            z: &f * &g,
+        }
+    }
+}
+
+/*
+Scalars modulo the order of the base point:
+    l = 2^446 - 13818066809895115352007386748515426880336692474882178609894547503885
+represented with 32 bits limbs in little endian during computation
+and 57 bytes little endian when serialized.
+*/
+
+// 2^446 - l
+static SC_C: [u32; 7] = [
+    0x54a7bb0d, 0xdc873d6d, 0x723a70aa, 0xde933d8d, 0x5129c96f, 0x3bb124b6, 0x8335dc16,
+];
+
+static SC_L: [u32; 14] = [
+    0xab5844f3, 0x2378c292, 0x8dc58f55, 0x216cc272, 0xaed63690, 0xc44edb49, 0x7cca23e9, 0xffffffff,
+    0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0x3fffffff,
+];
+
+fn sc_load(s: &[u8], out: &mut [u32]) {
+    for (i, b) in s.iter().enumerate() {
+        out[i / 4] |= (*b as u32) << (8 * (i % 4));
+    }
+}
+
+// Reduce a number of at most 913 bits modulo l, in constant time.
+//
+// Writing x = hi * 2^446 + lo, we have x = lo + hi * (2^446 - l) (mod l),
+// each such fold shrinking x by ~222 bits until it's below 2^446 < 2*l.
+fn sc_reduce_limbs(mut x: [u32; 30]) -> [u8; 57] {
+    for _ in 0..4 {
+        let mut hi = [0u32; 17];
+        for (i, h) in hi.iter_mut().enumerate() {
+            let upper = if 14 + i < 30 { x[14 + i] << 2 } else { 0 };
+            *h = (x[13 + i] >> 30) | upper;
+        }
+        x[13] &= 0x3fffffff;
+        for v in x[14..].iter_mut() {
+            *v = 0;
+        }
+        for (i, h) in hi.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, c) in SC_C.iter().enumerate() {
+                let t = (x[i + j] as u64) + (*h as u64) * (*c as u64) + carry;
+                x[i + j] = t as u32;
+                carry = t >> 32;
+            }
+            for v in x[i + 7..].iter_mut() {
+                let t = (*v as u64) + carry;
+                *v = t as u32;
+                carry = t >> 32;
+            }
+        }
+    }
+
+    // x < 2^446, subtract l once if x >= l
+    let mut t = [0u32; 14];
+    let mut borrow = 0u64;
+    for i in 0..14 {
+        let v = (x[i] as u64)
+            .wrapping_sub(SC_L[i] as u64)
+            .wrapping_sub(borrow);
+        t[i] = v as u32;
+        borrow = v >> 63;
+    }
+    let mask = (borrow as u32).wrapping_sub(1);
+
+    let mut out = [0u8; 57];
+    for i in 0..14 {
+        let v = (t[i] & mask) | (x[i] & !mask);
+        out[4 * i..4 * i + 4].copy_from_slice(&v.to_le_bytes());
+    }
+    out
+}
+
+/// Reduce a 114 bytes little endian number modulo l
+pub(crate) fn sc_reduce(s: &[u8]) -> [u8; 57] {
+    let mut x = [0u32; 30];
+    sc_load(&s[0..114], &mut x);
+    sc_reduce_limbs(x)
+}
+
+/// Compute (a * b + c) mod l, where a, b and c are 57 bytes little endian numbers
+pub(crate) fn sc_muladd(a: &[u8], b: &[u8], c: &[u8]) -> [u8; 57] {
+    let mut al = [0u32; 15];
+    let mut bl = [0u32; 15];
+    let mut x = [0u32; 30];
+    sc_load(&a[0..57], &mut al);
+    sc_load(&b[0..57], &mut bl);
+    sc_load(&c[0..57], &mut x);
+    for i in 0..15 {
+        let mut carry = 0u64;
+        for j in 0..15 {
+            let t = (x[i + j] as u64) + (al[i] as u64) * (bl[j] as u64) + carry;
+            x[i + j] = t as u32;
+            carry = t >> 32;
+        }
+        for v in x[i + 15..].iter_mut() {
+            let t = (*v as u64) + carry;
+            *v = t as u32;
+            carry = t >> 32;
+        }
+    }
+    sc_reduce_limbs(x)
+}
+
+/// Check that a 57 bytes little endian scalar is below l, in constant time
+pub(crate) fn sc_is_canonical(s: &[u8]) -> bool {
+    let mut x = [0u32; 15];
+    sc_load(&s[0..57], &mut x);
+    let mut borrow = 0u64;
+    for i in 0..14 {
+        let v = (x[i] as u64)
+            .wrapping_sub(SC_L[i] as u64)
+            .wrapping_sub(borrow);
+        borrow = v >> 63;
+    }
+    // the last byte has to be zero, and x < l if the subtraction borrowed
+    ((borrow as u32) & (((x[14] as i32 - 1) >> 31) as u32)) == 1
+}
+
+/// The `X448(k, u)` function of RFC 7748: clamp the scalar `k` and multiply
+/// the point of u-coordinate `u` by it
+///
+/// This computes a shared secret from a private key and the public key of
+/// the peer.
+pub fn x448(k: &[u8; 56], u: &[u8; 56]) -> [u8; 56] {
+    let mut e = *k;
+    e[0] &= 252;
+    e[55] |= 128;
+
+    let x1 = Fe::from_bytes(u);
+    let mut x2 = FE_ONE.clone();
+    let mut z2 = FE_ZERO.clone();
+    let mut x3 = x1.clone();
+    let mut z3 = FE_ONE.clone();
+
+    let mut swap: u64 = 0;
+    for pos in (0usize..448).rev() {
+        let b = ((e[pos / 8] >> (pos & 7)) & 1) as u64;
+        swap ^= b;
+        x2.maybe_swap_with(&mut x3, swap);
+        z2.maybe_swap_with(&mut z3, swap);
+        swap = b;
+
+        let a = &x2 + &z2;
+        let aa = a.square();
+        let b = &x2 - &z2;
+        let bb = b.square();
+        let e = &aa - &bb;
+        let c = &x3 + &z3;
+        let d = &x3 - &z3;
+        let da = &d * &a;
+        let cb = &c * &b;
+        x3 = (&da + &cb).square();
+        z3 = &x1 * &(&da - &cb).square();
+        x2 = &aa * &bb;
+        z2 = &e * &(&aa + &e.mul_small(39081));
+    }
+    x2.maybe_swap_with(&mut x3, swap);
+    z2.maybe_swap_with(&mut z3, swap);
+
+    (&z2.invert() * &x2).to_bytes()
+}
+
+/// Derives the public key of a private key: `X448(k, 5)`
+pub fn x448_base(k: &[u8; 56]) -> [u8; 56] {
+    let mut base = [0u8; 56];
+    base[0] = 5;
+    x448(k, &base)
+}
+
+/// Computes a shared secret from the curve448 private key (n) and public
+/// key (p), both 56 bytes long
+///
+/// This is [`x448`] taking slices, panicking if they are not 56 bytes long.
+pub fn curve448(n: &[u8], p: &[u8]) -> [u8; 56] {
+    let n = <&[u8; 56]>::try_from(n).expect("Private key should be 56 bytes long!");
+    let p = <&[u8; 56]>::try_from(p).expect("Public key should be 56 bytes long!");
+    x448(n, p)
+}
+
+/// Derives a public key from a private key
+///
+/// This is [`x448_base`] taking a slice, panicking if it is not 56 bytes long.
+pub fn curve448_base(x: &[u8]) -> [u8; 56] {
+    x448_base(<&[u8; 56]>::try_from(x).expect("Private key should be 56 bytes long!"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unhex(s: &str) -> alloc::vec::Vec<u8> {
+        (0..s.len() / 2)
+            .map(|i| u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn fe_invert_inverts() {
+        let mut e = [0u8; 56];
+        for (i, b) in e.iter_mut().enumerate() {
+            *b = (i * 37 + 11) as u8;
+        }
+        let x = Fe::from_bytes(&e);
+        assert_eq!((&x * &x.invert()).to_bytes(), FE_ONE.to_bytes());
+        assert_eq!(x.invert().invert().to_bytes(), x.to_bytes());
+    }
+
+    #[test]
+    fn fe_reduces_p() {
+        let mut p = [0xff; 56];
+        p[28] = 0xfe;
+        assert_eq!(Fe::from_bytes(&p).to_bytes(), [0u8; 56]);
+        p[0] = 0;
+        assert_eq!(Fe::from_bytes(&p).neg().to_bytes()[0], 0xff);
+    }
+
+    #[test]
+    fn base_point_roundtrip() {
+        let b = Point::base().to_bytes();
+        let p = Point::from_bytes_vartime(&b).unwrap();
+        assert_eq!(p.to_bytes()[..], b[..]);
+        assert_eq!(Point::base().dbl().to_bytes()[..], (&p + &p).to_bytes()[..]);
+    }
+
+    #[test]
+    fn scalar_order() {
+        let mut l = [0u8; 57];
+        for (i, v) in SC_L.iter().enumerate() {
+            l[4 * i..4 * i + 4].copy_from_slice(&v.to_le_bytes());
+        }
+        assert!(!sc_is_canonical(&l));
+        let ident = Point::base().scalarmult(&l);
+        assert_eq!(ident.to_bytes()[..], Point::identity().to_bytes()[..]);
+        l[0] -= 1;
+        assert!(sc_is_canonical(&l));
+        // (l - 1) * (l - 1) + 0 = 1 (mod l)
+        let one = sc_muladd(&l, &l, &[0u8; 57]);
+        let mut expected = [0u8; 57];
+        expected[0] = 1;
+        assert_eq!(one[..], expected[..]);
+        assert_eq!(sc_reduce(&[0xff; 114])[..], {
+            // (2^912 - 1) mod l
+            let mut r = [0u8; 57];
+            r.copy_from_slice(&unhex(
+                "81dee731a93f88112e1dad8707160f80293ea637fb19e320c5b624bb85c972cf17ae447cc4a34bc19c1aaf70d0e4b7bc522029b723f8392900",
+            ));
+            r
+        });
+    }
+
+    fn hex56(s: &str) -> [u8; 56] {
+        <[u8; 56]>::try_from(&unhex(s)[..]).unwrap()
+    }
+
+    // RFC 7748 section 5.2
+    #[test]
+    fn rfc7748_vectors() {
+        let k = hex56("3d262fddf9ec8e88495266fea19a34d28882acef045104d0d1aae121700a779c984c24f8cdd78fbff44943eba368f54b29259a4f1c600ad3");
+        let u = hex56("06fce640fa3487bfda5f6cf2d5263f8aad88334cbd07437f020f08f9814dc031ddbdc38c19c6da2583fa5429db94ada18aa7a7fb4ef8a086");
+        let r = hex56("ce3e4ff95a60dc6697da1db1d85e6afbdf79b50a2412d7546d5f239fe14fbaadeb445fc66a01b0779d98223961111e21766282f73dd96b6f");
+        assert_eq!(x448(&k, &u), r);
+        assert_eq!(curve448(&k, &u), r);
+
+        let k = hex56("203d494428b8399352665ddca42f9de8fef600908e0d461cb021f8c538345dd77c3e4806e25f46d3315c44e0a5b4371282dd2c8d5be3095f");
+        let u = hex56("0fbcc2f993cd56d3305b0b7d9e55d4c1a8fb5dbb52f8e9a1e9b6201b165d015894e56c4d3570bee52fe205e28a78b91cdfbde71ce8d157db");
+        let r = hex56("884a02576239ff7a2f2f63b2db6a9ff37047ac13568e1e30fe63c4a7ad1b3ee3a5700df34321d62077e63633c575c1c954514e99da7c179d");
+        assert_eq!(x448(&k, &u), r);
+        assert_eq!(curve448(&k, &u), r);
+    }
+
+    #[test]
+    fn rfc7748_iterated_once() {
+        let mut k = [0u8; 56];
+        k[0] = 5;
+        assert_eq!(
+            x448(&k, &k)[..],
+            unhex("3f482c8a9f19b01e6c46ee9711d9dc14fd4bf67af30765c2ae2b846a4d23a8cd0db897086239492caf350b51f833868b9bc2b3bca9cf4113")[..]
+        );
+    }
+
+    // RFC 7748 section 6.2
+    #[test]
+    fn rfc7748_dh() {
+        let alice = hex56("9a8f4925d1519f5775cf46b04b5800d4ee9ee8bae8bc5565d498c28dd9c9baf574a9419744897391006382a6f127ab1d9ac2d8c0a598726b");
+        let bob = hex56("1c306a7ac2a0e2e0990b294470cba339e6453772b075811d8fad0d1d6927c120bb5ee8972b0d3e21374c9c921b09d1b0366f10b65173992d");
+        let alice_pub = x448_base(&alice);
+        let bob_pub = x448_base(&bob);
+        assert_eq!(curve448_base(&alice), alice_pub);
+        assert_eq!(
+            alice_pub[..],
+            unhex("9b08f7cc31b7e3e67d22d5aea121074a273bd2b83de09c63faa73d2c22c5d9bbc836647241d953d40c5b12da88120d53177f80e532c41fa0")[..]
+        );
+        assert_eq!(
+            bob_pub[..],
+            unhex("3eb7a829b0cd20f5bcfc0b599b6feccf6da4627107bdb0d4f345b43027d8b972fc3e34fb4232a13ca706dcb57aec3dae07bdc1c67bf33609")[..]
+        );
+        let shared = hex56("07fff4181ac6cc95ec1c16a94a0f74d12da232ce40a77552281d282bb60c0b56fd2464c335543936521c24403085d59a449a5037514a879d");
+        assert_eq!(x448(&alice, &bob_pub), shared);
+        assert_eq!(x448(&bob, &alice_pub), shared);
+    }
+}
+
+#[cfg(all(test, feature = "with-bench"))]
+mod bench {
+    use super::*;
+    use test::Bencher;
+
+    #[bench]
+    pub fn mul_curve448_base(bh: &mut Bencher) {
+        bh.iter(|| x448_base(&[3u8; 56]));
+    }
+}
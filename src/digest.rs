@@ -11,6 +11,30 @@
 //! hasher.input_str("hello world");
 //! let hex = hasher.result_str();
 //! ```
+//!
+//! Hashing multiple byte strings with an unambiguous framing:
+//!
+//! ```rust
+//! use self::cryptoxide::digest::{absorb_framed, Digest};
+//! use self::cryptoxide::sha2::Sha256;
+//!
+//! let mut hasher = Sha256::new();
+//! absorb_framed(&mut hasher, &[b"user", b"context"]);
+//! let hex = hasher.result_str();
+//! ```
+//!
+//! Hashing formatted text without an intermediate string:
+//!
+//! ```rust
+//! use core::fmt::Write;
+//! use self::cryptoxide::digest::FmtHasher;
+//! use self::cryptoxide::sha2::Sha256;
+//!
+//! let mut hasher = FmtHasher::new(Sha256::new());
+//! write!(hasher, "{}:{}", "user", 42).unwrap();
+//! let mut out = [0u8; 32];
+//! hasher.finalize(&mut out);
+//! ```
 
 // Copyright 2012-2013 The Rust Project Developers. See the COPYRIGHT
 // file at the top-level directory of this distribution and at
@@ -22,12 +46,57 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use alloc::string::String;
-use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+#[cfg(feature = "alloc")]
 use core::iter::repeat;
 
+pub use crate::cryptoutil::absorb_framed;
+
+#[cfg(feature = "alloc")]
 static CHARS: &[u8] = b"0123456789abcdef";
 
+/// Error returned when the output buffer of a digest is not of the digest size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputSizeError {
+    /// Size of the digest output in bytes
+    pub expected: usize,
+    /// Size of the buffer given in bytes
+    pub actual: usize,
+}
+
+impl core::fmt::Display for OutputSizeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "invalid digest output size: expected {} bytes, got {}",
+            self.expected, self.actual
+        )
+    }
+}
+
+/// Panic with the standard message if the output buffer is not of the digest size
+#[cfg(any(
+    feature = "blake2",
+    feature = "sha1",
+    feature = "sha2",
+    feature = "sha3",
+    feature = "whirlpool",
+    feature = "ripemd160"
+))]
+#[inline]
+pub(crate) fn assert_output_size(out: &[u8], expected: usize) {
+    if out.len() != expected {
+        panic!(
+            "{}",
+            OutputSizeError {
+                expected,
+                actual: out.len()
+            }
+        );
+    }
+}
+
 /**
  * The Digest trait specifies an interface common to digest functions, such as SHA-1 and the SHA-2
  * family of digest functions.
@@ -47,10 +116,44 @@ pub trait Digest {
      *
      * # Arguments
      *
-     * * out - the vector to hold the result. Must be large enough to contain output_bits().
+     * * out - the vector to hold the result. Must be exactly of `output_bytes()` size,
+     *   otherwise this panics.
      */
     fn result(&mut self, out: &mut [u8]);
 
+    /**
+     * Retrieve the digest result, or return an error if the output buffer is
+     * not of `output_bytes()` size.
+     */
+    fn try_result(&mut self, out: &mut [u8]) -> Result<(), OutputSizeError> {
+        let expected = self.output_bytes();
+        if out.len() != expected {
+            return Err(OutputSizeError {
+                expected,
+                actual: out.len(),
+            });
+        }
+        self.result(out);
+        Ok(())
+    }
+
+    /**
+     * Retrieve the digest result as an array, without allocation.
+     *
+     * The size `N` of the array needs to be `output_bytes()`, otherwise this panics.
+     * An associated constant for the output size is not provided, as some digests have
+     * their output size chosen at runtime.
+     */
+    fn finalize_fixed<const N: usize>(&mut self) -> [u8; N]
+    where
+        Self: Sized,
+    {
+        assert!(N == self.output_bytes());
+        let mut out = [0u8; N];
+        self.result(&mut out);
+        out
+    }
+
     /**
      * Reset the digest. This method must be called after result() and before supplying more
      * data.
@@ -85,10 +188,26 @@ pub trait Digest {
         self.input(input.as_bytes());
     }
 
+    /**
+     * Feed all the data of a reader into the digest, returning the number of
+     * bytes read.
+     *
+     * The data is read in chunks until the end of the reader; on error, the
+     * data read so far has been fed into the digest.
+     */
+    #[cfg(feature = "std")]
+    fn input_reader<R: std::io::Read + ?Sized>(&mut self, r: &mut R) -> std::io::Result<u64>
+    where
+        Self: Sized,
+    {
+        crate::cryptoutil::read_chunks(r, |chunk| self.input(chunk))
+    }
+
     /**
      * Convenience function that retrieves the result of a digest as a
      * String in hexadecimal format.
      */
+    #[cfg(feature = "alloc")]
     fn result_str(&mut self) -> String {
         let mut buf: Vec<u8> = repeat(0).take((self.output_bits() + 7) / 8).collect();
         self.result(&mut buf);
@@ -103,3 +222,32 @@ pub trait Digest {
         unsafe { String::from_utf8_unchecked(v) }
     }
 }
+
+/// Wrapper feeding the text written with `core::fmt::Write`, e.g. by `write!`,
+/// into a digest as UTF-8 bytes
+#[derive(Clone)]
+pub struct FmtHasher<D>(D);
+
+impl<D: Digest> FmtHasher<D> {
+    /// Wrap a digest context
+    pub fn new(digest: D) -> Self {
+        FmtHasher(digest)
+    }
+
+    /// Retrieve the digest of the text written, see [`Digest::result`]
+    pub fn finalize(mut self, out: &mut [u8]) {
+        self.0.result(out)
+    }
+
+    /// Get back the digest context, to feed more data or retrieve the result
+    pub fn into_inner(self) -> D {
+        self.0
+    }
+}
+
+impl<D: Digest> core::fmt::Write for FmtHasher<D> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.0.input(s.as_bytes());
+        Ok(())
+    }
+}
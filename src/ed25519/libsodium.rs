@@ -0,0 +1,125 @@
+//! Compatibility with the libsodium `crypto_sign` API
+//!
+//! The secret key of libsodium's `crypto_sign_seed_keypair` is the 32 bytes
+//! seed followed by the 32 bytes public key, which is the layout of the secret
+//! key returned by [`keypair`](super::keypair): keys can be exchanged between
+//! the two libraries without conversion, and the detached signatures are the
+//! same as `crypto_sign_detached`.
+//!
+//! The combined mode of libsodium, where the signed message is the 64 bytes
+//! signature followed by the message, is provided by the `attach` function of
+//! the parent module (with the `alloc` feature) and by
+//! [`open_attached`](super::open_attached): `crypto_sign` is the `attach` of
+//! the [`signature`](super::signature) and the message, and `crypto_sign_open`
+//! is `open_attached`, which rejects non canonical signatures and small order
+//! points like libsodium does.
+//!
+//! ```
+//! use cryptoxide::ed25519::{attach, open_attached, signature};
+//! use cryptoxide::ed25519::libsodium::crypto_sign_seed_keypair;
+//!
+//! let seed = [0u8; 32]; // seed only for example !
+//! let (public, secret) = crypto_sign_seed_keypair(&seed);
+//! let signed = attach(&signature(b"message", &secret), b"message");
+//! assert_eq!(open_attached(&public, &signed), Some(&b"message"[..]));
+//! ```
+
+use super::{PRIVATE_KEY_LENGTH, PUBLIC_KEY_LENGTH, SEED_LENGTH};
+
+/// Create a keypair from a seed, like `crypto_sign_seed_keypair`
+///
+/// As in libsodium, the public key is returned first, followed by the
+/// secret key made of the seed and the public key.
+#[allow(deprecated)]
+pub fn crypto_sign_seed_keypair(
+    seed: &[u8; SEED_LENGTH],
+) -> ([u8; PUBLIC_KEY_LENGTH], [u8; PRIVATE_KEY_LENGTH]) {
+    let (secret, public) = super::keypair(seed);
+    (public, secret)
+}
+
+#[cfg(test)]
+#[cfg_attr(feature = "no-panic", allow(deprecated))]
+mod tests {
+    use super::crypto_sign_seed_keypair;
+    use crate::ed25519::try_keypair;
+    #[cfg(feature = "alloc")]
+    use crate::ed25519::{attach, open_attached, signature};
+
+    // RFC 8032 TEST 2, as produced by libsodium's crypto_sign
+    const SEED: [u8; 32] = [
+        0x4c, 0xcd, 0x08, 0x9b, 0x28, 0xff, 0x96, 0xda, 0x9d, 0xb6, 0xc3, 0x46, 0xec, 0x11, 0x4e,
+        0x0f, 0x5b, 0x8a, 0x31, 0x9f, 0x35, 0xab, 0xa6, 0x24, 0xda, 0x8c, 0xf6, 0xed, 0x4f, 0xb8,
+        0xa6, 0xfb,
+    ];
+    const PUBLIC: [u8; 32] = [
+        0x3d, 0x40, 0x17, 0xc3, 0xe8, 0x43, 0x89, 0x5a, 0x92, 0xb7, 0x0a, 0xa7, 0x4d, 0x1b, 0x7e,
+        0xbc, 0x9c, 0x98, 0x2c, 0xcf, 0x2e, 0xc4, 0x96, 0x8c, 0xc0, 0xcd, 0x55, 0xf1, 0x2a, 0xf4,
+        0x66, 0x0c,
+    ];
+    #[cfg(feature = "alloc")]
+    const SIGNED: [u8; 65] = [
+        0x92, 0xa0, 0x09, 0xa9, 0xf0, 0xd4, 0xca, 0xb8, 0x72, 0x0e, 0x82, 0x0b, 0x5f, 0x64, 0x25,
+        0x40, 0xa2, 0xb2, 0x7b, 0x54, 0x16, 0x50, 0x3f, 0x8f, 0xb3, 0x76, 0x22, 0x23, 0xeb, 0xdb,
+        0x69, 0xda, 0x08, 0x5a, 0xc1, 0xe4, 0x3e, 0x15, 0x99, 0x6e, 0x45, 0x8f, 0x36, 0x13, 0xd0,
+        0xf1, 0x1d, 0x8c, 0x38, 0x7b, 0x2e, 0xae, 0xb4, 0x30, 0x2a, 0xee, 0xb0, 0x0d, 0x29, 0x16,
+        0x12, 0xbb, 0x0c, 0x00, 0x72,
+    ];
+
+    // output of libsodium 1.0.18 crypto_sign_seed_keypair and crypto_sign,
+    // for the seed 00..1f and the message "crypto_sign of libsodium 1.0.18"
+    const LIBSODIUM_PUBLIC: [u8; 32] = [
+        0x03, 0xa1, 0x07, 0xbf, 0xf3, 0xce, 0x10, 0xbe, 0x1d, 0x70, 0xdd, 0x18, 0xe7, 0x4b, 0xc0,
+        0x99, 0x67, 0xe4, 0xd6, 0x30, 0x9b, 0xa5, 0x0d, 0x5f, 0x1d, 0xdc, 0x86, 0x64, 0x12, 0x55,
+        0x31, 0xb8,
+    ];
+    #[cfg(feature = "alloc")]
+    const LIBSODIUM_SIGNED: [u8; 95] = [
+        0x49, 0x54, 0x32, 0xc5, 0xd5, 0x28, 0xa2, 0x22, 0x3c, 0x1a, 0x33, 0x2a, 0x12, 0x26, 0xd1,
+        0x55, 0x1d, 0xe1, 0xdb, 0x0d, 0x27, 0xfa, 0xec, 0x99, 0x46, 0x80, 0x17, 0xec, 0x84, 0xde,
+        0x5e, 0x1a, 0x75, 0x6b, 0x16, 0x02, 0x35, 0xd4, 0x5b, 0x87, 0x9d, 0x7d, 0x28, 0xaf, 0xbe,
+        0x14, 0x2d, 0x5f, 0xa4, 0xda, 0x19, 0xcd, 0xa7, 0x17, 0x7a, 0xcd, 0x88, 0xc4, 0x28, 0x24,
+        0x2a, 0x49, 0xc1, 0x0d, 0x63, 0x72, 0x79, 0x70, 0x74, 0x6f, 0x5f, 0x73, 0x69, 0x67, 0x6e,
+        0x20, 0x6f, 0x66, 0x20, 0x6c, 0x69, 0x62, 0x73, 0x6f, 0x64, 0x69, 0x75, 0x6d, 0x20, 0x31,
+        0x2e, 0x30, 0x2e, 0x31, 0x38,
+    ];
+
+    #[test]
+    fn seed_keypair() {
+        let (public, secret) = crypto_sign_seed_keypair(&SEED);
+        assert_eq!(public, PUBLIC);
+        assert_eq!(&secret[..32], &SEED[..]);
+        assert_eq!(&secret[32..], &PUBLIC[..]);
+        assert_eq!(try_keypair(&SEED), Ok((secret, public)));
+
+        let seed: [u8; 32] = core::array::from_fn(|i| i as u8);
+        assert_eq!(crypto_sign_seed_keypair(&seed).0, LIBSODIUM_PUBLIC);
+    }
+
+    // crypto_sign is attach of the signature, crypto_sign_open is open_attached
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn sign_open() {
+        let (public, secret) = crypto_sign_seed_keypair(&SEED);
+        let signed = attach(&signature(&[0x72], &secret), &[0x72]);
+        assert_eq!(&signed[..], &SIGNED[..]);
+        assert_eq!(open_attached(&public, &signed), Some(&[0x72][..]));
+
+        let seed: [u8; 32] = core::array::from_fn(|i| i as u8);
+        let (public, secret) = crypto_sign_seed_keypair(&seed);
+        let message = &LIBSODIUM_SIGNED[64..];
+        assert_eq!(message, b"crypto_sign of libsodium 1.0.18");
+        let signed = attach(&signature(message, &secret), message);
+        assert_eq!(&signed[..], &LIBSODIUM_SIGNED[..]);
+        assert_eq!(open_attached(&public, &LIBSODIUM_SIGNED), Some(message));
+
+        let mut tampered = signed.clone();
+        tampered[64] ^= 1;
+        assert_eq!(open_attached(&public, &tampered), None);
+        assert_eq!(open_attached(&public, &signed[..63]), None);
+
+        let empty = attach(&signature(b"", &secret), b"");
+        assert_eq!(empty.len(), 64);
+        assert_eq!(open_attached(&public, &empty), Some(&b""[..]));
+    }
+}
@@ -0,0 +1,2150 @@
+//! ED25519 Signature Scheme
+//!
+//! # Examples
+//!
+//! Creating a signature, and verifying the signature:
+//!
+//! ```
+//! use cryptoxide::ed25519;
+//!
+//! let message = "messages".as_bytes();
+//! let seed = [0u8;32]; // seed only for example !
+//! let (secret, public) = ed25519::keypair(&seed[..]);
+//! let signature = ed25519::signature(message, &secret[..]);
+//! let verified = ed25519::verify(message, &public[..], &signature[..]);
+//! assert!(verified);
+//! ```
+//!
+//! Signing a large message with Ed25519ph, from its incrementally computed SHA512 prehash:
+//!
+//! ```
+//! use cryptoxide::{digest::Digest, ed25519, sha2::Sha512};
+//!
+//! let seed = [0u8;32]; // seed only for example !
+//! let (secret, public) = ed25519::keypair(&seed[..]);
+//! let mut hasher = Sha512::new();
+//! hasher.input(b"first part of the message, ");
+//! hasher.input(b"second part of the message");
+//! let mut prehash = [0u8; 64];
+//! hasher.result(&mut prehash);
+//! let signature = ed25519::signature_prehashed(&prehash, b"", &secret);
+//! assert!(ed25519::verify_prehashed(&prehash, b"", &public, &signature));
+//! ```
+//!
+//! The [`libsodium`] module documents the correspondence with the `crypto_sign`
+//! API of libsodium.
+
+use crate::curve25519::{
+    clamp_ed25519_scalar, curve25519, ge_scalarmult_base, sc_muladd, sc_reduce,
+    scalar_from_bytes_wide, scalar_is_canonical, scalar_reduce, Fe, GeCached, GeP2, GeP3,
+};
+use crate::digest::Digest;
+#[cfg(feature = "hkdf")]
+use crate::hkdf::Hkdf;
+use crate::sha2::{Sha256, Sha512};
+use crate::util::fixed_time_eq;
+use crate::util::secure_memset;
+use crate::util::Secret;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::array::TryFromSliceError;
+use core::convert::TryFrom;
+use core::ops::{Add, Mul, Sub};
+
+pub mod libsodium;
+
+pub const SEED_LENGTH: usize = 32;
+pub const PRIVATE_KEY_LENGTH: usize = 64;
+pub const PUBLIC_KEY_LENGTH: usize = 32;
+pub const SIGNATURE_LENGTH: usize = 64;
+
+/// Error returned by the `try_` functions when a key, a signature or a
+/// context is not of a valid length
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidLength;
+
+impl core::fmt::Display for InvalidLength {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid length of ed25519 key, signature or context")
+    }
+}
+
+fn check_length(data: &[u8], length: usize) -> Result<(), InvalidLength> {
+    if data.len() == length {
+        Ok(())
+    } else {
+        Err(InvalidLength)
+    }
+}
+
+/// Create a keypair of secret key and public key
+///
+/// Panics if the seed is not 32 bytes long, see [`try_keypair`] for a fallible version.
+#[cfg_attr(feature = "no-panic", deprecated(note = "use try_keypair"))]
+pub fn keypair(seed: &[u8]) -> ([u8; PRIVATE_KEY_LENGTH], [u8; PUBLIC_KEY_LENGTH]) {
+    assert!(
+        seed.len() == SEED_LENGTH,
+        "Seed should be {} bytes long!",
+        SEED_LENGTH
+    );
+
+    let mut secret: [u8; PRIVATE_KEY_LENGTH] = {
+        let mut hash_output: [u8; PRIVATE_KEY_LENGTH] = [0; PRIVATE_KEY_LENGTH];
+        let mut hasher = Sha512::new();
+        hasher.input(seed);
+        hasher.result(&mut hash_output);
+        clamp_ed25519_scalar(&mut hash_output[0..32]);
+        hash_output
+    };
+
+    let a = ge_scalarmult_base(<&[u8; 32]>::try_from(&secret[0..32]).unwrap());
+    let public_key = a.to_bytes();
+    for (dest, src) in (&mut secret[32..64]).iter_mut().zip(public_key.iter()) {
+        *dest = *src;
+    }
+    for (dest, src) in (&mut secret[0..32]).iter_mut().zip(seed.iter()) {
+        *dest = *src;
+    }
+    (secret, public_key)
+}
+
+/// Create a keypair of secret key and public key, or return an error
+/// if the seed is not 32 bytes long
+#[allow(deprecated)]
+pub fn try_keypair(
+    seed: &[u8],
+) -> Result<([u8; PRIVATE_KEY_LENGTH], [u8; PUBLIC_KEY_LENGTH]), InvalidLength> {
+    check_length(seed, SEED_LENGTH)?;
+    Ok(keypair(seed))
+}
+
+/// Create the secret key from a seed and a public key stored separately,
+/// checking that the public key is the one derived from the seed
+///
+/// Signing with a secret key made of a seed and a wrong public key produces
+/// invalid signatures, and may leak the secret key if the same message is also
+/// signed with the right public key. Return [`KeyError::PublicKeyMismatch`] if
+/// the stored public key does not match, e.g. after a storage corruption.
+#[allow(deprecated)]
+pub fn secret_key_from_seed_and_public(
+    seed: &[u8; SEED_LENGTH],
+    public_key: &[u8; PUBLIC_KEY_LENGTH],
+) -> Result<Secret<PRIVATE_KEY_LENGTH>, KeyError> {
+    let (secret, expected_public) = keypair(seed);
+    let secret = Secret::new(secret);
+    if !fixed_time_eq(&expected_public, public_key) {
+        return Err(KeyError::PublicKeyMismatch);
+    }
+    Ok(secret)
+}
+
+/// Generate a signature for the given message using a normal ED25519 secret key
+///
+/// Panics if the secret key is not 64 bytes long, see [`try_signature`] for a fallible version.
+#[cfg_attr(feature = "no-panic", deprecated(note = "use try_signature"))]
+pub fn signature(message: &[u8], secret_key: &[u8]) -> [u8; SIGNATURE_LENGTH] {
+    assert!(
+        secret_key.len() == PRIVATE_KEY_LENGTH,
+        "Private key should be {} bytes long!",
+        PRIVATE_KEY_LENGTH
+    );
+    let mut key = [0u8; PRIVATE_KEY_LENGTH];
+    key.copy_from_slice(secret_key);
+    let (_, secret) = commit(message, &key);
+    respond(secret)
+}
+
+/// Nonce commitment R of a signature, the first half of the signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Commitment(pub [u8; 32]);
+
+impl TryFrom<&[u8]> for Commitment {
+    type Error = TryFromSliceError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        <[u8; 32]>::try_from(bytes).map(Commitment)
+    }
+}
+
+impl AsRef<[u8]> for Commitment {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Secret state between [`commit`] and [`respond`]
+///
+/// It holds the secret nonce of the signature: revealing it, or using it for
+/// two different messages, reveals the secret key. For this reason it can't be
+/// cloned, is bound to the commitment and the message given to `commit`, is
+/// consumed by `respond`, and is wiped when dropped.
+pub struct CommitmentSecret {
+    scalar: Secret<32>,
+    nonce: Secret<32>,
+    commitment: [u8; 32],
+    challenge: [u8; 32],
+}
+
+/// First phase of the signature of a message: derive the nonce and compute
+/// its commitment R
+///
+/// The nonce is derived deterministically from the secret key and the message,
+/// as done by [`signature`], so that `respond` produces the same signature.
+pub fn commit(
+    message: &[u8],
+    secret_key: &[u8; PRIVATE_KEY_LENGTH],
+) -> (Commitment, CommitmentSecret) {
+    let seed = &secret_key[0..32];
+    let mut public_key = [0u8; PUBLIC_KEY_LENGTH];
+    public_key.copy_from_slice(&secret_key[32..64]);
+    let mut az: [u8; 64] = {
+        let mut hash_output: [u8; 64] = [0; 64];
+        let mut hasher = Sha512::new();
+        hasher.input(seed);
+        hasher.result(&mut hash_output);
+        clamp_ed25519_scalar(&mut hash_output[0..32]);
+        hash_output
+    };
+
+    let mut prefix = [0u8; 32];
+    prefix.copy_from_slice(&az[32..64]);
+    let nonce = nonce_from_prefix(&prefix, message);
+
+    let r = ge_scalarmult_base(&nonce).to_bytes();
+    let secret = CommitmentSecret {
+        scalar: Secret::try_from(&az[0..32]).unwrap(),
+        nonce: Secret::new(nonce),
+        commitment: r,
+        challenge: hram(&r, &public_key, message),
+    };
+    secure_memset(&mut az, 0);
+    (Commitment(r), secret)
+}
+
+/// Deterministic nonce r of a signature: the SHA512 hash of the prefix and the
+/// message, reduced modulo l
+///
+/// The prefix is the second half of the SHA512 hash of the seed, or of the
+/// extended secret key. The commitment R of the signature is the base point
+/// multiplied by the nonce. The nonce is as secret as the secret key.
+pub fn nonce_from_prefix(prefix: &[u8; 32], message: &[u8]) -> [u8; 32] {
+    let mut hash_output: [u8; 64] = [0; 64];
+    let mut hasher = Sha512::new();
+    hasher.input(prefix);
+    hasher.input(message);
+    hasher.result(&mut hash_output);
+    sc_reduce(&mut hash_output[0..64]);
+    let mut nonce = [0u8; 32];
+    nonce.copy_from_slice(&hash_output[0..32]);
+    nonce
+}
+
+/// Challenge k of a signature: the SHA512 hash of the commitment R, the public
+/// key and the message, reduced modulo l
+///
+/// The response S of the signature is `r + k * a` modulo l, for the nonce r
+/// and the secret scalar a. The challenge only depends on public values, so it
+/// can be computed outside of the device holding the secret scalar.
+pub fn hram(
+    signature_r: &[u8; 32],
+    public_key: &[u8; PUBLIC_KEY_LENGTH],
+    message: &[u8],
+) -> [u8; 32] {
+    let mut hasher = Sha512::new();
+    hasher.input(signature_r);
+    hasher.input(public_key);
+    hasher.input(message);
+    let mut hash_output: [u8; 64] = [0; 64];
+    hasher.result(&mut hash_output);
+    sc_reduce(&mut hash_output);
+    let mut k = [0u8; 32];
+    k.copy_from_slice(&hash_output[0..32]);
+    k
+}
+
+/// Second phase of the signature of a message: compute the response S and
+/// return the full signature R || S
+///
+/// The secret holds the commitment R and the challenge for the message given
+/// to [`commit`], so the response can only be for that message.
+pub fn respond(secret: CommitmentSecret) -> [u8; SIGNATURE_LENGTH] {
+    let mut signature: [u8; SIGNATURE_LENGTH] = [0; SIGNATURE_LENGTH];
+    signature[0..32].copy_from_slice(&secret.commitment);
+    sc_muladd(
+        &mut signature[32..64],
+        &secret.challenge,
+        secret.scalar.expose(),
+        secret.nonce.expose(),
+    );
+    signature
+}
+
+/// Generate a signature for the given message using a normal ED25519 secret key,
+/// or return an error if the secret key is not 64 bytes long
+#[allow(deprecated)]
+pub fn try_signature(
+    message: &[u8],
+    secret_key: &[u8],
+) -> Result<[u8; SIGNATURE_LENGTH], InvalidLength> {
+    check_length(secret_key, PRIVATE_KEY_LENGTH)?;
+    Ok(signature(message, secret_key))
+}
+
+fn extended_to_public(extended_secret: &[u8]) -> [u8; PUBLIC_KEY_LENGTH] {
+    // the extended secret is not necessarily clamped, reducing it gives the
+    // same point and clears the most significant bit
+    let mut scalar = [0u8; 32];
+    scalar.copy_from_slice(&extended_secret[0..32]);
+    let mut reduced = scalar_reduce(&scalar);
+    let a = ge_scalarmult_base(&reduced);
+    secure_memset(&mut scalar, 0);
+    secure_memset(&mut reduced, 0);
+    let public_key = a.to_bytes();
+    public_key
+}
+
+/// generate the public key associated with an extended secret key
+///
+/// Only the secret scalar, i.e. the first 32 bytes of the extended secret key,
+/// is used, so any key of at least 32 bytes is accepted.
+///
+/// Panics if the extended secret key is shorter than 32 bytes, see [`try_to_public`]
+/// for a fallible version.
+#[cfg_attr(feature = "no-panic", deprecated(note = "use try_to_public"))]
+pub fn to_public(extended_secret: &[u8]) -> [u8; PUBLIC_KEY_LENGTH] {
+    assert!(
+        extended_secret.len() >= 32,
+        "Extended secret key should be at least 32 bytes long!"
+    );
+    extended_to_public(extended_secret)
+}
+
+/// generate the public key associated with an extended secret key, or return
+/// an error if the extended secret key is shorter than 32 bytes
+///
+/// As with [`to_public`], only the first 32 bytes of the extended secret key are used.
+pub fn try_to_public(extended_secret: &[u8]) -> Result<[u8; PUBLIC_KEY_LENGTH], InvalidLength> {
+    if extended_secret.len() < 32 {
+        return Err(InvalidLength);
+    }
+    Ok(extended_to_public(extended_secret))
+}
+
+/// Generate a signature for the given message using an extended ED25519 secret key
+///
+/// Panics if the extended secret key is not 64 bytes long, see [`try_signature_extended`]
+/// for a fallible version.
+#[cfg_attr(feature = "no-panic", deprecated(note = "use try_signature_extended"))]
+pub fn signature_extended(message: &[u8], extended_secret: &[u8]) -> [u8; SIGNATURE_LENGTH] {
+    assert!(
+        extended_secret.len() == PRIVATE_KEY_LENGTH,
+        "Private key should be {} bytes long!",
+        PRIVATE_KEY_LENGTH
+    );
+    let public_key = extended_to_public(extended_secret);
+
+    let mut prefix = [0u8; 32];
+    prefix.copy_from_slice(&extended_secret[32..64]);
+    let nonce = nonce_from_prefix(&prefix, message);
+
+    let mut signature: [u8; SIGNATURE_LENGTH] = [0; SIGNATURE_LENGTH];
+    let r = ge_scalarmult_base(&nonce).to_bytes();
+    signature[0..32].copy_from_slice(&r);
+
+    let k = hram(&r, &public_key, message);
+    sc_muladd(&mut signature[32..64], &k, &extended_secret[0..32], &nonce);
+
+    signature
+}
+
+/// Generate a signature for the given message using an extended ED25519 secret key,
+/// or return an error if the extended secret key is not 64 bytes long
+#[allow(deprecated)]
+pub fn try_signature_extended(
+    message: &[u8],
+    extended_secret: &[u8],
+) -> Result<[u8; SIGNATURE_LENGTH], InvalidLength> {
+    check_length(extended_secret, PRIVATE_KEY_LENGTH)?;
+    Ok(signature_extended(message, extended_secret))
+}
+
+// check the signature for one public key, the hasher containing the prefix of
+// the hash up to R, and S having already been checked to be canonical
+fn verify_key(hash_prefix: &Sha512, message: &[u8], public_key: &[u8], signature: &[u8]) -> bool {
+    assert!(
+        public_key.len() == PUBLIC_KEY_LENGTH,
+        "Public key should be {} bytes long!",
+        PUBLIC_KEY_LENGTH
+    );
+
+    let a = match GeP3::from_bytes_negate_vartime(<&[u8; 32]>::try_from(public_key).unwrap()) {
+        Some(g) => g,
+        None => {
+            return false;
+        }
+    };
+    if is_zero_key(public_key) {
+        return false;
+    }
+
+    verify_key_precomp(
+        hash_prefix,
+        message,
+        public_key,
+        &a.odd_multiples(),
+        signature,
+    )
+}
+
+fn is_zero_key(public_key: &[u8]) -> bool {
+    let mut d = 0;
+    for pk_byte in public_key.iter() {
+        d |= *pk_byte;
+    }
+    d == 0
+}
+
+// same as `verify_key`, with the odd multiples of the negated public key point
+// already computed
+fn verify_key_precomp(
+    hash_prefix: &Sha512,
+    message: &[u8],
+    public_key: &[u8],
+    neg_a: &[GeCached; 8],
+    signature: &[u8],
+) -> bool {
+    let mut hasher = hash_prefix.clone();
+    hasher.input(public_key);
+    hasher.input(message);
+    check_hram(hasher, neg_a, signature)
+}
+
+// check the signature given the hasher containing R, A and the message
+fn check_hram(mut hasher: Sha512, neg_a: &[GeCached; 8], signature: &[u8]) -> bool {
+    let mut hash: [u8; 64] = [0; 64];
+    hasher.result(&mut hash);
+    sc_reduce(&mut hash);
+
+    let r = GeP2::double_scalarmult_vartime_precomp(hash.as_ref(), neg_a, &signature[32..64]);
+    let rcheck = r.to_bytes();
+
+    fixed_time_eq(rcheck.as_ref(), &signature[0..32])
+}
+
+fn signature_s_is_canonical(signature: &[u8]) -> bool {
+    let mut s = [0u8; 32];
+    s.copy_from_slice(&signature[32..64]);
+    scalar_is_canonical(&s)
+}
+
+/// Reason for a signature to be rejected by [`precheck`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyFailure {
+    /// The public key is not 32 bytes long
+    PublicKeyLength,
+    /// The signature is not 64 bytes long
+    SignatureLength,
+    /// The S half of the signature is not reduced modulo the group order
+    NonCanonicalS,
+    /// The public key is all zeros
+    ZeroKey,
+}
+
+impl core::fmt::Display for VerifyFailure {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            VerifyFailure::PublicKeyLength => write!(f, "invalid length of ed25519 public key"),
+            VerifyFailure::SignatureLength => write!(f, "invalid length of ed25519 signature"),
+            VerifyFailure::NonCanonicalS => write!(f, "non canonical ed25519 signature"),
+            VerifyFailure::ZeroKey => write!(f, "all zero ed25519 public key"),
+        }
+    }
+}
+
+/// Run the cheap checks of [`verify`] on a public key and a signature
+///
+/// These are the checks of the lengths, of S being reduced and of the public
+/// key not being all zeros, which don't involve any curve arithmetic nor the
+/// message. A server can use this to reject malformed input before spending
+/// CPU on the verification; `verify` does the same checks first, so it
+/// returns `false` for any input rejected here.
+pub fn precheck(public_key: &[u8], signature: &[u8]) -> Result<(), VerifyFailure> {
+    if public_key.len() != PUBLIC_KEY_LENGTH {
+        return Err(VerifyFailure::PublicKeyLength);
+    }
+    if signature.len() != SIGNATURE_LENGTH {
+        return Err(VerifyFailure::SignatureLength);
+    }
+    if !signature_s_is_canonical(signature) {
+        return Err(VerifyFailure::NonCanonicalS);
+    }
+    if is_zero_key(public_key) {
+        return Err(VerifyFailure::ZeroKey);
+    }
+    Ok(())
+}
+
+/// Verify that a signature is valid for a given message for an associated public key
+///
+/// The public key doesn't need to be canonically encoded, see
+/// [`public_key_is_canonical`] and [`verify_strict`].
+///
+/// Panics if the public key is not 32 bytes long or the signature not 64 bytes long,
+/// see [`try_verify`] for a fallible version.
+#[cfg_attr(feature = "no-panic", deprecated(note = "use try_verify"))]
+pub fn verify(message: &[u8], public_key: &[u8], signature: &[u8]) -> bool {
+    assert!(
+        public_key.len() == PUBLIC_KEY_LENGTH,
+        "Public key should be {} bytes long!",
+        PUBLIC_KEY_LENGTH
+    );
+    assert!(
+        signature.len() == SIGNATURE_LENGTH,
+        "signature should be {} bytes long!",
+        SIGNATURE_LENGTH
+    );
+
+    if precheck(public_key, signature).is_err() {
+        return false;
+    }
+
+    let mut hasher = Sha512::new();
+    hasher.input(&signature[0..32]);
+    verify_key(&hasher, message, public_key, signature)
+}
+
+/// Verify that a signature is valid for a given message for an associated public key,
+/// or return an error if the public key or the signature are not of the expected length
+#[allow(deprecated)]
+pub fn try_verify(
+    message: &[u8],
+    public_key: &[u8],
+    signature: &[u8],
+) -> Result<bool, InvalidLength> {
+    check_length(public_key, PUBLIC_KEY_LENGTH)?;
+    check_length(signature, SIGNATURE_LENGTH)?;
+    Ok(verify(message, public_key, signature))
+}
+
+/// Verify a signature with the public key stored in the second half of a secret key
+///
+/// The 64 bytes secret key returned by [`keypair`] is the seed followed by the
+/// public key, so a signer can check its own signatures, e.g. to detect faults,
+/// without tracking the public key separately. The public key is not checked
+/// against the seed.
+#[allow(deprecated)]
+pub fn verify_with_secret(
+    message: &[u8],
+    secret_key: &[u8; PRIVATE_KEY_LENGTH],
+    signature: &[u8; SIGNATURE_LENGTH],
+) -> bool {
+    verify(message, &secret_key[32..64], signature)
+}
+
+/// Public key decoded in advance, to verify many signatures made with the same key
+///
+/// [`verify`] decodes the public key and precomputes multiples of the point
+/// for each signature. A prepared key does it once, and gives the same result
+/// as `verify` for every message and signature.
+///
+/// ```
+/// use cryptoxide::ed25519::{self, PreparedVerifyingKey};
+///
+/// let (secret, public) = ed25519::keypair(&[0u8; 32]); // seed only for example !
+/// let key = PreparedVerifyingKey::new(&public).unwrap();
+/// for message in [&b"first"[..], &b"second"[..]].iter() {
+///     let signature = ed25519::signature(message, &secret);
+///     assert!(key.verify(message, &signature));
+/// }
+/// ```
+#[derive(Clone)]
+pub struct PreparedVerifyingKey {
+    public_key: [u8; PUBLIC_KEY_LENGTH],
+    neg_a: [GeCached; 8],
+}
+
+impl PreparedVerifyingKey {
+    /// Decode a public key
+    ///
+    /// Return [`KeyError::NotOnCurve`] if the bytes are not the encoding of a
+    /// point of the curve, and [`KeyError::SmallOrder`] for the all zero key,
+    /// as these keys are rejected by [`verify`]. Other small order keys are
+    /// accepted like `verify` does, see [`check_public_key`] to reject them.
+    pub fn new(public_key: &[u8; PUBLIC_KEY_LENGTH]) -> Result<Self, KeyError> {
+        let a = GeP3::from_bytes_negate_vartime(public_key).ok_or(KeyError::NotOnCurve)?;
+        if is_zero_key(public_key) {
+            return Err(KeyError::SmallOrder);
+        }
+        Ok(PreparedVerifyingKey {
+            public_key: *public_key,
+            neg_a: a.odd_multiples(),
+        })
+    }
+
+    /// The public key bytes
+    pub fn public_key(&self) -> &[u8; PUBLIC_KEY_LENGTH] {
+        &self.public_key
+    }
+
+    /// Whether the public key bytes are the canonical encoding of the point
+    ///
+    /// See [`public_key_is_canonical`]: a key accepted by [`PreparedVerifyingKey::new`]
+    /// may not be canonical, which [`verify_strict`] rejects.
+    pub fn is_canonical(&self) -> bool {
+        public_key_is_canonical(&self.public_key)
+    }
+
+    /// Verify that a signature is valid for a given message, like [`verify`]
+    pub fn verify(&self, message: &[u8], signature: &[u8; SIGNATURE_LENGTH]) -> bool {
+        if !signature_s_is_canonical(signature) {
+            return false;
+        }
+        let mut hasher = Sha512::new();
+        hasher.input(&signature[0..32]);
+        verify_key_precomp(&hasher, message, &self.public_key, &self.neg_a, signature)
+    }
+}
+
+impl TryFrom<[u8; PUBLIC_KEY_LENGTH]> for PreparedVerifyingKey {
+    type Error = KeyError;
+
+    fn try_from(public_key: [u8; PUBLIC_KEY_LENGTH]) -> Result<Self, Self::Error> {
+        PreparedVerifyingKey::new(&public_key)
+    }
+}
+
+impl AsRef<[u8]> for PreparedVerifyingKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.public_key
+    }
+}
+
+/// Reason for a public key to be rejected by [`check_public_key`],
+/// by [`secret_key_from_seed_and_public`], or by the conversion to
+/// [`PreparedVerifyingKey`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyError {
+    /// The y coordinate of the point is not reduced modulo p
+    NonCanonical,
+    /// The bytes are not the encoding of a point of the curve
+    NotOnCurve,
+    /// The point is of small order, i.e. in the torsion subgroup
+    SmallOrder,
+    /// The public key is not the one of the secret key
+    PublicKeyMismatch,
+}
+
+impl core::fmt::Display for KeyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            KeyError::NonCanonical => write!(f, "non canonical ed25519 point encoding"),
+            KeyError::NotOnCurve => write!(f, "not an ed25519 curve point"),
+            KeyError::SmallOrder => write!(f, "ed25519 point of small order"),
+            KeyError::PublicKeyMismatch => write!(f, "ed25519 public key mismatch"),
+        }
+    }
+}
+
+// check that a point is on the curve, canonically encoded with y < p, and not of small order
+fn check_point(s: &[u8; 32]) -> Result<(), KeyError> {
+    let p = GeP3::from_bytes_negate_vartime(s).ok_or(KeyError::NotOnCurve)?;
+    let mut y = *s;
+    y[31] &= 0x7f;
+    if Fe::from_bytes(s).to_bytes() != y {
+        return Err(KeyError::NonCanonical);
+    }
+    if p.is_small_order() {
+        return Err(KeyError::SmallOrder);
+    }
+    Ok(())
+}
+
+/// Check that a public key from an untrusted source is valid
+///
+/// This is the validation done on the public key by [`verify_strict`]:
+/// the key needs to be the canonical encoding of a point of the curve
+/// which is not of small order. The error gives the reason of the rejection.
+pub fn check_public_key(public_key: &[u8; PUBLIC_KEY_LENGTH]) -> Result<(), KeyError> {
+    check_point(public_key)
+}
+
+/// Return the canonical encoding of the point of a public key, or `None` if
+/// the bytes are not the encoding of a point of the curve
+///
+/// A point has several encodings when its y coordinate is not reduced modulo p
+/// (y + p < 2^255 only for a few small y), or when its x coordinate is zero with
+/// the sign bit set. The hash of a signature covers the public key bytes, so a
+/// signature made for one encoding doesn't verify with another one.
+pub fn normalize_public_key(
+    public_key: &[u8; PUBLIC_KEY_LENGTH],
+) -> Option<[u8; PUBLIC_KEY_LENGTH]> {
+    GeP3::from_bytes(public_key).map(|p| p.to_bytes())
+}
+
+/// Check whether a public key is the canonical encoding of a point of the curve
+///
+/// [`verify_strict`] and [`check_public_key`] reject non canonical keys, while
+/// [`verify`] and [`PreparedVerifyingKey`] accept them, so that an application
+/// can decide of its own policy. Some of the points with a non canonical
+/// encoding, like the one with y = 3, are not of small order.
+pub fn public_key_is_canonical(public_key: &[u8; PUBLIC_KEY_LENGTH]) -> bool {
+    normalize_public_key(public_key).as_ref() == Some(public_key)
+}
+
+/// Verify that a signature is valid for a given message for an associated public key,
+/// with stricter rules than [`verify`]
+///
+/// In addition to the checks done by `verify`, the signature is rejected if
+/// the public key or the R component of the signature are not canonically
+/// encoded or are points of small order. A small order public key makes
+/// signatures valid for any message, which `verify` accepts.
+///
+/// Any signature accepted by `verify_strict` is also accepted by `verify`.
+///
+/// Panics on invalid lengths like `verify`, see [`try_verify_strict`] for a fallible version.
+#[cfg_attr(feature = "no-panic", deprecated(note = "use try_verify_strict"))]
+pub fn verify_strict(message: &[u8], public_key: &[u8], signature: &[u8]) -> bool {
+    assert!(
+        public_key.len() == PUBLIC_KEY_LENGTH,
+        "Public key should be {} bytes long!",
+        PUBLIC_KEY_LENGTH
+    );
+    assert!(
+        signature.len() == SIGNATURE_LENGTH,
+        "signature should be {} bytes long!",
+        SIGNATURE_LENGTH
+    );
+
+    if !signature_s_is_canonical(signature)
+        || check_point(<&[u8; 32]>::try_from(public_key).unwrap()).is_err()
+        || check_point(<&[u8; 32]>::try_from(&signature[0..32]).unwrap()).is_err()
+    {
+        return false;
+    }
+
+    let mut hasher = Sha512::new();
+    hasher.input(&signature[0..32]);
+    verify_key(&hasher, message, public_key, signature)
+}
+
+/// Same as [`verify_strict`], but return an error if the public key or the
+/// signature are not of the expected length
+#[allow(deprecated)]
+pub fn try_verify_strict(
+    message: &[u8],
+    public_key: &[u8],
+    signature: &[u8],
+) -> Result<bool, InvalidLength> {
+    check_length(public_key, PUBLIC_KEY_LENGTH)?;
+    check_length(signature, SIGNATURE_LENGTH)?;
+    Ok(verify_strict(message, public_key, signature))
+}
+
+/// Verify a signature for a given message against a set of candidate public keys
+///
+/// Return the index of the first public key for which the signature is valid,
+/// or `None` if the signature isn't valid for any of them. Candidate keys of
+/// an invalid length never match. Panics if the signature is not 64 bytes long.
+///
+/// As the public key is hashed before the message, the message still need to
+/// be hashed once per candidate key; only the checks on the signature and
+/// the hashing of R are shared between the candidates.
+pub fn verify_any(message: &[u8], public_keys: &[&[u8]], signature: &[u8]) -> Option<usize> {
+    assert!(
+        signature.len() == SIGNATURE_LENGTH,
+        "signature should be {} bytes long!",
+        SIGNATURE_LENGTH
+    );
+
+    if !signature_s_is_canonical(signature) {
+        return None;
+    }
+
+    let mut hasher = Sha512::new();
+    hasher.input(&signature[0..32]);
+    public_keys.iter().position(|public_key| {
+        public_key.len() == PUBLIC_KEY_LENGTH && verify_key(&hasher, message, public_key, signature)
+    })
+}
+
+/// Verify signatures of the same message by different public keys, e.g. the
+/// signatures of a block by the validators of a consensus protocol
+///
+/// Return for each public key and signature at the same index whether the
+/// signature is valid, as [`verify`] would; keys and signatures of invalid
+/// lengths are reported as invalid. Panics if the numbers of public keys and
+/// signatures differ.
+///
+/// The hash of each signature covers R and the public key before the message,
+/// so the message is still hashed once per signature: the SHA512 state after
+/// the message can't be shared, only the message borrow and the result vector are.
+#[cfg(feature = "alloc")]
+pub fn verify_same_message(
+    message: &[u8],
+    public_keys: &[&[u8]],
+    signatures: &[&[u8]],
+) -> Vec<bool> {
+    assert_eq!(public_keys.len(), signatures.len());
+    public_keys
+        .iter()
+        .zip(signatures.iter())
+        .map(|(public_key, signature)| {
+            if precheck(public_key, signature).is_err() {
+                return false;
+            }
+            let mut hasher = Sha512::new();
+            hasher.input(&signature[0..32]);
+            verify_key(&hasher, message, public_key, signature)
+        })
+        .collect()
+}
+
+/// Reduce the S component of a signature modulo l, returning whether it was changed
+///
+/// Signatures with a non canonical S, i.e. S + k l instead of S, are rejected
+/// by [`verify`]; this turns them back into the canonical signature, which
+/// verifies if the original signature was otherwise valid. A signature that is
+/// already canonical is left untouched.
+pub fn canonicalize_signature(signature: &mut [u8; SIGNATURE_LENGTH]) -> bool {
+    if signature_s_is_canonical(signature) {
+        return false;
+    }
+    let mut s = [0u8; 64];
+    s[0..32].copy_from_slice(&signature[32..64]);
+    signature[32..64].copy_from_slice(&scalar_from_bytes_wide(&s));
+    true
+}
+
+/// Join a detached signature and its message into an attached signed message,
+/// made of the signature followed by the message
+#[cfg(feature = "alloc")]
+pub fn attach(signature: &[u8; SIGNATURE_LENGTH], message: &[u8]) -> Vec<u8> {
+    let mut signed = Vec::with_capacity(SIGNATURE_LENGTH + message.len());
+    signed.extend_from_slice(signature);
+    signed.extend_from_slice(message);
+    signed
+}
+
+/// Split an attached signed message into its signature and message, without copying
+///
+/// Return `None` if `signed` is shorter than a signature.
+pub fn detach(signed: &[u8]) -> Option<(&[u8; SIGNATURE_LENGTH], &[u8])> {
+    if signed.len() < SIGNATURE_LENGTH {
+        return None;
+    }
+    let (signature, message) = signed.split_at(SIGNATURE_LENGTH);
+    // the length of signature is checked above
+    let signature = <&[u8; SIGNATURE_LENGTH]>::try_from(signature).unwrap();
+    Some((signature, message))
+}
+
+/// Incremental verification of an attached signed message, for messages too
+/// large to be kept in memory
+///
+/// The verifier is created with the signature prefix of the signed message,
+/// then fed with the rest of the message by chunks. The result is the same as
+/// [`verify_strict`] of the whole message, as done by [`open_attached`]; the
+/// message must not be used before [`AttachedVerifier::finalize`] has returned
+/// `true`.
+///
+/// ```
+/// use cryptoxide::ed25519::{attach, detach, keypair, signature, AttachedVerifier};
+///
+/// let (secret, public) = keypair(&[1u8; 32]);
+/// let signed = attach(&signature(b"large download", &secret), b"large download");
+///
+/// let (sig, message) = detach(&signed).unwrap();
+/// let mut verifier = AttachedVerifier::new(&public, sig);
+/// for chunk in message.chunks(4) {
+///     verifier.update(chunk);
+/// }
+/// assert!(verifier.finalize());
+/// ```
+#[derive(Clone)]
+pub struct AttachedVerifier {
+    key: Option<PreparedVerifyingKey>,
+    signature: [u8; SIGNATURE_LENGTH],
+    hasher: Sha512,
+}
+
+impl AttachedVerifier {
+    /// Start the verification of a message with the given public key and signature
+    pub fn new(public_key: &[u8; PUBLIC_KEY_LENGTH], signature: &[u8; SIGNATURE_LENGTH]) -> Self {
+        let mut commitment = [0u8; 32];
+        commitment.copy_from_slice(&signature[0..32]);
+        let mut hasher = Sha512::new();
+        hasher.input(&signature[0..32]);
+        hasher.input(public_key);
+        // the checks of verify_strict on the public key and R
+        let key = match (check_point(public_key), check_point(&commitment)) {
+            (Ok(()), Ok(())) => PreparedVerifyingKey::new(public_key).ok(),
+            _ => None,
+        };
+        AttachedVerifier {
+            key,
+            signature: *signature,
+            hasher,
+        }
+    }
+
+    /// Add the next chunk of the message
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.hasher.input(chunk);
+    }
+
+    /// Return whether the signature is valid for the whole message
+    pub fn finalize(self) -> bool {
+        if !signature_s_is_canonical(&self.signature) {
+            return false;
+        }
+        match self.key {
+            Some(key) => check_hram(self.hasher, &key.neg_a, &self.signature),
+            None => false,
+        }
+    }
+}
+
+/// Verify an attached signed message, returning the message if the signature is valid
+///
+/// The message is returned as a subslice of `signed`. The verification is done
+/// with [`verify_strict`], like libsodium's `crypto_sign_open`.
+#[allow(deprecated)]
+pub fn open_attached<'a>(
+    public_key: &[u8; PUBLIC_KEY_LENGTH],
+    signed: &'a [u8],
+) -> Option<&'a [u8]> {
+    let (signature, message) = detach(signed)?;
+    if verify_strict(message, public_key, signature) {
+        Some(message)
+    } else {
+        None
+    }
+}
+
+/// Length of the SHA512 prehash of the message used by Ed25519ph
+pub const PREHASH_LENGTH: usize = 64;
+
+/// Maximum length of the context string of Ed25519ph
+pub const CONTEXT_MAX_LENGTH: usize = 255;
+
+// dom2(1, context) prefix of the Ed25519ph hashes
+fn hash_dom2_prehashed(hasher: &mut Sha512, context: &[u8]) {
+    hasher.input(b"SigEd25519 no Ed25519 collisions");
+    hasher.input(&[1, context.len() as u8]);
+    hasher.input(context);
+}
+
+/// Generate an Ed25519ph signature for a message given by its SHA512 prehash,
+/// using a normal ED25519 secret key
+///
+/// The prehash is the SHA512 digest of the message, which can be computed
+/// incrementally with [`Sha512`]. The context, at most
+/// 255 bytes and usually empty, need to be given again to verify the signature.
+///
+/// Ed25519ph signatures are not compatible with pure Ed25519 signatures
+/// of either the message or its prehash.
+///
+/// Panics if the secret key is not 64 bytes long or the context is too long,
+/// see [`try_signature_prehashed`] for a fallible version.
+#[cfg_attr(feature = "no-panic", deprecated(note = "use try_signature_prehashed"))]
+pub fn signature_prehashed(
+    prehash: &[u8; PREHASH_LENGTH],
+    context: &[u8],
+    secret_key: &[u8],
+) -> [u8; SIGNATURE_LENGTH] {
+    assert!(
+        secret_key.len() == PRIVATE_KEY_LENGTH,
+        "Private key should be {} bytes long!",
+        PRIVATE_KEY_LENGTH
+    );
+    assert!(
+        context.len() <= CONTEXT_MAX_LENGTH,
+        "Context should be at most {} bytes long!",
+        CONTEXT_MAX_LENGTH
+    );
+
+    let seed = &secret_key[0..32];
+    let public_key = &secret_key[32..64];
+    let az: [u8; 64] = {
+        let mut hash_output: [u8; 64] = [0; 64];
+        let mut hasher = Sha512::new();
+        hasher.input(seed);
+        hasher.result(&mut hash_output);
+        clamp_ed25519_scalar(&mut hash_output[0..32]);
+        hash_output
+    };
+
+    let nonce = {
+        let mut hash_output: [u8; 64] = [0; 64];
+        let mut hasher = Sha512::new();
+        hash_dom2_prehashed(&mut hasher, context);
+        hasher.input(&az[32..64]);
+        hasher.input(prehash);
+        hasher.result(&mut hash_output);
+        sc_reduce(&mut hash_output[0..64]);
+        hash_output
+    };
+
+    let mut signature: [u8; SIGNATURE_LENGTH] = [0; SIGNATURE_LENGTH];
+    let r: GeP3 = ge_scalarmult_base(<&[u8; 32]>::try_from(&nonce[0..32]).unwrap());
+    signature[0..32].copy_from_slice(&r.to_bytes());
+
+    {
+        let mut hasher = Sha512::new();
+        hash_dom2_prehashed(&mut hasher, context);
+        hasher.input(&signature[0..32]);
+        hasher.input(public_key);
+        hasher.input(prehash);
+        let mut hram: [u8; 64] = [0; 64];
+        hasher.result(&mut hram);
+        sc_reduce(&mut hram);
+        sc_muladd(
+            &mut signature[32..64],
+            &hram[0..32],
+            &az[0..32],
+            &nonce[0..32],
+        );
+    }
+
+    signature
+}
+
+/// Same as [`signature_prehashed`], but return an error if the secret key
+/// is not 64 bytes long or the context is too long
+#[allow(deprecated)]
+pub fn try_signature_prehashed(
+    prehash: &[u8; PREHASH_LENGTH],
+    context: &[u8],
+    secret_key: &[u8],
+) -> Result<[u8; SIGNATURE_LENGTH], InvalidLength> {
+    check_length(secret_key, PRIVATE_KEY_LENGTH)?;
+    if context.len() > CONTEXT_MAX_LENGTH {
+        return Err(InvalidLength);
+    }
+    Ok(signature_prehashed(prehash, context, secret_key))
+}
+
+/// Verify that an Ed25519ph signature is valid for a message given by its
+/// SHA512 prehash and a context, for an associated public key
+///
+/// Panics on invalid key or signature lengths like [`verify`], see
+/// [`try_verify_prehashed`] for a fallible version.
+#[cfg_attr(feature = "no-panic", deprecated(note = "use try_verify_prehashed"))]
+pub fn verify_prehashed(
+    prehash: &[u8; PREHASH_LENGTH],
+    context: &[u8],
+    public_key: &[u8],
+    signature: &[u8],
+) -> bool {
+    assert!(
+        public_key.len() == PUBLIC_KEY_LENGTH,
+        "Public key should be {} bytes long!",
+        PUBLIC_KEY_LENGTH
+    );
+    assert!(
+        signature.len() == SIGNATURE_LENGTH,
+        "signature should be {} bytes long!",
+        SIGNATURE_LENGTH
+    );
+    if context.len() > CONTEXT_MAX_LENGTH {
+        return false;
+    }
+
+    if !signature_s_is_canonical(signature) {
+        return false;
+    }
+
+    let mut hasher = Sha512::new();
+    hash_dom2_prehashed(&mut hasher, context);
+    hasher.input(&signature[0..32]);
+    verify_key(&hasher, prehash, public_key, signature)
+}
+
+/// Same as [`verify_prehashed`], but return an error if the public key or
+/// the signature are not of the expected length
+#[allow(deprecated)]
+pub fn try_verify_prehashed(
+    prehash: &[u8; PREHASH_LENGTH],
+    context: &[u8],
+    public_key: &[u8],
+    signature: &[u8],
+) -> Result<bool, InvalidLength> {
+    check_length(public_key, PUBLIC_KEY_LENGTH)?;
+    check_length(signature, SIGNATURE_LENGTH)?;
+    Ok(verify_prehashed(prehash, context, public_key, signature))
+}
+
+/// Curve25519 DH (Diffie Hellman) between a curve25519 public key and a ed25519 private key
+///
+/// Panics if the public key or the private key are too short, see [`try_exchange`]
+/// for a fallible version.
+#[cfg_attr(feature = "no-panic", deprecated(note = "use try_exchange"))]
+pub fn exchange(public_key: &[u8], private_key: &[u8]) -> [u8; 32] {
+    let ed_y = Fe::from_bytes(<&[u8; 32]>::try_from(&public_key[0..32]).unwrap());
+    // Produce public key in Montgomery form.
+    let mont_x = edwards_to_montgomery_x(&ed_y);
+
+    // Produce private key from seed component (bytes 0 to 32)
+    // of the Ed25519 extended private key (64 bytes).
+    let mut seed = [0u8; SEED_LENGTH];
+    seed.copy_from_slice(&private_key[0..32]);
+    let secret = x25519_secret_from_seed(&seed);
+
+    let shared_mont_x: [u8; 32] = curve25519(secret.expose(), &mont_x.to_bytes()); // priv., pub.
+
+    shared_mont_x
+}
+
+/// Derive the X25519 secret key corresponding to an ed25519 seed
+///
+/// This is the SHA512 hash of the seed, truncated to 32 bytes and clamped,
+/// which is the private key used by [`exchange`]. The matching X25519 public key
+/// is the `curve25519_base` of the secret, which is also the Montgomery form of
+/// the ed25519 public key. This allows to build Diffie-Hellman exchanges compatible
+/// with `exchange` from the curve25519 functions.
+///
+/// This is the same as [`x25519::secret_from_hash`](crate::x25519::secret_from_hash).
+pub fn x25519_secret_from_seed(seed: &[u8; SEED_LENGTH]) -> Secret<32> {
+    crate::x25519::secret_from_hash(seed)
+}
+
+/// Curve25519 DH between a curve25519 public key and a ed25519 private key,
+/// or return an error if the public key is not 32 bytes long or the private
+/// key not 64 bytes long
+#[allow(deprecated)]
+pub fn try_exchange(public_key: &[u8], private_key: &[u8]) -> Result<[u8; 32], InvalidLength> {
+    check_length(public_key, PUBLIC_KEY_LENGTH)?;
+    check_length(private_key, PRIVATE_KEY_LENGTH)?;
+    Ok(exchange(public_key, private_key))
+}
+
+/// Curve25519 DH like [`exchange`], with the shared secret bound to a protocol `context`
+/// and to the public keys of both parties
+///
+/// The raw shared secret of [`exchange`] is the same for every protocol using
+/// the same identity keys. Here it is passed through HKDF-SHA512, with an empty
+/// salt and `context` as the info, so that protocols using distinct contexts
+/// derive unrelated shared secrets. The input keying material is the raw shared
+/// secret followed by the two ed25519 public keys in ascending byte order, so
+/// both parties get the same output when they use the same context.
+///
+/// Return [`KeyError::SmallOrder`] if the shared secret is all zeros, which
+/// happens when the peer public key is of small order and the exchange doesn't
+/// depend on the private key.
+///
+/// ```
+/// use cryptoxide::ed25519::{exchange_domain, keypair};
+///
+/// let (alice_secret, alice_public) = keypair(&[1u8; 32]);
+/// let (bob_secret, bob_public) = keypair(&[2u8; 32]);
+/// assert_eq!(
+///     exchange_domain(&bob_public, &alice_secret, b"my protocol v1"),
+///     exchange_domain(&alice_public, &bob_secret, b"my protocol v1"),
+/// );
+/// ```
+#[cfg(feature = "hkdf")]
+#[allow(deprecated)]
+pub fn exchange_domain(
+    public_key: &[u8; PUBLIC_KEY_LENGTH],
+    private_key: &[u8; PRIVATE_KEY_LENGTH],
+    context: &[u8],
+) -> Result<[u8; 32], KeyError> {
+    let mut ikm = [0u8; 32 + 2 * PUBLIC_KEY_LENGTH];
+    ikm[0..32].copy_from_slice(&exchange(public_key, private_key));
+    if is_zero_key(&ikm[0..32]) {
+        return Err(KeyError::SmallOrder);
+    }
+    let own_public_key = &private_key[32..64];
+    let (first, second) = if own_public_key <= &public_key[..] {
+        (own_public_key, &public_key[..])
+    } else {
+        (&public_key[..], own_public_key)
+    };
+    ikm[32..64].copy_from_slice(first);
+    ikm[64..96].copy_from_slice(second);
+    let hkdf = Hkdf::new(Sha512::new(), &[], &ikm);
+    secure_memset(&mut ikm, 0);
+    let mut out = [0u8; 32];
+    hkdf.expand(context, &mut out)
+        .expect("32 bytes is within the HKDF-SHA512 output limit");
+    Ok(out)
+}
+
+fn edwards_to_montgomery_x(ed_y: &Fe) -> Fe {
+    let ed_z = &Fe([1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let temp_x = ed_z.add(ed_y);
+    let temp_z = ed_z.sub(ed_y);
+    let temp_z_inv = temp_z.invert();
+
+    let mont_x = temp_x.mul(temp_z_inv);
+
+    mont_x
+}
+
+/// Length of a public key fingerprint
+pub const FINGERPRINT_LENGTH: usize = 8;
+
+/// Short fingerprint of a public key, for display and key selection
+///
+/// The fingerprint is the first 8 bytes of the SHA256 digest of the
+/// 32 bytes public key. This format is stable and will not change.
+///
+/// A fingerprint is not collision resistant, and should not be used in
+/// place of the full public key to identify the signer of a message.
+pub fn fingerprint(public_key: &[u8]) -> [u8; FINGERPRINT_LENGTH] {
+    assert!(
+        public_key.len() == PUBLIC_KEY_LENGTH,
+        "Public key should be {} bytes long!",
+        PUBLIC_KEY_LENGTH
+    );
+    let mut hasher = Sha256::new();
+    hasher.input(public_key);
+    let mut hash = [0u8; 32];
+    hasher.result(&mut hash);
+    let mut out = [0u8; FINGERPRINT_LENGTH];
+    out.copy_from_slice(&hash[..FINGERPRINT_LENGTH]);
+    out
+}
+
+/// Compare two secret keys in constant time
+///
+/// Both keys need to be normal 64 bytes secret keys as returned by [`keypair`],
+/// or both 64 bytes extended secret keys.
+pub fn secret_key_eq(a: &[u8], b: &[u8]) -> bool {
+    assert!(
+        a.len() == PRIVATE_KEY_LENGTH && b.len() == PRIVATE_KEY_LENGTH,
+        "Private key should be {} bytes long!",
+        PRIVATE_KEY_LENGTH
+    );
+    fixed_time_eq(a, b)
+}
+
+#[cfg(test)]
+#[cfg_attr(feature = "no-panic", allow(deprecated))]
+mod wycheproof;
+
+#[cfg(test)]
+#[cfg_attr(feature = "no-panic", allow(deprecated))]
+mod tests {
+    #[cfg(feature = "alloc")]
+    use super::{
+        attach, detach, open_attached, verify_same_message, AttachedVerifier, SIGNATURE_LENGTH,
+    };
+    use super::{
+        canonicalize_signature, check_public_key, commit, exchange, fingerprint, hram, keypair,
+        nonce_from_prefix, normalize_public_key, precheck, public_key_is_canonical, respond,
+        secret_key_eq, secret_key_from_seed_and_public, signature, signature_prehashed, to_public,
+        try_exchange, try_keypair, try_signature, try_signature_extended, try_signature_prehashed,
+        try_to_public, try_verify, try_verify_prehashed, try_verify_strict, verify, verify_any,
+        verify_prehashed, verify_strict, verify_with_secret, x25519_secret_from_seed, Commitment,
+        InvalidLength, KeyError, PreparedVerifyingKey, VerifyFailure,
+    };
+    use crate::curve25519::{
+        clamp_ed25519_scalar, clamp_x25519_scalar, curve25519, curve25519_base, ge_scalarmult_base,
+        sc_muladd, scalar_from_bytes_wide, L,
+    };
+    use crate::digest::Digest;
+    use crate::sha2::Sha512;
+    use crate::util::Secret;
+    use core::convert::TryFrom;
+
+    fn do_keypair_case(seed: [u8; 32], expected_secret: [u8; 64], expected_public: [u8; 32]) {
+        let (actual_secret, actual_public) = keypair(seed.as_ref());
+        assert_eq!(actual_secret.to_vec(), expected_secret.to_vec());
+        assert_eq!(actual_public.to_vec(), expected_public.to_vec());
+    }
+
+    #[test]
+    fn keypair_cases() {
+        do_keypair_case(
+            [
+                0x26, 0x27, 0xf6, 0x85, 0x97, 0x15, 0xad, 0x1d, 0xd2, 0x94, 0xdd, 0xc4, 0x76, 0x19,
+                0x39, 0x31, 0xf1, 0xad, 0xb5, 0x58, 0xf0, 0x93, 0x97, 0x32, 0x19, 0x2b, 0xd1, 0xc0,
+                0xfd, 0x16, 0x8e, 0x4e,
+            ],
+            [
+                0x26, 0x27, 0xf6, 0x85, 0x97, 0x15, 0xad, 0x1d, 0xd2, 0x94, 0xdd, 0xc4, 0x76, 0x19,
+                0x39, 0x31, 0xf1, 0xad, 0xb5, 0x58, 0xf0, 0x93, 0x97, 0x32, 0x19, 0x2b, 0xd1, 0xc0,
+                0xfd, 0x16, 0x8e, 0x4e, 0x5d, 0x6d, 0x23, 0x6b, 0x52, 0xd1, 0x8e, 0x3a, 0xb6, 0xd6,
+                0x07, 0x2f, 0xb6, 0xe4, 0xc7, 0xd4, 0x6b, 0xd5, 0x9a, 0xd9, 0xcc, 0x19, 0x47, 0x26,
+                0x5f, 0x00, 0xb7, 0x20, 0xfa, 0x2c, 0x8f, 0x66,
+            ],
+            [
+                0x5d, 0x6d, 0x23, 0x6b, 0x52, 0xd1, 0x8e, 0x3a, 0xb6, 0xd6, 0x07, 0x2f, 0xb6, 0xe4,
+                0xc7, 0xd4, 0x6b, 0xd5, 0x9a, 0xd9, 0xcc, 0x19, 0x47, 0x26, 0x5f, 0x00, 0xb7, 0x20,
+                0xfa, 0x2c, 0x8f, 0x66,
+            ],
+        );
+        do_keypair_case(
+            [
+                0x29, 0x23, 0xbe, 0x84, 0xe1, 0x6c, 0xd6, 0xae, 0x52, 0x90, 0x49, 0xf1, 0xf1, 0xbb,
+                0xe9, 0xeb, 0xb3, 0xa6, 0xdb, 0x3c, 0x87, 0x0c, 0x3e, 0x99, 0x24, 0x5e, 0x0d, 0x1c,
+                0x06, 0xb7, 0x47, 0xde,
+            ],
+            [
+                0x29, 0x23, 0xbe, 0x84, 0xe1, 0x6c, 0xd6, 0xae, 0x52, 0x90, 0x49, 0xf1, 0xf1, 0xbb,
+                0xe9, 0xeb, 0xb3, 0xa6, 0xdb, 0x3c, 0x87, 0x0c, 0x3e, 0x99, 0x24, 0x5e, 0x0d, 0x1c,
+                0x06, 0xb7, 0x47, 0xde, 0x5d, 0x83, 0x31, 0x26, 0x56, 0x0c, 0xb1, 0x9a, 0x14, 0x19,
+                0x37, 0x27, 0x78, 0x96, 0xf0, 0xfd, 0x43, 0x7b, 0xa6, 0x80, 0x1e, 0xb2, 0x10, 0xac,
+                0x4c, 0x39, 0xd9, 0x00, 0x72, 0xd7, 0x0d, 0xa8,
+            ],
+            [
+                0x5d, 0x83, 0x31, 0x26, 0x56, 0x0c, 0xb1, 0x9a, 0x14, 0x19, 0x37, 0x27, 0x78, 0x96,
+                0xf0, 0xfd, 0x43, 0x7b, 0xa6, 0x80, 0x1e, 0xb2, 0x10, 0xac, 0x4c, 0x39, 0xd9, 0x00,
+                0x72, 0xd7, 0x0d, 0xa8,
+            ],
+        );
+    }
+
+    #[test]
+    fn keypair_matches_mont() {
+        let seed = [
+            0x26, 0x27, 0xf6, 0x85, 0x97, 0x15, 0xad, 0x1d, 0xd2, 0x94, 0xdd, 0xc4, 0x76, 0x19,
+            0x39, 0x31, 0xf1, 0xad, 0xb5, 0x58, 0xf0, 0x93, 0x97, 0x32, 0x19, 0x2b, 0xd1, 0xc0,
+            0xfd, 0x16, 0x8e, 0x4e,
+        ];
+        let (ed_private, ed_public) = keypair(seed.as_ref());
+
+        let mut hasher = Sha512::new();
+        hasher.input(&ed_private[0..32]);
+        let mut hash: [u8; 64] = [0; 64];
+        hasher.result(&mut hash);
+        clamp_x25519_scalar(&mut hash[0..32]);
+
+        let cv_public = curve25519_base(&hash);
+
+        let edx_ss = exchange(&ed_public, &ed_private);
+        let cv_ss = curve25519(&hash, &cv_public);
+
+        assert_eq!(edx_ss.to_vec(), cv_ss.to_vec());
+    }
+
+    fn do_sign_verify_case(seed: [u8; 32], message: &[u8], expected_signature: [u8; 64]) {
+        let (secret_key, public_key) = keypair(seed.as_ref());
+        let mut actual_signature = signature(message, secret_key.as_ref());
+        assert_eq!(expected_signature.to_vec(), actual_signature.to_vec());
+
+        let (commitment, secret) = commit(message, &secret_key);
+        assert_eq!(commitment.0, expected_signature[0..32]);
+        assert_eq!(respond(secret).to_vec(), expected_signature.to_vec());
+        assert!(verify(
+            message,
+            public_key.as_ref(),
+            actual_signature.as_ref()
+        ));
+
+        for &(index, flip) in [(0, 1), (31, 0x80), (20, 0xff)].iter() {
+            actual_signature[index] ^= flip;
+            assert!(!verify(
+                message,
+                public_key.as_ref(),
+                actual_signature.as_ref()
+            ));
+            actual_signature[index] ^= flip;
+        }
+
+        let mut public_key_corrupt = public_key;
+        public_key_corrupt[0] ^= 1;
+        assert!(!verify(
+            message,
+            public_key_corrupt.as_ref(),
+            actual_signature.as_ref()
+        ));
+    }
+
+    #[test]
+    fn verify_rejects_non_canonical_s() {
+        // order of the base point, little endian
+        const L: [u8; 32] = [
+            0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9,
+            0xde, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x10,
+        ];
+        let message = b"malleable";
+        let (secret_key, public_key) = keypair(&[0x42; 32]);
+        let sig = signature(message, &secret_key);
+        assert!(verify(message, &public_key, &sig));
+
+        // S + L is an equivalent scalar that would verify if not rejected
+        let mut malleated = sig;
+        let mut carry = 0u16;
+        for (s, l) in malleated[32..64].iter_mut().zip(L.iter()) {
+            let v = (*s as u16) + (*l as u16) + carry;
+            *s = v as u8;
+            carry = v >> 8;
+        }
+        assert_eq!(carry, 0);
+        assert!(!verify(message, &public_key, &malleated));
+    }
+
+    // test vector from RFC8032 section 7.3, and the same key with a context
+    #[test]
+    fn prehashed_cases() {
+        let seed = [
+            0x83, 0x3f, 0xe6, 0x24, 0x09, 0x23, 0x7b, 0x9d, 0x62, 0xec, 0x77, 0x58, 0x75, 0x20,
+            0x91, 0x1e, 0x9a, 0x75, 0x9c, 0xec, 0x1d, 0x19, 0x75, 0x5b, 0x7d, 0xa9, 0x01, 0xb9,
+            0x6d, 0xca, 0x3d, 0x42,
+        ];
+        let (secret_key, public_key) = keypair(&seed);
+        assert_eq!(
+            public_key,
+            [
+                0xec, 0x17, 0x2b, 0x93, 0xad, 0x5e, 0x56, 0x3b, 0xf4, 0x93, 0x2c, 0x70, 0xe1, 0x24,
+                0x50, 0x34, 0xc3, 0x54, 0x67, 0xef, 0x2e, 0xfd, 0x4d, 0x64, 0xeb, 0xf8, 0x19, 0x68,
+                0x34, 0x67, 0xe2, 0xbf
+            ]
+        );
+
+        // prehash computed incrementally, forking the hash state on the common prefix
+        let mut prefix = Sha512::new();
+        prefix.input(b"a");
+        let mut hasher = prefix.clone();
+        hasher.input(b"bc");
+        let mut prehash = [0u8; 64];
+        hasher.result(&mut prehash);
+
+        let sig = signature_prehashed(&prehash, b"", &secret_key);
+        assert_eq!(
+            sig.to_vec(),
+            [
+                0x98, 0xa7, 0x02, 0x22, 0xf0, 0xb8, 0x12, 0x1a, 0xa9, 0xd3, 0x0f, 0x81, 0x3d, 0x68,
+                0x3f, 0x80, 0x9e, 0x46, 0x2b, 0x46, 0x9c, 0x7f, 0xf8, 0x76, 0x39, 0x49, 0x9b, 0xb9,
+                0x4e, 0x6d, 0xae, 0x41, 0x31, 0xf8, 0x50, 0x42, 0x46, 0x3c, 0x2a, 0x35, 0x5a, 0x20,
+                0x03, 0xd0, 0x62, 0xad, 0xf5, 0xaa, 0xa1, 0x0b, 0x8c, 0x61, 0xe6, 0x36, 0x06, 0x2a,
+                0xaa, 0xd1, 0x1c, 0x2a, 0x26, 0x08, 0x34, 0x06
+            ]
+            .to_vec()
+        );
+        assert!(verify_prehashed(&prehash, b"", &public_key, &sig));
+        assert!(!verify_prehashed(&prehash, b"foo", &public_key, &sig));
+        assert!(!verify(&prehash, &public_key, &sig));
+        assert!(!verify(b"abc", &public_key, &sig));
+
+        let sig = signature_prehashed(&prehash, b"foo", &secret_key);
+        assert_eq!(
+            sig.to_vec(),
+            [
+                0xe0, 0x39, 0x70, 0x2b, 0x4c, 0x25, 0x95, 0xa6, 0xa5, 0x41, 0xac, 0x85, 0x09, 0x23,
+                0x6e, 0x29, 0x90, 0x47, 0x47, 0x95, 0x33, 0x0c, 0x9b, 0x34, 0xa7, 0x5f, 0x58, 0xa6,
+                0x60, 0x12, 0x9e, 0x08, 0xfd, 0x73, 0x69, 0x43, 0xfb, 0x19, 0x43, 0xa5, 0x57, 0x20,
+                0xb9, 0xe0, 0x95, 0x7b, 0x1e, 0xd6, 0x73, 0x48, 0x16, 0x61, 0x9f, 0x13, 0x88, 0xf4,
+                0x3f, 0x73, 0xe6, 0xe3, 0xba, 0xa8, 0x1c, 0x0e
+            ]
+            .to_vec()
+        );
+        assert!(verify_prehashed(&prehash, b"foo", &public_key, &sig));
+        assert!(!verify_prehashed(&prehash, b"", &public_key, &sig));
+        prehash[0] ^= 1;
+        assert!(!verify_prehashed(&prehash, b"foo", &public_key, &sig));
+    }
+
+    #[test]
+    fn verify_any_candidates() {
+        let message = b"signed by one of many";
+        let (secret_key, public_key) = keypair(&[1; 32]);
+        let (_, other1) = keypair(&[2; 32]);
+        let (_, other2) = keypair(&[3; 32]);
+        let invalid = [0u8; 32];
+        let sig = signature(message, &secret_key);
+
+        assert_eq!(
+            verify_any(message, &[&other1, &other2, &public_key], &sig),
+            Some(2)
+        );
+        assert_eq!(
+            verify_any(message, &[&public_key, &other1, &public_key], &sig),
+            Some(0)
+        );
+        assert_eq!(verify_any(message, &[&invalid, &public_key], &sig), Some(1));
+        assert_eq!(
+            verify_any(message, &[&public_key[..31], &public_key], &sig),
+            Some(1)
+        );
+        assert_eq!(verify_any(message, &[&[0u8; 33][..]], &sig), None);
+        assert_eq!(verify_any(message, &[&other1, &other2], &sig), None);
+        assert_eq!(verify_any(message, &[], &sig), None);
+        assert_eq!(
+            verify_any(b"another message", &[&other1, &public_key], &sig),
+            None
+        );
+
+        let mut corrupted = sig;
+        corrupted[40] ^= 1;
+        assert_eq!(
+            verify_any(message, &[&other1, &public_key], &corrupted),
+            None
+        );
+    }
+
+    #[test]
+    fn conversions() {
+        let (_, public) = keypair(&[3u8; 32]);
+
+        let key = PreparedVerifyingKey::try_from(public).unwrap();
+        assert_eq!(key.as_ref(), &public[..]);
+        assert_eq!(key.public_key(), &public);
+        assert_eq!(
+            PreparedVerifyingKey::try_from([0u8; 32]).err(),
+            Some(KeyError::SmallOrder)
+        );
+
+        let commitment = Commitment::try_from(&public[..]).unwrap();
+        assert_eq!(commitment, Commitment(public));
+        assert_eq!(commitment.as_ref(), &public[..]);
+        assert!(Commitment::try_from(&public[..31]).is_err());
+        assert!(Commitment::try_from(&[0u8; 33][..]).is_err());
+    }
+
+    #[test]
+    fn public_key_canonical_encodings() {
+        let (_, public) = keypair(&[5; 32]);
+        assert!(public_key_is_canonical(&public));
+        assert_eq!(normalize_public_key(&public), Some(public));
+        assert!(PreparedVerifyingKey::new(&public).unwrap().is_canonical());
+
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        // y = p + 1, and y = 1 with the sign bit set for x = 0
+        let mut identity_p = [0xffu8; 32];
+        identity_p[0] = 0xee;
+        identity_p[31] = 0x7f;
+        let mut identity_neg = identity;
+        identity_neg[31] = 0x80;
+        // y = p, the same point as y = 0
+        let mut y_p = [0xffu8; 32];
+        y_p[0] = 0xed;
+        y_p[31] = 0x7f;
+        // y = p + 3, the same point as y = 3 which is not of small order
+        let mut y_p3 = y_p;
+        y_p3[0] = 0xf0;
+        let mut y3 = [0u8; 32];
+        y3[0] = 3;
+
+        assert!(public_key_is_canonical(&identity));
+        for (key, canonical) in [
+            (identity_p, identity),
+            (identity_neg, identity),
+            (y_p, [0u8; 32]),
+            (y_p3, y3),
+        ]
+        .iter()
+        {
+            assert!(!public_key_is_canonical(key));
+            assert_eq!(normalize_public_key(key), Some(*canonical));
+            assert!(check_public_key(key).is_err());
+            assert!(!PreparedVerifyingKey::new(key).unwrap().is_canonical());
+        }
+
+        // R = identity and S = 0 is valid for any message with an identity
+        // public key, which verify accepts and verify_strict rejects
+        let mut sig = [0u8; 64];
+        sig[0] = 1;
+        assert!(verify(b"any message", &identity_p, &sig));
+        assert!(!verify_strict(b"any message", &identity_p, &sig));
+        assert_eq!(check_public_key(&y3), Ok(()));
+        assert_eq!(check_public_key(&y_p3), Err(KeyError::NonCanonical));
+
+        let mut not_on_curve = [0u8; 32];
+        not_on_curve[0] = 2;
+        assert_eq!(normalize_public_key(&not_on_curve), None);
+        assert!(!public_key_is_canonical(&not_on_curve));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn verify_same_message_mixed() {
+        let message = b"block signed by the validators";
+        let keys: std::vec::Vec<_> = (1..=4u8).map(|i| keypair(&[i; 32])).collect();
+        let sigs: std::vec::Vec<_> = keys.iter().map(|(sk, _)| signature(message, sk)).collect();
+        let other = signature(b"another block", &keys[2].0);
+        let mut corrupted = sigs[3];
+        corrupted[10] ^= 1;
+
+        let public_keys: std::vec::Vec<&[u8]> = keys.iter().map(|(_, pk)| &pk[..]).collect();
+        let all: std::vec::Vec<&[u8]> = sigs.iter().map(|s| &s[..]).collect();
+        assert_eq!(
+            verify_same_message(message, &public_keys, &all),
+            [true, true, true, true]
+        );
+
+        let mixed: [&[u8]; 4] = [&sigs[0], &sigs[0], &other, &corrupted];
+        assert_eq!(
+            verify_same_message(message, &public_keys, &mixed),
+            [true, false, false, false]
+        );
+
+        let short_key: [&[u8]; 2] = [&public_keys[0][0..31], public_keys[1]];
+        let short_sig: [&[u8]; 2] = [&sigs[0], &sigs[1][0..63]];
+        assert_eq!(
+            verify_same_message(message, &short_key, &short_sig),
+            [false, false]
+        );
+        assert!(verify_same_message(message, &[], &[]).is_empty());
+    }
+
+    #[test]
+    fn sign_verify_cases() {
+        do_sign_verify_case(
+            [
+                0x2d, 0x20, 0x86, 0x83, 0x2c, 0xc2, 0xfe, 0x3f, 0xd1, 0x8c, 0xb5, 0x1d, 0x6c, 0x5e,
+                0x99, 0xa5, 0x75, 0x9f, 0x02, 0x21, 0x1f, 0x85, 0xe5, 0xff, 0x2f, 0x90, 0x4a, 0x78,
+                0x0f, 0x58, 0x00, 0x6f,
+            ],
+            [
+                0x89, 0x8f, 0x9c, 0x4b, 0x2c, 0x6e, 0xe9, 0xe2, 0x28, 0x76, 0x1c, 0xa5, 0x08, 0x97,
+                0xb7, 0x1f, 0xfe, 0xca, 0x1c, 0x35, 0x28, 0x46, 0xf5, 0xfe, 0x13, 0xf7, 0xd3, 0xd5,
+                0x7e, 0x2c, 0x15, 0xac, 0x60, 0x90, 0x0c, 0xa3, 0x2c, 0x5b, 0x5d, 0xd9, 0x53, 0xc9,
+                0xa6, 0x81, 0x0a, 0xcc, 0x64, 0x39, 0x4f, 0xfd, 0x14, 0x98, 0x26, 0xd9, 0x98, 0x06,
+                0x29, 0x2a, 0xdd, 0xd1, 0x3f, 0xc3, 0xbb, 0x7d, 0xac, 0x70, 0x1c, 0x5b, 0x4a, 0x2d,
+                0x61, 0x5d, 0x15, 0x96, 0x01, 0x28, 0xed, 0x9f, 0x73, 0x6b, 0x98, 0x85, 0x4f, 0x6f,
+                0x07, 0x05, 0xb0, 0xf0, 0xda, 0xcb, 0xdc, 0x2c, 0x26, 0x2d, 0x27, 0x39, 0x75, 0x19,
+                0x14, 0x9b, 0x0e, 0x4c, 0xbe, 0x16, 0x77, 0xc5, 0x76, 0xc1, 0x39, 0x7a, 0xae, 0x5c,
+                0xe3, 0x49, 0x16, 0xe3, 0x51, 0x31, 0x04, 0x63, 0x2e, 0xc2, 0x19, 0x0d, 0xb8, 0xd2,
+                0x22, 0x89, 0xc3, 0x72, 0x3c, 0x8d, 0x01, 0x21, 0x3c, 0xad, 0x80, 0x3f, 0x4d, 0x75,
+                0x74, 0xc4, 0xdb, 0xb5, 0x37, 0x31, 0xb0, 0x1c, 0x8e, 0xc7, 0x5d, 0x08, 0x2e, 0xf7,
+                0xdc, 0x9d, 0x7f, 0x1b, 0x73, 0x15, 0x9f, 0x63, 0xdb, 0x56, 0xaa, 0x12, 0xa2, 0xca,
+                0x39, 0xea, 0xce, 0x6b, 0x28, 0xe4, 0xc3, 0x1d, 0x9d, 0x25, 0x67, 0x41, 0x45, 0x2e,
+                0x83, 0x87, 0xe1, 0x53, 0x6d, 0x03, 0x02, 0x6e, 0xe4, 0x84, 0x10, 0xd4, 0x3b, 0x21,
+                0x91, 0x88, 0xba, 0x14, 0xa8, 0xaf,
+            ]
+            .as_ref(),
+            [
+                0x91, 0x20, 0x91, 0x66, 0x1e, 0xed, 0x18, 0xa4, 0x03, 0x4b, 0xc7, 0xdb, 0x4b, 0xd6,
+                0x0f, 0xe2, 0xde, 0xeb, 0xf3, 0xff, 0x3b, 0x6b, 0x99, 0x8d, 0xae, 0x20, 0x94, 0xb6,
+                0x09, 0x86, 0x5c, 0x20, 0x19, 0xec, 0x67, 0x22, 0xbf, 0xdc, 0x87, 0xbd, 0xa5, 0x40,
+                0x91, 0x92, 0x2e, 0x11, 0xe3, 0x93, 0xf5, 0xfd, 0xce, 0xea, 0x3e, 0x09, 0x1f, 0x2e,
+                0xe6, 0xbc, 0x62, 0xdf, 0x94, 0x8e, 0x99, 0x09,
+            ],
+        );
+        do_sign_verify_case(
+            [
+                0x33, 0x19, 0x17, 0x82, 0xc1, 0x70, 0x4f, 0x60, 0xd0, 0x84, 0x8d, 0x75, 0x62, 0xa2,
+                0xfa, 0x19, 0xf9, 0x92, 0x4f, 0xea, 0x4e, 0x77, 0x33, 0xcd, 0x45, 0xf6, 0xc3, 0x2f,
+                0x21, 0x9a, 0x72, 0x91,
+            ],
+            [
+                0x77, 0x13, 0x43, 0x5a, 0x0e, 0x34, 0x6f, 0x67, 0x71, 0xae, 0x5a, 0xde, 0xa8, 0x7a,
+                0xe7, 0xa4, 0x52, 0xc6, 0x5d, 0x74, 0x8f, 0x48, 0x69, 0xd3, 0x1e, 0xd3, 0x67, 0x47,
+                0xc3, 0x28, 0xdd, 0xc4, 0xec, 0x0e, 0x48, 0x67, 0x93, 0xa5, 0x1c, 0x67, 0x66, 0xf7,
+                0x06, 0x48, 0x26, 0xd0, 0x74, 0x51, 0x4d, 0xd0, 0x57, 0x41, 0xf3, 0xbe, 0x27, 0x3e,
+                0xf2, 0x1f, 0x28, 0x0e, 0x49, 0x07, 0xed, 0x89, 0xbe, 0x30, 0x1a, 0x4e, 0xc8, 0x49,
+                0x6e, 0xb6, 0xab, 0x90, 0x00, 0x06, 0xe5, 0xa3, 0xc8, 0xe9, 0xc9, 0x93, 0x62, 0x1d,
+                0x6a, 0x3b, 0x0f, 0x6c, 0xba, 0xd0, 0xfd, 0xde, 0xf3, 0xb9, 0xc8, 0x2d,
+            ]
+            .as_ref(),
+            [
+                0x4b, 0x8d, 0x9b, 0x1e, 0xca, 0x54, 0x00, 0xea, 0xc6, 0xf5, 0xcc, 0x0c, 0x94, 0x39,
+                0x63, 0x00, 0x52, 0xf7, 0x34, 0xce, 0x45, 0x3e, 0x94, 0x26, 0xf3, 0x19, 0xdd, 0x96,
+                0x03, 0xb6, 0xae, 0xae, 0xb9, 0xd2, 0x3a, 0x5f, 0x93, 0xf0, 0x6a, 0x46, 0x00, 0x18,
+                0xf0, 0x69, 0xdf, 0x19, 0x44, 0x48, 0xf5, 0x60, 0x51, 0xab, 0x9e, 0x6b, 0xfa, 0xeb,
+                0x64, 0x10, 0x16, 0xf7, 0xa9, 0x0b, 0xe2, 0x0c,
+            ],
+        );
+    }
+
+    #[test]
+    fn nonce_from_bytes_wide() {
+        let seed = [0x42u8; 32];
+        let (secret, _) = keypair(&seed);
+        let message = b"hash to scalar";
+
+        let mut az = [0u8; 64];
+        let mut hasher = Sha512::new();
+        hasher.input(&seed);
+        hasher.result(&mut az);
+
+        let mut hash = [0u8; 64];
+        let mut hasher = Sha512::new();
+        hasher.input(&az[32..64]);
+        hasher.input(message);
+        hasher.result(&mut hash);
+        let nonce = scalar_from_bytes_wide(&hash);
+
+        // R is the nonce times the base point
+        let sig = signature(message, &secret);
+        assert_eq!(ge_scalarmult_base(&nonce).to_bytes(), sig[0..32]);
+    }
+
+    #[test]
+    fn try_functions() {
+        let (secret, public) = keypair(&[9u8; 32]);
+        assert_eq!(try_keypair(&[9u8; 32]), Ok((secret, public)));
+        assert_eq!(try_keypair(&[9u8; 31]), Err(InvalidLength));
+
+        let message = b"fallible";
+        let sig = signature(message, &secret);
+        assert_eq!(try_signature(message, &secret), Ok(sig));
+        assert_eq!(try_signature(message, &secret[..63]), Err(InvalidLength));
+
+        assert_eq!(try_verify(message, &public, &sig), Ok(true));
+        assert_eq!(try_verify(b"other", &public, &sig), Ok(false));
+        assert_eq!(try_verify(message, &public[..31], &sig), Err(InvalidLength));
+        assert_eq!(try_verify(message, &public, &sig[..63]), Err(InvalidLength));
+        assert_eq!(try_verify_strict(message, &public, &sig), Ok(true));
+        assert_eq!(
+            try_verify_strict(message, &public, &sig[..63]),
+            Err(InvalidLength)
+        );
+
+        let mut extended = [0u8; 64];
+        let mut hasher = Sha512::new();
+        hasher.input(&[9u8; 32]);
+        hasher.result(&mut extended);
+        clamp_x25519_scalar(&mut extended[0..32]);
+        assert_eq!(try_to_public(&extended), Ok(public));
+        assert_eq!(try_to_public(&extended[..32]), Ok(public));
+        assert_eq!(to_public(&extended[..32]), public);
+        assert_eq!(try_to_public(&extended[..31]), Err(InvalidLength));
+        let sig_ext = try_signature_extended(message, &extended).unwrap();
+        assert!(verify(message, &public, &sig_ext));
+        assert_eq!(
+            try_signature_extended(message, &extended[..63]),
+            Err(InvalidLength)
+        );
+
+        // the scalar of an extended secret may have its most significant bit
+        // set, adding multiples of l to it doesn't change the public key
+        let mut high = extended;
+        for _ in 0..8 {
+            let mut carry = 0u16;
+            for (b, l) in high[0..32].iter_mut().zip(L.iter()) {
+                let sum = *b as u16 + *l as u16 + carry;
+                *b = sum as u8;
+                carry = sum >> 8;
+            }
+            assert_eq!(carry, 0);
+        }
+        assert!(high[31] > 127);
+        assert_eq!(try_to_public(&high), try_to_public(&extended));
+
+        let prehash = [1u8; 64];
+        let sig_ph = try_signature_prehashed(&prehash, b"", &secret).unwrap();
+        assert_eq!(
+            try_verify_prehashed(&prehash, b"", &public, &sig_ph),
+            Ok(true)
+        );
+        assert_eq!(
+            try_signature_prehashed(&prehash, &[0u8; 256], &secret),
+            Err(InvalidLength)
+        );
+        assert_eq!(
+            try_verify_prehashed(&prehash, b"", &public, &sig_ph[..1]),
+            Err(InvalidLength)
+        );
+
+        let (_, other_public) = keypair(&[10u8; 32]);
+        assert_eq!(
+            try_exchange(&other_public, &secret),
+            Ok(exchange(&other_public, &secret))
+        );
+        assert_eq!(
+            try_exchange(&other_public, &secret[..32]),
+            Err(InvalidLength)
+        );
+    }
+
+    #[test]
+    fn x25519_secret() {
+        let seed = [3u8; 32];
+        let (secret, _) = keypair(&seed);
+        let (_, peer_public) = keypair(&[4u8; 32]);
+        let peer_x25519_public = curve25519_base(x25519_secret_from_seed(&[4u8; 32]).expose());
+
+        let x25519_secret = x25519_secret_from_seed(&seed);
+        assert_eq!(
+            curve25519(x25519_secret.expose(), &peer_x25519_public),
+            exchange(&peer_public, &secret)
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn attached_verifier_chunked() {
+        let (secret, public) = keypair(&[9u8; 32]);
+        let mut state = 0x12345678u32;
+        let message: std::vec::Vec<u8> = (0..100_000)
+            .map(|_| {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                (state >> 16) as u8
+            })
+            .collect();
+        let sig = signature(&message, &secret);
+        let signed = attach(&sig, &message);
+
+        let check = |public: &[u8; 32], signed: &[u8], chunk_size: usize| {
+            let (sig, message) = detach(signed).unwrap();
+            let mut verifier = AttachedVerifier::new(public, sig);
+            for chunk in message.chunks(chunk_size) {
+                verifier.update(chunk);
+            }
+            let result = verifier.finalize();
+            assert_eq!(result, verify_strict(message, public, sig));
+            assert_eq!(result, open_attached(public, signed).is_some());
+            result
+        };
+
+        for chunk_size in [1, 63, 64, 1000, 100_000].iter() {
+            assert!(check(&public, &signed, *chunk_size));
+        }
+
+        let mut tampered = signed.clone();
+        tampered[SIGNATURE_LENGTH + 50_000] ^= 1;
+        assert!(!check(&public, &tampered, 4096));
+
+        let mut bad_sig = signed.clone();
+        bad_sig[10] ^= 1;
+        assert!(!check(&public, &bad_sig, 4096));
+
+        let (_, other_public) = keypair(&[10u8; 32]);
+        assert!(!check(&other_public, &signed, 4096));
+        assert!(!check(&[0u8; 32], &signed, 4096));
+
+        // the identity public key with R the identity and S = 0 is accepted by
+        // verify for any message, but not by the strict verification
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        let mut forged = [0u8; SIGNATURE_LENGTH];
+        forged[0] = 1;
+        let forged_signed = attach(&forged, &message);
+        assert!(verify(&message, &identity, &forged));
+        assert!(!check(&identity, &forged_signed, 4096));
+    }
+
+    #[test]
+    fn precheck_rejects_malformed() {
+        let (secret, public) = keypair(&[11u8; 32]);
+        let message = b"precheck";
+        let sig = signature(message, &secret);
+        assert_eq!(precheck(&public, &sig), Ok(()));
+
+        assert_eq!(
+            precheck(&public[..31], &sig),
+            Err(VerifyFailure::PublicKeyLength)
+        );
+        assert_eq!(
+            precheck(&public, &sig[..63]),
+            Err(VerifyFailure::SignatureLength)
+        );
+        assert_eq!(precheck(&[0u8; 32], &sig), Err(VerifyFailure::ZeroKey));
+        assert!(!verify(message, &[0u8; 32], &sig));
+
+        // S + L is the same scalar, but not reduced
+        let mut s = [0u8; 32];
+        s.copy_from_slice(&sig[32..]);
+        let mut carry = 0u16;
+        for (b, l) in s.iter_mut().zip(L.iter()) {
+            let sum = *b as u16 + *l as u16 + carry;
+            *b = sum as u8;
+            carry = sum >> 8;
+        }
+        let mut unreduced = sig;
+        unreduced[32..].copy_from_slice(&s);
+        assert_eq!(
+            precheck(&public, &unreduced),
+            Err(VerifyFailure::NonCanonicalS)
+        );
+        assert!(!verify(message, &public, &unreduced));
+
+        // a bad R passes the precheck, and is only caught by the verification
+        let mut bad_r = sig;
+        bad_r[0] ^= 1;
+        assert_eq!(precheck(&public, &bad_r), Ok(()));
+        assert!(!verify(message, &public, &bad_r));
+    }
+
+    #[test]
+    fn verify_self() {
+        let (secret, public) = keypair(&[8u8; 32]);
+        let message = b"signed by a service";
+        let mut sig = signature(message, &secret);
+        assert!(verify_with_secret(message, &secret, &sig));
+        assert_eq!(
+            verify_with_secret(message, &secret, &sig),
+            verify(message, &public, &sig)
+        );
+        assert!(!verify_with_secret(b"other message", &secret, &sig));
+
+        sig[5] ^= 0x10;
+        assert!(!verify_with_secret(message, &secret, &sig));
+        assert_eq!(
+            verify_with_secret(message, &secret, &sig),
+            verify(message, &public, &sig)
+        );
+    }
+
+    #[cfg(feature = "hkdf")]
+    #[test]
+    fn exchange_with_domain() {
+        use super::exchange_domain;
+
+        let (secret, public) = keypair(&[5u8; 32]);
+        let (peer_secret, peer_public) = keypair(&[6u8; 32]);
+
+        let shared = exchange_domain(&peer_public, &secret, b"protocol A").unwrap();
+        assert_eq!(
+            Ok(shared),
+            exchange_domain(&public, &peer_secret, b"protocol A")
+        );
+        assert_ne!(
+            Ok(shared),
+            exchange_domain(&peer_public, &secret, b"protocol B")
+        );
+        assert_ne!(Ok(shared), exchange_domain(&peer_public, &secret, b""));
+        assert_ne!(shared, exchange(&peer_public, &secret));
+
+        // the shared secret also depends on the public keys of both parties
+        let mut other_secret = secret;
+        other_secret[32] ^= 1;
+        assert_ne!(
+            Ok(shared),
+            exchange_domain(&peer_public, &other_secret, b"protocol A")
+        );
+
+        // the identity gives an all zero shared secret for any private key
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        assert_eq!(exchange(&identity, &secret), [0u8; 32]);
+        assert_eq!(
+            exchange_domain(&identity, &secret, b"protocol A"),
+            Err(KeyError::SmallOrder)
+        );
+    }
+
+    #[test]
+    fn canonicalize() {
+        let (secret, public) = keypair(&[7u8; 32]);
+        let message = b"relayed message";
+        let sig = signature(message, &secret);
+
+        let mut canonical = sig;
+        assert!(!canonicalize_signature(&mut canonical));
+        assert_eq!(canonical, sig);
+
+        // S + l, which still fits in 256 bits
+        let mut malleated = sig;
+        let mut carry = 0u16;
+        for i in 0..32 {
+            let v = sig[32 + i] as u16 + L[i] as u16 + carry;
+            malleated[32 + i] = v as u8;
+            carry = v >> 8;
+        }
+        assert_eq!(carry, 0);
+        assert!(!verify(message, &public, &malleated));
+
+        assert!(canonicalize_signature(&mut malleated));
+        assert_eq!(malleated, sig);
+        assert!(verify(message, &public, &malleated));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn attached_signature() {
+        let (secret, public) = keypair(&[9u8; 32]);
+        let message = b"attached message";
+        let sig = signature(message, &secret);
+
+        let signed = attach(&sig, message);
+        assert_eq!(signed.len(), 64 + message.len());
+        let (detached_sig, detached_message) = detach(&signed).unwrap();
+        assert_eq!(detached_sig, &sig);
+        assert_eq!(detached_message, &message[..]);
+        assert_eq!(open_attached(&public, &signed), Some(&message[..]));
+
+        let empty = attach(&signature(b"", &secret), b"");
+        assert_eq!(detach(&empty).unwrap().1, &b""[..]);
+        assert_eq!(open_attached(&public, &empty), Some(&b""[..]));
+
+        let mut tampered = signed.clone();
+        tampered[70] ^= 1;
+        assert_eq!(open_attached(&public, &tampered), None);
+        let mut tampered = signed.clone();
+        tampered[0] ^= 1;
+        assert_eq!(open_attached(&public, &tampered), None);
+        assert_eq!(detach(&signed[..63]), None);
+        assert_eq!(open_attached(&public, &signed[..63]), None);
+    }
+
+    #[test]
+    fn seed_and_public() {
+        let seed = [5u8; 32];
+        let (secret, public) = keypair(&seed);
+        assert_eq!(
+            secret_key_from_seed_and_public(&seed, &public),
+            Ok(Secret::new(secret))
+        );
+
+        let mut tampered = public;
+        tampered[31] ^= 0x80;
+        assert_eq!(
+            secret_key_from_seed_and_public(&seed, &tampered),
+            Err(KeyError::PublicKeyMismatch)
+        );
+        let (_, other_public) = keypair(&[6u8; 32]);
+        assert_eq!(
+            secret_key_from_seed_and_public(&seed, &other_public),
+            Err(KeyError::PublicKeyMismatch)
+        );
+    }
+
+    #[test]
+    fn hram_and_nonce() {
+        let (secret, public) = keypair(&[9u8; 32]);
+        let mut az = [0u8; 64];
+        let mut hasher = Sha512::new();
+        hasher.input(&secret[0..32]);
+        hasher.result(&mut az);
+        clamp_ed25519_scalar(&mut az[0..32]);
+        let mut prefix = [0u8; 32];
+        prefix.copy_from_slice(&az[32..64]);
+
+        for message in [&b""[..], &b"air-gapped"[..], &[0xaau8; 200][..]].iter() {
+            let sig = signature(message, &secret);
+
+            // the device holding the secret computes the nonce and R
+            let nonce = nonce_from_prefix(&prefix, message);
+            let r = ge_scalarmult_base(&nonce).to_bytes();
+            assert_eq!(&r[..], &sig[0..32]);
+
+            // the coordinator computes the challenge from public values
+            let k = hram(&r, &public, message);
+            let mut s = [0u8; 32];
+            sc_muladd(&mut s, &k, &az[0..32], &nonce);
+            assert_eq!(&s[..], &sig[32..64]);
+        }
+    }
+
+    #[test]
+    fn prepared_verifying_key() {
+        let messages: [&[u8]; 3] = [b"", b"abc", &[0x55u8; 300]];
+        for seed in 0..4u8 {
+            let (secret, public) = keypair(&[seed; 32]);
+            let key = PreparedVerifyingKey::new(&public).unwrap();
+            assert_eq!(key.public_key(), &public);
+            for message in messages.iter() {
+                let sig = signature(message, &secret);
+                assert!(key.verify(message, &sig));
+
+                let mut tampered = sig;
+                tampered[seed as usize] ^= 1;
+                assert_eq!(
+                    key.verify(message, &tampered),
+                    verify(message, &public, &tampered)
+                );
+                assert!(!key.verify(b"other message", &sig));
+
+                // S + l is not canonical
+                let mut malleated = sig;
+                let mut carry = 0u16;
+                for i in 0..32 {
+                    let v = sig[32 + i] as u16 + L[i] as u16 + carry;
+                    malleated[32 + i] = v as u8;
+                    carry = v >> 8;
+                }
+                assert!(!key.verify(message, &malleated));
+            }
+        }
+
+        assert!(matches!(
+            PreparedVerifyingKey::new(&[0u8; 32]),
+            Err(KeyError::SmallOrder)
+        ));
+        let not_on_curve = (0..=255u8)
+            .map(|b| [b; 32])
+            .find(|k| check_public_key(k) == Err(KeyError::NotOnCurve))
+            .unwrap();
+        assert!(matches!(
+            PreparedVerifyingKey::new(&not_on_curve),
+            Err(KeyError::NotOnCurve)
+        ));
+    }
+
+    #[test]
+    fn fingerprint_stable() {
+        let (_, public) = keypair(&[0u8; 32]);
+        assert_eq!(
+            fingerprint(&public),
+            [0x13, 0x9e, 0x39, 0x40, 0xe6, 0x4b, 0x54, 0x91]
+        );
+        let (_, other) = keypair(&[1u8; 32]);
+        assert_ne!(fingerprint(&public), fingerprint(&other));
+    }
+
+    #[test]
+    fn public_key_checks() {
+        let (_, public) = keypair(&[0u8; 32]);
+        assert_eq!(check_public_key(&public), Ok(()));
+
+        // y = 2 is not the coordinate of a point of the curve
+        let mut not_on_curve = [0u8; 32];
+        not_on_curve[0] = 2;
+        assert_eq!(check_public_key(&not_on_curve), Err(KeyError::NotOnCurve));
+
+        // identity, encoded with y = 1 + p
+        let mut non_canonical = [0xffu8; 32];
+        non_canonical[0] = 0xee;
+        non_canonical[31] = 0x7f;
+        assert_eq!(
+            check_public_key(&non_canonical),
+            Err(KeyError::NonCanonical)
+        );
+
+        // identity and point of order 8
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        assert_eq!(check_public_key(&identity), Err(KeyError::SmallOrder));
+        let order8 = [
+            0xc7, 0x17, 0x6a, 0x70, 0x3d, 0x4d, 0xd8, 0x4f, 0xba, 0x3c, 0x0b, 0x76, 0x0d, 0x10,
+            0x67, 0x0f, 0x2a, 0x20, 0x53, 0xfa, 0x2c, 0x39, 0xcc, 0xc6, 0x4e, 0xc7, 0xfd, 0x77,
+            0x92, 0xac, 0x03, 0x7a,
+        ];
+        assert_eq!(check_public_key(&order8), Err(KeyError::SmallOrder));
+    }
+
+    #[test]
+    fn secret_key_equality() {
+        let (secret, _) = keypair(&[0u8; 32]);
+        let (other, _) = keypair(&[1u8; 32]);
+        assert!(secret_key_eq(&secret, &secret.clone()));
+        assert!(!secret_key_eq(&secret, &other));
+        for i in [0, 31, 32, 63] {
+            let mut altered = secret;
+            altered[i] ^= 1;
+            assert!(!secret_key_eq(&secret, &altered));
+        }
+    }
+}
+
+#[cfg(all(test, feature = "with-bench"))]
+mod bench {
+    use super::{keypair, signature, verify, PreparedVerifyingKey};
+    use test::Bencher;
+
+    #[bench]
+    pub fn keypair_generation(bh: &mut Bencher) {
+        bh.iter(|| keypair(&[3u8; 32]));
+    }
+
+    #[bench]
+    pub fn sign_small(bh: &mut Bencher) {
+        let (secret, _) = keypair(&[3u8; 32]);
+        let message = [1u8; 64];
+        bh.iter(|| signature(&message, &secret));
+    }
+
+    #[bench]
+    pub fn verify_small(bh: &mut Bencher) {
+        let (secret, public) = keypair(&[3u8; 32]);
+        let message = [1u8; 64];
+        let sig = signature(&message, &secret);
+        bh.iter(|| verify(&message, &public, &sig));
+    }
+
+    #[bench]
+    pub fn verify_prepared_small(bh: &mut Bencher) {
+        let (secret, public) = keypair(&[3u8; 32]);
+        let message = [1u8; 64];
+        let sig = signature(&message, &secret);
+        let key = PreparedVerifyingKey::new(&public).unwrap();
+        bh.iter(|| key.verify(&message, &sig));
+    }
+}
@@ -0,0 +1,515 @@
+//! Verification test vectors in the format of the Project Wycheproof
+//! `eddsa_test.json` file, covering the acceptance rules of [`verify`] and
+//! [`verify_strict`].
+//!
+//! [`WYCHEPROOF_VECTORS`] are the valid signatures of the first test group of
+//! `eddsa_test.json`, with their `tcId`. The file itself couldn't be vendored,
+//! so the invalid cases of [`TEST_VECTORS`] are not taken from it: they were
+//! written for this crate after the Wycheproof test comments (canonical S,
+//! modified signatures, special values of R and S, and small order or non
+//! canonical public keys and R), from the public key and the signatures of
+//! RFC8032 test 1. libsodium 1.0.18's `crypto_sign_verify_detached` gives the
+//! `valid_strict` result for all of them. Their `id` is local to this file.
+//!
+//! Signatures of other sizes than 64 bytes are not included, as `verify`
+//! requires the size to be correct.
+//!
+//! `valid` is the expected result of `verify`, and `valid_strict` of `verify_strict`.
+
+use super::{verify, verify_strict};
+use alloc::vec::Vec;
+
+struct TestVector {
+    id: u32,
+    comment: &'static str,
+    public_key: &'static str,
+    message: &'static str,
+    signature: &'static str,
+    valid: bool,
+    valid_strict: bool,
+}
+
+fn unhex(s: &str) -> Vec<u8> {
+    (0..s.len() / 2)
+        .map(|i| u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn verify_test_vectors() {
+    for tv in WYCHEPROOF_VECTORS.iter().chain(TEST_VECTORS.iter()) {
+        let public_key = unhex(tv.public_key);
+        let message = unhex(tv.message);
+        let signature = unhex(tv.signature);
+        assert_eq!(
+            verify(&message, &public_key, &signature),
+            tv.valid,
+            "verify: {} {}",
+            tv.id,
+            tv.comment
+        );
+        assert_eq!(
+            verify_strict(&message, &public_key, &signature),
+            tv.valid_strict,
+            "verify_strict: {} {}",
+            tv.id,
+            tv.comment
+        );
+    }
+}
+
+const WYCHEPROOF_VECTORS: &[TestVector] = &[
+    TestVector {
+        id: 1,
+        comment: "",
+        public_key: "7d4d0e7f6153a69b6242b522abbee685fda4420f8834b108c3bdae369ef549fa",
+        message: "",
+        signature: "d4fbdb52bfa726b44d1786a8c0d171c3e62ca83c9e5bbe63de0bb2483f8fd6cc1429ab72cafc41ab56af02ff8fcc43b99bfe4c7ae940f60f38ebaa9d311c4007",
+        valid: true,
+        valid_strict: true,
+    },
+    TestVector {
+        id: 2,
+        comment: "",
+        public_key: "7d4d0e7f6153a69b6242b522abbee685fda4420f8834b108c3bdae369ef549fa",
+        message: "78",
+        signature: "d80737358ede548acb173ef7e0399f83392fe8125b2ce877de7975d8b726ef5b1e76632280ee38afad12125ea44b961bf92f1178c9fa819d020869975bcbe109",
+        valid: true,
+        valid_strict: true,
+    },
+    TestVector {
+        id: 3,
+        comment: "",
+        public_key: "7d4d0e7f6153a69b6242b522abbee685fda4420f8834b108c3bdae369ef549fa",
+        message: "54657374",
+        signature: "7c38e026f29e14aabd059a0f2db8b0cd783040609a8be684db12f82a27774ab07a9155711ecfaf7f99f277bad0c6ae7e39d4eef676573336a5c51eb6f946b30d",
+        valid: true,
+        valid_strict: true,
+    },
+    TestVector {
+        id: 4,
+        comment: "",
+        public_key: "7d4d0e7f6153a69b6242b522abbee685fda4420f8834b108c3bdae369ef549fa",
+        message: "48656c6c6f",
+        signature: "1c1ad976cbaae3b31dee07971cf92c928ce2091a85f5899f5e11ecec90fc9f8e93df18c5037ec9b29c07195ad284e63d548cd0a6fe358cc775bd6c1608d2c905",
+        valid: true,
+        valid_strict: true,
+    },
+    TestVector {
+        id: 5,
+        comment: "",
+        public_key: "7d4d0e7f6153a69b6242b522abbee685fda4420f8834b108c3bdae369ef549fa",
+        message: "313233343030",
+        signature: "657c1492402ab5ce03e2c3a7f0384d051b9cf3570f1207fc78c1bcc98c281c2bf0cf5b3a289976458a1be6277a5055545253b45b07dcc1abd96c8b989c00f301",
+        valid: true,
+        valid_strict: true,
+    },
+    TestVector {
+        id: 6,
+        comment: "",
+        public_key: "7d4d0e7f6153a69b6242b522abbee685fda4420f8834b108c3bdae369ef549fa",
+        message: "000000000000000000000000",
+        signature: "d46543bfb892f84ec124dcdfc847034c19363bf3fc2fa89b1267833a14856e52e60736918783f950b6f1dd8d40dc343247cd43ce054c2d68ef974f7ed0f3c60f",
+        valid: true,
+        valid_strict: true,
+    },
+];
+
+const TEST_VECTORS: &[TestVector] = &[
+    TestVector {
+        id: 1,
+        comment: "",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "",
+        signature: "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        valid: true,
+        valid_strict: true,
+    },
+    TestVector {
+        id: 2,
+        comment: "",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "78",
+        signature: "18d0b7f5338df220d1ec7c8e820a9f59afcb5dbd480d04842e5d3a8f139efd9b5ab4fe6ff283ab7667f4736530bf8ea25f189083283280c5acc05ac4a5fdc609",
+        valid: true,
+        valid_strict: true,
+    },
+    TestVector {
+        id: 3,
+        comment: "",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0358333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab66ac1112ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32490b",
+        valid: true,
+        valid_strict: true,
+    },
+    TestVector {
+        id: 4,
+        comment: "",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "48656c6c6f",
+        signature: "52dc29f7ec08cf13d82af0738b2d12ff7da1b967866e9cf9bcd22d7972f1be2cfad44b3018e30969edd07a0fb902a95685707003011c50de3b1cec146a0d4207",
+        valid: true,
+        valid_strict: true,
+    },
+    TestVector {
+        id: 5,
+        comment: "",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f404142434445464748494a4b4c4d4e4f505152535455565758595a5b5c5d5e5f",
+        signature: "70d9cce12e9f7d8fe2d9a16a3e68496f2fdabb33c0a5505bef48daff24fa057ed4582550c4aa7613c22d68b19bd955eb947f8f6d64825ec6d8ec47de82640a0a",
+        valid: true,
+        valid_strict: true,
+    },
+    TestVector {
+        id: 6,
+        comment: "wrong message",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657375",
+        signature: "0358333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab66ac1112ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32490b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 7,
+        comment: "modified bit 0 in R",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0258333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab66ac1112ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32490b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 8,
+        comment: "modified bit 1 in R",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0158333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab66ac1112ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32490b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 9,
+        comment: "modified bit 7 in R",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "8358333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab66ac1112ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32490b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 10,
+        comment: "modified bit 8 in R",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0359333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab66ac1112ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32490b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 11,
+        comment: "modified bit 16 in R",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0358323856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab66ac1112ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32490b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 12,
+        comment: "modified bit 254 in R",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0358333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eaf66ac1112ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32490b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 13,
+        comment: "modified bit 255 in R",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0358333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6ea366ac1112ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32490b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 14,
+        comment: "modified bit 0 in S",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0358333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab66bc1112ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32490b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 15,
+        comment: "modified bit 1 in S",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0358333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab668c1112ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32490b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 16,
+        comment: "modified bit 7 in S",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0358333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab6eac1112ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32490b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 17,
+        comment: "modified bit 8 in S",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0358333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab66ac0112ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32490b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 18,
+        comment: "modified bit 16 in S",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0358333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab66ac1102ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32490b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 19,
+        comment: "modified bit 252 in S",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0358333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab66ac1112ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32491b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 20,
+        comment: "modified bit 253 in S",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0358333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab66ac1112ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32492b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 21,
+        comment: "checking malleability: S + L",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0358333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab657950787ca3d6cf127a4aae5ff509ba3fbd15baf8f6d8bb12a4142748c32491b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 22,
+        comment: "checking malleability: S + 2L",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0358333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab64469fde3e4a07e49fe40a288de4a7ab8fbd15baf8f6d8bb12a4142748c32492b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 23,
+        comment: "S replaced by L - S",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0358333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab68312e4326a88b8be84954460bda22286042ea4507092744ed5bebd8b73cdb604",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 24,
+        comment: "R negated",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0358333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6ea366ac1112ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32490b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 25,
+        comment: "R and S swapped",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "6ac1112ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32490b0358333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab6",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 26,
+        comment: "special values for r and s: r = 0, s = 0",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 27,
+        comment: "special values for r and s: r = 0, s = 1",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "00000000000000000000000000000000000000000000000000000000000000000100000000000000000000000000000000000000000000000000000000000000",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 28,
+        comment: "special values for r and s: r = 0, s = L",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0000000000000000000000000000000000000000000000000000000000000000edd3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 29,
+        comment: "special values for r and s: r = 1, s = 0",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "01000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 30,
+        comment: "special values for r and s: r = 1, s = 1",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "01000000000000000000000000000000000000000000000000000000000000000100000000000000000000000000000000000000000000000000000000000000",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 31,
+        comment: "special values for r and s: r = 1, s = L",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0100000000000000000000000000000000000000000000000000000000000000edd3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 32,
+        comment: "special values for r and s: r = L, s = 0",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "edd3f55c1a631258d69cf7a2def9de14000000000000000000000000000000100000000000000000000000000000000000000000000000000000000000000000",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 33,
+        comment: "special values for r and s: r = L, s = 1",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "edd3f55c1a631258d69cf7a2def9de14000000000000000000000000000000100100000000000000000000000000000000000000000000000000000000000000",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 34,
+        comment: "special values for r and s: r = L, s = L",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "edd3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010edd3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 35,
+        comment: "all zero public key",
+        public_key: "0000000000000000000000000000000000000000000000000000000000000000",
+        message: "54657374",
+        signature: "0358333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab66ac1112ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32490b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 36,
+        comment: "public key with sign bit flipped",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707519a",
+        message: "54657374",
+        signature: "0358333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab66ac1112ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32490b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 37,
+        comment: "public key of other key",
+        public_key: "3b6a27bcceb6a42d62a3a8d02a6f0d73653215771de243a63ac048a18b59da29",
+        message: "54657374",
+        signature: "0358333856a92f93b519f4dcef9876f94215d8dd719f42220291ee2acef6eab66ac1112ab0da59995107b3422157bc8efbd15baf8f6d8bb12a4142748c32490b",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 38,
+        comment: "small order public key: identity",
+        public_key: "0100000000000000000000000000000000000000000000000000000000000000",
+        message: "54657374",
+        signature: "01000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        valid: true,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 39,
+        comment: "small order public key: identity, other message",
+        public_key: "0100000000000000000000000000000000000000000000000000000000000000",
+        message: "616e79206d657373616765",
+        signature: "01000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        valid: true,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 40,
+        comment: "non canonical public key: p + 1 encoding of identity",
+        public_key: "eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        message: "54657374",
+        signature: "01000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        valid: true,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 41,
+        comment: "small order public key: order 8",
+        public_key: "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac037a",
+        message: "736d616c6c206f726465722034",
+        signature: "01000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+        valid: true,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 42,
+        comment: "mixed order public key",
+        public_key: "9158312a9a8d6e3b34c891d6d61444f8b8211c5117ebad15bdb0bd68b07e0245",
+        message: "6d69786564206f726465722033",
+        signature: "9ad28f30c50f5948f4c1f1dd99ef10ae52e1c81f4c8883581e62b571636361246c94db5e46549d6f52c9e98f222d144ad42bd468c2f7cb61a469c3cd1b89880c",
+        valid: true,
+        valid_strict: true,
+    },
+    TestVector {
+        id: 43,
+        comment: "non canonical R",
+        public_key: "0100000000000000000000000000000000000000000000000000000000000000",
+        message: "54657374",
+        signature: "eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f0000000000000000000000000000000000000000000000000000000000000000",
+        valid: false,
+        valid_strict: false,
+    },
+    TestVector {
+        id: 44,
+        comment: "small order R: identity",
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        message: "54657374",
+        signature: "0100000000000000000000000000000000000000000000000000000000000000cf6fc35cc9dbd6bd1aaf7755067ff04ac70cb9c8b11420a6fef12bceed0d0d0e",
+        valid: true,
+        valid_strict: false,
+    },
+];
@@ -0,0 +1,255 @@
+//! ED448 Signature Scheme
+//!
+//! Ed448 as specified in [RFC8032](https://tools.ietf.org/html/rfc8032),
+//! using SHAKE256 as hash function and the dom4 prefix for domain separation.
+//!
+//! # Examples
+//!
+//! Creating a signature, and verifying the signature:
+//!
+//! ```
+//! use cryptoxide::ed448;
+//!
+//! let message = "messages".as_bytes();
+//! let seed = [0u8;57]; // seed only for example !
+//! let (secret, public) = ed448::keypair(&seed[..]);
+//! let signature = ed448::signature(message, &secret[..]);
+//! let verified = ed448::verify(message, &public[..], &signature[..]);
+//! assert!(verified);
+//! ```
+//!
+
+use crate::curve448::{sc_is_canonical, sc_muladd, sc_reduce, Point};
+use crate::sha3::Shake256;
+use crate::util::fixed_time_eq;
+
+pub const SEED_LENGTH: usize = 57;
+pub const PRIVATE_KEY_LENGTH: usize = 114;
+pub const PUBLIC_KEY_LENGTH: usize = 57;
+pub const SIGNATURE_LENGTH: usize = 114;
+
+/// Maximum length of a context string
+pub const CONTEXT_MAX_LENGTH: usize = 255;
+
+// SHAKE256(dom4(0, context) || parts..) reduced modulo l
+fn hash_reduced(context: &[u8], parts: &[&[u8]]) -> [u8; 57] {
+    let mut hasher = Shake256::new();
+    hasher.input(b"SigEd448");
+    hasher.input(&[0, context.len() as u8]);
+    hasher.input(context);
+    for part in parts.iter() {
+        hasher.input(part);
+    }
+    let mut hash = [0u8; 114];
+    hasher.squeeze(&mut hash);
+    sc_reduce(&hash)
+}
+
+// expand the seed into the clamped secret scalar and the nonce prefix
+fn expand_seed(seed: &[u8]) -> [u8; 114] {
+    let mut hash_output = [0u8; 114];
+    let mut hasher = Shake256::new();
+    hasher.input(seed);
+    hasher.squeeze(&mut hash_output);
+    hash_output[0] &= 252;
+    hash_output[55] |= 128;
+    hash_output[56] = 0;
+    hash_output
+}
+
+/// Create a keypair of secret key and public key
+pub fn keypair(seed: &[u8]) -> ([u8; PRIVATE_KEY_LENGTH], [u8; PUBLIC_KEY_LENGTH]) {
+    assert!(
+        seed.len() == SEED_LENGTH,
+        "Seed should be {} bytes long!",
+        SEED_LENGTH
+    );
+
+    let az = expand_seed(seed);
+    let public_key = Point::base().scalarmult(&az[0..57]).to_bytes();
+
+    let mut secret = [0u8; PRIVATE_KEY_LENGTH];
+    secret[0..57].copy_from_slice(seed);
+    secret[57..114].copy_from_slice(&public_key);
+    (secret, public_key)
+}
+
+/// Generate a signature for the given message using a normal ED448 secret key
+pub fn signature(message: &[u8], secret_key: &[u8]) -> [u8; SIGNATURE_LENGTH] {
+    signature_with_context(message, &[], secret_key)
+}
+
+/// Generate a signature for the given message and context using a normal ED448 secret key
+///
+/// The context, at most 255 bytes, binds the signature to a specific usage,
+/// and the same context need to be given to verify the signature.
+pub fn signature_with_context(
+    message: &[u8],
+    context: &[u8],
+    secret_key: &[u8],
+) -> [u8; SIGNATURE_LENGTH] {
+    assert!(
+        secret_key.len() == PRIVATE_KEY_LENGTH,
+        "Private key should be {} bytes long!",
+        PRIVATE_KEY_LENGTH
+    );
+    assert!(
+        context.len() <= CONTEXT_MAX_LENGTH,
+        "Context should be at most {} bytes long!",
+        CONTEXT_MAX_LENGTH
+    );
+
+    let seed = &secret_key[0..57];
+    let public_key = &secret_key[57..114];
+    let az = expand_seed(seed);
+
+    let nonce = hash_reduced(context, &[&az[57..114], message]);
+
+    let mut signature = [0u8; SIGNATURE_LENGTH];
+    let r = Point::base().scalarmult(&nonce);
+    signature[0..57].copy_from_slice(&r.to_bytes());
+
+    let hram = hash_reduced(context, &[&signature[0..57], public_key, message]);
+    let s = sc_muladd(&hram, &az[0..57], &nonce);
+    signature[57..114].copy_from_slice(&s);
+
+    signature
+}
+
+/// Verify that a signature is valid for a given message for an associated public key
+pub fn verify(message: &[u8], public_key: &[u8], signature: &[u8]) -> bool {
+    verify_with_context(message, &[], public_key, signature)
+}
+
+/// Verify that a signature is valid for a given message and context for an associated public key
+pub fn verify_with_context(
+    message: &[u8],
+    context: &[u8],
+    public_key: &[u8],
+    signature: &[u8],
+) -> bool {
+    assert!(
+        public_key.len() == PUBLIC_KEY_LENGTH,
+        "Public key should be {} bytes long!",
+        PUBLIC_KEY_LENGTH
+    );
+    assert!(
+        signature.len() == SIGNATURE_LENGTH,
+        "signature should be {} bytes long!",
+        SIGNATURE_LENGTH
+    );
+    if context.len() > CONTEXT_MAX_LENGTH {
+        return false;
+    }
+
+    if !sc_is_canonical(&signature[57..114]) {
+        return false;
+    }
+
+    let a = match Point::from_bytes_vartime(public_key) {
+        Some(g) => g,
+        None => return false,
+    };
+    let r = match Point::from_bytes_vartime(&signature[0..57]) {
+        Some(g) => g,
+        None => return false,
+    };
+
+    let k = hash_reduced(context, &[&signature[0..57], public_key, message]);
+
+    // check [4][S]B = [4]R + [4][k]A
+    let sb = Point::base().scalarmult(&signature[57..114]).dbl().dbl();
+    let rka = (&r + &a.scalarmult(&k)).dbl().dbl();
+
+    fixed_time_eq(&sb.to_bytes(), &rka.to_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{keypair, signature, signature_with_context, verify, verify_with_context};
+    use alloc::vec::Vec;
+
+    fn unhex(s: &str) -> Vec<u8> {
+        (0..s.len() / 2)
+            .map(|i| u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn do_sign_verify_case(
+        seed: &str,
+        public: &str,
+        message: &str,
+        context: &str,
+        expected_signature: &str,
+    ) {
+        let message = unhex(message);
+        let context = unhex(context);
+        let (secret_key, public_key) = keypair(&unhex(seed));
+        assert_eq!(public_key.to_vec(), unhex(public));
+
+        let mut actual_signature = signature_with_context(&message, &context, &secret_key);
+        assert_eq!(actual_signature.to_vec(), unhex(expected_signature));
+        assert!(verify_with_context(
+            &message,
+            &context,
+            &public_key,
+            &actual_signature
+        ));
+
+        for &(index, flip) in [(0, 1), (56, 0x80), (80, 0xff)].iter() {
+            actual_signature[index] ^= flip;
+            assert!(!verify_with_context(
+                &message,
+                &context,
+                &public_key,
+                &actual_signature
+            ));
+            actual_signature[index] ^= flip;
+        }
+
+        let mut public_key_corrupt = public_key;
+        public_key_corrupt[0] ^= 1;
+        assert!(!verify_with_context(
+            &message,
+            &context,
+            &public_key_corrupt,
+            &actual_signature
+        ));
+        assert!(!verify_with_context(
+            &message,
+            b"other context",
+            &public_key,
+            &actual_signature
+        ));
+    }
+
+    // test vectors from RFC8032 section 7.4
+    #[test]
+    fn rfc8032_cases() {
+        do_sign_verify_case(
+            "6c82a562cb808d10d632be89c8513ebf6c929f34ddfa8c9f63c9960ef6e348a3528c8a3fcc2f044e39a3fc5b94492f8f032e7549a20098f95b",
+            "5fd7449b59b461fd2ce787ec616ad46a1da1342485a70e1f8a0ea75d80e96778edf124769b46c7061bd6783df1e50f6cd1fa1abeafe8256180",
+            "",
+            "",
+            "533a37f6bbe457251f023c0d88f976ae2dfb504a843e34d2074fd823d41a591f2b233f034f628281f2fd7a22ddd47d7828c59bd0a21bfd3980ff0d2028d4b18a9df63e006c5d1c2d345b925d8dc00b4104852db99ac5c7cdda8530a113a0f4dbb61149f05a7363268c71d95808ff2e652600",
+        );
+        do_sign_verify_case(
+            "c4eab05d357007c632f3dbb48489924d552b08fe0c353a0d4a1f00acda2c463afbea67c5e8d2877c5e3bc397a659949ef8021e954e0a12274e",
+            "43ba28f430cdff456ae531545f7ecd0ac834a55d9358c0372bfa0c6c6798c0866aea01eb00742802b8438ea4cb82169c235160627b4c3a9480",
+            "03",
+            "666f6f",
+            "d4f8f6131770dd46f40867d6fd5d5055de43541f8c5e35abbcd001b32a89f7d2151f7647f11d8ca2ae279fb842d607217fce6e042f6815ea000c85741de5c8da1144a6a1aba7f96de42505d7a7298524fda538fccbbb754f578c1cad10d54d0d5428407e85dcbc98a49155c13764e66c3c00",
+        );
+    }
+
+    #[test]
+    fn sign_verify_roundtrip() {
+        let (secret_key, public_key) = keypair(&[0x42; 57]);
+        for len in [0usize, 1, 56, 57, 200].iter() {
+            let message: Vec<u8> = (0..*len).map(|i| i as u8).collect();
+            let sig = signature(&message, &secret_key);
+            assert!(verify(&message, &public_key, &sig));
+            assert!(!verify(b"another message", &public_key, &sig));
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! Encrypt-then-MAC composition of a stream cipher and a MAC
+//!
+//! The plaintext is encrypted first, and the MAC is computed over the
+//! additional authenticated data and the ciphertext, so that the tag is
+//! checked before anything is decrypted. The MAC input is:
+//!
+//! `aad || ciphertext || le64(len(aad)) || le64(len(ciphertext))`
+//!
+//! The cipher and the MAC need to use independent keys, and as with any
+//! stream cipher, a nonce must never be reused with the same key.
+//!
+//! [`seal`] and [`open`] work in place, with the tag in a separate buffer.
+//! [`seal_detached`] and [`open_detached`] (requires the `alloc` feature)
+//! return the ciphertext and the tag separately instead, for wire formats
+//! storing the tag apart from the ciphertext.
+//!
+//! # Examples
+//!
+//! ```
+//! use cryptoxide::{chacha20::ChaCha20, etm, hmac::Hmac, sha2::Sha256};
+//!
+//! let cipher_key = [1u8; 32];
+//! let mac_key = [2u8; 32];
+//! let nonce = [0u8; 12];
+//! let mut data = *b"hello world!";
+//! let mut tag = [0u8; 32];
+//!
+//! let mut cipher = ChaCha20::new(&cipher_key, &nonce);
+//! let mut mac = Hmac::new(Sha256::new(), &mac_key);
+//! etm::seal(&mut cipher, &mut mac, b"header", &mut data, &mut tag);
+//!
+//! let mut cipher = ChaCha20::new(&cipher_key, &nonce);
+//! let mut mac = Hmac::new(Sha256::new(), &mac_key);
+//! assert!(etm::open(&mut cipher, &mut mac, b"header", &mut data, &tag));
+//! assert_eq!(&data, b"hello world!");
+//! ```
+
+#[cfg(feature = "chacha")]
+use crate::chacha20::ChaCha20;
+use crate::mac::Mac;
+#[cfg(feature = "salsa")]
+use crate::salsa20::Salsa20;
+use crate::util::fixed_time_eq;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Stream cipher, xoring a keystream with the data
+pub trait StreamCipher {
+    /// Encrypt or decrypt the data in place
+    fn process_mut(&mut self, data: &mut [u8]);
+}
+
+#[cfg(feature = "chacha")]
+impl StreamCipher for ChaCha20 {
+    fn process_mut(&mut self, data: &mut [u8]) {
+        ChaCha20::process_mut(self, data)
+    }
+}
+
+#[cfg(feature = "salsa")]
+impl StreamCipher for Salsa20 {
+    fn process_mut(&mut self, data: &mut [u8]) {
+        Salsa20::process_mut(self, data)
+    }
+}
+
+/// Maximum size in bytes of the tag, i.e. of the MAC output
+pub const MAX_TAG_LENGTH: usize = 64;
+
+fn authenticate<M: Mac>(mac: &mut M, aad: &[u8], ciphertext: &[u8], out: &mut [u8]) {
+    mac.reset();
+    mac.input(aad);
+    mac.input(ciphertext);
+    mac.input(&(aad.len() as u64).to_le_bytes());
+    mac.input(&(ciphertext.len() as u64).to_le_bytes());
+    mac.raw_result(out);
+    mac.reset();
+}
+
+/// Encrypt the data in place, and write the tag authenticating the
+/// additional data and the ciphertext
+///
+/// `mac` needs to be keyed, and `tag` to be of the MAC output size.
+pub fn seal<C: StreamCipher, M: Mac>(
+    cipher: &mut C,
+    mac: &mut M,
+    aad: &[u8],
+    data: &mut [u8],
+    tag: &mut [u8],
+) {
+    assert!(tag.len() == mac.output_bytes());
+    cipher.process_mut(data);
+    authenticate(mac, aad, data, tag);
+}
+
+/// Check the tag of the additional data and the ciphertext, and decrypt the data
+/// in place if the tag is valid
+///
+/// The tag is compared in constant time. If it is not valid, `false` is
+/// returned and the data is left untouched.
+///
+/// `mac` needs to be keyed, and `tag` to be of the MAC output size, which
+/// needs to be at most [`MAX_TAG_LENGTH`].
+#[must_use = "if the result is not checked, then the data will not be verified against tempering"]
+pub fn open<C: StreamCipher, M: Mac>(
+    cipher: &mut C,
+    mac: &mut M,
+    aad: &[u8],
+    data: &mut [u8],
+    tag: &[u8],
+) -> bool {
+    let tag_len = mac.output_bytes();
+    assert!(tag_len <= MAX_TAG_LENGTH);
+    assert!(tag.len() == tag_len);
+
+    let mut expected = [0u8; MAX_TAG_LENGTH];
+    authenticate(mac, aad, data, &mut expected[..tag_len]);
+    if !fixed_time_eq(&expected[..tag_len], tag) {
+        return false;
+    }
+    cipher.process_mut(data);
+    true
+}
+
+/// Encrypt the plaintext, returning the ciphertext and the tag separately
+///
+/// The ciphertext and the tag are the same as the ones of [`seal`], the tag
+/// being of the MAC output size. `mac` needs to be keyed.
+#[cfg(feature = "alloc")]
+pub fn seal_detached<C: StreamCipher, M: Mac>(
+    cipher: &mut C,
+    mac: &mut M,
+    aad: &[u8],
+    plaintext: &[u8],
+) -> (Vec<u8>, Vec<u8>) {
+    let mut ciphertext = plaintext.to_vec();
+    let mut tag = alloc::vec![0u8; mac.output_bytes()];
+    seal(cipher, mac, aad, &mut ciphertext, &mut tag);
+    (ciphertext, tag)
+}
+
+/// Decrypt a ciphertext with a separate tag, as produced by [`seal_detached`]
+///
+/// Returns `None` if the tag doesn't match, in which case no decrypted data
+/// is returned.
+///
+/// `mac` needs to be keyed, and `tag` to be of the MAC output size, which
+/// needs to be at most [`MAX_TAG_LENGTH`].
+#[cfg(feature = "alloc")]
+pub fn open_detached<C: StreamCipher, M: Mac>(
+    cipher: &mut C,
+    mac: &mut M,
+    aad: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8],
+) -> Option<Vec<u8>> {
+    let mut plaintext = ciphertext.to_vec();
+    if open(cipher, mac, aad, &mut plaintext, tag) {
+        Some(plaintext)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{open, seal};
+    use crate::chacha20::ChaCha20;
+    use crate::hmac::Hmac;
+    use crate::mac::Mac;
+    use crate::salsa20::Salsa20;
+    use crate::sha2::Sha256;
+
+    const CIPHER_KEY: [u8; 32] = [1; 32];
+    const MAC_KEY: [u8; 32] = [2; 32];
+    const NONCE: [u8; 8] = [3; 8];
+    const PLAINTEXT: &[u8; 40] = b"encrypt-then-MAC with chacha20 and hmac!";
+
+    fn sealed() -> ([u8; 40], [u8; 32]) {
+        let mut data = *PLAINTEXT;
+        let mut tag = [0u8; 32];
+        let mut mac = Hmac::new(Sha256::new(), &MAC_KEY);
+        seal(
+            &mut ChaCha20::new(&CIPHER_KEY, &NONCE),
+            &mut mac,
+            b"aad",
+            &mut data,
+            &mut tag,
+        );
+        (data, tag)
+    }
+
+    fn open_with(aad: &[u8], data: &mut [u8], tag: &[u8]) -> bool {
+        let mut mac = Hmac::new(Sha256::new(), &MAC_KEY);
+        open(
+            &mut ChaCha20::new(&CIPHER_KEY, &NONCE),
+            &mut mac,
+            aad,
+            data,
+            tag,
+        )
+    }
+
+    #[test]
+    fn seal_open() {
+        let (mut data, tag) = sealed();
+
+        let mut expected = *PLAINTEXT;
+        ChaCha20::new(&CIPHER_KEY, &NONCE).process_mut(&mut expected);
+        assert_eq!(data, expected);
+
+        // tag of aad || ciphertext || lengths
+        let mut mac = Hmac::new(Sha256::new(), &MAC_KEY);
+        mac.input(b"aad");
+        mac.input(&data);
+        mac.input(&[3, 0, 0, 0, 0, 0, 0, 0, 40, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(mac.result().code(), &tag[..]);
+
+        assert!(open_with(b"aad", &mut data, &tag));
+        assert_eq!(&data, PLAINTEXT);
+    }
+
+    #[test]
+    fn open_tampered() {
+        let (data, tag) = sealed();
+
+        for i in 0..data.len() {
+            let mut tampered = data;
+            tampered[i] ^= 0x01;
+            assert!(!open_with(b"aad", &mut tampered, &tag));
+            // the ciphertext is not decrypted
+            tampered[i] ^= 0x01;
+            assert_eq!(tampered, data);
+        }
+
+        let mut tampered_tag = tag;
+        tampered_tag[31] ^= 0x80;
+        assert!(!open_with(b"aad", &mut data.clone(), &tampered_tag));
+        assert!(!open_with(b"aae", &mut data.clone(), &tag));
+        // moving bytes between the aad and the ciphertext changes the lengths
+        assert!(!open_with(
+            b"aa",
+            &mut [b"d".as_ref(), &data].concat(),
+            &tag
+        ));
+    }
+
+    #[test]
+    fn salsa20() {
+        let mut data = *PLAINTEXT;
+        let mut tag = [0u8; 32];
+        let mut mac = Hmac::new(Sha256::new(), &MAC_KEY);
+        let mut cipher = Salsa20::new(&CIPHER_KEY, &NONCE);
+        seal(&mut cipher, &mut mac, b"", &mut data, &mut tag);
+        let mut cipher = Salsa20::new(&CIPHER_KEY, &NONCE);
+        assert!(open(&mut cipher, &mut mac, b"", &mut data, &tag));
+        assert_eq!(&data, PLAINTEXT);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn detached_matches_in_place() {
+        use super::{open_detached, seal_detached};
+
+        let (data, tag) = sealed();
+        let mut mac = Hmac::new(Sha256::new(), &MAC_KEY);
+        let (ciphertext, detached_tag) = seal_detached(
+            &mut ChaCha20::new(&CIPHER_KEY, &NONCE),
+            &mut mac,
+            b"aad",
+            PLAINTEXT,
+        );
+        assert_eq!(ciphertext, data);
+        assert_eq!(detached_tag, tag);
+
+        let plaintext = open_detached(
+            &mut ChaCha20::new(&CIPHER_KEY, &NONCE),
+            &mut mac,
+            b"aad",
+            &ciphertext,
+            &tag,
+        );
+        assert_eq!(plaintext.as_deref(), Some(&PLAINTEXT[..]));
+
+        let mut tampered_tag = tag;
+        tampered_tag[0] ^= 0x01;
+        let plaintext = open_detached(
+            &mut ChaCha20::new(&CIPHER_KEY, &NONCE),
+            &mut mac,
+            b"aad",
+            &ciphertext,
+            &tampered_tag,
+        );
+        assert_eq!(plaintext, None);
+    }
+}
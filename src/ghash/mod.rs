@@ -0,0 +1,435 @@
+//! GHASH universal hash function, as used by the GCM mode of operation [Specification][1].
+//!
+//! GHASH evaluates the polynomial made of the 16 bytes blocks of the input
+//! at the point `H` (the hash key) over GF(2^128). It is not a MAC on its own:
+//! the result need to be encrypted (e.g. as in GCM), and the key must be secret.
+//!
+//! Data is processed in 16 bytes blocks, the last partial block being
+//! padded with zeros, which can also be forced in the middle of the
+//! input with [`Ghash::pad_zeroes`], as GCM does between the associated
+//! data and the ciphertext.
+//!
+//! On x86 and x86-64, the `pclmulqdq` instruction is used when the target
+//! feature is enabled at compile time, otherwise a portable constant-time
+//! implementation is used.
+//!
+//! # Examples
+//!
+//! ```
+//! use cryptoxide::ghash::Ghash;
+//!
+//! let mut context = Ghash::new(&[1u8; 16]);
+//! context.input(b"data to hash");
+//! let result = context.result();
+//! ```
+//!
+//! [`Polyval`] is the POLYVAL universal hash function of AES-GCM-SIV [RFC 8452][2],
+//! computed with the same multiplication.
+//!
+//! [1]: <https://nvlpubs.nist.gov/nistpubs/Legacy/SP/nistspecialpublication800-38d.pdf>
+//! [2]: <https://datatracker.ietf.org/doc/html/rfc8452>
+
+#[cfg(not(all(
+    not(feature = "force-portable"),
+    any(target_arch = "x86", target_arch = "x86_64"),
+    all(target_feature = "sse2", target_feature = "pclmulqdq")
+)))]
+mod reference;
+
+#[cfg(all(
+    not(feature = "force-portable"),
+    any(target_arch = "x86", target_arch = "x86_64"),
+    all(target_feature = "sse2", target_feature = "pclmulqdq")
+))]
+mod pclmul;
+
+#[cfg(not(all(
+    not(feature = "force-portable"),
+    any(target_arch = "x86", target_arch = "x86_64"),
+    all(target_feature = "sse2", target_feature = "pclmulqdq")
+)))]
+use reference::gf_mul;
+#[cfg(not(all(
+    not(feature = "force-portable"),
+    any(target_arch = "x86", target_arch = "x86_64"),
+    all(target_feature = "sse2", target_feature = "pclmulqdq")
+)))]
+pub(crate) const BACKEND: Backend = Backend::Portable;
+
+#[cfg(all(
+    not(feature = "force-portable"),
+    any(target_arch = "x86", target_arch = "x86_64"),
+    all(target_feature = "sse2", target_feature = "pclmulqdq")
+))]
+use pclmul::gf_mul;
+#[cfg(all(
+    not(feature = "force-portable"),
+    any(target_arch = "x86", target_arch = "x86_64"),
+    all(target_feature = "sse2", target_feature = "pclmulqdq")
+))]
+pub(crate) const BACKEND: Backend = Backend::Pclmulqdq;
+
+use crate::cpu::Backend;
+use crate::mac::OneTimeMac;
+
+/// Size of the hash key H in bytes
+pub const KEY_LENGTH: usize = 16;
+
+/// Size of the GHASH blocks and output in bytes
+pub const BLOCK_LENGTH: usize = 16;
+
+fn load(block: &[u8]) -> [u64; 2] {
+    let mut hi = [0u8; 8];
+    let mut lo = [0u8; 8];
+    hi.copy_from_slice(&block[0..8]);
+    lo.copy_from_slice(&block[8..16]);
+    [u64::from_be_bytes(hi), u64::from_be_bytes(lo)]
+}
+
+/// GHASH Context
+#[derive(Clone)]
+pub struct Ghash {
+    h: [u64; 2],
+    y: [u64; 2],
+    buffer: [u8; BLOCK_LENGTH],
+    leftover: usize,
+}
+
+impl Ghash {
+    /// Create a new GHASH context from the hash key H
+    pub fn new(h: &[u8; KEY_LENGTH]) -> Self {
+        Ghash {
+            h: load(h),
+            y: [0, 0],
+            buffer: [0; BLOCK_LENGTH],
+            leftover: 0,
+        }
+    }
+
+    fn block(&mut self, block: &[u8]) {
+        let x = load(block);
+        self.y[0] ^= x[0];
+        self.y[1] ^= x[1];
+        gf_mul(&mut self.y, &self.h);
+    }
+
+    /// Process more data
+    pub fn input(&mut self, mut data: &[u8]) {
+        if self.leftover > 0 {
+            let want = core::cmp::min(BLOCK_LENGTH - self.leftover, data.len());
+            self.buffer[self.leftover..self.leftover + want].copy_from_slice(&data[..want]);
+            self.leftover += want;
+            data = &data[want..];
+            if self.leftover < BLOCK_LENGTH {
+                return;
+            }
+            let block = self.buffer;
+            self.block(&block);
+            self.leftover = 0;
+        }
+
+        let mut blocks = data.chunks_exact(BLOCK_LENGTH);
+        for block in &mut blocks {
+            self.block(block);
+        }
+
+        let rem = blocks.remainder();
+        self.buffer[..rem.len()].copy_from_slice(rem);
+        self.leftover = rem.len();
+    }
+
+    /// Complete the pending partial block, if any, with zeros
+    ///
+    /// The next input will start a new block
+    pub fn pad_zeroes(&mut self) {
+        if self.leftover > 0 {
+            for b in self.buffer[self.leftover..].iter_mut() {
+                *b = 0;
+            }
+            let block = self.buffer;
+            self.block(&block);
+            self.leftover = 0;
+        }
+    }
+
+    /// Reset the context to its initial state, keeping the same hash key
+    pub fn reset(&mut self) {
+        self.y = [0, 0];
+        self.buffer = [0; BLOCK_LENGTH];
+        self.leftover = 0;
+    }
+
+    /// Return the GHASH of all the data processed, padding the last partial block with zeros
+    pub fn result(mut self) -> [u8; BLOCK_LENGTH] {
+        self.pad_zeroes();
+        let mut out = [0u8; BLOCK_LENGTH];
+        out[0..8].copy_from_slice(&self.y[0].to_be_bytes());
+        out[8..16].copy_from_slice(&self.y[1].to_be_bytes());
+        out
+    }
+}
+
+impl OneTimeMac for Ghash {
+    fn input(&mut self, data: &[u8]) {
+        Ghash::input(self, data)
+    }
+
+    fn finalize(self) -> [u8; BLOCK_LENGTH] {
+        self.result()
+    }
+}
+
+/// POLYVAL Context
+///
+/// POLYVAL is GHASH with the bytes of each block, and of the result, in reverse
+/// order, and with the hash key multiplied by x (RFC 8452 appendix A), which is
+/// how it is computed here. The last partial block is padded with zeros.
+#[derive(Clone)]
+pub struct Polyval {
+    h: [u64; 2],
+    y: [u64; 2],
+    buffer: [u8; BLOCK_LENGTH],
+    leftover: usize,
+}
+
+fn load_reversed(block: &[u8]) -> [u64; 2] {
+    let mut rev = [0u8; BLOCK_LENGTH];
+    for (r, b) in rev.iter_mut().zip(block.iter().rev()) {
+        *r = *b;
+    }
+    load(&rev)
+}
+
+impl Polyval {
+    /// Create a new POLYVAL context from the hash key H
+    pub fn new(h: &[u8; KEY_LENGTH]) -> Self {
+        // multiply the key by x in the GHASH bit ordering
+        let [hi, lo] = load_reversed(h);
+        let carry = 0u64.wrapping_sub(lo & 1);
+        let h = [
+            (hi >> 1) ^ (carry & 0xe100000000000000),
+            (lo >> 1) | (hi << 63),
+        ];
+        Polyval {
+            h,
+            y: [0, 0],
+            buffer: [0; BLOCK_LENGTH],
+            leftover: 0,
+        }
+    }
+
+    fn block(&mut self, block: &[u8]) {
+        let x = load_reversed(block);
+        self.y[0] ^= x[0];
+        self.y[1] ^= x[1];
+        gf_mul(&mut self.y, &self.h);
+    }
+
+    /// Process more data
+    pub fn input(&mut self, mut data: &[u8]) {
+        if self.leftover > 0 {
+            let want = core::cmp::min(BLOCK_LENGTH - self.leftover, data.len());
+            self.buffer[self.leftover..self.leftover + want].copy_from_slice(&data[..want]);
+            self.leftover += want;
+            data = &data[want..];
+            if self.leftover < BLOCK_LENGTH {
+                return;
+            }
+            let block = self.buffer;
+            self.block(&block);
+            self.leftover = 0;
+        }
+
+        let mut blocks = data.chunks_exact(BLOCK_LENGTH);
+        for block in &mut blocks {
+            self.block(block);
+        }
+
+        let rem = blocks.remainder();
+        self.buffer[..rem.len()].copy_from_slice(rem);
+        self.leftover = rem.len();
+    }
+
+    /// Complete the pending partial block, if any, with zeros
+    ///
+    /// The next input will start a new block
+    pub fn pad_zeroes(&mut self) {
+        if self.leftover > 0 {
+            for b in self.buffer[self.leftover..].iter_mut() {
+                *b = 0;
+            }
+            let block = self.buffer;
+            self.block(&block);
+            self.leftover = 0;
+        }
+    }
+
+    /// Return the POLYVAL of all the data processed, padding the last partial block with zeros
+    pub fn result(mut self) -> [u8; BLOCK_LENGTH] {
+        self.pad_zeroes();
+        let mut out = [0u8; BLOCK_LENGTH];
+        out[0..8].copy_from_slice(&self.y[1].to_le_bytes());
+        out[8..16].copy_from_slice(&self.y[0].to_le_bytes());
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{gf_mul, load, Ghash, Polyval};
+    use crate::mac::OneTimeMac;
+
+    #[test]
+    fn gf_mul_vectors() {
+        // GCM specification test case 2: X1 = C1 * H
+        let h = [
+            0x66, 0xe9, 0x4b, 0xd4, 0xef, 0x8a, 0x2c, 0x3b, 0x88, 0x4c, 0xfa, 0x59, 0xca, 0x34,
+            0x2b, 0x2e,
+        ];
+        let c = [
+            0x03, 0x88, 0xda, 0xce, 0x60, 0xb6, 0xa3, 0x92, 0xf3, 0x28, 0xc2, 0xb9, 0x71, 0xb2,
+            0xfe, 0x78,
+        ];
+        let mut y = load(&c);
+        gf_mul(&mut y, &load(&h));
+        assert_eq!(y, [0x5e2ec74691706288, 0x2c85b0685353deb7]);
+
+        // the multiplicative identity is the block with only the first bit set
+        let mut y = load(&c);
+        gf_mul(&mut y, &[0x8000000000000000, 0]);
+        assert_eq!(y, load(&c));
+
+        let mut y = load(&c);
+        gf_mul(&mut y, &[0, 0]);
+        assert_eq!(y, [0, 0]);
+    }
+
+    #[test]
+    fn ghash_vectors() {
+        // GCM specification test case 2
+        let h = [
+            0x66, 0xe9, 0x4b, 0xd4, 0xef, 0x8a, 0x2c, 0x3b, 0x88, 0x4c, 0xfa, 0x59, 0xca, 0x34,
+            0x2b, 0x2e,
+        ];
+        let mut ctx = Ghash::new(&h);
+        ctx.input(&[
+            0x03, 0x88, 0xda, 0xce, 0x60, 0xb6, 0xa3, 0x92, 0xf3, 0x28, 0xc2, 0xb9, 0x71, 0xb2,
+            0xfe, 0x78,
+        ]);
+        ctx.input(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x80]);
+        assert_eq!(
+            ctx.result(),
+            [
+                0xf3, 0x8c, 0xbb, 0x1a, 0xd6, 0x92, 0x23, 0xdc, 0xc3, 0x45, 0x7a, 0xe5, 0xb6, 0xb0,
+                0xf8, 0x85
+            ]
+        );
+
+        // unaligned input with a final partial block, from a reference implementation
+        let mut h = [0u8; 16];
+        for (i, b) in h.iter_mut().enumerate() {
+            *b = 16 + i as u8;
+        }
+        let mut data = [0u8; 100];
+        for (i, b) in data.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        let expected = [
+            0xcb, 0x97, 0xcf, 0xfb, 0xdc, 0x22, 0xf3, 0xb8, 0x00, 0xc1, 0x12, 0x7c, 0xd5, 0x88,
+            0xbf, 0xc2,
+        ];
+        for split in [0usize, 1, 15, 16, 17, 50, 99, 100].iter() {
+            let mut ctx = Ghash::new(&h);
+            ctx.input(&data[..*split]);
+            ctx.input(&data[*split..]);
+            assert_eq!(ctx.result(), expected);
+        }
+
+        let mut ctx = Ghash::new(&h);
+        ctx.input(b"garbage");
+        ctx.reset();
+        ctx.input(&data);
+        assert_eq!(ctx.result(), expected);
+    }
+
+    #[test]
+    fn pad_zeroes() {
+        let h = [0x42u8; 16];
+        let mut ctx1 = Ghash::new(&h);
+        ctx1.input(b"abc");
+        ctx1.pad_zeroes();
+        ctx1.input(b"def");
+        let mut ctx2 = Ghash::new(&h);
+        ctx2.input(b"abc\0\0\0\0\0\0\0\0\0\0\0\0\0def");
+        assert_eq!(ctx1.result(), ctx2.result());
+    }
+
+    #[test]
+    fn polyval_vectors() {
+        // RFC 8452 appendix A
+        let h = [
+            0x25, 0x62, 0x93, 0x47, 0x58, 0x92, 0x42, 0x76, 0x1d, 0x31, 0xf8, 0x26, 0xba, 0x4b,
+            0x75, 0x7b,
+        ];
+        let x = [
+            0x4f, 0x4f, 0x95, 0x66, 0x8c, 0x83, 0xdf, 0xb6, 0x40, 0x17, 0x62, 0xbb, 0x2d, 0x01,
+            0xa2, 0x62, 0xd1, 0xa2, 0x4d, 0xdd, 0x27, 0x21, 0xd0, 0x06, 0xbb, 0xe4, 0x5f, 0x20,
+            0xd3, 0xc9, 0xf3, 0x62,
+        ];
+        let expected = [
+            0xf7, 0xa3, 0xb4, 0x7b, 0x84, 0x61, 0x19, 0xfa, 0xe5, 0xb7, 0x86, 0x6c, 0xf5, 0xe5,
+            0xb7, 0x7e,
+        ];
+        for split in [0usize, 5, 16, 31, 32].iter() {
+            let mut ctx = Polyval::new(&h);
+            ctx.input(&x[..*split]);
+            ctx.input(&x[*split..]);
+            assert_eq!(ctx.result(), expected);
+        }
+    }
+
+    #[test]
+    fn one_time_mac() {
+        fn tag<M: OneTimeMac>(mut mac: M, data: &[u8]) -> [u8; 16] {
+            mac.input(data);
+            mac.finalize()
+        }
+
+        let h = [0x42u8; 16];
+        let mut ctx = Ghash::new(&h);
+        ctx.input(b"one time");
+        assert_eq!(tag(Ghash::new(&h), b"one time"), ctx.result());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn one_time_mac_object() {
+        use crate::mac::DynOneTimeMac;
+        use alloc::boxed::Box;
+
+        let h = [0x42u8; 16];
+        let mut boxed: Box<dyn DynOneTimeMac> = Box::new(Ghash::new(&h));
+        boxed.input(b"one ");
+        boxed.input(b"time");
+        let mut ctx = Ghash::new(&h);
+        ctx.input(b"one time");
+        assert_eq!(boxed.finalize_boxed(), ctx.result());
+    }
+}
+
+#[cfg(all(test, feature = "with-bench"))]
+mod bench {
+    use super::Ghash;
+    use test::Bencher;
+
+    #[bench]
+    pub fn ghash_1k(bh: &mut Bencher) {
+        let data = [1u8; 1024];
+        bh.iter(|| {
+            let mut ctx = Ghash::new(&[0x42; 16]);
+            ctx.input(&data);
+            ctx.result()
+        });
+        bh.bytes = data.len() as u64;
+    }
+}
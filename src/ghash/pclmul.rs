@@ -0,0 +1,66 @@
+//! GF(2^128) multiplication using the carryless multiplication instruction
+//!
+//! Follows the method described in Intel's "Carry-Less Multiplication
+//! Instruction and its Usage for Computing the GCM Mode" white paper.
+
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+/// Multiply `y` by `h` in GF(2^128) using the GHASH bit ordering,
+/// with both elements given as their big endian (high, low) 64 bits halves
+pub(crate) fn gf_mul(y: &mut [u64; 2], h: &[u64; 2]) {
+    unsafe {
+        let a = _mm_set_epi64x(y[0] as i64, y[1] as i64);
+        let b = _mm_set_epi64x(h[0] as i64, h[1] as i64);
+
+        // 256 bits carryless product in tmp6:tmp3
+        let mut tmp3 = _mm_clmulepi64_si128(a, b, 0x00);
+        let mut tmp4 = _mm_clmulepi64_si128(a, b, 0x10);
+        let mut tmp5 = _mm_clmulepi64_si128(a, b, 0x01);
+        let mut tmp6 = _mm_clmulepi64_si128(a, b, 0x11);
+        tmp4 = _mm_xor_si128(tmp4, tmp5);
+        tmp5 = _mm_slli_si128(tmp4, 8);
+        tmp4 = _mm_srli_si128(tmp4, 8);
+        tmp3 = _mm_xor_si128(tmp3, tmp5);
+        tmp6 = _mm_xor_si128(tmp6, tmp4);
+
+        // shift the product left by one, as the bits are reflected
+        let mut tmp7 = _mm_srli_epi32(tmp3, 31);
+        let mut tmp8 = _mm_srli_epi32(tmp6, 31);
+        tmp3 = _mm_slli_epi32(tmp3, 1);
+        tmp6 = _mm_slli_epi32(tmp6, 1);
+        let mut tmp9 = _mm_srli_si128(tmp7, 12);
+        tmp8 = _mm_slli_si128(tmp8, 4);
+        tmp7 = _mm_slli_si128(tmp7, 4);
+        tmp3 = _mm_or_si128(tmp3, tmp7);
+        tmp6 = _mm_or_si128(tmp6, tmp8);
+        tmp6 = _mm_or_si128(tmp6, tmp9);
+
+        // reduction modulo x^128 + x^7 + x^2 + x + 1
+        tmp7 = _mm_slli_epi32(tmp3, 31);
+        tmp8 = _mm_slli_epi32(tmp3, 30);
+        tmp9 = _mm_slli_epi32(tmp3, 25);
+        tmp7 = _mm_xor_si128(tmp7, tmp8);
+        tmp7 = _mm_xor_si128(tmp7, tmp9);
+        tmp8 = _mm_srli_si128(tmp7, 4);
+        tmp7 = _mm_slli_si128(tmp7, 12);
+        tmp3 = _mm_xor_si128(tmp3, tmp7);
+
+        let mut tmp2 = _mm_srli_epi32(tmp3, 1);
+        tmp4 = _mm_srli_epi32(tmp3, 2);
+        tmp5 = _mm_srli_epi32(tmp3, 7);
+        tmp2 = _mm_xor_si128(tmp2, tmp4);
+        tmp2 = _mm_xor_si128(tmp2, tmp5);
+        tmp2 = _mm_xor_si128(tmp2, tmp8);
+        tmp3 = _mm_xor_si128(tmp3, tmp2);
+        tmp6 = _mm_xor_si128(tmp6, tmp3);
+
+        let mut out = [0u64; 2];
+        _mm_storeu_si128(out.as_mut_ptr() as *mut __m128i, tmp6);
+        y[0] = out[1];
+        y[1] = out[0];
+    }
+}
@@ -0,0 +1,80 @@
+//! Portable constant-time multiplication in GF(2^128)
+//!
+//! This is the `ctmul64` method from BearSSL: the carryless multiplications
+//! are done with integer multiplications where the bits are spread out with
+//! holes, so that the carries never interfere with the result bits.
+
+// carryless multiplication of two 64 bits values, keeping the low 64 bits
+fn bmul64(x: u64, y: u64) -> u64 {
+    const M0: u64 = 0x1111111111111111;
+    const M1: u64 = 0x2222222222222222;
+    const M2: u64 = 0x4444444444444444;
+    const M3: u64 = 0x8888888888888888;
+
+    let x0 = x & M0;
+    let x1 = x & M1;
+    let x2 = x & M2;
+    let x3 = x & M3;
+    let y0 = y & M0;
+    let y1 = y & M1;
+    let y2 = y & M2;
+    let y3 = y & M3;
+
+    let z0 = x0.wrapping_mul(y0) ^ x1.wrapping_mul(y3) ^ x2.wrapping_mul(y2) ^ x3.wrapping_mul(y1);
+    let z1 = x0.wrapping_mul(y1) ^ x1.wrapping_mul(y0) ^ x2.wrapping_mul(y3) ^ x3.wrapping_mul(y2);
+    let z2 = x0.wrapping_mul(y2) ^ x1.wrapping_mul(y1) ^ x2.wrapping_mul(y0) ^ x3.wrapping_mul(y3);
+    let z3 = x0.wrapping_mul(y3) ^ x1.wrapping_mul(y2) ^ x2.wrapping_mul(y1) ^ x3.wrapping_mul(y0);
+
+    (z0 & M0) | (z1 & M1) | (z2 & M2) | (z3 & M3)
+}
+
+/// Multiply `y` by `h` in GF(2^128) using the GHASH bit ordering,
+/// with both elements given as their big endian (high, low) 64 bits halves
+pub(crate) fn gf_mul(y: &mut [u64; 2], h: &[u64; 2]) {
+    let h1 = h[0];
+    let h0 = h[1];
+    let h0r = h0.reverse_bits();
+    let h1r = h1.reverse_bits();
+    let h2 = h0 ^ h1;
+    let h2r = h0r ^ h1r;
+
+    let y1 = y[0];
+    let y0 = y[1];
+    let y0r = y0.reverse_bits();
+    let y1r = y1.reverse_bits();
+    let y2 = y0 ^ y1;
+    let y2r = y0r ^ y1r;
+
+    // karatsuba on the low and (bit reversed) high halves of the products
+    let z0 = bmul64(y0, h0);
+    let z1 = bmul64(y1, h1);
+    let mut z2 = bmul64(y2, h2);
+    let mut z0h = bmul64(y0r, h0r);
+    let mut z1h = bmul64(y1r, h1r);
+    let mut z2h = bmul64(y2r, h2r);
+    z2 ^= z0 ^ z1;
+    z2h ^= z0h ^ z1h;
+    z0h = z0h.reverse_bits() >> 1;
+    z1h = z1h.reverse_bits() >> 1;
+    z2h = z2h.reverse_bits() >> 1;
+
+    let mut v0 = z0;
+    let mut v1 = z0h ^ z2;
+    let mut v2 = z1 ^ z2h;
+    let mut v3 = z1h;
+
+    // shift the 256 bits product by one, as the bits are reflected
+    v3 = (v3 << 1) | (v2 >> 63);
+    v2 = (v2 << 1) | (v1 >> 63);
+    v1 = (v1 << 1) | (v0 >> 63);
+    v0 <<= 1;
+
+    // reduction modulo x^128 + x^7 + x^2 + x + 1
+    v2 ^= v0 ^ (v0 >> 1) ^ (v0 >> 2) ^ (v0 >> 7);
+    v1 ^= (v0 << 63) ^ (v0 << 62) ^ (v0 << 57);
+    v3 ^= v1 ^ (v1 >> 1) ^ (v1 >> 2) ^ (v1 >> 7);
+    v2 ^= (v1 << 63) ^ (v1 << 62) ^ (v1 << 57);
+
+    y[0] = v3;
+    y[1] = v2;
+}
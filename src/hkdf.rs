@@ -16,6 +16,7 @@
 //!
 //! [1]: <https://tools.ietf.org/html/rfc5869>
 
+use alloc::vec;
 use alloc::vec::Vec;
 use core::iter::repeat;
 
@@ -23,6 +24,7 @@ use crate::cryptoutil::copy_memory;
 use crate::digest::Digest;
 use crate::hmac::Hmac;
 use crate::mac::Mac;
+use crate::util::secure_memset;
 
 /// Execute the HKDF-Extract function.  Applications MUST NOT use this for
 /// password hashing.
@@ -33,7 +35,7 @@ use crate::mac::Mac;
 /// * ikm - The input keying material to use.
 /// * prk - The output buffer to fill with a `digest.output_bytes()` length
 ///         pseudo random key.
-pub fn hkdf_extract<D: Digest>(mut digest: D, salt: &[u8], ikm: &[u8], prk: &mut [u8]) {
+pub fn hkdf_extract<D: Digest + Clone>(mut digest: D, salt: &[u8], ikm: &[u8], prk: &mut [u8]) {
     assert!(prk.len() == digest.output_bytes());
     digest.reset();
 
@@ -51,10 +53,13 @@ pub fn hkdf_extract<D: Digest>(mut digest: D, salt: &[u8], ikm: &[u8], prk: &mut
 /// * prk - The pseudorandom key of at least `digest.output_bytes()` octets.
 /// * info - The optional context and application specific information to use.
 /// * okm - The output buffer to fill with the derived key value.
-pub fn hkdf_expand<D: Digest>(mut digest: D, prk: &[u8], info: &[u8], okm: &mut [u8]) {
+pub fn hkdf_expand<D: Digest + Clone>(mut digest: D, prk: &[u8], info: &[u8], okm: &mut [u8]) {
     digest.reset();
+    expand(Hmac::new(digest, prk), info, okm);
+}
 
-    let mut mac = Hmac::new(digest, prk);
+// HKDF-Expand with the HMAC keyed by the PRK, panics if okm is longer than 255 blocks
+fn expand<D: Digest + Clone>(mut mac: Hmac<D>, info: &[u8], okm: &mut [u8]) {
     let os = mac.output_bytes();
     let mut t: Vec<u8> = repeat(0).take(os).collect();
     let mut n: u8 = 0;
@@ -76,13 +81,65 @@ pub fn hkdf_expand<D: Digest>(mut digest: D, prk: &[u8], info: &[u8], okm: &mut
     }
 }
 
+/// Error returned when the requested output of HKDF-Expand is longer than
+/// 255 times the digest output size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidLength;
+
+impl core::fmt::Display for InvalidLength {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid hkdf output length")
+    }
+}
+
+/// HKDF context holding the pseudo random key, to run HKDF-Extract once
+/// and HKDF-Expand many times with different `info`
+///
+/// ```
+/// use cryptoxide::{sha2::Sha256, hkdf::Hkdf};
+///
+/// let hkdf = Hkdf::new(Sha256::new(), b"salt", b"input");
+/// let mut encryption_key = [0u8; 32];
+/// let mut mac_key = [0u8; 32];
+/// hkdf.expand(b"encryption", &mut encryption_key).unwrap();
+/// hkdf.expand(b"authentication", &mut mac_key).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct Hkdf<D> {
+    mac: Hmac<D>,
+}
+
+impl<D: Digest + Clone> Hkdf<D> {
+    /// Run HKDF-Extract on the input keying material and keep the resulting
+    /// pseudo random key
+    pub fn new(mut digest: D, salt: &[u8], ikm: &[u8]) -> Self {
+        digest.reset();
+        let mut prk = vec![0u8; digest.output_bytes()];
+        hkdf_extract(digest.clone(), salt, ikm, &mut prk);
+        let mac = Hmac::new(digest, &prk);
+        secure_memset(&mut prk, 0);
+        Hkdf { mac }
+    }
+
+    /// Run HKDF-Expand with the pseudo random key, filling `okm`
+    ///
+    /// Return an error if `okm` is longer than 255 times the digest output size.
+    pub fn expand(&self, info: &[u8], okm: &mut [u8]) -> Result<(), InvalidLength> {
+        if okm.len() > 255 * self.mac.output_bytes() {
+            return Err(InvalidLength);
+        }
+        expand(self.mac.clone(), info, okm);
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::iter::repeat;
     use std::vec::Vec;
 
     use crate::digest::Digest;
-    use crate::hkdf::{hkdf_expand, hkdf_extract};
+    use crate::hkdf::{hkdf_expand, hkdf_extract, Hkdf, InvalidLength};
     use crate::sha2::Sha256;
 
     struct TestVector<D: Digest> {
@@ -167,6 +224,43 @@ mod test {
             assert!(okm.len() == t.l);
             hkdf_expand(t.digest.clone(), &prk[..], &t.info[..], &mut okm);
             assert!(okm == t.okm);
+
+            let hkdf = Hkdf::new(t.digest.clone(), &t.salt[..], &t.ikm[..]);
+            for _ in 0..2 {
+                let mut okm = vec![0u8; t.okm.len()];
+                assert_eq!(hkdf.expand(&t.info[..], &mut okm), Ok(()));
+                assert!(okm == t.okm);
+            }
         }
     }
+
+    #[test]
+    fn test_hkdf_multiple_expands() {
+        // RFC 5869 test cases 1 and 3 have the same ikm, with a salt in case 1 only
+        let ikm = [0x0bu8; 22];
+        let hkdf = Hkdf::new(Sha256::new(), &[], &ikm);
+
+        let mut expected = [0u8; 42];
+        let mut prk = [0u8; 32];
+        hkdf_extract(Sha256::new(), &[], &ikm, &mut prk);
+        let mut okm = [0u8; 42];
+        for info in [&b""[..], &b"first"[..], &b"second"[..], &b""[..]].iter() {
+            hkdf_expand(Sha256::new(), &prk, info, &mut expected);
+            hkdf.expand(info, &mut okm).unwrap();
+            assert_eq!(okm, expected);
+        }
+        assert_eq!(
+            &okm[..],
+            &[
+                0x8d, 0xa4, 0xe7, 0x75, 0xa5, 0x63, 0xc1, 0x8f, 0x71, 0x5f, 0x80, 0x2a, 0x06, 0x3c,
+                0x5a, 0x31, 0xb8, 0xa1, 0x1f, 0x5c, 0x5e, 0xe1, 0x87, 0x9e, 0xc3, 0x45, 0x4e, 0x5f,
+                0x3c, 0x73, 0x8d, 0x2d, 0x9d, 0x20, 0x13, 0x95, 0xfa, 0xa4, 0xb6, 0x1a, 0x96, 0xc8,
+            ][..]
+        );
+
+        let mut longest = vec![0u8; 255 * 32];
+        assert_eq!(hkdf.expand(b"", &mut longest), Ok(()));
+        let mut too_long = vec![0u8; 255 * 32 + 1];
+        assert_eq!(hkdf.expand(b"", &mut too_long), Err(InvalidLength));
+    }
 }
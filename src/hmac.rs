@@ -13,19 +13,40 @@
 //! h.input(input);
 //! let mac = h.result();
 //! ```
-
-use core::iter::repeat;
+//!
+//! Once the result is obtained, the context can't absorb any more data. To get
+//! the MAC of the data absorbed so far while continuing with more data, e.g. for
+//! periodic checkpoints over a running log, finalize a clone of the context:
+//!
+//! ```
+//! use cryptoxide::{hmac::Hmac, mac::Mac, sha2::Sha256};
+//!
+//! let mut h = Hmac::new(Sha256::new(), b"key");
+//! h.input(b"first entry");
+//! let checkpoint = h.clone().result();
+//! h.input(b"second entry");
+//! let mac = h.result();
+//! ```
 
 use crate::cryptoutil;
 use crate::digest::Digest;
-use crate::mac::{Mac, MacResult};
+use crate::mac::Mac;
+#[cfg(feature = "alloc")]
 use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::iter::repeat;
 
 /// HMAC context parametrized by the hashing function
+///
+/// Cloning the context is the supported way to obtain the MAC of the data
+/// absorbed so far without preventing the original context to absorb more data.
+#[derive(Clone)]
 pub struct Hmac<D> {
     digest: D,
-    i_key: Vec<u8>,
-    o_key: Vec<u8>,
+    // digest states right after absorbing the inner and outer keys, so that
+    // resetting and finalizing don't need to process the keys again
+    i_digest: D,
+    o_digest: D,
     finished: bool,
 }
 
@@ -35,35 +56,40 @@ fn derive_key(key: &mut [u8], mask: u8) {
     }
 }
 
+/// Largest block size of the digests of this crate (SHAKE128), which bounds the size of
+/// the key buffer when building without the `alloc` feature
+#[cfg(not(feature = "alloc"))]
+const MAX_BLOCK_SIZE: usize = 168;
+
 // The key that Hmac processes must be the same as the block size of the underlying Digest. If the
 // provided key is smaller than that, we just pad it with zeros. If its larger, we hash it and then
 // pad it with zeros.
-fn expand_key<D: Digest>(digest: &mut D, key: &[u8]) -> Vec<u8> {
-    let bs = digest.block_size();
-    let mut expanded_key: Vec<u8> = repeat(0).take(bs).collect();
+fn expand_key<D: Digest>(digest: &mut D, key: &[u8], expanded_key: &mut [u8]) {
+    let bs = expanded_key.len();
 
     if key.len() <= bs {
-        cryptoutil::copy_memory(key, &mut expanded_key);
+        cryptoutil::copy_memory(key, expanded_key);
     } else {
         let output_size = digest.output_bytes();
         digest.input(key);
         digest.result(&mut expanded_key[..output_size]);
         digest.reset();
     }
-    expanded_key
 }
 
 // Hmac uses two keys derived from the provided key - one by xoring every byte with 0x36 and another
-// with 0x5c.
-fn create_keys<D: Digest>(digest: &mut D, key: &[u8]) -> (Vec<u8>, Vec<u8>) {
-    let mut i_key = expand_key(digest, key);
-    let mut o_key = i_key.clone();
-    derive_key(&mut i_key, 0x36);
-    derive_key(&mut o_key, 0x5c);
-    (i_key, o_key)
+// with 0x5c. They are absorbed in the inner and outer digests respectively.
+fn absorb_keys<D: Digest + Clone>(digest: &mut D, key: &[u8], expanded_key: &mut [u8]) -> D {
+    expand_key(digest, key, expanded_key);
+    let mut o_digest = digest.clone();
+    derive_key(expanded_key, 0x36);
+    digest.input(expanded_key);
+    derive_key(expanded_key, 0x36 ^ 0x5c);
+    o_digest.input(expanded_key);
+    o_digest
 }
 
-impl<D: Digest> Hmac<D> {
+impl<D: Digest + Clone> Hmac<D> {
     /// Create a new Hmac instance.
     ///
     /// # Arguments
@@ -71,45 +97,84 @@ impl<D: Digest> Hmac<D> {
     /// * key - The key to use.
     ///
     pub fn new(mut digest: D, key: &[u8]) -> Hmac<D> {
-        let (i_key, o_key) = create_keys(&mut digest, key);
-        digest.input(&i_key[..]);
+        let bs = digest.block_size();
+
+        #[cfg(feature = "alloc")]
+        let mut expanded_key: Vec<u8> = repeat(0).take(bs).collect();
+        #[cfg(not(feature = "alloc"))]
+        let mut expanded_key = {
+            assert!(bs <= MAX_BLOCK_SIZE);
+            [0u8; MAX_BLOCK_SIZE]
+        };
+        let o_digest = absorb_keys(&mut digest, key, &mut expanded_key[..bs]);
+
         Hmac {
-            digest: digest,
-            i_key: i_key,
-            o_key: o_key,
+            digest: digest.clone(),
+            i_digest: digest,
+            o_digest: o_digest,
             finished: false,
         }
     }
 }
 
-impl<D: Digest> Mac for Hmac<D> {
+/// HMAC keyed once and used for the MAC of many messages
+///
+/// The inner and outer digest states right after absorbing the key are
+/// computed once by `new`, and each call to [`PrecomputedHmac::mac`] starts
+/// from clones of those states, without mutating the context. This is useful
+/// in loops computing the MAC of many short messages with the same key, as
+/// done by PBKDF2, HKDF expansion or TOTP.
+#[derive(Clone)]
+pub struct PrecomputedHmac<D> {
+    i_digest: D,
+    o_digest: D,
+}
+
+impl<D: Digest + Clone> PrecomputedHmac<D> {
+    /// Create a new context, absorbing the key in the inner and outer digests
+    pub fn new(digest: D, key: &[u8]) -> Self {
+        let hmac = Hmac::new(digest, key);
+        PrecomputedHmac {
+            i_digest: hmac.i_digest,
+            o_digest: hmac.o_digest,
+        }
+    }
+
+    /// Compute the MAC of `data` into `out`, which needs to be of the
+    /// digest output size
+    pub fn mac(&self, data: &[u8], out: &mut [u8]) {
+        let mut digest = self.i_digest.clone();
+        digest.input(data);
+        digest.result(out);
+        let mut digest = self.o_digest.clone();
+        digest.input(out);
+        digest.result(out);
+    }
+
+    /// Get the size of the MAC code, in bytes
+    pub fn output_bytes(&self) -> usize {
+        self.i_digest.output_bytes()
+    }
+}
+
+impl<D: Digest + Clone> Mac for Hmac<D> {
     fn input(&mut self, data: &[u8]) {
         assert!(!self.finished);
         self.digest.input(data);
     }
 
     fn reset(&mut self) {
-        self.digest.reset();
-        self.digest.input(&self.i_key[..]);
+        self.digest = self.i_digest.clone();
         self.finished = false;
     }
 
-    fn result(&mut self) -> MacResult {
-        let output_size = self.digest.output_bytes();
-        let mut code: Vec<u8> = repeat(0).take(output_size).collect();
-
-        self.raw_result(&mut code);
-
-        MacResult::new_from_owned(code)
-    }
-
     fn raw_result(&mut self, output: &mut [u8]) {
         if !self.finished {
             self.digest.result(output);
 
-            self.digest.reset();
-            self.digest.input(&self.o_key[..]);
-            self.digest.input(output);
+            let mut o_digest = self.o_digest.clone();
+            o_digest.input(output);
+            self.digest = o_digest;
 
             self.finished = true;
         }
@@ -127,8 +192,8 @@ mod test {
     use std::iter::repeat;
     use std::vec::Vec;
 
-    use crate::hmac::Hmac;
-    use crate::mac::Mac;
+    use crate::hmac::{Hmac, PrecomputedHmac};
+    use crate::mac::{Mac, MacError};
 
     #[cfg(feature = "blake2")]
     use crate::blake2s::Blake2s;
@@ -188,6 +253,43 @@ mod test {
         }
     }
 
+    #[cfg(all(feature = "sha2", feature = "alloc"))]
+    #[test]
+    fn hmac_sha256_truncated() {
+        use crate::mac::MacResult;
+
+        // RFC 4231 test case 5, HMAC-SHA-256 truncated to 128 bits
+        let key = [0x0c; 20];
+        let data = b"Test With Truncation";
+        let expected = [
+            0xa3, 0xb6, 0x16, 0x74, 0x73, 0x10, 0x0e, 0xe0, 0x6e, 0x0c, 0x79, 0x6c, 0x29, 0x55,
+            0x55, 0x2b,
+        ];
+
+        let mut h = Hmac::new(Sha256::new(), &key[..]);
+        h.input(&data[..]);
+        let truncated = h.result().truncated(16);
+        assert_eq!(truncated.code(), &expected[..]);
+        assert!(truncated == MacResult::new(&expected[..]));
+
+        h.reset();
+        h.input(&data[..]);
+        assert!(h.verify_truncated(&expected[..]));
+
+        let mut wrong = expected;
+        wrong[15] ^= 1;
+        h.reset();
+        h.input(&data[..]);
+        assert!(!h.verify_truncated(&wrong[..]));
+
+        h.reset();
+        h.input(&data[..]);
+        assert!(!h.verify_truncated(&[]));
+        h.reset();
+        h.input(&data[..]);
+        assert!(!h.verify_truncated(&[0u8; 33][..]));
+    }
+
     #[cfg(feature = "blake2")]
     #[test]
     fn hmac_blake2s() {
@@ -209,4 +311,104 @@ mod test {
         h.raw_result(&mut output);
         assert_eq!(&output[..], &expected[..]);
     }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn hmac_sha256_checkpoint() {
+        let key = b"checkpoint key";
+        let mac_of = |data: &[u8]| {
+            let mut h = Hmac::new(Sha256::new(), key);
+            h.input(data);
+            let mut tag = [0u8; 32];
+            h.raw_result(&mut tag);
+            tag
+        };
+
+        let mut h = Hmac::new(Sha256::new(), key);
+        h.input(b"A");
+        let mut tag1 = [0u8; 32];
+        h.clone().raw_result(&mut tag1);
+        h.input(b"B");
+        let mut tag2 = [0u8; 32];
+        h.raw_result(&mut tag2);
+        assert_eq!(tag1, mac_of(b"A"));
+        assert_eq!(tag2, mac_of(b"AB"));
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn precomputed_hmac() {
+        let long_key = [0x61u8; 200];
+        for t in tests().iter() {
+            let ph = PrecomputedHmac::new(Sha256::new(), &t.key[..]);
+            assert_eq!(ph.output_bytes(), 32);
+            let mut output = [0u8; 32];
+            ph.mac(&t.data[..], &mut output);
+            assert_eq!(&output[..], &t.expected[..]);
+        }
+
+        let ph = PrecomputedHmac::new(Sha256::new(), &long_key);
+        for len in 0..130 {
+            let data: Vec<u8> = (0..len as u8).collect();
+            let mut h = Hmac::new(Sha256::new(), &long_key);
+            h.input(&data);
+            let mut output = [0u8; 32];
+            ph.mac(&data, &mut output);
+            let mut expected = [0u8; 32];
+            h.raw_result(&mut expected);
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn hmac_sha256_verify_slice() {
+        for t in tests().iter() {
+            let mut h = Hmac::new(Sha256::new(), &t.key[..]);
+            h.input(&t.data[..]);
+            assert_eq!(h.verify_slice(&t.expected[..]), Ok(()));
+
+            let mut tampered = t.expected.clone();
+            tampered[31] ^= 1;
+            let mut h = Hmac::new(Sha256::new(), &t.key[..]);
+            h.input(&t.data[..]);
+            assert_eq!(h.verify_slice(&tampered), Err(MacError::Mismatch));
+
+            let mut h = Hmac::new(Sha256::new(), &t.key[..]);
+            h.input(&t.data[..]);
+            assert_eq!(
+                h.verify_slice(&t.expected[..16]),
+                Err(MacError::LengthMismatch)
+            );
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hmac_input_reader() {
+        use std::io::{Cursor, Read};
+
+        // reader returning at most 1000 bytes at a time
+        struct ShortReads<R>(R);
+        impl<R: Read> Read for ShortReads<R> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let n = core::cmp::min(buf.len(), 1000);
+                self.0.read(&mut buf[..n])
+            }
+        }
+
+        let data: Vec<u8> = (0..20000u32).map(|i| i as u8).collect();
+        let mut h = Hmac::new(Sha256::new(), b"key");
+        h.input(&data);
+        let expected = h.result();
+
+        let mut h = Hmac::new(Sha256::new(), b"key");
+        assert_eq!(h.input_reader(&mut Cursor::new(&data)).unwrap(), 20000);
+        assert!(h.result() == expected);
+
+        let mut h = Hmac::new(Sha256::new(), b"key");
+        let read = h.input_reader(&mut ShortReads(Cursor::new(&data)));
+        assert_eq!(read.unwrap(), 20000);
+        assert!(h.result() == expected);
+    }
 }
@@ -0,0 +1,161 @@
+//! Key Derivation Functions using a pseudorandom function (KBKDF)
+//!
+//! This module implements the counter mode KDF of [NIST SP 800-108][1],
+//! over any [`Mac`] used as the pseudorandom function, usually HMAC.
+//! [`counter_mode`] encodes the label and context as the fixed input data,
+//! while [`counter_mode_fixed_input`] takes it already encoded.
+//!
+//! # Examples
+//!
+//! ```
+//! use cryptoxide::{hmac::Hmac, kdf::counter_mode, sha2::Sha256};
+//!
+//! let key = [0u8; 32];
+//! let mut prf = Hmac::new(Sha256::new(), &key);
+//! let mut derived = [0u8; 48];
+//! counter_mode(&mut prf, b"label", b"context", &mut derived);
+//! ```
+//!
+//! [1]: <https://csrc.nist.gov/publications/detail/sp/800-108/rev-1/final>
+
+use crate::mac::Mac;
+
+/// Maximum output size in bytes of the pseudorandom function
+pub const MAX_PRF_OUTPUT_BYTES: usize = 64;
+
+/// Derive a key in counter mode, as specified in section 4.1 of NIST SP 800-108
+///
+/// Each block of output is the PRF of `[i]_2 || Label || 0x00 || Context || [L]_2`,
+/// where the block counter `i` starting at 1 and the output length `L` in bits
+/// are both encoded as 32 bits big endian integers, and placed before and after
+/// the fixed input data respectively.
+///
+/// `prf` needs to be keyed with the key derivation key, and is reset before the
+/// computation of each block. Its output size need to be at most
+/// [`MAX_PRF_OUTPUT_BYTES`].
+pub fn counter_mode<M: Mac>(prf: &mut M, label: &[u8], context: &[u8], out: &mut [u8]) {
+    let l = (out.len() as u64) * 8;
+    assert!(l <= u32::MAX as u64);
+    counter_blocks(prf, &[label, &[0], context, &(l as u32).to_be_bytes()], out)
+}
+
+/// Derive a key in counter mode from already encoded fixed input data
+///
+/// Each block of output is the PRF of `[i]_2 || FixedInputData`, with the
+/// block counter `i` starting at 1 encoded as a 32 bits big endian integer.
+/// This is the form used by the NIST CAVP KBKDF test vectors, for the
+/// protocols which don't encode the fixed input data as [`counter_mode`] does.
+///
+/// `prf` has the same requirements as for [`counter_mode`].
+pub fn counter_mode_fixed_input<M: Mac>(prf: &mut M, fixed_input: &[u8], out: &mut [u8]) {
+    assert!((out.len() as u64) * 8 <= u32::MAX as u64);
+    counter_blocks(prf, &[fixed_input], out)
+}
+
+fn counter_blocks<M: Mac>(prf: &mut M, fixed_input: &[&[u8]], out: &mut [u8]) {
+    let h = prf.output_bytes();
+    assert!(h > 0 && h <= MAX_PRF_OUTPUT_BYTES);
+
+    let mut block = [0u8; MAX_PRF_OUTPUT_BYTES];
+    for (i, chunk) in out.chunks_mut(h).enumerate() {
+        prf.reset();
+        prf.input(&(i as u32 + 1).to_be_bytes());
+        for part in fixed_input {
+            prf.input(part);
+        }
+        prf.raw_result(&mut block[..h]);
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+    prf.reset();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{counter_mode, counter_mode_fixed_input};
+    use crate::hmac::Hmac;
+    use crate::sha2::Sha256;
+
+    // output of the pyca/cryptography KBKDFHMAC implementation, with a 32 bits
+    // counter before the fixed input data and a 32 bits length
+    #[test]
+    fn hmac_sha256() {
+        let key: [u8; 32] = core::array::from_fn(|i| i as u8);
+        let mut prf = Hmac::new(Sha256::new(), &key);
+
+        let mut out = [0u8; 32];
+        counter_mode(&mut prf, b"label", b"context", &mut out);
+        assert_eq!(
+            out,
+            [
+                0x30, 0x37, 0x90, 0xcf, 0xe3, 0x63, 0xab, 0xe9, 0x68, 0x2d, 0xbf, 0xff, 0x59, 0x41,
+                0xf2, 0x3b, 0x32, 0xad, 0xdc, 0x96, 0xda, 0x72, 0xf4, 0xc7, 0xe5, 0xb2, 0x0e, 0x9f,
+                0x59, 0xa4, 0xe5, 0x70,
+            ]
+        );
+
+        // partial last block
+        let mut out = [0u8; 42];
+        counter_mode(&mut prf, b"label", b"context", &mut out);
+        assert_eq!(
+            out,
+            [
+                0xb9, 0xcd, 0x5f, 0x63, 0x23, 0xf0, 0x1f, 0x46, 0x80, 0x65, 0x08, 0x55, 0xf1, 0xeb,
+                0xea, 0x9b, 0x4c, 0x54, 0xc0, 0x81, 0x31, 0xb5, 0x06, 0xfc, 0x28, 0xc8, 0x56, 0x36,
+                0x4a, 0x38, 0xa2, 0xf4, 0xfb, 0x68, 0x0c, 0x12, 0xea, 0x51, 0x69, 0x68, 0x87, 0xd9,
+            ]
+        );
+
+        let mut out = [0u8; 64];
+        counter_mode(&mut prf, b"label", b"context", &mut out);
+        assert_eq!(
+            out,
+            [
+                0xa9, 0x67, 0x42, 0xda, 0xb6, 0x29, 0x38, 0x5c, 0x2f, 0xda, 0x3a, 0xb3, 0x1f, 0xf8,
+                0x0a, 0xe5, 0xab, 0x8f, 0x18, 0xd6, 0x1a, 0x90, 0x3f, 0x75, 0xd4, 0xcb, 0x97, 0x42,
+                0x2e, 0x3b, 0x95, 0x86, 0x4f, 0x65, 0x03, 0x29, 0xd5, 0x58, 0xa4, 0x92, 0x70, 0x7f,
+                0xde, 0x74, 0x67, 0x25, 0x06, 0x78, 0x68, 0xd9, 0x64, 0x7d, 0x1a, 0x5d, 0x07, 0xf5,
+                0x10, 0xbb, 0x09, 0xaa, 0x8d, 0x05, 0xef, 0xaa,
+            ]
+        );
+
+        let mut out = [0u8; 16];
+        counter_mode(&mut prf, b"", b"", &mut out);
+        assert_eq!(
+            out,
+            [
+                0x91, 0xde, 0x66, 0xc2, 0xdd, 0x1d, 0x2a, 0xf8, 0x2d, 0x85, 0x2c, 0x9f, 0x4d, 0x85,
+                0x8b, 0xb0,
+            ]
+        );
+    }
+
+    // output of the pyca/cryptography KBKDFHMAC implementation given the
+    // fixed input data, with a 32 bits counter before it. The NIST CAVP
+    // KBKDF response files use this form, but are not included here.
+    #[test]
+    fn hmac_sha256_fixed_input() {
+        let key: [u8; 32] = core::array::from_fn(|i| i as u8);
+        let fixed_input: [u8; 60] = core::array::from_fn(|i| 0x40 + i as u8);
+        let mut prf = Hmac::new(Sha256::new(), &key);
+
+        let mut out = [0u8; 40];
+        counter_mode_fixed_input(&mut prf, &fixed_input, &mut out);
+        assert_eq!(
+            out,
+            [
+                0x46, 0xe2, 0xbe, 0x6e, 0xa1, 0x75, 0xd2, 0x8d, 0x93, 0x7d, 0x33, 0x13, 0x6f, 0xe4,
+                0xa2, 0x99, 0x7e, 0x27, 0x6d, 0x87, 0xcc, 0x06, 0xdb, 0xa5, 0x66, 0x3e, 0x0f, 0x75,
+                0xec, 0x29, 0xf6, 0x1b, 0xc8, 0x0f, 0x12, 0x05, 0xd1, 0x53, 0xc3, 0x29,
+            ]
+        );
+
+        // counter_mode is the fixed input form with its own encoding
+        let mut fixed_input = std::vec::Vec::new();
+        fixed_input.extend_from_slice(b"label\0context");
+        fixed_input.extend_from_slice(&256u32.to_be_bytes());
+        let mut expected = [0u8; 32];
+        counter_mode(&mut prf, b"label", b"context", &mut expected);
+        counter_mode_fixed_input(&mut prf, &fixed_input, &mut out[..32]);
+        assert_eq!(out[..32], expected);
+    }
+}
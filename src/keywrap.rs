@@ -0,0 +1,280 @@
+//! AES Key Wrap [RFC 3394][1], to encrypt keys with a key-encryption key (KEK)
+//!
+//! The wrapped key is 8 bytes longer than the key, the extra bytes being
+//! the integrity check value verified when unwrapping. The KEK is an AES
+//! key of 16, 24 or 32 bytes, and the wrapped key needs to be a multiple
+//! of 8 bytes, of at least 16 bytes.
+//!
+//! # Examples
+//!
+//! ```
+//! use cryptoxide::keywrap;
+//!
+//! let kek = [1u8; 32];
+//! let key = [2u8; 32];
+//! let wrapped = keywrap::try_wrap(&kek, &key).unwrap();
+//! assert_eq!(wrapped.len(), 40);
+//! assert_eq!(keywrap::try_unwrap(&kek, &wrapped).unwrap(), key);
+//! ```
+//!
+//! [1]: <https://datatracker.ietf.org/doc/html/rfc3394>
+
+use crate::aes::{Aes, BLOCK_LENGTH};
+use crate::util::{fixed_time_eq, secure_memset};
+use alloc::vec::Vec;
+
+/// Default initial value of RFC 3394, checked when unwrapping
+const DEFAULT_IV: [u8; 8] = [0xa6; 8];
+
+/// Error returned by [`try_wrap`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapError {
+    /// The KEK is not 16, 24 or 32 bytes long
+    InvalidKek,
+    /// The key is not a multiple of 8 bytes of at least 16 bytes
+    InvalidLength,
+}
+
+impl core::fmt::Display for WrapError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            WrapError::InvalidKek => write!(f, "invalid KEK length"),
+            WrapError::InvalidLength => write!(f, "invalid key length"),
+        }
+    }
+}
+
+/// Error returned by [`unwrap`] and [`try_unwrap`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnwrapError {
+    /// The KEK is not 16, 24 or 32 bytes long, only returned by [`try_unwrap`]
+    InvalidKek,
+    /// The wrapped key is not a multiple of 8 bytes of at least 24 bytes
+    InvalidLength,
+    /// The integrity check failed: the KEK is not the one used to wrap,
+    /// or the wrapped key has been modified
+    IntegrityCheckFailed,
+}
+
+impl core::fmt::Display for UnwrapError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            UnwrapError::InvalidKek => write!(f, "invalid KEK length"),
+            UnwrapError::InvalidLength => write!(f, "invalid wrapped key length"),
+            UnwrapError::IntegrityCheckFailed => write!(f, "key unwrap integrity check failed"),
+        }
+    }
+}
+
+/// Wrap `key` with the key-encryption key `kek`
+///
+/// Panics if `kek` is not 16, 24 or 32 bytes long, or if `key` is
+/// not a multiple of 8 bytes of at least 16 bytes, see [`try_wrap`]
+/// for a fallible version.
+#[cfg_attr(feature = "no-panic", deprecated(note = "use try_wrap"))]
+pub fn wrap(kek: &[u8], key: &[u8]) -> Vec<u8> {
+    match try_wrap(kek, key) {
+        Ok(wrapped) => wrapped,
+        Err(WrapError::InvalidKek) => panic!("KEK of 16, 24 or 32 bytes"),
+        Err(WrapError::InvalidLength) => panic!("key of a multiple of 8 bytes, of at least 16"),
+    }
+}
+
+/// Wrap `key` with the key-encryption key `kek`, or return an error if
+/// `kek` is not 16, 24 or 32 bytes long, or if `key` is not a multiple of
+/// 8 bytes of at least 16 bytes
+pub fn try_wrap(kek: &[u8], key: &[u8]) -> Result<Vec<u8>, WrapError> {
+    let n = key.len() / 8;
+    if n < 2 || 8 * n != key.len() {
+        return Err(WrapError::InvalidLength);
+    }
+    let aes = Aes::try_new(kek).ok_or(WrapError::InvalidKek)?;
+
+    let mut out = Vec::with_capacity(key.len() + 8);
+    out.extend_from_slice(&DEFAULT_IV);
+    out.extend_from_slice(key);
+
+    let mut block = [0u8; BLOCK_LENGTH];
+    for j in 0..6 {
+        for i in 1..=n {
+            block[0..8].copy_from_slice(&out[0..8]);
+            block[8..16].copy_from_slice(&out[8 * i..8 * i + 8]);
+            aes.encrypt_block(&mut block);
+            let t = ((n * j + i) as u64).to_be_bytes();
+            for k in 0..8 {
+                out[k] = block[k] ^ t[k];
+            }
+            out[8 * i..8 * i + 8].copy_from_slice(&block[8..16]);
+        }
+    }
+    secure_memset(&mut block, 0);
+    Ok(out)
+}
+
+/// Unwrap a key wrapped with the key-encryption key `kek`
+///
+/// The integrity check value is compared in constant time. Panics if
+/// `kek` is not 16, 24 or 32 bytes long, see [`try_unwrap`] for a version
+/// returning [`UnwrapError::InvalidKek`] instead.
+#[cfg_attr(feature = "no-panic", deprecated(note = "use try_unwrap"))]
+pub fn unwrap(kek: &[u8], wrapped: &[u8]) -> Result<Vec<u8>, UnwrapError> {
+    match try_unwrap(kek, wrapped) {
+        Err(UnwrapError::InvalidKek) => panic!("KEK of 16, 24 or 32 bytes"),
+        result => result,
+    }
+}
+
+/// Unwrap a key wrapped with the key-encryption key `kek`, or return
+/// [`UnwrapError::InvalidKek`] if `kek` is not 16, 24 or 32 bytes long
+pub fn try_unwrap(kek: &[u8], wrapped: &[u8]) -> Result<Vec<u8>, UnwrapError> {
+    let aes = Aes::try_new(kek).ok_or(UnwrapError::InvalidKek)?;
+    // number of 8 bytes blocks of the key
+    let n = (wrapped.len() / 8).saturating_sub(1);
+    if n < 2 || 8 * (n + 1) != wrapped.len() {
+        return Err(UnwrapError::InvalidLength);
+    }
+
+    let mut a = [0u8; 8];
+    a.copy_from_slice(&wrapped[0..8]);
+    let mut key = wrapped[8..].to_vec();
+
+    let mut block = [0u8; BLOCK_LENGTH];
+    for j in (0..6).rev() {
+        for i in (1..=n).rev() {
+            let t = ((n * j + i) as u64).to_be_bytes();
+            for k in 0..8 {
+                block[k] = a[k] ^ t[k];
+            }
+            block[8..16].copy_from_slice(&key[8 * (i - 1)..8 * i]);
+            aes.decrypt_block(&mut block);
+            a.copy_from_slice(&block[0..8]);
+            key[8 * (i - 1)..8 * i].copy_from_slice(&block[8..16]);
+        }
+    }
+    secure_memset(&mut block, 0);
+
+    if fixed_time_eq(&a, &DEFAULT_IV) {
+        Ok(key)
+    } else {
+        secure_memset(&mut key, 0);
+        Err(UnwrapError::IntegrityCheckFailed)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(feature = "no-panic", allow(deprecated))]
+mod tests {
+    use super::*;
+
+    fn kek() -> Vec<u8> {
+        (0..32).collect()
+    }
+
+    const KEY_DATA: [u8; 32] = [
+        0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee,
+        0xff, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+        0x0e, 0x0f,
+    ];
+
+    fn check(kek: &[u8], key: &[u8], expected: &[u8]) {
+        let wrapped = wrap(kek, key);
+        assert_eq!(&wrapped[..], expected);
+        assert_eq!(unwrap(kek, &wrapped).unwrap(), key);
+    }
+
+    #[test]
+    fn rfc3394_vectors() {
+        // 4.1 Wrap 128 bits of Key Data with a 128-bit KEK
+        check(
+            &kek()[0..16],
+            &KEY_DATA[0..16],
+            &[
+                0x1f, 0xa6, 0x8b, 0x0a, 0x81, 0x12, 0xb4, 0x47, 0xae, 0xf3, 0x4b, 0xd8, 0xfb, 0x5a,
+                0x7b, 0x82, 0x9d, 0x3e, 0x86, 0x23, 0x71, 0xd2, 0xcf, 0xe5,
+            ],
+        );
+        // 4.3 Wrap 128 bits of Key Data with a 256-bit KEK
+        check(
+            &kek(),
+            &KEY_DATA[0..16],
+            &[
+                0x64, 0xe8, 0xc3, 0xf9, 0xce, 0x0f, 0x5b, 0xa2, 0x63, 0xe9, 0x77, 0x79, 0x05, 0x81,
+                0x8a, 0x2a, 0x93, 0xc8, 0x19, 0x1e, 0x7d, 0x6e, 0x8a, 0xe7,
+            ],
+        );
+        // 4.6 Wrap 256 bits of Key Data with a 256-bit KEK
+        check(
+            &kek(),
+            &KEY_DATA,
+            &[
+                0x28, 0xc9, 0xf4, 0x04, 0xc4, 0xb8, 0x10, 0xf4, 0xcb, 0xcc, 0xb3, 0x5c, 0xfb, 0x87,
+                0xf8, 0x26, 0x3f, 0x57, 0x86, 0xe2, 0xd8, 0x0e, 0xd3, 0x26, 0xcb, 0xc7, 0xf0, 0xe7,
+                0x1a, 0x99, 0xf4, 0x3b, 0xfb, 0x98, 0x8b, 0x9b, 0x7a, 0x02, 0xdd, 0x21,
+            ],
+        );
+    }
+
+    #[test]
+    fn unwrap_rejects() {
+        let wrapped = wrap(&kek(), &KEY_DATA);
+        for i in 0..wrapped.len() {
+            let mut tampered = wrapped.clone();
+            tampered[i] ^= 0x10;
+            assert_eq!(
+                unwrap(&kek(), &tampered),
+                Err(UnwrapError::IntegrityCheckFailed)
+            );
+        }
+        assert_eq!(
+            unwrap(&kek()[0..16], &wrapped),
+            Err(UnwrapError::IntegrityCheckFailed)
+        );
+        assert_eq!(
+            unwrap(&kek(), &wrapped[0..36]),
+            Err(UnwrapError::InvalidLength)
+        );
+        for len in [0, 4, 8, 16, 23] {
+            assert_eq!(
+                unwrap(&kek(), &wrapped[0..len]),
+                Err(UnwrapError::InvalidLength)
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_kek() {
+        for len in [0, 8, 15, 20, 33] {
+            let kek = [1u8; 33];
+            assert_eq!(try_wrap(&kek[..len], &KEY_DATA), Err(WrapError::InvalidKek));
+            assert_eq!(
+                try_unwrap(&kek[..len], &[0u8; 40]),
+                Err(UnwrapError::InvalidKek)
+            );
+        }
+        assert_eq!(
+            try_wrap(&kek(), &KEY_DATA[..8]),
+            Err(WrapError::InvalidLength)
+        );
+        assert_eq!(
+            try_wrap(&kek(), &KEY_DATA[..20]),
+            Err(WrapError::InvalidLength)
+        );
+        assert_eq!(try_wrap(&kek(), &KEY_DATA), Ok(wrap(&kek(), &KEY_DATA)));
+        assert_eq!(
+            try_unwrap(&kek(), &wrap(&kek(), &KEY_DATA)),
+            Ok(KEY_DATA.to_vec())
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrap_panics_on_invalid_kek() {
+        wrap(&[0u8; 20], &KEY_DATA);
+    }
+
+    #[test]
+    #[should_panic]
+    fn unwrap_panics_on_invalid_kek() {
+        let _ = unwrap(&[0u8; 20], &[0u8; 40]);
+    }
+}
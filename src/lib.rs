@@ -18,11 +18,38 @@
 //! * Maintained
 //! * Extended ED25519 support for extended secret key (64 bytes) support
 //! * Proper implementation of ChaChaPoly1305
-//! * Many cryptographic algorithms removed: AES, Blowfish, Fortuna, RC4, RIPEMD160, Whirlpool, MD5, SHA1.
+//! * Many cryptographic algorithms removed: AES, Blowfish, Fortuna, RC4, MD5, SHA1.
 //!
 //! As with everything cryptographic implementations, please make sure it suits your security requirements,
 //! and review and audit before using.
 //!
+//! # `no-panic` feature
+//!
+//! The functions taking keys, signatures or parameters of a size or value only
+//! known at runtime panic on invalid input. Each of them has a `try_` version
+//! returning an error or `None` instead, always available:
+//!
+//! * `ed25519`: `keypair`, `signature`, `signature_extended`, `to_public`, `verify`,
+//!   `verify_strict`, `signature_prehashed`, `verify_prehashed` and `exchange`
+//! * `blake2b::Blake2b`: `new` and `new_keyed`, and `blake2b` replaced by `try_new_keyed`
+//! * `pbkdf2`: `pbkdf2`, `pbkdf2_u64` and `pbkdf2_with_scratch`
+//! * `digest::Digest`: `result`
+//! * `aes::Aes`: `new`
+//! * `aesgcmsiv::AesGcmSiv`: `new`
+//! * `keywrap`: `wrap` and `unwrap`
+//!
+//! Enabling the `no-panic` feature marks the panicking versions as deprecated, so
+//! that any use of them is reported at build time. The signatures don't change,
+//! as cargo features need to be additive. Other functions, like `scrypt` or
+//! `ed25519::verify_any`, still panic on invalid parameters.
+//!
+//! # `force-portable` feature
+//!
+//! The SIMD implementations of blake2, sha256, chacha, ghash and crc32c are
+//! selected at compile time from the enabled target features. Enabling the
+//! `force-portable` feature always uses the portable implementations instead;
+//! the [`cpu`] module reports which implementations are in use.
+//!
 
 #![warn(clippy::all)]
 #![allow(clippy::unreadable_literal)]
@@ -39,13 +66,27 @@
 #[cfg(feature = "with-bench")]
 extern crate test;
 
-#[cfg(not(feature = "std"))]
+#[cfg(any(feature = "alloc", test))]
 extern crate alloc;
 
-#[cfg(test)]
+#[cfg(any(feature = "std", test))]
 #[macro_use]
 extern crate std;
 
+#[cfg(feature = "aes")]
+pub mod aes;
+
+#[cfg(feature = "aesgcmsiv")]
+pub mod aesgcmsiv;
+
+#[cfg(feature = "argon2")]
+pub mod argon2;
+
+#[cfg(feature = "bcrypt")]
+pub mod bcrypt;
+#[cfg(feature = "bcrypt")]
+mod blowfish;
+
 #[cfg(feature = "blake2")]
 pub mod blake2;
 
@@ -55,6 +96,8 @@ pub mod blake2b;
 #[cfg(feature = "blake2")]
 pub mod blake2s;
 
+pub mod bytes;
+
 #[cfg(feature = "chacha")]
 pub mod chacha;
 
@@ -64,37 +107,80 @@ pub mod chacha20;
 #[cfg(all(feature = "chacha", feature = "poly1305"))]
 pub mod chacha20poly1305;
 
+pub mod cpu;
+
+#[cfg(feature = "ctrdrbg")]
+pub mod ctrdrbg;
+
 #[cfg(feature = "curve25519")]
 pub mod curve25519;
+#[cfg(feature = "curve448")]
+pub mod curve448;
 pub mod digest;
 
+#[cfg(feature = "crc")]
+pub mod crc;
 #[cfg(feature = "ed25519")]
 pub mod ed25519;
+#[cfg(feature = "ed448")]
+pub mod ed448;
+#[cfg(feature = "etm")]
+pub mod etm;
+#[cfg(feature = "ghash")]
+pub mod ghash;
 #[cfg(feature = "hkdf")]
 pub mod hkdf;
 #[cfg(feature = "hmac")]
 pub mod hmac;
+#[cfg(feature = "kdf")]
+pub mod kdf;
+#[cfg(feature = "keywrap")]
+pub mod keywrap;
 #[cfg(feature = "mac")]
 pub mod mac;
+#[cfg(feature = "merkle")]
+pub mod merkle;
 #[cfg(feature = "pbkdf2")]
 pub mod pbkdf2;
 #[cfg(feature = "poly1305")]
 pub mod poly1305;
+#[cfg(feature = "rfc6979")]
+pub mod rfc6979;
+#[cfg(feature = "ripemd160")]
+pub mod ripemd160;
 #[cfg(feature = "scrypt")]
 pub mod scrypt;
 
 #[cfg(feature = "salsa")]
 pub mod salsa20;
 
+#[cfg(all(feature = "chacha", feature = "poly1305"))]
+pub mod secretstream;
+
+pub mod selftest;
+pub use selftest::{self_test, SelfTestFailure};
+
 #[cfg(feature = "sha1")]
 pub mod sha1;
 
+#[cfg(feature = "siphash")]
+pub mod siphash;
+
 #[cfg(feature = "sha2")]
 pub mod sha2;
 
 #[cfg(feature = "sha3")]
 pub mod sha3;
 
+#[cfg(feature = "whirlpool")]
+pub mod whirlpool;
+
+#[cfg(feature = "curve25519")]
+pub mod x25519;
+
+#[cfg(feature = "chacha")]
+pub mod xchacha20;
+
 mod cryptoutil;
 mod simd;
 pub mod util;
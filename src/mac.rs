@@ -9,7 +9,8 @@
  */
 
 use crate::util::fixed_time_eq;
-use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec::Vec};
 
 /**
  * The `Mac` trait defines methods for a Message Authentication function.
@@ -29,10 +30,29 @@ pub trait Mac {
      */
     fn reset(&mut self);
 
+    /**
+     * Process all the data of a reader, returning the number of bytes read.
+     *
+     * The data is read in chunks until the end of the reader; on error, the
+     * data read so far has been processed.
+     */
+    #[cfg(feature = "std")]
+    fn input_reader<R: std::io::Read + ?Sized>(&mut self, r: &mut R) -> std::io::Result<u64>
+    where
+        Self: Sized,
+    {
+        crate::cryptoutil::read_chunks(r, |chunk| self.input(chunk))
+    }
+
     /**
      * Obtain the result of a Mac computation as a `MacResult`.
      */
-    fn result(&mut self) -> MacResult;
+    #[cfg(feature = "alloc")]
+    fn result(&mut self) -> MacResult {
+        let mut code: Vec<u8> = alloc::vec![0; self.output_bytes()];
+        self.raw_result(&mut code);
+        MacResult::new_from_owned(code)
+    }
 
     /**
      * Obtain the result of a Mac computation as [u8]. This method should be used very carefully
@@ -45,15 +65,195 @@ pub trait Mac {
      * Get the size of the Mac code, in bytes.
      */
     fn output_bytes(&self) -> usize;
+
+    /**
+     * Obtain the result of the Mac computation and compare its leading bytes with
+     * a truncated code in constant time, as used by protocols with truncated tags.
+     *
+     * Return false if the expected code is empty or longer than the full Mac code.
+     * The length of the truncation is not secret, and should be fixed by the protocol.
+     */
+    #[cfg(feature = "alloc")]
+    fn verify_truncated(&mut self, expected: &[u8]) -> bool {
+        let result = self.result();
+        let code = result.code();
+        if expected.is_empty() || expected.len() > code.len() {
+            return false;
+        }
+        fixed_time_eq(&code[..expected.len()], expected)
+    }
+
+    /**
+     * Obtain the result of the Mac computation and compare it with the expected
+     * code in constant time.
+     *
+     * Return `Err(MacError::LengthMismatch)` if the expected code is not of the
+     * size of the Mac code, and `Err(MacError::Mismatch)` if the codes differ.
+     * Panics if the Mac code is longer than [`MAX_DYN_OUTPUT_BYTES`].
+     */
+    fn verify_slice(&mut self, expected: &[u8]) -> Result<(), MacError> {
+        let len = self.output_bytes();
+        assert!(len <= MAX_DYN_OUTPUT_BYTES);
+        if expected.len() != len {
+            return Err(MacError::LengthMismatch);
+        }
+        let mut code = [0u8; MAX_DYN_OUTPUT_BYTES];
+        self.raw_result(&mut code[..len]);
+        if fixed_time_eq(&code[..len], expected) {
+            Ok(())
+        } else {
+            Err(MacError::Mismatch)
+        }
+    }
+}
+
+/// Error returned by [`Mac::verify_slice`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MacError {
+    /// The expected code is not equal to the computed code
+    Mismatch,
+    /// The expected code is not of the size of the Mac code
+    LengthMismatch,
+}
+
+impl core::fmt::Display for MacError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MacError::Mismatch => write!(f, "mac code mismatch"),
+            MacError::LengthMismatch => write!(f, "invalid mac code length"),
+        }
+    }
+}
+
+/// Maximum size in bytes of the code supported by [`Mac::verify_slice`] and [`DynMac::verify`]
+pub const MAX_DYN_OUTPUT_BYTES: usize = 64;
+
+/**
+ * Object-safe version of the `Mac` trait, to use a Mac chosen at runtime
+ * as a `Box<dyn DynMac>` or `&mut dyn DynMac`.
+ *
+ * It is implemented for every type implementing `Mac`.
+ */
+pub trait DynMac {
+    /**
+     * Process input data.
+     */
+    fn input(&mut self, data: &[u8]);
+
+    /**
+     * Reset the Mac state to begin processing another input stream.
+     */
+    fn reset(&mut self);
+
+    /**
+     * Write the result of the Mac computation in `out`, which needs to be of
+     * the size of the Mac code. The same care as `Mac::raw_result` applies.
+     */
+    fn raw_result_into(&mut self, out: &mut [u8]);
+
+    /**
+     * Get the size of the Mac code, in bytes.
+     */
+    fn output_bytes(&self) -> usize;
+
+    /**
+     * Obtain the result of the Mac computation and compare it with the
+     * expected code in constant time.
+     *
+     * Return false if the expected code is not of the size of the Mac code.
+     * Panics if the Mac code is longer than [`MAX_DYN_OUTPUT_BYTES`].
+     */
+    fn verify(&mut self, expected: &[u8]) -> bool;
+}
+
+impl<M: Mac> DynMac for M {
+    fn input(&mut self, data: &[u8]) {
+        Mac::input(self, data)
+    }
+
+    fn reset(&mut self) {
+        Mac::reset(self)
+    }
+
+    fn raw_result_into(&mut self, out: &mut [u8]) {
+        assert!(out.len() == Mac::output_bytes(self));
+        self.raw_result(out)
+    }
+
+    fn output_bytes(&self) -> usize {
+        Mac::output_bytes(self)
+    }
+
+    fn verify(&mut self, expected: &[u8]) -> bool {
+        self.verify_slice(expected).is_ok()
+    }
+}
+
+/// Size in bytes of the tag of the [`OneTimeMac`] implementations
+pub const ONE_TIME_MAC_LENGTH: usize = 16;
+
+/**
+ * The `OneTimeMac` trait defines methods for a one-time authenticator, like
+ * Poly1305 or GHASH, whose key must never authenticate more than one message.
+ *
+ * Unlike `Mac`, there is no way to reset the state: the tag is obtained by
+ * consuming the context, so that a context can't authenticate anything else
+ * once its tag has been produced. This doesn't prevent cloning a context, and
+ * the key itself must not be used for another context.
+ */
+pub trait OneTimeMac {
+    /**
+     * Process input data.
+     */
+    fn input(&mut self, data: &[u8]);
+
+    /**
+     * Compute the tag of the data processed, consuming the context.
+     */
+    fn finalize(self) -> [u8; ONE_TIME_MAC_LENGTH]
+    where
+        Self: Sized;
+}
+
+/**
+ * Object-safe version of the `OneTimeMac` trait, to use a one-time MAC chosen
+ * at runtime as a `Box<dyn DynOneTimeMac>`.
+ *
+ * It is implemented for every type implementing `OneTimeMac`.
+ */
+#[cfg(feature = "alloc")]
+pub trait DynOneTimeMac {
+    /**
+     * Process input data.
+     */
+    fn input(&mut self, data: &[u8]);
+
+    /**
+     * Compute the tag of the data processed, consuming the boxed context.
+     */
+    fn finalize_boxed(self: Box<Self>) -> [u8; ONE_TIME_MAC_LENGTH];
+}
+
+#[cfg(feature = "alloc")]
+impl<M: OneTimeMac> DynOneTimeMac for M {
+    fn input(&mut self, data: &[u8]) {
+        OneTimeMac::input(self, data)
+    }
+
+    fn finalize_boxed(self: Box<Self>) -> [u8; ONE_TIME_MAC_LENGTH] {
+        (*self).finalize()
+    }
 }
 
 /**
  * A `MacResult` wraps a Mac code and provides a safe Eq implementation that runs in fixed time.
  */
+#[cfg(feature = "alloc")]
 pub struct MacResult {
     code: Vec<u8>,
 }
 
+#[cfg(feature = "alloc")]
 impl MacResult {
     /**
      * Create a new `MacResult`.
@@ -78,8 +278,47 @@ impl MacResult {
     pub fn code(&self) -> &[u8] {
         &self.code[..]
     }
+
+    /**
+     * Create a new `MacResult` keeping only the first `n` bytes of the code.
+     *
+     * Panics if `n` is zero or greater than the length of the code.
+     */
+    pub fn truncated(&self, n: usize) -> MacResult {
+        assert!(n > 0 && n <= self.code.len());
+        MacResult::new(&self.code[..n])
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<&[u8]> for MacResult {
+    fn from(code: &[u8]) -> Self {
+        MacResult::new(code)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<Vec<u8>> for MacResult {
+    fn from(code: Vec<u8>) -> Self {
+        MacResult::new_from_owned(code)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> From<[u8; N]> for MacResult {
+    fn from(code: [u8; N]) -> Self {
+        MacResult::new(&code)
+    }
 }
 
+#[cfg(feature = "alloc")]
+impl AsRef<[u8]> for MacResult {
+    fn as_ref(&self) -> &[u8] {
+        self.code()
+    }
+}
+
+#[cfg(feature = "alloc")]
 impl PartialEq for MacResult {
     fn eq(&self, x: &MacResult) -> bool {
         let lhs = self.code();
@@ -88,4 +327,86 @@ impl PartialEq for MacResult {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl Eq for MacResult {}
+
+#[cfg(all(
+    test,
+    feature = "alloc",
+    feature = "hmac",
+    feature = "blake2",
+    feature = "sha2"
+))]
+#[cfg_attr(feature = "no-panic", allow(deprecated))]
+mod tests {
+    use super::{DynMac, MacResult};
+    use crate::blake2b::Blake2b;
+    use crate::hmac::Hmac;
+    use crate::sha2::Sha256;
+    use alloc::boxed::Box;
+
+    fn mac_by_name(name: &str, key: &[u8]) -> Option<Box<dyn DynMac>> {
+        match name {
+            "hmac-sha256" => Some(Box::new(Hmac::new(Sha256::new(), key))),
+            "blake2b-256" => Some(Box::new(Blake2b::new_keyed(32, key))),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn dispatch() {
+        let key = [0x0bu8; 20];
+        let tests: [(&str, [u8; 32]); 2] = [
+            (
+                // RFC 4231 test case 1
+                "hmac-sha256",
+                [
+                    0xb0, 0x34, 0x4c, 0x61, 0xd8, 0xdb, 0x38, 0x53, 0x5c, 0xa8, 0xaf, 0xce, 0xaf,
+                    0x0b, 0xf1, 0x2b, 0x88, 0x1d, 0xc2, 0x00, 0xc9, 0x83, 0x3d, 0xa7, 0x26, 0xe9,
+                    0x37, 0x6c, 0x2e, 0x32, 0xcf, 0xf7,
+                ],
+            ),
+            (
+                "blake2b-256",
+                [
+                    0xe1, 0xe6, 0xc2, 0x9f, 0xc7, 0xf7, 0x88, 0xbc, 0x58, 0xbe, 0xbb, 0x86, 0x48,
+                    0xdf, 0x85, 0x5a, 0x93, 0x95, 0x0f, 0x06, 0x04, 0x79, 0x70, 0xab, 0x06, 0x90,
+                    0x6b, 0x6b, 0x40, 0x7e, 0x4d, 0xc7,
+                ],
+            ),
+        ];
+        for (name, expected) in tests.iter() {
+            let mut mac = mac_by_name(name, &key).unwrap();
+            assert_eq!(mac.output_bytes(), 32);
+            mac.input(b"Hi ");
+            mac.input(b"There");
+            let mut out = [0u8; 32];
+            mac.raw_result_into(&mut out);
+            assert_eq!(&out, expected, "{}", name);
+
+            let verify = |data: &[u8], code: &[u8]| {
+                let mut mac = mac_by_name(name, &key).unwrap();
+                mac.input(data);
+                mac.verify(code)
+            };
+            assert!(verify(b"Hi There", expected));
+            assert!(!verify(b"Hi There", &expected[..31]));
+            assert!(!verify(b"Hi there", expected));
+        }
+        assert!(mac_by_name("md5", &key).is_none());
+    }
+
+    #[test]
+    fn mac_result_conversions() {
+        let code = [7u8; 32];
+        let a = MacResult::from(code);
+        let b = MacResult::from(&code[..]);
+        let c = MacResult::from(code.to_vec());
+        assert!(a == b && b == c);
+        assert_eq!(a.as_ref(), &code[..]);
+        // a code of any length is accepted, and compared with its length
+        let short = MacResult::from(&code[..16]);
+        assert_eq!(short.as_ref().len(), 16);
+        assert!(short != a);
+    }
+}
@@ -0,0 +1,331 @@
+//! Binary Merkle trees over a [`Digest`]
+//!
+//! The leaves are hashed with a `0x00` prefix and the internal nodes with a
+//! `0x01` prefix, so that a leaf can never be taken for an internal node.
+//! The [`Mode`] selects how a level with an odd number of nodes is handled.
+//! [`Mode::Rfc6962`] should be used unless compatibility requires
+//! [`Mode::DuplicateLast`], whose root is ambiguous.
+//!
+//! # Examples
+//!
+//! ```
+//! use cryptoxide::{merkle::{inclusion_proof, root, verify_inclusion, Mode}, sha2::Sha256};
+//!
+//! let leaves: [&[u8]; 3] = [b"a", b"b", b"c"];
+//! let tree_root = root(Sha256::new(), Mode::Rfc6962, &leaves);
+//!
+//! let proof = inclusion_proof(Sha256::new(), Mode::Rfc6962, &leaves, 2).unwrap();
+//! assert!(verify_inclusion(Sha256::new(), Mode::Rfc6962, b"c", 2, 3, &proof, &tree_root));
+//! ```
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::digest::Digest;
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Shape of the tree when a level has an odd number of nodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// The last node of a level with an odd number of nodes is paired with
+    /// itself, like in the bitcoin transaction tree
+    ///
+    /// This makes the root ambiguous (CVE-2012-2459): the leaves
+    /// `[a, b, c]` and `[a, b, c, c]` have the same root, so the root alone
+    /// doesn't commit to the number of leaves. Only use this mode for
+    /// compatibility, and prefer [`Mode::Rfc6962`] otherwise.
+    DuplicateLast,
+    /// The tree of RFC 6962 (Certificate Transparency): the left subtree
+    /// is the largest complete tree, and the last node of an odd level is
+    /// promoted to the next level unchanged
+    Rfc6962,
+}
+
+fn hash_leaf<D: Digest>(digest: &mut D, leaf: &[u8]) -> Vec<u8> {
+    let mut out = vec![0u8; digest.output_bytes()];
+    digest.reset();
+    digest.input(&[LEAF_PREFIX]);
+    digest.input(leaf);
+    digest.result(&mut out);
+    out
+}
+
+fn hash_node<D: Digest>(digest: &mut D, left: &[u8], right: &[u8]) -> Vec<u8> {
+    let mut out = vec![0u8; digest.output_bytes()];
+    digest.reset();
+    digest.input(&[NODE_PREFIX]);
+    digest.input(left);
+    digest.input(right);
+    digest.result(&mut out);
+    out
+}
+
+// largest power of 2 strictly smaller than n, for n > 1
+fn split_point(n: usize) -> usize {
+    let mut k = 1;
+    while k << 1 < n {
+        k <<= 1;
+    }
+    k
+}
+
+fn rfc6962_root<D: Digest>(digest: &mut D, leaves: &[&[u8]]) -> Vec<u8> {
+    if leaves.len() == 1 {
+        return hash_leaf(digest, leaves[0]);
+    }
+    let k = split_point(leaves.len());
+    let left = rfc6962_root(digest, &leaves[..k]);
+    let right = rfc6962_root(digest, &leaves[k..]);
+    hash_node(digest, &left, &right)
+}
+
+fn rfc6962_path<D: Digest>(
+    digest: &mut D,
+    leaves: &[&[u8]],
+    index: usize,
+    proof: &mut Vec<Vec<u8>>,
+) {
+    if leaves.len() == 1 {
+        return;
+    }
+    let k = split_point(leaves.len());
+    if index < k {
+        rfc6962_path(digest, &leaves[..k], index, proof);
+        proof.push(rfc6962_root(digest, &leaves[k..]));
+    } else {
+        rfc6962_path(digest, &leaves[k..], index - k, proof);
+        proof.push(rfc6962_root(digest, &leaves[..k]));
+    }
+}
+
+fn next_level<D: Digest>(digest: &mut D, level: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    level
+        .chunks(2)
+        .map(|pair| {
+            let right = pair.get(1).unwrap_or(&pair[0]);
+            hash_node(digest, &pair[0], right)
+        })
+        .collect()
+}
+
+/// Compute the root of the Merkle tree of the leaves
+///
+/// The result has the size of the digest output. The root of an empty
+/// tree is the hash of the empty string, as in RFC 6962.
+pub fn root<D: Digest>(mut digest: D, mode: Mode, leaves: &[&[u8]]) -> Vec<u8> {
+    if leaves.is_empty() {
+        let mut out = vec![0u8; digest.output_bytes()];
+        digest.reset();
+        digest.result(&mut out);
+        return out;
+    }
+    match mode {
+        Mode::Rfc6962 => rfc6962_root(&mut digest, leaves),
+        Mode::DuplicateLast => {
+            let mut level: Vec<Vec<u8>> =
+                leaves.iter().map(|l| hash_leaf(&mut digest, l)).collect();
+            while level.len() > 1 {
+                level = next_level(&mut digest, &level);
+            }
+            level.pop().unwrap()
+        }
+    }
+}
+
+/// Generate the proof of inclusion of the leaf at `index` in the tree
+///
+/// The proof is the list of the sibling hashes from the leaf up to the root,
+/// to be checked with [`verify_inclusion`]. Return `None` if `index` is not
+/// the index of a leaf.
+pub fn inclusion_proof<D: Digest>(
+    mut digest: D,
+    mode: Mode,
+    leaves: &[&[u8]],
+    index: usize,
+) -> Option<Vec<Vec<u8>>> {
+    if index >= leaves.len() {
+        return None;
+    }
+    let mut proof = Vec::new();
+    match mode {
+        Mode::Rfc6962 => rfc6962_path(&mut digest, leaves, index, &mut proof),
+        Mode::DuplicateLast => {
+            let mut level: Vec<Vec<u8>> =
+                leaves.iter().map(|l| hash_leaf(&mut digest, l)).collect();
+            let mut i = index;
+            while level.len() > 1 {
+                let sibling = level.get(i ^ 1).unwrap_or(&level[i]);
+                proof.push(sibling.clone());
+                level = next_level(&mut digest, &level);
+                i >>= 1;
+            }
+        }
+    }
+    Some(proof)
+}
+
+/// Check a proof of inclusion of `leaf` at `index` in a tree of `tree_size`
+/// leaves with the given root
+pub fn verify_inclusion<D: Digest>(
+    mut digest: D,
+    mode: Mode,
+    leaf: &[u8],
+    index: usize,
+    tree_size: usize,
+    proof: &[Vec<u8>],
+    root: &[u8],
+) -> bool {
+    if index >= tree_size {
+        return false;
+    }
+    let mut hash = hash_leaf(&mut digest, leaf);
+    match mode {
+        Mode::Rfc6962 => {
+            // RFC 9162 section 2.1.3.2
+            let mut f = index;
+            let mut s = tree_size - 1;
+            for p in proof {
+                if s == 0 {
+                    return false;
+                }
+                if f & 1 == 1 || f == s {
+                    hash = hash_node(&mut digest, p, &hash);
+                    if f & 1 == 0 {
+                        while f & 1 == 0 && f != 0 {
+                            f >>= 1;
+                            s >>= 1;
+                        }
+                    }
+                } else {
+                    hash = hash_node(&mut digest, &hash, p);
+                }
+                f >>= 1;
+                s >>= 1;
+            }
+            if s != 0 {
+                return false;
+            }
+        }
+        Mode::DuplicateLast => {
+            let mut f = index;
+            let mut size = tree_size;
+            for p in proof {
+                if size == 1 {
+                    return false;
+                }
+                hash = if f & 1 == 1 {
+                    hash_node(&mut digest, p, &hash)
+                } else {
+                    hash_node(&mut digest, &hash, p)
+                };
+                f >>= 1;
+                size -= size / 2;
+            }
+            if size != 1 {
+                return false;
+            }
+        }
+    }
+    hash[..] == root[..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha2::Sha256;
+
+    fn sha256(parts: &[&[u8]]) -> Vec<u8> {
+        let mut d = Sha256::new();
+        for p in parts {
+            d.input(p);
+        }
+        let mut out = vec![0u8; 32];
+        d.result(&mut out);
+        out
+    }
+
+    const LEAVES: [&[u8]; 5] = [b"alpha", b"beta", b"gamma", b"delta", b"epsilon"];
+
+    #[test]
+    fn four_leaves() {
+        let h: Vec<Vec<u8>> = LEAVES[..4].iter().map(|l| sha256(&[&[0], l])).collect();
+        let h01 = sha256(&[&[1], &h[0], &h[1]]);
+        let h23 = sha256(&[&[1], &h[2], &h[3]]);
+        let expected = sha256(&[&[1], &h01, &h23]);
+        assert_eq!(
+            expected,
+            [
+                0x42, 0xfc, 0x54, 0xee, 0xb6, 0x35, 0x2f, 0x90, 0xcc, 0x81, 0xfd, 0xd5, 0x79, 0x12,
+                0x92, 0xcc, 0xa3, 0x97, 0x4a, 0x16, 0x82, 0x08, 0xb3, 0x95, 0xb0, 0x6a, 0x76, 0x24,
+                0x0b, 0x24, 0x88, 0x4d,
+            ]
+        );
+
+        for mode in [Mode::DuplicateLast, Mode::Rfc6962].iter() {
+            assert_eq!(root(Sha256::new(), *mode, &LEAVES[..4]), expected);
+            assert_eq!(
+                inclusion_proof(Sha256::new(), *mode, &LEAVES[..4], 2).unwrap(),
+                vec![h[3].clone(), h01.clone()]
+            );
+        }
+    }
+
+    #[test]
+    fn odd_levels() {
+        let h: Vec<Vec<u8>> = LEAVES.iter().map(|l| sha256(&[&[0], l])).collect();
+        let h01 = sha256(&[&[1], &h[0], &h[1]]);
+        let h23 = sha256(&[&[1], &h[2], &h[3]]);
+        let h0123 = sha256(&[&[1], &h01, &h23]);
+
+        let rfc = sha256(&[&[1], &h0123, &h[4]]);
+        assert_eq!(root(Sha256::new(), Mode::Rfc6962, &LEAVES), rfc);
+
+        let h44 = sha256(&[&[1], &h[4], &h[4]]);
+        let h4444 = sha256(&[&[1], &h44, &h44]);
+        let dup = sha256(&[&[1], &h0123, &h4444]);
+        assert_eq!(root(Sha256::new(), Mode::DuplicateLast, &LEAVES), dup);
+
+        assert_eq!(root(Sha256::new(), Mode::Rfc6962, &LEAVES[..1]), h[0]);
+        assert_eq!(root(Sha256::new(), Mode::Rfc6962, &[]), sha256(&[]));
+    }
+
+    #[test]
+    fn duplicate_last_ambiguity() {
+        // CVE-2012-2459: duplicating the last leaf doesn't change the root
+        let leaves: [&[u8]; 3] = [b"a", b"b", b"c"];
+        let duplicated: [&[u8]; 4] = [b"a", b"b", b"c", b"c"];
+        assert_eq!(
+            root(Sha256::new(), Mode::DuplicateLast, &leaves),
+            root(Sha256::new(), Mode::DuplicateLast, &duplicated)
+        );
+        assert_ne!(
+            root(Sha256::new(), Mode::Rfc6962, &leaves),
+            root(Sha256::new(), Mode::Rfc6962, &duplicated)
+        );
+    }
+
+    #[test]
+    fn proofs() {
+        for mode in [Mode::DuplicateLast, Mode::Rfc6962].iter() {
+            for n in 1..=LEAVES.len() {
+                let leaves = &LEAVES[..n];
+                let r = root(Sha256::new(), *mode, leaves);
+                for (i, leaf) in leaves.iter().enumerate() {
+                    let proof = inclusion_proof(Sha256::new(), *mode, leaves, i).unwrap();
+                    let verify = |leaf: &[u8], i: usize, size: usize, proof: &[Vec<u8>]| {
+                        verify_inclusion(Sha256::new(), *mode, leaf, i, size, proof, &r)
+                    };
+                    assert!(verify(leaf, i, n, &proof));
+                    assert!(!verify(b"other", i, n, &proof));
+                    if n > 1 {
+                        assert!(!verify(leaf, (i + 1) % n, n, &proof));
+                        assert!(!verify(leaf, i, n, &proof[1..]));
+                    }
+                }
+                assert_eq!(inclusion_proof(Sha256::new(), *mode, leaves, n), None);
+            }
+        }
+    }
+}
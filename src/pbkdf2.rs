@@ -22,9 +22,43 @@
 
 use crate::cryptoutil::{copy_memory, write_u32_be};
 use crate::mac::Mac;
+#[cfg(feature = "alloc")]
 use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
 use core::iter::repeat;
 
+/// Largest output size of the `Mac` supported by [`pbkdf2`] when building without the `alloc`
+/// feature. Use [`pbkdf2_with_scratch`] for larger outputs.
+#[cfg(not(feature = "alloc"))]
+pub const MAX_OUTPUT_BYTES: usize = 64;
+
+/// Error returned by the `try_` functions when the parameters of PBKDF2 are not valid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidParameters;
+
+impl core::fmt::Display for InvalidParameters {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid pbkdf2 parameters")
+    }
+}
+
+// Check the conditions asserted by `derive`: a non zero iteration count, a number of
+// blocks fitting in 32 bits, and a scratch buffer of at least twice the Mac output size
+fn check_parameters(
+    os: usize,
+    c: u64,
+    output_len: usize,
+    scratch_len: usize,
+) -> Result<(), InvalidParameters> {
+    if c == 0 || os == 0 || scratch_len < 2 * os {
+        return Err(InvalidParameters);
+    }
+    if output_len as u64 > u32::MAX as u64 * os as u64 {
+        return Err(InvalidParameters);
+    }
+    Ok(())
+}
+
 // Calculate a block of the output of size equal to the output_bytes of the underlying Mac function
 // `mac` - The Mac function to use
 // `salt` - the salt value to use
@@ -35,7 +69,7 @@ use core::iter::repeat;
 fn calculate_block<M: Mac>(
     mac: &mut M,
     salt: &[u8],
-    c: u32,
+    c: u64,
     idx: u32,
     scratch: &mut [u8],
     block: &mut [u8],
@@ -76,6 +110,8 @@ fn calculate_block<M: Mac>(
  * better security, so, applications that do not have a requirement to use PBKDF2 specifically
  * should consider using that function instead.
  *
+ * Without the `alloc` feature, the output size of `mac` must not exceed `MAX_OUTPUT_BYTES`.
+ *
  * # Arguments
  * * `mac` - The Pseudo Random Function to use.
  * * `salt` - The salt value to use.
@@ -83,17 +119,135 @@ fn calculate_block<M: Mac>(
  *       factor in determining the security of the derived key.
  * * `output` - The output buffer to fill with the derived key value.
  *
+ * Panics if `c` is zero, see [`try_pbkdf2`] for a fallible version.
  */
+#[cfg_attr(feature = "no-panic", deprecated(note = "use try_pbkdf2"))]
 pub fn pbkdf2<M: Mac>(mac: &mut M, salt: &[u8], c: u32, output: &mut [u8]) {
-    assert!(c > 0);
+    derive_alloc(mac, salt, c as u64, output)
+}
+
+/**
+ * Execute the PBKDF2 Key Derivation Function with a 64 bits iteration count, for the
+ * configurations and interoperable formats exceeding the range of [`pbkdf2`].
+ *
+ * Without the `alloc` feature, the output size of `mac` must not exceed `MAX_OUTPUT_BYTES`.
+ *
+ * # Arguments
+ * * `mac` - The Pseudo Random Function to use.
+ * * `salt` - The salt value to use.
+ * * `c` - The iteration count.
+ * * `output` - The output buffer to fill with the derived key value.
+ *
+ * Panics if `c` is zero, see [`try_pbkdf2_u64`] for a fallible version.
+ */
+#[cfg_attr(feature = "no-panic", deprecated(note = "use try_pbkdf2_u64"))]
+pub fn pbkdf2_u64<M: Mac>(mac: &mut M, salt: &[u8], c: u64, output: &mut [u8]) {
+    derive_alloc(mac, salt, c, output)
+}
+
+/**
+ * Execute the PBKDF2 Key Derivation Function, or return an error if the iteration count is zero,
+ * the output is too long, or without the `alloc` feature, if the output size of `mac` exceeds
+ * `MAX_OUTPUT_BYTES`.
+ */
+pub fn try_pbkdf2<M: Mac>(
+    mac: &mut M,
+    salt: &[u8],
+    c: u32,
+    output: &mut [u8],
+) -> Result<(), InvalidParameters> {
+    try_pbkdf2_u64(mac, salt, c as u64, output)
+}
+
+/**
+ * Execute the PBKDF2 Key Derivation Function with a 64 bits iteration count, or return an error
+ * on invalid parameters like [`try_pbkdf2`].
+ */
+pub fn try_pbkdf2_u64<M: Mac>(
+    mac: &mut M,
+    salt: &[u8],
+    c: u64,
+    output: &mut [u8],
+) -> Result<(), InvalidParameters> {
+    let os = mac.output_bytes();
+    #[cfg(not(feature = "alloc"))]
+    if os > MAX_OUTPUT_BYTES {
+        return Err(InvalidParameters);
+    }
+    check_parameters(os, c, output.len(), 2 * os)?;
+    derive_alloc(mac, salt, c, output);
+    Ok(())
+}
 
+// PBKDF2 with a scratch buffer allocated or on the stack without the `alloc` feature
+fn derive_alloc<M: Mac>(mac: &mut M, salt: &[u8], c: u64, output: &mut [u8]) {
     let os = mac.output_bytes();
 
-    // A temporary storage array needed by calculate_block. This is really only necessary if c > 1.
+    // A temporary storage needed by calculate_block. This is really only necessary if c > 1.
     // Most users of pbkdf2 should use a value much larger than 1, so, this allocation should almost
     // always be necessary. A big exception is Scrypt. However, this allocation is unlikely to be
     // the bottleneck in Scrypt performance.
-    let mut scratch: Vec<u8> = repeat(0).take(os).collect();
+    #[cfg(feature = "alloc")]
+    let mut scratch: Vec<u8> = repeat(0).take(2 * os).collect();
+    #[cfg(not(feature = "alloc"))]
+    let mut scratch = {
+        assert!(os <= MAX_OUTPUT_BYTES);
+        [0u8; 2 * MAX_OUTPUT_BYTES]
+    };
+
+    derive(mac, salt, c, output, &mut scratch[..2 * os]);
+}
+
+/**
+ * Execute the PBKDF2 Key Derivation Function using a caller provided scratch buffer,
+ * which doesn't need any allocation.
+ *
+ * # Arguments
+ * * `mac` - The Pseudo Random Function to use.
+ * * `salt` - The salt value to use.
+ * * `c` - The iteration count.
+ * * `output` - The output buffer to fill with the derived key value.
+ * * `scratch` - A temporary buffer of at least twice `mac.output_bytes()` bytes.
+ *
+ * Panics if `c` is zero or `scratch` is too small, see [`try_pbkdf2_with_scratch`] for a
+ * fallible version.
+ */
+#[cfg_attr(feature = "no-panic", deprecated(note = "use try_pbkdf2_with_scratch"))]
+pub fn pbkdf2_with_scratch<M: Mac>(
+    mac: &mut M,
+    salt: &[u8],
+    c: u32,
+    output: &mut [u8],
+    scratch: &mut [u8],
+) {
+    derive(mac, salt, c as u64, output, scratch)
+}
+
+/**
+ * Execute the PBKDF2 Key Derivation Function using a caller provided scratch buffer, or return
+ * an error if the iteration count is zero, the output is too long or the scratch buffer is
+ * smaller than twice `mac.output_bytes()` bytes.
+ */
+pub fn try_pbkdf2_with_scratch<M: Mac>(
+    mac: &mut M,
+    salt: &[u8],
+    c: u32,
+    output: &mut [u8],
+    scratch: &mut [u8],
+) -> Result<(), InvalidParameters> {
+    check_parameters(mac.output_bytes(), c as u64, output.len(), scratch.len())?;
+    derive(mac, salt, c as u64, output, scratch);
+    Ok(())
+}
+
+// PBKDF2 with a scratch buffer split between the iterations and the last partial block
+fn derive<M: Mac>(mac: &mut M, salt: &[u8], c: u64, output: &mut [u8], scratch: &mut [u8]) {
+    assert!(c > 0);
+
+    let os = mac.output_bytes();
+    assert!(scratch.len() >= 2 * os);
+    let (scratch, tmp) = scratch.split_at_mut(os);
+    let tmp = &mut tmp[..os];
 
     let mut idx: u32 = 0;
 
@@ -102,21 +256,107 @@ pub fn pbkdf2<M: Mac>(mac: &mut M, salt: &[u8], c: u32, output: &mut [u8]) {
         idx = idx.checked_add(1).expect("PBKDF2 size limit exceeded.");
 
         if chunk.len() == os {
-            calculate_block(mac, salt, c, idx, &mut scratch, chunk);
+            calculate_block(mac, salt, c, idx, scratch, chunk);
         } else {
-            let mut tmp: Vec<u8> = repeat(0).take(os).collect();
-            calculate_block(mac, salt, c, idx, &mut scratch[..], &mut tmp[..]);
+            calculate_block(mac, salt, c, idx, scratch, tmp);
             let chunk_len = chunk.len();
             copy_memory(&tmp[..chunk_len], chunk);
         }
     }
 }
 
-#[cfg(test)]
+/**
+ * PBKDF2 output computed lazily, one block of the size of the `Mac` output for each call
+ * of `next`, without materializing the whole output.
+ *
+ * The blocks are the same as the output of [`pbkdf2`] for `output_len` bytes, the last
+ * block being padded with zeros past `output_len` if it is not a multiple of `BLOCK`.
+ */
+pub struct Pbkdf2Iter<'a, M: Mac, const BLOCK: usize> {
+    mac: &'a mut M,
+    salt: &'a [u8],
+    c: u64,
+    idx: u32,
+    remaining: usize,
+}
+
+impl<'a, M: Mac, const BLOCK: usize> Pbkdf2Iter<'a, M, BLOCK> {
+    /**
+     * Create the iterator over the blocks of `output_len` bytes of PBKDF2 output, or return an
+     * error if the iteration count is zero, the output is too long or `BLOCK` is not the output
+     * size of `mac`.
+     */
+    pub fn new(
+        mac: &'a mut M,
+        salt: &'a [u8],
+        c: u64,
+        output_len: usize,
+    ) -> Result<Self, InvalidParameters> {
+        if mac.output_bytes() != BLOCK {
+            return Err(InvalidParameters);
+        }
+        check_parameters(BLOCK, c, output_len, 2 * BLOCK)?;
+        Ok(Pbkdf2Iter {
+            mac,
+            salt,
+            c,
+            idx: 0,
+            remaining: output_len,
+        })
+    }
+}
+
+impl<'a, M: Mac, const BLOCK: usize> Iterator for Pbkdf2Iter<'a, M, BLOCK> {
+    type Item = [u8; BLOCK];
+
+    fn next(&mut self) -> Option<[u8; BLOCK]> {
+        if self.remaining == 0 {
+            return None;
+        }
+        // the number of blocks is checked to fit in 32 bits by `new`
+        self.idx += 1;
+
+        let mut scratch = [0u8; BLOCK];
+        let mut block = [0u8; BLOCK];
+        calculate_block(
+            self.mac,
+            self.salt,
+            self.c,
+            self.idx,
+            &mut scratch,
+            &mut block,
+        );
+        if self.remaining < BLOCK {
+            for b in block[self.remaining..].iter_mut() {
+                *b = 0;
+            }
+            self.remaining = 0;
+        } else {
+            self.remaining -= BLOCK;
+        }
+        Some(block)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = (self.remaining + BLOCK - 1) / BLOCK;
+        (n, Some(n))
+    }
+}
+
+impl<'a, M: Mac, const BLOCK: usize> ExactSizeIterator for Pbkdf2Iter<'a, M, BLOCK> {}
+
+#[cfg(all(test, feature = "hmac", feature = "sha1", feature = "sha2"))]
+#[cfg_attr(feature = "no-panic", allow(deprecated))]
 mod test {
-    use super::pbkdf2;
+    use super::{
+        pbkdf2, pbkdf2_u64, pbkdf2_with_scratch, try_pbkdf2, try_pbkdf2_with_scratch,
+        InvalidParameters, Pbkdf2Iter,
+    };
     use crate::hmac::Hmac;
+    use crate::mac::Mac;
     use crate::sha1::Sha1;
+    use crate::sha2::Sha256;
+    use std::vec::Vec;
 
     #[test]
     fn test1() {
@@ -133,4 +373,219 @@ mod test {
             ]
         )
     }
+
+    #[test]
+    fn test_multi_block() {
+        let password = b"passwordPASSWORDpassword";
+        let salt = b"saltSALTsaltSALTsaltSALTsaltSALTsalt";
+        let c = 4096;
+        let mut out = [0u8; 25];
+        pbkdf2(&mut Hmac::new(Sha1::new(), password), salt, c, &mut out);
+        assert_eq!(
+            out,
+            [
+                0x3d, 0x2e, 0xec, 0x4f, 0xe4, 0x1c, 0x84, 0x9b, 0x80, 0xc8, 0xd8, 0x36, 0x62, 0xc0,
+                0xe4, 0x4a, 0x8b, 0x29, 0x1a, 0x96, 0x4c, 0xf2, 0xf0, 0x70, 0x38
+            ]
+        )
+    }
+
+    // straightforward PBKDF2 rekeying the HMAC for every single PRF call
+    fn pbkdf2_rekeying(password: &[u8], salt: &[u8], c: u32, output: &mut [u8]) {
+        for (i, chunk) in output.chunks_mut(32).enumerate() {
+            let mut u = Vec::from(salt);
+            u.extend_from_slice(&(i as u32 + 1).to_be_bytes());
+            let mut t = [0u8; 32];
+            for _ in 0..c {
+                let mut mac = Hmac::new(Sha256::new(), password);
+                mac.input(&u);
+                let mut next = [0u8; 32];
+                mac.raw_result(&mut next);
+                for (t, n) in t.iter_mut().zip(next.iter()) {
+                    *t ^= n;
+                }
+                u = next.to_vec();
+            }
+            let chunk_len = chunk.len();
+            chunk.copy_from_slice(&t[..chunk_len]);
+        }
+    }
+
+    #[test]
+    fn test_same_as_rekeying() {
+        let password = b"passwd";
+        let salt = b"salt";
+        for &(c, len) in [(1, 64), (3, 70), (17, 33)].iter() {
+            let mut expected = vec![0u8; len];
+            let mut out = vec![0u8; len];
+            pbkdf2_rekeying(password, salt, c, &mut expected);
+            pbkdf2(&mut Hmac::new(Sha256::new(), password), salt, c, &mut out);
+            assert_eq!(out, expected);
+        }
+    }
+
+    // only uses the API available without the `alloc` feature: RFC 7914 PBKDF2-HMAC-SHA256 vector
+    #[test]
+    fn test_with_scratch_no_alloc() {
+        let mut scratch = [0u8; 64];
+        let mut out = [0u8; 64];
+        pbkdf2_with_scratch(
+            &mut Hmac::new(Sha256::new(), b"passwd"),
+            b"salt",
+            1,
+            &mut out,
+            &mut scratch,
+        );
+        assert_eq!(
+            out,
+            [
+                0x55, 0xac, 0x04, 0x6e, 0x56, 0xe3, 0x08, 0x9f, 0xec, 0x16, 0x91, 0xc2, 0x25, 0x44,
+                0xb6, 0x05, 0xf9, 0x41, 0x85, 0x21, 0x6d, 0xde, 0x04, 0x65, 0xe6, 0x8b, 0x9d, 0x57,
+                0xc2, 0x0d, 0xac, 0xbc, 0x49, 0xca, 0x9c, 0xcc, 0xf1, 0x79, 0xb6, 0x45, 0x99, 0x16,
+                0x64, 0xb3, 0x9d, 0x77, 0xef, 0x31, 0x7c, 0x71, 0xb8, 0x45, 0xb1, 0xe3, 0x0b, 0xd5,
+                0x09, 0x11, 0x20, 0x41, 0xd3, 0xa1, 0x97, 0x83
+            ]
+        );
+    }
+
+    #[test]
+    fn test_u64_same_as_u32() {
+        let mut out32 = [0u8; 40];
+        let mut out64 = [0u8; 40];
+        pbkdf2(
+            &mut Hmac::new(Sha256::new(), b"password"),
+            b"salt",
+            2,
+            &mut out32,
+        );
+        pbkdf2_u64(
+            &mut Hmac::new(Sha256::new(), b"password"),
+            b"salt",
+            2,
+            &mut out64,
+        );
+        assert_eq!(out32, out64);
+    }
+
+    #[test]
+    fn test_try_pbkdf2() {
+        let mut mac = Hmac::new(Sha256::new(), b"password");
+        let mut expected = [0u8; 40];
+        pbkdf2(&mut mac, b"salt", 2, &mut expected);
+
+        let mut out = [0u8; 40];
+        assert_eq!(try_pbkdf2(&mut mac, b"salt", 2, &mut out), Ok(()));
+        assert_eq!(out, expected);
+        assert_eq!(
+            try_pbkdf2(&mut mac, b"salt", 0, &mut out),
+            Err(InvalidParameters)
+        );
+
+        let mut out = [0u8; 40];
+        let mut scratch = [0u8; 64];
+        assert_eq!(
+            try_pbkdf2_with_scratch(&mut mac, b"salt", 2, &mut out, &mut scratch),
+            Ok(())
+        );
+        assert_eq!(out, expected);
+        assert_eq!(
+            try_pbkdf2_with_scratch(&mut mac, b"salt", 2, &mut out, &mut scratch[..63]),
+            Err(InvalidParameters)
+        );
+    }
+
+    #[test]
+    fn test_iter_same_as_pbkdf2() {
+        for &len in [32, 33, 70, 96].iter() {
+            let mut mac = Hmac::new(Sha256::new(), b"password");
+            let mut expected = vec![0u8; len];
+            pbkdf2(&mut mac, b"salt", 3, &mut expected);
+
+            let iter = Pbkdf2Iter::<_, 32>::new(&mut mac, b"salt", 3, len).unwrap();
+            assert_eq!(iter.len(), (len + 31) / 32);
+            let out: Vec<u8> = iter.flatten().collect();
+            assert_eq!(out.len(), (len + 31) / 32 * 32);
+            assert_eq!(&out[..len], &expected[..]);
+            assert!(out[len..].iter().all(|&b| b == 0));
+        }
+
+        let mut mac = Hmac::new(Sha256::new(), b"password");
+        let mut iter = Pbkdf2Iter::<_, 32>::new(&mut mac, b"salt", 3, 1 << 20).unwrap();
+        let mut expected = [0u8; 64];
+        pbkdf2(
+            &mut Hmac::new(Sha256::new(), b"password"),
+            b"salt",
+            3,
+            &mut expected,
+        );
+        assert_eq!(&iter.next().unwrap()[..], &expected[..32]);
+        assert_eq!(&iter.next().unwrap()[..], &expected[32..]);
+    }
+
+    #[test]
+    fn test_iter_invalid() {
+        let mut mac = Hmac::new(Sha256::new(), b"password");
+        assert!(Pbkdf2Iter::<_, 20>::new(&mut mac, b"salt", 3, 40).is_err());
+        assert!(Pbkdf2Iter::<_, 32>::new(&mut mac, b"salt", 0, 40).is_err());
+        let iter = Pbkdf2Iter::<_, 32>::new(&mut mac, b"salt", 3, 0).unwrap();
+        assert_eq!(iter.count(), 0);
+    }
+}
+
+// HMAC-SHA256 and PBKDF2 through the APIs available without the `alloc` feature.
+// This module doesn't use `alloc` or `std` itself, and is also built by
+// `cargo test --no-default-features --features hmac,pbkdf2,sha2`.
+#[cfg(all(test, feature = "hmac", feature = "sha2"))]
+mod no_alloc_test {
+    use super::{try_pbkdf2, try_pbkdf2_with_scratch};
+    use crate::hmac::{Hmac, PrecomputedHmac};
+    use crate::mac::Mac;
+    use crate::sha2::Sha256;
+
+    // RFC 4231 test case 2
+    const HMAC_KEY: &[u8] = b"Jefe";
+    const HMAC_DATA: &[u8] = b"what do ya want for nothing?";
+    const HMAC_EXPECTED: [u8; 32] = [
+        0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08, 0x95, 0x75,
+        0xc7, 0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec, 0x58, 0xb9, 0x64, 0xec,
+        0x38, 0x43,
+    ];
+
+    // RFC 7914 section 11, PBKDF2-HMAC-SHA256 of "passwd" and "salt" with 1 iteration
+    const PBKDF2_EXPECTED: [u8; 64] = [
+        0x55, 0xac, 0x04, 0x6e, 0x56, 0xe3, 0x08, 0x9f, 0xec, 0x16, 0x91, 0xc2, 0x25, 0x44, 0xb6,
+        0x05, 0xf9, 0x41, 0x85, 0x21, 0x6d, 0xde, 0x04, 0x65, 0xe6, 0x8b, 0x9d, 0x57, 0xc2, 0x0d,
+        0xac, 0xbc, 0x49, 0xca, 0x9c, 0xcc, 0xf1, 0x79, 0xb6, 0x45, 0x99, 0x16, 0x64, 0xb3, 0x9d,
+        0x77, 0xef, 0x31, 0x7c, 0x71, 0xb8, 0x45, 0xb1, 0xe3, 0x0b, 0xd5, 0x09, 0x11, 0x20, 0x41,
+        0xd3, 0xa1, 0x97, 0x83,
+    ];
+
+    #[test]
+    fn hmac_sha256() {
+        let mut out = [0u8; 32];
+        let mut h = Hmac::new(Sha256::new(), HMAC_KEY);
+        h.input(HMAC_DATA);
+        h.raw_result(&mut out);
+        assert_eq!(out, HMAC_EXPECTED);
+
+        let mut h = Hmac::new(Sha256::new(), HMAC_KEY);
+        h.input(HMAC_DATA);
+        assert_eq!(h.verify_slice(&HMAC_EXPECTED), Ok(()));
+
+        PrecomputedHmac::new(Sha256::new(), HMAC_KEY).mac(HMAC_DATA, &mut out);
+        assert_eq!(out, HMAC_EXPECTED);
+    }
+
+    #[test]
+    fn pbkdf2_hmac_sha256() {
+        let mut out = [0u8; 64];
+        let mut mac = Hmac::new(Sha256::new(), b"passwd");
+        try_pbkdf2(&mut mac, b"salt", 1, &mut out).unwrap();
+        assert_eq!(out, PBKDF2_EXPECTED);
+
+        let mut out = [0u8; 64];
+        let mut scratch = [0u8; 64];
+        try_pbkdf2_with_scratch(&mut mac, b"salt", 1, &mut out, &mut scratch).unwrap();
+        assert_eq!(out, PBKDF2_EXPECTED);
+    }
 }
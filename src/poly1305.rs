@@ -5,7 +5,7 @@
 //! ```
 //! use cryptoxide::{mac::Mac, poly1305::Poly1305};
 //!
-//! let mut context = Poly1305::new(&[0u8;32]);
+//! let mut context = Poly1305::new([0u8;32]);
 //! context.input(b"data to authenticate");
 //! let mac = context.result();
 //! ```
@@ -18,12 +18,18 @@
 use core::cmp::min;
 
 use crate::cryptoutil::{read_u32_le, write_u32_le};
-use crate::mac::{Mac, MacResult};
+use crate::mac::{Mac, OneTimeMac};
 
 /// `Poly1305` Context
 ///
 /// Use the `Mac` traits for interaction
-#[derive(Clone, Copy)]
+///
+/// Poly1305 is a one-time authenticator: a key must never be used to
+/// authenticate more than one message. Once the MAC has been computed, with
+/// `result`, `raw_result` or [`Poly1305::finalize`], any further `input` or
+/// `reset` panics instead of silently authenticating more data under the same
+/// key. The context is not `Copy`, so that it isn't duplicated implicitly.
+#[derive(Clone)]
 pub struct Poly1305 {
     r: [u32; 5],
     h: [u32; 5],
@@ -31,6 +37,7 @@ pub struct Poly1305 {
     leftover: usize,
     buffer: [u8; 16],
     finalized: bool,
+    done: bool, // whether the MAC has been computed
 }
 
 #[inline(always)]
@@ -39,9 +46,11 @@ fn mul64(a: u32, b: u32) -> u64 {
 }
 
 impl Poly1305 {
-    /// Create a new `Poly1305` context using the key (32 bytes)
-    pub fn new(key: &[u8]) -> Self {
-        assert!(key.len() == 32);
+    /// Create a new `Poly1305` context using the one-time key (32 bytes)
+    ///
+    /// The key is taken by value to emphasize that it is consumed by the
+    /// context, and must not be used for any other message.
+    pub fn new(key: [u8; 32]) -> Self {
         let mut poly = Poly1305 {
             r: [0u32; 5],
             h: [0u32; 5],
@@ -49,6 +58,7 @@ impl Poly1305 {
             leftover: 0,
             buffer: [0u8; 16],
             finalized: false,
+            done: false,
         };
 
         // r &= 0xffffffc0ffffffc0ffffffc0fffffff
@@ -118,6 +128,13 @@ impl Poly1305 {
         self.h[4] = h4;
     }
 
+    /// Compute the MAC of the data processed, consuming the context
+    pub fn finalize(mut self) -> [u8; 16] {
+        let mut mac = [0u8; 16];
+        self.raw_result(&mut mac);
+        mac
+    }
+
     #[rustfmt::skip]
     fn finish(&mut self) {
         if self.leftover > 0 {
@@ -188,7 +205,10 @@ impl Poly1305 {
 
 impl Mac for Poly1305 {
     fn input(&mut self, data: &[u8]) {
-        assert!(!self.finalized);
+        assert!(
+            !self.done && !self.finalized,
+            "Poly1305 one-time key reused after the MAC was computed"
+        );
         let mut m = data;
 
         if self.leftover > 0 {
@@ -222,22 +242,24 @@ impl Mac for Poly1305 {
         self.leftover = m.len();
     }
 
+    /// Discard the data processed so far, keeping the same key
+    ///
+    /// As the key is one-time, this panics once the MAC has been computed.
     fn reset(&mut self) {
+        assert!(
+            !self.done,
+            "Poly1305 one-time key reused after the MAC was computed"
+        );
         self.h = [0u32; 5];
         self.leftover = 0;
         self.finalized = false;
     }
 
-    fn result(&mut self) -> MacResult {
-        let mut mac = [0u8; 16];
-        self.raw_result(&mut mac);
-        MacResult::new(&mac[..])
-    }
-
     fn raw_result(&mut self, output: &mut [u8]) {
         assert!(output.len() >= 16);
-        if !self.finalized {
+        if !self.done {
             self.finish();
+            self.done = true;
         }
         write_u32_le(&mut output[0..4], self.h[0]);
         write_u32_le(&mut output[4..8], self.h[1]);
@@ -250,13 +272,23 @@ impl Mac for Poly1305 {
     }
 }
 
+impl OneTimeMac for Poly1305 {
+    fn input(&mut self, data: &[u8]) {
+        Mac::input(self, data)
+    }
+
+    fn finalize(self) -> [u8; 16] {
+        Poly1305::finalize(self)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::mac::Mac;
     use crate::poly1305::Poly1305;
 
-    fn poly1305(key: &[u8], msg: &[u8], mac: &mut [u8]) {
-        let mut poly = Poly1305::new(key);
+    fn poly1305(key: &[u8; 32], msg: &[u8], mac: &mut [u8]) {
+        let mut poly = Poly1305::new(*key);
         poly.input(msg);
         poly.raw_result(mac);
     }
@@ -291,7 +323,7 @@ mod test {
         poly1305(&key, &msg, &mut mac);
         assert_eq!(&mac[..], &expected[..]);
 
-        let mut poly = Poly1305::new(&key);
+        let mut poly = Poly1305::new(key);
         poly.input(&msg[0..32]);
         poly.input(&msg[32..96]);
         poly.input(&msg[96..112]);
@@ -340,18 +372,94 @@ mod test {
             0x3d, 0x39,
         ];
 
-        let mut tpoly = Poly1305::new(&total_key);
+        let mut tpoly = Poly1305::new(total_key);
         for i in 0..256 {
             let key = [i as u8; 32];
             let msg = [i as u8; 256];
             let mut mac = [0u8; 16];
-            poly1305(&key[..], &msg[0..i], &mut mac);
+            poly1305(&key, &msg[0..i], &mut mac);
             tpoly.input(&mac);
         }
         tpoly.raw_result(&mut mac);
         assert_eq!(&mac[..], &total_mac[..]);
     }
 
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn one_time_mac_object() {
+        use crate::mac::DynOneTimeMac;
+        use alloc::boxed::Box;
+
+        let key = *b"this is 32-byte key for Poly1305";
+        let mut poly: Box<dyn DynOneTimeMac> = Box::new(Poly1305::new(key));
+        poly.input(b"Hello ");
+        poly.input(b"world!");
+        assert_eq!(
+            poly.finalize_boxed(),
+            [
+                0xa6, 0xf7, 0x45, 0x00, 0x8f, 0x81, 0xc9, 0x16, 0xa2, 0x0d, 0xcc, 0x74, 0xee, 0xf2,
+                0xb2, 0xf0,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_finalize_and_reuse() {
+        let key = *b"this is 32-byte key for Poly1305";
+        let expected = [
+            0xa6, 0xf7, 0x45, 0x00, 0x8f, 0x81, 0xc9, 0x16, 0xa2, 0x0d, 0xcc, 0x74, 0xee, 0xf2,
+            0xb2, 0xf0,
+        ];
+        let mut poly = Poly1305::new(key);
+        poly.input(b"Hello world!");
+        assert_eq!(poly.finalize(), expected);
+
+        // computing the MAC again gives the same result
+        let mut poly = Poly1305::new(key);
+        poly.input(b"Hello world!");
+        let mut mac1 = [0u8; 16];
+        let mut mac2 = [0u8; 16];
+        poly.raw_result(&mut mac1);
+        poly.raw_result(&mut mac2);
+        assert_eq!(mac1, expected);
+        assert_eq!(mac2, expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "Poly1305 one-time key reused")]
+    fn test_input_after_result() {
+        // a message multiple of the block size doesn't leave a partial block to finish
+        let mut poly = Poly1305::new([1u8; 32]);
+        poly.input(&[0u8; 32]);
+        poly.raw_result(&mut [0u8; 16]);
+        poly.input(b"another message");
+    }
+
+    #[test]
+    #[should_panic(expected = "Poly1305 one-time key reused")]
+    fn test_reset_after_result() {
+        let mut poly = Poly1305::new([1u8; 32]);
+        poly.input(b"message");
+        poly.raw_result(&mut [0u8; 16]);
+        poly.reset();
+    }
+
+    #[test]
+    fn test_reset_before_result() {
+        let key = *b"this is 32-byte key for Poly1305";
+        let mut poly = Poly1305::new(key);
+        poly.input(b"discarded");
+        poly.reset();
+        poly.input(b"Hello world!");
+        assert_eq!(
+            poly.finalize(),
+            [
+                0xa6, 0xf7, 0x45, 0x00, 0x8f, 0x81, 0xc9, 0x16, 0xa2, 0x0d, 0xcc, 0x74, 0xee, 0xf2,
+                0xb2, 0xf0,
+            ]
+        );
+    }
+
     #[test]
     fn test_tls_vectors() {
         // from http://tools.ietf.org/html/draft-agl-tls-chacha20poly1305-04
@@ -387,7 +495,7 @@ mod bench {
         let key = [0u8; 32];
         let bytes = [1u8; 10];
         bh.iter(|| {
-            let mut poly = Poly1305::new(&key);
+            let mut poly = Poly1305::new(key);
             poly.input(&bytes);
             poly.raw_result(&mut mac);
         });
@@ -400,7 +508,7 @@ mod bench {
         let key = [0u8; 32];
         let bytes = [1u8; 1024];
         bh.iter(|| {
-            let mut poly = Poly1305::new(&key);
+            let mut poly = Poly1305::new(key);
             poly.input(&bytes);
             poly.raw_result(&mut mac);
         });
@@ -413,7 +521,7 @@ mod bench {
         let key = [0u8; 32];
         let bytes = [1u8; 65536];
         bh.iter(|| {
-            let mut poly = Poly1305::new(&key);
+            let mut poly = Poly1305::new(key);
             poly.input(&bytes);
             poly.raw_result(&mut mac);
         });
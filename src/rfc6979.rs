@@ -0,0 +1,268 @@
+//! Deterministic nonce generation for DSA and ECDSA as specified in [RFC6979][1]
+//!
+//! The nonce `k` is derived from the private key and the hash of the message
+//! to sign with HMAC-DRBG, removing the need for a good source of randomness
+//! at signing time, which is a common cause of private key leakage.
+//!
+//! # Examples
+//!
+//! ```
+//! use cryptoxide::{digest::Digest, rfc6979::generate_k, sha2::Sha256};
+//!
+//! // order of the P-256 curve
+//! let order = [
+//!     0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+//!     0xbc, 0xe6, 0xfa, 0xad, 0xa7, 0x17, 0x9e, 0x84, 0xf3, 0xb9, 0xca, 0xc2, 0xfc, 0x63, 0x25, 0x51,
+//! ];
+//! let private_key = [0x42u8; 32];
+//! let mut message_hash = [0u8; 32];
+//! let mut h = Sha256::new();
+//! h.input(b"message");
+//! h.result(&mut message_hash);
+//!
+//! let k = generate_k(Sha256::new(), &order, &private_key, &message_hash);
+//! assert_eq!(k.len(), order.len());
+//! ```
+//!
+//! [1]: <https://tools.ietf.org/html/rfc6979>
+
+use alloc::{vec, vec::Vec};
+use core::cmp::Ordering;
+
+use crate::digest::Digest;
+use crate::hmac::Hmac;
+use crate::mac::Mac;
+
+// HMAC-DRBG state as described in section 3.2 of the RFC
+struct HmacDrbg<D> {
+    digest: D,
+    k: Vec<u8>,
+    v: Vec<u8>,
+}
+
+impl<D: Digest + Clone> HmacDrbg<D> {
+    fn new(mut digest: D, seed: &[&[u8]]) -> Self {
+        digest.reset();
+        let hlen = digest.output_bytes();
+        let mut drbg = HmacDrbg {
+            digest,
+            k: vec![0; hlen],
+            v: vec![1; hlen],
+        };
+        drbg.update(0x00, seed);
+        drbg.update(0x01, seed);
+        drbg
+    }
+
+    // K = HMAC_K(V || sep || seed), V = HMAC_K(V)
+    fn update(&mut self, sep: u8, seed: &[&[u8]]) {
+        let mut mac = Hmac::new(self.digest.clone(), &self.k);
+        mac.input(&self.v);
+        mac.input(&[sep]);
+        for s in seed.iter() {
+            mac.input(s);
+        }
+        mac.raw_result(&mut self.k);
+        self.next_v();
+    }
+
+    // V = HMAC_K(V)
+    fn next_v(&mut self) {
+        let mut mac = Hmac::new(self.digest.clone(), &self.k);
+        mac.input(&self.v);
+        mac.raw_result(&mut self.v);
+    }
+
+    fn generate(&mut self, out: &mut [u8]) {
+        for chunk in out.chunks_mut(self.v.len()) {
+            self.next_v();
+            chunk.copy_from_slice(&self.v[..chunk.len()]);
+        }
+    }
+}
+
+// length in bits of a big endian number without leading zero bytes
+fn bit_length(n: &[u8]) -> usize {
+    n.len() * 8 - n[0].leading_zeros() as usize
+}
+
+// bits2int of section 2.3.2, returning the integer as `rolen` big endian bytes
+fn bits2int(b: &[u8], qlen: usize, rolen: usize) -> Vec<u8> {
+    let mut out = vec![0u8; rolen];
+    if b.len() >= rolen {
+        out.copy_from_slice(&b[..rolen]);
+        let shift = rolen * 8 - qlen;
+        if shift > 0 {
+            for i in (0..rolen).rev() {
+                let carry = if i > 0 { out[i - 1] << (8 - shift) } else { 0 };
+                out[i] = (out[i] >> shift) | carry;
+            }
+        }
+    } else {
+        out[rolen - b.len()..].copy_from_slice(b);
+    }
+    out
+}
+
+// a -= b, for numbers of the same length with a >= b
+fn sub_assign(a: &mut [u8], b: &[u8]) {
+    let mut borrow = 0i16;
+    for (x, y) in a.iter_mut().zip(b.iter()).rev() {
+        let d = *x as i16 - *y as i16 - borrow;
+        *x = d as u8;
+        borrow = (d < 0) as i16;
+    }
+}
+
+fn is_zero(n: &[u8]) -> bool {
+    n.iter().all(|b| *b == 0)
+}
+
+/// Generate the deterministic nonce `k` for the given group order, private key and message hash
+///
+/// * `digest` is the hash function used by HMAC-DRBG, normally the one used to
+///   hash the message.
+/// * `order` is the big endian encoding of the group order `q`, without leading zero bytes.
+/// * `private_key` is the big endian encoding of the private key `x`, of the same
+///   length as `order`, and must be in the range `[1, q)`.
+/// * `message_hash` is the hash of the message to sign.
+///
+/// The nonce is returned as a big endian number of the same length as `order`,
+/// and is in the range `[1, q)`.
+pub fn generate_k<D: Digest + Clone>(
+    digest: D,
+    order: &[u8],
+    private_key: &[u8],
+    message_hash: &[u8],
+) -> Vec<u8> {
+    assert!(!order.is_empty() && order[0] != 0);
+    assert!(private_key.len() == order.len());
+    assert!(!is_zero(private_key) && private_key.cmp(order) == Ordering::Less);
+
+    let qlen = bit_length(order);
+    let rolen = order.len();
+
+    // bits2octets(h1): bits2int(h1) reduced modulo q, which needs at most one subtraction
+    let mut h1 = bits2int(message_hash, qlen, rolen);
+    if h1.as_slice() >= order {
+        sub_assign(&mut h1, order);
+    }
+
+    let mut drbg = HmacDrbg::new(digest, &[private_key, &h1]);
+    let mut t = vec![0u8; rolen];
+    loop {
+        drbg.generate(&mut t);
+        let k = bits2int(&t, qlen, rolen);
+        if !is_zero(&k) && k.as_slice() < order {
+            return k;
+        }
+        drbg.update(0x00, &[]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::generate_k;
+    use crate::digest::Digest;
+    #[cfg(feature = "sha1")]
+    use crate::sha1::Sha1;
+    use crate::sha2::{Sha256, Sha512};
+    use alloc::{vec, vec::Vec};
+
+    fn hash<D: Digest>(mut d: D, msg: &[u8]) -> Vec<u8> {
+        let mut out = vec![0u8; d.output_bytes()];
+        d.input(msg);
+        d.result(&mut out);
+        out
+    }
+
+    fn check<D: Digest + Clone>(d: D, q: &[u8], x: &[u8], msg: &[u8], expected: &[u8]) {
+        let h = hash(d.clone(), msg);
+        assert_eq!(generate_k(d, q, x, &h), expected);
+    }
+
+    // RFC6979 A.2.5: ECDSA, 256 Bits (Prime Field)
+    #[test]
+    fn p256() {
+        let q = [
+            0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xbc, 0xe6, 0xfa, 0xad, 0xa7, 0x17, 0x9e, 0x84, 0xf3, 0xb9, 0xca, 0xc2,
+            0xfc, 0x63, 0x25, 0x51,
+        ];
+        let x = [
+            0xc9, 0xaf, 0xa9, 0xd8, 0x45, 0xba, 0x75, 0x16, 0x6b, 0x5c, 0x21, 0x57, 0x67, 0xb1,
+            0xd6, 0x93, 0x4e, 0x50, 0xc3, 0xdb, 0x36, 0xe8, 0x9b, 0x12, 0x7b, 0x8a, 0x62, 0x2b,
+            0x12, 0x0f, 0x67, 0x21,
+        ];
+        check(
+            Sha256::new(),
+            &q,
+            &x,
+            b"sample",
+            &[
+                0xa6, 0xe3, 0xc5, 0x7d, 0xd0, 0x1a, 0xbe, 0x90, 0x08, 0x65, 0x38, 0x39, 0x83, 0x55,
+                0xdd, 0x4c, 0x3b, 0x17, 0xaa, 0x87, 0x33, 0x82, 0xb0, 0xf2, 0x4d, 0x61, 0x29, 0x49,
+                0x3d, 0x8a, 0xad, 0x60,
+            ],
+        );
+        check(
+            Sha256::new(),
+            &q,
+            &x,
+            b"test",
+            &[
+                0xd1, 0x6b, 0x6a, 0xe8, 0x27, 0xf1, 0x71, 0x75, 0xe0, 0x40, 0x87, 0x1a, 0x1c, 0x7e,
+                0xc3, 0x50, 0x01, 0x92, 0xc4, 0xc9, 0x26, 0x77, 0x33, 0x6e, 0xc2, 0x53, 0x7a, 0xca,
+                0xee, 0x00, 0x08, 0xe0,
+            ],
+        );
+        // hash longer than the order
+        check(
+            Sha512::new(),
+            &q,
+            &x,
+            b"sample",
+            &[
+                0x5f, 0xa8, 0x1c, 0x63, 0x10, 0x9b, 0xad, 0xb8, 0x8c, 0x1f, 0x36, 0x7b, 0x47, 0xda,
+                0x60, 0x6d, 0xa2, 0x8c, 0xad, 0x69, 0xaa, 0x22, 0xc4, 0xfe, 0x6a, 0xd7, 0xdf, 0x73,
+                0xa7, 0x17, 0x3a, 0xa5,
+            ],
+        );
+        // hash shorter than the order
+        #[cfg(feature = "sha1")]
+        check(
+            Sha1::new(),
+            &q,
+            &x,
+            b"sample",
+            &[
+                0x88, 0x29, 0x05, 0xf1, 0x22, 0x7f, 0xd6, 0x20, 0xfb, 0xf2, 0xab, 0xf2, 0x12, 0x44,
+                0xf0, 0xba, 0x83, 0xd0, 0xdc, 0x3a, 0x91, 0x03, 0xdb, 0xbe, 0xe4, 0x3a, 0x1f, 0xb8,
+                0x58, 0x10, 0x9d, 0xb4,
+            ],
+        );
+    }
+
+    // RFC6979 A.1.2: order of 163 bits, not a multiple of 8
+    #[test]
+    fn order_163_bits() {
+        let q = [
+            0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x01, 0x08, 0xa2,
+            0xe0, 0xcc, 0x0d, 0x99, 0xf8, 0xa5, 0xef,
+        ];
+        let x = [
+            0x00, 0x9a, 0x4d, 0x67, 0x92, 0x29, 0x5a, 0x7f, 0x73, 0x0f, 0xc3, 0xf2, 0xb4, 0x9c,
+            0xbc, 0x0f, 0x62, 0xe8, 0x62, 0x27, 0x2f,
+        ];
+        check(
+            Sha256::new(),
+            &q,
+            &x,
+            b"sample",
+            &[
+                0x02, 0x3a, 0xf4, 0x07, 0x4c, 0x90, 0xa0, 0x2b, 0x3f, 0xe6, 0x1d, 0x28, 0x6d, 0x5c,
+                0x87, 0xf4, 0x25, 0xe6, 0xbd, 0xd8, 0x1b,
+            ],
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//! An implementation of the RIPEMD-160 cryptographic hash algorithm.
+//!
+//! RIPEMD-160 is a 160 bits hash function, mostly provided for interoperability,
+//! notably for the bitcoin address derivation with [`hash160`].
+//!
+//! # Example
+//!
+//! ```
+//! use cryptoxide::{ripemd160::Ripemd160, digest::Digest};
+//!
+//! let mut digest = [0u8; 20];
+//! let mut context = Ripemd160::new();
+//! context.input(b"hello world");
+//! context.result(&mut digest);
+//! ```
+//!
+//! # References
+//! [1]: <https://homes.esat.kuleuven.be/~bosselae/ripemd160.html>
+
+use crate::cryptoutil::{read_u32v_le, write_u32v_le, write_u64_le, FixedBuffer};
+use crate::digest::{assert_output_size, Digest};
+
+const STATE_LEN: usize = 5;
+const BLOCK_BYTES: usize = 64;
+
+const H: [u32; STATE_LEN] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+// constants of the left and right lines, for each round of 16 steps
+const KL: [u32; 5] = [0x00000000, 0x5A827999, 0x6ED9EBA1, 0x8F1BBCDC, 0xA953FD4E];
+const KR: [u32; 5] = [0x50A28BE6, 0x5C4DD124, 0x6D703EF3, 0x7A6D76E9, 0x00000000];
+
+// message word selected at each step of the left and right lines
+const RL: [usize; 80] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, //
+    7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5, 2, 14, 11, 8, //
+    3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12, //
+    1, 9, 11, 10, 0, 8, 12, 4, 13, 3, 7, 15, 14, 5, 6, 2, //
+    4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+];
+const RR: [usize; 80] = [
+    5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12, //
+    6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12, 4, 9, 1, 2, //
+    15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13, //
+    8, 6, 4, 1, 3, 11, 15, 0, 5, 12, 2, 13, 9, 7, 10, 14, //
+    12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+];
+
+// rotation amount at each step of the left and right lines
+const SL: [u32; 80] = [
+    11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8, //
+    7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15, 9, 11, 7, 13, 12, //
+    11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5, //
+    11, 12, 14, 15, 14, 15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12, //
+    9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+];
+const SR: [u32; 80] = [
+    8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6, //
+    9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12, 7, 6, 15, 13, 11, //
+    9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5, //
+    15, 5, 8, 11, 14, 14, 6, 14, 6, 9, 12, 9, 12, 5, 15, 8, //
+    8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+];
+
+// boolean function of the given round, the right line using them in reverse order
+#[inline]
+fn f(round: usize, x: u32, y: u32, z: u32) -> u32 {
+    match round {
+        0 => x ^ y ^ z,
+        1 => (x & y) | (!x & z),
+        2 => (x | !y) ^ z,
+        3 => (x & z) | (y & !z),
+        _ => x ^ (y | !z),
+    }
+}
+
+/// Process a block of 64 bytes with the RIPEMD-160 compression function
+pub fn ripemd160_digest_block(h: &mut [u32; STATE_LEN], block: &[u8]) {
+    assert_eq!(block.len(), BLOCK_BYTES);
+    let mut x = [0u32; 16];
+    read_u32v_le(&mut x, block);
+
+    let [mut al, mut bl, mut cl, mut dl, mut el] = *h;
+    let [mut ar, mut br, mut cr, mut dr, mut er] = *h;
+
+    for j in 0..80 {
+        let round = j / 16;
+
+        let t = al
+            .wrapping_add(f(round, bl, cl, dl))
+            .wrapping_add(x[RL[j]])
+            .wrapping_add(KL[round])
+            .rotate_left(SL[j])
+            .wrapping_add(el);
+        al = el;
+        el = dl;
+        dl = cl.rotate_left(10);
+        cl = bl;
+        bl = t;
+
+        let t = ar
+            .wrapping_add(f(4 - round, br, cr, dr))
+            .wrapping_add(x[RR[j]])
+            .wrapping_add(KR[round])
+            .rotate_left(SR[j])
+            .wrapping_add(er);
+        ar = er;
+        er = dr;
+        dr = cr.rotate_left(10);
+        cr = br;
+        br = t;
+    }
+
+    let t = h[1].wrapping_add(cl).wrapping_add(dr);
+    h[1] = h[2].wrapping_add(dl).wrapping_add(er);
+    h[2] = h[3].wrapping_add(el).wrapping_add(ar);
+    h[3] = h[4].wrapping_add(al).wrapping_add(br);
+    h[4] = h[0].wrapping_add(bl).wrapping_add(cr);
+    h[0] = t;
+}
+
+/// Structure representing the state of a RIPEMD-160 computation
+#[derive(Clone)]
+pub struct Ripemd160 {
+    h: [u32; STATE_LEN],
+    length_bits: u64,
+    buffer: FixedBuffer<BLOCK_BYTES>,
+    computed: bool,
+}
+
+impl Ripemd160 {
+    /// Construct a `Ripemd160` context
+    pub const fn new() -> Self {
+        Ripemd160 {
+            h: H,
+            length_bits: 0,
+            buffer: FixedBuffer::new(),
+            computed: false,
+        }
+    }
+}
+
+impl Digest for Ripemd160 {
+    fn reset(&mut self) {
+        self.h = H;
+        self.length_bits = 0;
+        self.buffer.reset();
+        self.computed = false;
+    }
+
+    fn input(&mut self, msg: &[u8]) {
+        assert!(!self.computed);
+        self.length_bits = (msg.len() as u64)
+            .checked_mul(8)
+            .and_then(|bits| self.length_bits.checked_add(bits))
+            .expect("Numeric overflow occured.");
+        let h = &mut self.h;
+        self.buffer.input(msg, |d| {
+            for block in d.chunks(BLOCK_BYTES) {
+                ripemd160_digest_block(h, block);
+            }
+        });
+    }
+
+    fn result(&mut self, out: &mut [u8]) {
+        assert_output_size(out, 20);
+        if !self.computed {
+            let h = &mut self.h;
+            self.buffer
+                .standard_padding(8, |d| ripemd160_digest_block(h, d));
+            write_u64_le(self.buffer.next(8), self.length_bits);
+            ripemd160_digest_block(h, self.buffer.full_buffer());
+            self.computed = true;
+        }
+        write_u32v_le(&mut out[..20], &self.h);
+    }
+
+    fn output_bits(&self) -> usize {
+        160
+    }
+
+    fn block_size(&self) -> usize {
+        BLOCK_BYTES
+    }
+}
+
+/// Compute the RIPEMD-160 hash of `input`
+pub fn ripemd160(input: &[u8]) -> [u8; 20] {
+    let mut hasher = Ripemd160::new();
+    hasher.input(input);
+    hasher.finalize_fixed()
+}
+
+/// Compute `RIPEMD160(SHA256(input))`, the hash used by bitcoin for addresses
+///
+/// The whole 32 bytes SHA-256 digest is hashed by RIPEMD-160.
+///
+/// ```
+/// use cryptoxide::ripemd160::hash160;
+///
+/// let public_key = [
+///     0x02, 0x79, 0xbe, 0x66, 0x7e, 0xf9, 0xdc, 0xbb, 0xac, 0x55, 0xa0, 0x62, 0x95, 0xce, 0x87,
+///     0x0b, 0x07, 0x02, 0x9b, 0xfc, 0xdb, 0x2d, 0xce, 0x28, 0xd9, 0x59, 0xf2, 0x81, 0x5b, 0x16,
+///     0xf8, 0x17, 0x98,
+/// ];
+/// let key_hash = hash160(&public_key);
+/// assert_eq!(key_hash[0..4], [0x75, 0x1e, 0x76, 0xe8]);
+/// ```
+#[cfg(feature = "sha2")]
+pub fn hash160(input: &[u8]) -> [u8; 20] {
+    ripemd160(&crate::sha2::sha256(input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cryptoutil::test::result_hex;
+
+    fn check(input: &[u8], expected: &str) {
+        let mut r = Ripemd160::new();
+        r.input(input);
+        assert_eq!(result_hex(&mut r), expected);
+
+        // same result when processing the input byte by byte
+        let mut r = Ripemd160::new();
+        for b in input.iter() {
+            r.input(&[*b]);
+        }
+        assert_eq!(result_hex(&mut r), expected);
+    }
+
+    #[test]
+    fn reference_vectors() {
+        check(b"", "9c1185a5c5e9fc54612808977ee8f548b2258d31");
+        check(b"a", "0bdc9d2d256b3ee9daae347be6f4dc835a467ffe");
+        check(b"abc", "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc");
+        check(
+            b"message digest",
+            "5d0689ef49d2fae572b881b123a85ffa21595f36",
+        );
+        check(
+            b"abcdefghijklmnopqrstuvwxyz",
+            "f71c27109c692c1b56bbdceb5b9d2865b3708dbc",
+        );
+        check(
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+            "12a053384a9c0c88e405a06c27dcf49ada62eb2b",
+        );
+        check(
+            b"12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+            "9b752e45573d4b39f4dbd3323cab82bf63326bfb",
+        );
+    }
+
+    #[test]
+    fn million_a() {
+        let mut r = Ripemd160::new();
+        for _ in 0..1000 {
+            r.input(&[b'a'; 1000]);
+        }
+        assert_eq!(
+            result_hex(&mut r),
+            "52783243c1697bdbe16d37f97f68f08325dc1528"
+        );
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn hash160_p2pkh() {
+        // compressed public key of the secret key 1, with the P2PKH address
+        // 1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH
+        let public_key = [
+            0x02, 0x79, 0xbe, 0x66, 0x7e, 0xf9, 0xdc, 0xbb, 0xac, 0x55, 0xa0, 0x62, 0x95, 0xce,
+            0x87, 0x0b, 0x07, 0x02, 0x9b, 0xfc, 0xdb, 0x2d, 0xce, 0x28, 0xd9, 0x59, 0xf2, 0x81,
+            0x5b, 0x16, 0xf8, 0x17, 0x98,
+        ];
+        assert_eq!(
+            hash160(&public_key),
+            [
+                0x75, 0x1e, 0x76, 0xe8, 0x19, 0x91, 0x96, 0xd4, 0x54, 0x94, 0x1c, 0x45, 0xd1, 0xb3,
+                0xa3, 0x23, 0xf1, 0x43, 0x3b, 0xd6,
+            ]
+        );
+    }
+}
@@ -1,5 +1,17 @@
 //! Salsa20 Stream Cipher
 //!
+//! Implementation of the [Salsa20 spec](https://cr.yp.to/snuffle/spec.pdf),
+//! with 20 rounds, a 16 or 32 bytes key and a 8 bytes nonce, as created with
+//! [`Salsa20::new`].
+//!
+//! [`Salsa20::new_xsalsa20`] is the [XSalsa20](https://cr.yp.to/snuffle/xsalsa-20110204.pdf)
+//! variant with a 32 bytes key and a 24 bytes nonce, as used by NaCl's
+//! `crypto_secretbox`. Its subkey is derived with [`hsalsa20`], which is also
+//! what NaCl's `crypto_box` applies to the X25519 shared secret.
+//!
+//! As with any stream cipher, there's only one operation [`Salsa20::process`]
+//! for both encryption and decryption.
+//!
 //! # Examples
 //!
 //! Combine a simple input using a 128 bits key and 64 bit nonce:
@@ -26,7 +38,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::cryptoutil::{read_u32_le, write_u32_le, xor_keystream};
+use crate::cryptoutil::{read_u32_le, write_u32_le, xor_keystream, xor_keystream_mut};
 use crate::simd::u32x4;
 
 use core::cmp;
@@ -108,6 +120,10 @@ impl Salsa20 {
         }
     }
 
+    /// Create a new XSalsa20 context, with a 32 bytes key and a 24 bytes nonce
+    ///
+    /// The extended nonce is large enough to be chosen at random for each message,
+    /// from a cryptographically secure random number generator.
     pub fn new_xsalsa20(key: &[u8], nonce: &[u8]) -> Salsa20 {
         assert!(key.len() == 32);
         assert!(nonce.len() == 24);
@@ -205,7 +221,7 @@ impl Salsa20 {
         self.offset = 0;
     }
 
-    fn hsalsa20_hash(&mut self, out: &mut [u8]) {
+    fn hsalsa20_hash(&self, out: &mut [u8]) {
         let mut state = self.state.clone();
         for _ in 0..10 {
             columnround(&mut state);
@@ -223,6 +239,25 @@ impl Salsa20 {
         }
     }
 
+    /// Process the input in place through the cipher xoring
+    pub fn process_mut(&mut self, data: &mut [u8]) {
+        let len = data.len();
+        let mut i = 0;
+        while i < len {
+            // If there is no keystream available in the output buffer,
+            // generate the next block.
+            if self.offset == 64 {
+                self.hash();
+            }
+
+            // Process the min(available keystream, remaining input length).
+            let count = cmp::min(64 - self.offset, len - i);
+            xor_keystream_mut(&mut data[i..i + count], &self.output[self.offset..]);
+            i += count;
+            self.offset += count;
+        }
+    }
+
     /// Process the input through the cipher, xoring the byte one-by-one
     ///
     /// the output need to be the same size as the input otherwise
@@ -251,15 +286,25 @@ impl Salsa20 {
     }
 }
 
-pub fn hsalsa20(key: &[u8], nonce: &[u8], out: &mut [u8]) {
-    assert!(key.len() == 32);
-    assert!(nonce.len() == 16);
-    let mut h = Salsa20 {
+/// HSalsa20 function, deriving a 32 bytes subkey from a key and a 16 bytes nonce
+///
+/// The 20 rounds of Salsa20 are applied to the key and the nonce, and the
+/// diagonal and the nonce words of the state are returned, without the
+/// feed-forward addition of the input. NaCl uses it to derive the XSalsa20
+/// subkey, and the `crypto_box` key from an X25519 shared secret with a zero
+/// nonce.
+///
+/// Like HChaCha20, it is a PRF keyed by `key`, not a hash function: its
+/// output is only pseudorandom for a secret and uniformly random key.
+pub fn hsalsa20(key: &[u8; 32], nonce: &[u8; 16]) -> [u8; 32] {
+    let h = Salsa20 {
         state: Salsa20::expand(key, nonce),
         output: [0; 64],
         offset: 64,
     };
-    h.hsalsa20_hash(out);
+    let mut out = [0u8; 32];
+    h.hsalsa20_hash(&mut out);
+    out
 }
 
 #[cfg(test)]
@@ -267,7 +312,7 @@ mod test {
     use alloc::vec::Vec;
     use std::iter::repeat;
 
-    use super::Salsa20;
+    use super::{hsalsa20, Salsa20};
 
     use crate::digest::Digest;
     use crate::sha2::Sha256;
@@ -364,6 +409,23 @@ mod test {
         xsalsa20.process(&input, &mut stream);
         assert!(stream[..] == result[..]);
     }
+
+    #[test]
+    fn test_hsalsa20() {
+        // NaCl crypto_box shared key test (core1): HSalsa20 of the X25519
+        // shared secret with a zero nonce
+        let shared = [
+            0x4a, 0x5d, 0x9d, 0x5b, 0xa4, 0xce, 0x2d, 0xe1, 0x72, 0x8e, 0x3b, 0xf4, 0x80, 0x35,
+            0x0f, 0x25, 0xe0, 0x7e, 0x21, 0xc9, 0x47, 0xd1, 0x9e, 0x33, 0x76, 0xf0, 0x9b, 0x3c,
+            0x1e, 0x16, 0x17, 0x42,
+        ];
+        let expected = [
+            0x1b, 0x27, 0x55, 0x64, 0x73, 0xe9, 0x85, 0xd4, 0x62, 0xcd, 0x51, 0x19, 0x7a, 0x9a,
+            0x46, 0xc7, 0x60, 0x09, 0x54, 0x9e, 0xac, 0x64, 0x74, 0xf2, 0x06, 0xc4, 0xee, 0x08,
+            0x44, 0xf6, 0x83, 0x89,
+        ];
+        assert_eq!(hsalsa20(&shared, &[0u8; 16]), expected);
+    }
 }
 
 #[cfg(all(test, feature = "with-bench"))]
@@ -23,6 +23,7 @@ use core::mem::size_of;
 use crate::cryptoutil::copy_memory;
 use crate::cryptoutil::{read_u32_le, read_u32v_le, write_u32_le};
 use crate::hmac::Hmac;
+#[cfg_attr(feature = "no-panic", allow(deprecated))]
 use crate::pbkdf2::pbkdf2;
 use crate::sha2::Sha256;
 
@@ -225,6 +226,7 @@ impl ScryptParams {
  * * output - The resulting derived key is returned in this byte vector.
  *
  */
+#[cfg_attr(feature = "no-panic", allow(deprecated))]
 pub fn scrypt(password: &[u8], salt: &[u8], params: &ScryptParams, output: &mut [u8]) {
     // This check required by Scrypt:
     // check output.len() > 0 && output.len() <= (2^32 - 1) * 32
@@ -0,0 +1,380 @@
+//! Encryption of a stream of messages, compatible with libsodium's
+//! `crypto_secretstream_xchacha20poly1305`
+//!
+//! A stream is a sequence of messages encrypted with XChaCha20-Poly1305 under
+//! the same key, each message being authenticated along with its position
+//! in the stream: messages can't be reordered, removed or duplicated without
+//! being detected. The nonces are managed by the [`State`], and the key is
+//! renewed automatically after 2^32 messages or on demand.
+//!
+//! Each encrypted message carries a tag, chosen by the sender, which can be
+//! used to mark the end of a group of messages ([`TAG_PUSH`]), to force the
+//! renewal of the key ([`TAG_REKEY`]), or the end of the stream ([`TAG_FINAL`]).
+//! Since the end of the stream is only marked by a tag, the receiver needs to
+//! check that the last message of the stream has the tag `TAG_FINAL` to detect
+//! a truncation of the stream.
+//!
+//! ```
+//! use cryptoxide::secretstream::{pull_init, push_init, ABYTES, TAG_FINAL, TAG_MESSAGE};
+//!
+//! let key = [1u8; 32];
+//! // use the operating system RNG here, this is only for example !
+//! let (mut state, header) = push_init(&key, |buf| buf.fill(0x42));
+//!
+//! let mut c1 = [0u8; 5 + ABYTES];
+//! let mut c2 = [0u8; 6 + ABYTES];
+//! state.push(b"hello", b"", TAG_MESSAGE, &mut c1);
+//! state.push(b" world", b"", TAG_FINAL, &mut c2);
+//!
+//! // the header is sent before the encrypted messages
+//! let mut state = pull_init(&key, &header);
+//! let mut m1 = [0u8; 5];
+//! let mut m2 = [0u8; 6];
+//! assert_eq!(state.pull(&c1, b"", &mut m1), Some(TAG_MESSAGE));
+//! assert_eq!(state.pull(&c2, b"", &mut m2), Some(TAG_FINAL));
+//! assert_eq!(&m1, b"hello");
+//! assert_eq!(&m2, b" world");
+//! ```
+
+use crate::chacha20::{hchacha20, ChaCha20};
+use crate::mac::OneTimeMac;
+use crate::poly1305::Poly1305;
+use crate::util::{fixed_time_eq, secure_memset};
+
+/// Size of the key in bytes
+pub const KEYBYTES: usize = 32;
+
+/// Size of the stream header in bytes
+pub const HEADERBYTES: usize = 24;
+
+/// Size added to each message by the encryption, the encrypted tag and the MAC
+pub const ABYTES: usize = 1 + 16;
+
+/// Tag of a regular message
+pub const TAG_MESSAGE: u8 = 0;
+
+/// Tag marking the end of a group of messages, without other effect
+pub const TAG_PUSH: u8 = 1;
+
+/// Tag renewing the key after the message
+pub const TAG_REKEY: u8 = 2;
+
+/// Tag marking the end of the stream, renewing the key after the message
+pub const TAG_FINAL: u8 = TAG_PUSH | TAG_REKEY;
+
+const PAD0: [u8; 16] = [0; 16];
+
+/// State of an encrypted stream, on the sender or on the receiver side
+#[derive(Clone)]
+pub struct State {
+    key: [u8; KEYBYTES],
+    // little endian 32 bits counter followed by the 8 bytes of the internal nonce
+    nonce: [u8; 12],
+}
+
+impl Drop for State {
+    fn drop(&mut self) {
+        secure_memset(&mut self.key[..], 0);
+    }
+}
+
+/// Start the encryption of a stream, with the stream header filled by `fill_random`
+///
+/// `fill_random` needs to fill the header from a cryptographically secure random
+/// number generator, such as the operating system RNG: the same key can then be
+/// used for many streams. The header needs to be sent to the receiver, which
+/// starts the decryption with [`pull_init`].
+pub fn push_init<F: FnOnce(&mut [u8])>(
+    key: &[u8; KEYBYTES],
+    fill_random: F,
+) -> (State, [u8; HEADERBYTES]) {
+    let mut header = [0u8; HEADERBYTES];
+    fill_random(&mut header);
+    (State::init(key, &header), header)
+}
+
+/// Start the decryption of a stream, given the header created by [`push_init`]
+pub fn pull_init(key: &[u8; KEYBYTES], header: &[u8; HEADERBYTES]) -> State {
+    State::init(key, header)
+}
+
+impl State {
+    fn init(key: &[u8; KEYBYTES], header: &[u8; HEADERBYTES]) -> Self {
+        let mut hnonce = [0u8; 16];
+        hnonce.copy_from_slice(&header[0..16]);
+        let mut nonce = [0u8; 12];
+        nonce[4..12].copy_from_slice(&header[16..24]);
+        let mut state = State {
+            key: hchacha20(key, &hnonce),
+            nonce,
+        };
+        state.reset_counter();
+        state
+    }
+
+    fn reset_counter(&mut self) {
+        self.nonce[0..4].copy_from_slice(&1u32.to_le_bytes());
+    }
+
+    /// Renew the key explicitly
+    ///
+    /// The sender and the receiver need to renew the key at the same position
+    /// of the stream. Sending a message with [`TAG_REKEY`] does it implicitly.
+    pub fn rekey(&mut self) {
+        let mut new_key_and_inonce = [0u8; KEYBYTES + 8];
+        new_key_and_inonce[0..KEYBYTES].copy_from_slice(&self.key);
+        new_key_and_inonce[KEYBYTES..].copy_from_slice(&self.nonce[4..12]);
+        ChaCha20::new(&self.key, &self.nonce).process_mut(&mut new_key_and_inonce);
+        self.key.copy_from_slice(&new_key_and_inonce[0..KEYBYTES]);
+        self.nonce[4..12].copy_from_slice(&new_key_and_inonce[KEYBYTES..]);
+        secure_memset(&mut new_key_and_inonce[..], 0);
+        self.reset_counter();
+    }
+
+    // create the cipher, skipping the first block used for the poly1305 key,
+    // and the MAC with the authenticated data already added
+    fn start(&self, aad: &[u8]) -> (ChaCha20, Poly1305) {
+        let mut cipher = ChaCha20::new(&self.key, &self.nonce);
+        let mut block = [0u8; 64];
+        cipher.process_mut(&mut block);
+        let mut poly_key = [0u8; 32];
+        poly_key.copy_from_slice(&block[0..32]);
+        secure_memset(&mut block[..], 0);
+        let mut mac = Poly1305::new(poly_key);
+        mac.input(aad);
+        mac.input(&PAD0[..0x10usize.wrapping_sub(aad.len()) & 0xf]);
+        (cipher, mac)
+    }
+
+    fn finish(mut mac: Poly1305, aad: &[u8], encrypted: &[u8]) -> [u8; 16] {
+        // the padding of libsodium is the message length modulo 16
+        // instead of the padding to a multiple of 16
+        mac.input(&PAD0[..encrypted.len() & 0xf]);
+        mac.input(&(aad.len() as u64).to_le_bytes());
+        mac.input(&(64 + encrypted.len() as u64).to_le_bytes());
+        OneTimeMac::finalize(mac)
+    }
+
+    fn next(&mut self, mac: &[u8; 16], tag: u8) {
+        for (n, m) in self.nonce[4..12].iter_mut().zip(mac.iter()) {
+            *n ^= *m;
+        }
+        let mut counter = [0u8; 4];
+        counter.copy_from_slice(&self.nonce[0..4]);
+        let counter = u32::from_le_bytes(counter).wrapping_add(1);
+        self.nonce[0..4].copy_from_slice(&counter.to_le_bytes());
+        if tag & TAG_REKEY != 0 || counter == 0 {
+            self.rekey();
+        }
+    }
+
+    /// Encrypt the next message of the stream with the given tag
+    ///
+    /// The output needs to be `ABYTES` bytes longer than the message.
+    pub fn push(&mut self, message: &[u8], aad: &[u8], tag: u8, out: &mut [u8]) {
+        assert!(out.len() == message.len() + ABYTES);
+        let (mut cipher, mut mac) = self.start(aad);
+
+        let mut block = [0u8; 64];
+        block[0] = tag;
+        cipher.process_mut(&mut block);
+        mac.input(&block);
+        out[0] = block[0];
+
+        let (encrypted, out_mac) = out[1..].split_at_mut(message.len());
+        cipher.process(message, encrypted);
+        mac.input(encrypted);
+        let mac = Self::finish(mac, aad, encrypted);
+        out_mac.copy_from_slice(&mac);
+
+        self.next(&mac, tag);
+    }
+
+    /// Decrypt the next message of the stream, returning its tag, or `None`
+    /// if the message is not authentic
+    ///
+    /// The output needs to be `ABYTES` bytes shorter than the encrypted message.
+    /// On failure, the state is unchanged and the output is not written.
+    pub fn pull(&mut self, encrypted: &[u8], aad: &[u8], out: &mut [u8]) -> Option<u8> {
+        assert!(encrypted.len() >= ABYTES && out.len() == encrypted.len() - ABYTES);
+        let (mut cipher, mut mac) = self.start(aad);
+
+        let mut block = [0u8; 64];
+        block[0] = encrypted[0];
+        cipher.process_mut(&mut block);
+        let tag = block[0];
+        block[0] = encrypted[0];
+        mac.input(&block);
+
+        let (ciphertext, expected_mac) = encrypted[1..].split_at(out.len());
+        mac.input(ciphertext);
+        let mac = Self::finish(mac, aad, ciphertext);
+        if !fixed_time_eq(&mac, expected_mac) {
+            return None;
+        }
+        cipher.process(ciphertext, out);
+
+        self.next(&mac, tag);
+        Some(tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn unhex(s: &str) -> Vec<u8> {
+        (0..s.len() / 2)
+            .map(|i| u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap())
+            .collect()
+    }
+
+    struct Chunk {
+        message: Vec<u8>,
+        aad: &'static [u8],
+        tag: u8,
+        rekey_before: bool,
+        encrypted: &'static str,
+    }
+
+    // generated with libsodium 1.0.18, with the key 00 01 .. 1f
+    // and the header 40 41 .. 57
+    fn chunks() -> Vec<Chunk> {
+        vec![
+            Chunk {
+                message: b"first message".to_vec(),
+                aad: b"ad",
+                tag: TAG_MESSAGE,
+                rekey_before: false,
+                encrypted: "0da5f1329a7ff8c52c1910c2fb35954a72fb482f0ffd5023357b827438a9",
+            },
+            Chunk {
+                message: Vec::new(),
+                aad: b"",
+                tag: TAG_PUSH,
+                rekey_before: false,
+                encrypted: "3fbdc00280c62ca10367a19ae4b7e6a8c7",
+            },
+            Chunk {
+                message: (0..100).collect(),
+                aad: b"",
+                tag: TAG_REKEY,
+                rekey_before: false,
+                encrypted: "edfca393bb22ec14f8322259492cd568bb51ce692bbdeb39fc5e249dd30ca9a8\
+                            056ffb65dd83d9b0512f2f94289f0eeb6cd8332039dddd5e65f1b3edb05b13f4\
+                            46e0b96985be070e5ba671f7e70fe3f488289e0fee8dfa6d89f446936d019166\
+                            f4338c46904d3d4752472adf908bb2b47e1b15aa64",
+            },
+            Chunk {
+                message: vec![0x11; 17],
+                aad: b"header",
+                tag: TAG_MESSAGE,
+                rekey_before: false,
+                encrypted: "0d26f5b9a22e299d819e01c0a84e3a7e64d3e8e68f2eec14c44c49d912e8bba8\
+                            f0ca",
+            },
+            Chunk {
+                message: vec![0x22; 64],
+                aad: b"",
+                tag: TAG_MESSAGE,
+                rekey_before: true,
+                encrypted: "ce41e8449c62565b513a74ed285e011beac0e482df829802efe24a55c40ddf76\
+                            bf76620e8f24daa5fa667366e74972f061b11767eec4766adfd06cba3d43521f\
+                            962ac368d6c2bd647dd9b21c54d2d74387",
+            },
+            Chunk {
+                message: b"last".to_vec(),
+                aad: b"",
+                tag: TAG_FINAL,
+                rekey_before: false,
+                encrypted: "59ae0042d459627d39d6bd6245ec9cadb3960c60d8",
+            },
+            Chunk {
+                message: b"xyz".to_vec(),
+                aad: &[0x33; 40],
+                tag: TAG_MESSAGE,
+                rekey_before: false,
+                encrypted: "e9e7a06271e47c401299d8145b1b04975935cfc0",
+            },
+        ]
+    }
+
+    const KEY: [u8; 32] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+        0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d,
+        0x1e, 0x1f,
+    ];
+
+    fn header() -> [u8; HEADERBYTES] {
+        core::array::from_fn(|i| 0x40 + i as u8)
+    }
+
+    #[test]
+    fn push_vectors() {
+        let (mut state, h) = push_init(&KEY, |buf| buf.copy_from_slice(&header()));
+        assert_eq!(h, header());
+        for chunk in chunks().iter() {
+            if chunk.rekey_before {
+                state.rekey();
+            }
+            let mut out = vec![0u8; chunk.message.len() + ABYTES];
+            state.push(&chunk.message, chunk.aad, chunk.tag, &mut out);
+            assert_eq!(out, unhex(chunk.encrypted));
+        }
+    }
+
+    #[test]
+    fn pull_vectors() {
+        let mut state = pull_init(&KEY, &header());
+        for chunk in chunks().iter() {
+            if chunk.rekey_before {
+                state.rekey();
+            }
+            let encrypted = unhex(chunk.encrypted);
+            let mut out = vec![0u8; encrypted.len() - ABYTES];
+
+            // tampered message and wrong aad, leaving the state unchanged
+            let mut tampered = encrypted.clone();
+            tampered[0] ^= 1;
+            assert_eq!(state.pull(&tampered, chunk.aad, &mut out), None);
+            assert_eq!(state.pull(&encrypted, b"wrong aad", &mut out), None);
+
+            assert_eq!(state.pull(&encrypted, chunk.aad, &mut out), Some(chunk.tag));
+            assert_eq!(out, chunk.message);
+        }
+    }
+
+    #[test]
+    fn counter_wrap() {
+        let mut push_state = pull_init(&KEY, &header());
+        let mut pull_state = push_state.clone();
+
+        for message in [&b"first"[..], &b"second"[..]].iter() {
+            push_state.nonce[0..4].copy_from_slice(&u32::MAX.to_le_bytes());
+            pull_state.nonce[0..4].copy_from_slice(&u32::MAX.to_le_bytes());
+            let key = push_state.key;
+            let mut encrypted = vec![0u8; message.len() + ABYTES];
+            push_state.push(message, b"", TAG_MESSAGE, &mut encrypted);
+            // the counter wrapped and the key was renewed
+            assert_eq!(&push_state.nonce[0..4], &[1, 0, 0, 0]);
+            assert_ne!(push_state.key, key);
+            let mut out = vec![0u8; message.len()];
+            assert_eq!(
+                pull_state.pull(&encrypted, b"", &mut out),
+                Some(TAG_MESSAGE)
+            );
+            assert_eq!(&out[..], *message);
+            assert_eq!(push_state.key, pull_state.key);
+        }
+    }
+
+    #[test]
+    fn reordered() {
+        let chunks = chunks();
+        let mut state = pull_init(&KEY, &header());
+        let encrypted = unhex(chunks[1].encrypted);
+        let mut out = vec![0u8; encrypted.len() - ABYTES];
+        assert_eq!(state.pull(&encrypted, chunks[1].aad, &mut out), None);
+    }
+}
@@ -0,0 +1,143 @@
+//! Known-answer self tests, to run at startup
+//!
+//! [`self_test`] computes a known answer for each enabled primitive and
+//! compares it with the expected value, reporting the first mismatch.
+//! This checks that the library code has not been corrupted (e.g. on disk
+//! or in memory) before it is used, as required by FIPS style deployments.
+//!
+//! # Examples
+//!
+//! ```
+//! cryptoxide::self_test().expect("cryptoxide self test");
+//! ```
+
+/// Primitive whose known-answer test failed, returned by [`self_test`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelfTestFailure {
+    /// SHA-256 of "abc"
+    Sha256,
+    /// Blake2b-512 of "abc"
+    Blake2b,
+    /// HMAC-SHA256 of RFC 4231 test case 1
+    HmacSha256,
+    /// Ed25519 signature and verification of RFC 8032 test 1
+    Ed25519,
+}
+
+impl core::fmt::Display for SelfTestFailure {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let name = match self {
+            SelfTestFailure::Sha256 => "sha256",
+            SelfTestFailure::Blake2b => "blake2b",
+            SelfTestFailure::HmacSha256 => "hmac-sha256",
+            SelfTestFailure::Ed25519 => "ed25519",
+        };
+        write!(f, "{} known-answer test failed", name)
+    }
+}
+
+/// Run the known-answer test of each primitive enabled by the crate features
+///
+/// Return the first primitive whose result doesn't match the expected one.
+pub fn self_test() -> Result<(), SelfTestFailure> {
+    #[cfg(feature = "sha2")]
+    check(sha256(), SelfTestFailure::Sha256)?;
+    #[cfg(feature = "blake2")]
+    check(blake2b(), SelfTestFailure::Blake2b)?;
+    #[cfg(all(feature = "hmac", feature = "sha2"))]
+    check(hmac_sha256(), SelfTestFailure::HmacSha256)?;
+    #[cfg(feature = "ed25519")]
+    check(ed25519(), SelfTestFailure::Ed25519)?;
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn check(passed: bool, failure: SelfTestFailure) -> Result<(), SelfTestFailure> {
+    if passed {
+        Ok(())
+    } else {
+        Err(failure)
+    }
+}
+
+#[cfg(feature = "sha2")]
+fn sha256() -> bool {
+    const EXPECTED: [u8; 32] = [
+        0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae, 0x22,
+        0x23, 0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61, 0xf2, 0x00,
+        0x15, 0xad,
+    ];
+    crate::sha2::sha256(b"abc") == EXPECTED
+}
+
+#[cfg(feature = "blake2")]
+fn blake2b() -> bool {
+    const EXPECTED: [u8; 64] = [
+        0xba, 0x80, 0xa5, 0x3f, 0x98, 0x1c, 0x4d, 0x0d, 0x6a, 0x27, 0x97, 0xb6, 0x9f, 0x12, 0xf6,
+        0xe9, 0x4c, 0x21, 0x2f, 0x14, 0x68, 0x5a, 0xc4, 0xb7, 0x4b, 0x12, 0xbb, 0x6f, 0xdb, 0xff,
+        0xa2, 0xd1, 0x7d, 0x87, 0xc5, 0x39, 0x2a, 0xab, 0x79, 0x2d, 0xc2, 0x52, 0xd5, 0xde, 0x45,
+        0x33, 0xcc, 0x95, 0x18, 0xd3, 0x8a, 0xa8, 0xdb, 0xf1, 0x92, 0x5a, 0xb9, 0x23, 0x86, 0xed,
+        0xd4, 0x00, 0x99, 0x23,
+    ];
+    match crate::blake2b::Blake2b::try_new(64) {
+        Ok(ctx) => ctx.chain(b"abc").finalize_array::<64>() == EXPECTED,
+        Err(_) => false,
+    }
+}
+
+#[cfg(all(feature = "hmac", feature = "sha2"))]
+fn hmac_sha256() -> bool {
+    use crate::mac::Mac;
+
+    const EXPECTED: [u8; 32] = [
+        0xb0, 0x34, 0x4c, 0x61, 0xd8, 0xdb, 0x38, 0x53, 0x5c, 0xa8, 0xaf, 0xce, 0xaf, 0x0b, 0xf1,
+        0x2b, 0x88, 0x1d, 0xc2, 0x00, 0xc9, 0x83, 0x3d, 0xa7, 0x26, 0xe9, 0x37, 0x6c, 0x2e, 0x32,
+        0xcf, 0xf7,
+    ];
+    let mut hmac = crate::hmac::Hmac::new(crate::sha2::Sha256::new(), &[0x0b; 20]);
+    hmac.input(b"Hi There");
+    let mut code = [0u8; 32];
+    hmac.raw_result(&mut code);
+    code == EXPECTED
+}
+
+#[cfg(feature = "ed25519")]
+fn ed25519() -> bool {
+    use crate::ed25519;
+
+    const SEED: [u8; 32] = [
+        0x9d, 0x61, 0xb1, 0x9d, 0xef, 0xfd, 0x5a, 0x60, 0xba, 0x84, 0x4a, 0xf4, 0x92, 0xec, 0x2c,
+        0xc4, 0x44, 0x49, 0xc5, 0x69, 0x7b, 0x32, 0x69, 0x19, 0x70, 0x3b, 0xac, 0x03, 0x1c, 0xae,
+        0x7f, 0x60,
+    ];
+    const PUBLIC: [u8; 32] = [
+        0xd7, 0x5a, 0x98, 0x01, 0x82, 0xb1, 0x0a, 0xb7, 0xd5, 0x4b, 0xfe, 0xd3, 0xc9, 0x64, 0x07,
+        0x3a, 0x0e, 0xe1, 0x72, 0xf3, 0xda, 0xa6, 0x23, 0x25, 0xaf, 0x02, 0x1a, 0x68, 0xf7, 0x07,
+        0x51, 0x1a,
+    ];
+    const SIGNATURE: [u8; 64] = [
+        0xe5, 0x56, 0x43, 0x00, 0xc3, 0x60, 0xac, 0x72, 0x90, 0x86, 0xe2, 0xcc, 0x80, 0x6e, 0x82,
+        0x8a, 0x84, 0x87, 0x7f, 0x1e, 0xb8, 0xe5, 0xd9, 0x74, 0xd8, 0x73, 0xe0, 0x65, 0x22, 0x49,
+        0x01, 0x55, 0x5f, 0xb8, 0x82, 0x15, 0x90, 0xa3, 0x3b, 0xac, 0xc6, 0x1e, 0x39, 0x70, 0x1c,
+        0xf9, 0xb4, 0x6b, 0xd2, 0x5b, 0xf5, 0xf0, 0x59, 0x5b, 0xbe, 0x24, 0x65, 0x51, 0x41, 0x43,
+        0x8e, 0x7a, 0x10, 0x0b,
+    ];
+    let (secret, public) = match ed25519::try_keypair(&SEED) {
+        Ok(keys) => keys,
+        Err(_) => return false,
+    };
+    public == PUBLIC
+        && ed25519::try_signature(b"", &secret) == Ok(SIGNATURE)
+        && ed25519::try_verify(b"", &public, &SIGNATURE) == Ok(true)
+        && ed25519::try_verify(b"x", &public, &SIGNATURE) == Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn self_test_passes() {
+        assert_eq!(self_test(), Ok(()));
+    }
+}
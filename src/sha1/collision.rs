@@ -0,0 +1,983 @@
+//! SHA-1 with detection of collision attacks
+//!
+//! This implements the counter-cryptanalysis of [Stevens and Shumow][1], as used by Git
+//! and GitHub: every compressed block is checked against the 32 disturbance vectors
+//! used by the known SHA-1 collision attacks, including the SHAttered attack. For each
+//! disturbance vector, the compression of the sibling block of a possible near-collision
+//! is recomputed from an intermediate state, and an attack is detected if it produces
+//! the same chaining value. The chance of a false positive is negligible.
+//!
+//! Without the unavoidable bit conditions filtering of the reference implementation,
+//! all disturbance vectors are checked for every block, which makes [`Sha1Cd`] much
+//! slower than [`super::Sha1`].
+//!
+//! [1]: <https://github.com/cr-marcstevens/sha1collisiondetection>
+
+use super::add_bytes_to_bits;
+use crate::cryptoutil::{read_u32v_be, write_u32_be, FixedBuffer};
+use crate::digest::{assert_output_size, Digest};
+
+const STATE_LEN: usize = 5;
+const BLOCK_LEN: usize = 16;
+const STEPS: usize = 80;
+
+// steps at which the intermediate states are needed by the recompression
+const TESTT: [usize; 2] = [58, 65];
+
+struct DisturbanceVector {
+    // the step of the intermediate state used for the recompression
+    testt: usize,
+    // the differences of the expanded message words
+    dm: [u32; STEPS],
+}
+
+// Disturbance vectors and their message differences, from the sha1collisiondetection library
+#[rustfmt::skip]
+const SHA1_DVS: [DisturbanceVector; 32] = [
+    // I(43,0)
+    DisturbanceVector {
+        testt: 58,
+        dm: [
+            0x08000000, 0x9800000c, 0xd8000010, 0x08000010, 0xb8000010, 0x98000000,
+            0x60000000, 0x00000008, 0xc0000000, 0x90000014, 0x10000010, 0xb8000014,
+            0x28000000, 0x20000010, 0x48000000, 0x08000018, 0x60000000, 0x90000010,
+            0xf0000010, 0x90000008, 0xc0000000, 0x90000010, 0xf0000010, 0xb0000008,
+            0x40000000, 0x90000000, 0xf0000010, 0x90000018, 0x60000000, 0x90000010,
+            0x90000010, 0x90000000, 0x80000000, 0x00000010, 0xa0000000, 0x20000000,
+            0xa0000000, 0x20000010, 0x00000000, 0x20000010, 0x20000000, 0x00000010,
+            0x20000000, 0x00000010, 0xa0000000, 0x00000000, 0x20000000, 0x20000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000001, 0x00000020,
+            0x00000001, 0x40000002, 0x40000040, 0x40000002, 0x80000004, 0x80000080,
+            0x80000006, 0x00000049, 0x00000103, 0x80000009, 0x80000012, 0x80000202,
+            0x00000018, 0x00000164, 0x00000408, 0x800000e6, 0x8000004c, 0x00000803,
+            0x80000161, 0x80000599,
+        ],
+    },
+    // I(44,0)
+    DisturbanceVector {
+        testt: 58,
+        dm: [
+            0xb4000008, 0x08000000, 0x9800000c, 0xd8000010, 0x08000010, 0xb8000010,
+            0x98000000, 0x60000000, 0x00000008, 0xc0000000, 0x90000014, 0x10000010,
+            0xb8000014, 0x28000000, 0x20000010, 0x48000000, 0x08000018, 0x60000000,
+            0x90000010, 0xf0000010, 0x90000008, 0xc0000000, 0x90000010, 0xf0000010,
+            0xb0000008, 0x40000000, 0x90000000, 0xf0000010, 0x90000018, 0x60000000,
+            0x90000010, 0x90000010, 0x90000000, 0x80000000, 0x00000010, 0xa0000000,
+            0x20000000, 0xa0000000, 0x20000010, 0x00000000, 0x20000010, 0x20000000,
+            0x00000010, 0x20000000, 0x00000010, 0xa0000000, 0x00000000, 0x20000000,
+            0x20000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000001,
+            0x00000020, 0x00000001, 0x40000002, 0x40000040, 0x40000002, 0x80000004,
+            0x80000080, 0x80000006, 0x00000049, 0x00000103, 0x80000009, 0x80000012,
+            0x80000202, 0x00000018, 0x00000164, 0x00000408, 0x800000e6, 0x8000004c,
+            0x00000803, 0x80000161,
+        ],
+    },
+    // I(45,0)
+    DisturbanceVector {
+        testt: 58,
+        dm: [
+            0xf4000014, 0xb4000008, 0x08000000, 0x9800000c, 0xd8000010, 0x08000010,
+            0xb8000010, 0x98000000, 0x60000000, 0x00000008, 0xc0000000, 0x90000014,
+            0x10000010, 0xb8000014, 0x28000000, 0x20000010, 0x48000000, 0x08000018,
+            0x60000000, 0x90000010, 0xf0000010, 0x90000008, 0xc0000000, 0x90000010,
+            0xf0000010, 0xb0000008, 0x40000000, 0x90000000, 0xf0000010, 0x90000018,
+            0x60000000, 0x90000010, 0x90000010, 0x90000000, 0x80000000, 0x00000010,
+            0xa0000000, 0x20000000, 0xa0000000, 0x20000010, 0x00000000, 0x20000010,
+            0x20000000, 0x00000010, 0x20000000, 0x00000010, 0xa0000000, 0x00000000,
+            0x20000000, 0x20000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000001, 0x00000020, 0x00000001, 0x40000002, 0x40000040, 0x40000002,
+            0x80000004, 0x80000080, 0x80000006, 0x00000049, 0x00000103, 0x80000009,
+            0x80000012, 0x80000202, 0x00000018, 0x00000164, 0x00000408, 0x800000e6,
+            0x8000004c, 0x00000803,
+        ],
+    },
+    // I(46,0)
+    DisturbanceVector {
+        testt: 58,
+        dm: [
+            0x2c000010, 0xf4000014, 0xb4000008, 0x08000000, 0x9800000c, 0xd8000010,
+            0x08000010, 0xb8000010, 0x98000000, 0x60000000, 0x00000008, 0xc0000000,
+            0x90000014, 0x10000010, 0xb8000014, 0x28000000, 0x20000010, 0x48000000,
+            0x08000018, 0x60000000, 0x90000010, 0xf0000010, 0x90000008, 0xc0000000,
+            0x90000010, 0xf0000010, 0xb0000008, 0x40000000, 0x90000000, 0xf0000010,
+            0x90000018, 0x60000000, 0x90000010, 0x90000010, 0x90000000, 0x80000000,
+            0x00000010, 0xa0000000, 0x20000000, 0xa0000000, 0x20000010, 0x00000000,
+            0x20000010, 0x20000000, 0x00000010, 0x20000000, 0x00000010, 0xa0000000,
+            0x00000000, 0x20000000, 0x20000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000001, 0x00000020, 0x00000001, 0x40000002, 0x40000040,
+            0x40000002, 0x80000004, 0x80000080, 0x80000006, 0x00000049, 0x00000103,
+            0x80000009, 0x80000012, 0x80000202, 0x00000018, 0x00000164, 0x00000408,
+            0x800000e6, 0x8000004c,
+        ],
+    },
+    // I(46,2)
+    DisturbanceVector {
+        testt: 58,
+        dm: [
+            0xb0000040, 0xd0000053, 0xd0000022, 0x20000000, 0x60000032, 0x60000043,
+            0x20000040, 0xe0000042, 0x60000002, 0x80000001, 0x00000020, 0x00000003,
+            0x40000052, 0x40000040, 0xe0000052, 0xa0000000, 0x80000040, 0x20000001,
+            0x20000060, 0x80000001, 0x40000042, 0xc0000043, 0x40000022, 0x00000003,
+            0x40000042, 0xc0000043, 0xc0000022, 0x00000001, 0x40000002, 0xc0000043,
+            0x40000062, 0x80000001, 0x40000042, 0x40000042, 0x40000002, 0x00000002,
+            0x00000040, 0x80000002, 0x80000000, 0x80000002, 0x80000040, 0x00000000,
+            0x80000040, 0x80000000, 0x00000040, 0x80000000, 0x00000040, 0x80000002,
+            0x00000000, 0x80000000, 0x80000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000004, 0x00000080, 0x00000004, 0x00000009, 0x00000101,
+            0x00000009, 0x00000012, 0x00000202, 0x0000001a, 0x00000124, 0x0000040c,
+            0x00000026, 0x0000004a, 0x0000080a, 0x00000060, 0x00000590, 0x00001020,
+            0x0000039a, 0x00000132,
+        ],
+    },
+    // I(47,0)
+    DisturbanceVector {
+        testt: 58,
+        dm: [
+            0xc8000010, 0x2c000010, 0xf4000014, 0xb4000008, 0x08000000, 0x9800000c,
+            0xd8000010, 0x08000010, 0xb8000010, 0x98000000, 0x60000000, 0x00000008,
+            0xc0000000, 0x90000014, 0x10000010, 0xb8000014, 0x28000000, 0x20000010,
+            0x48000000, 0x08000018, 0x60000000, 0x90000010, 0xf0000010, 0x90000008,
+            0xc0000000, 0x90000010, 0xf0000010, 0xb0000008, 0x40000000, 0x90000000,
+            0xf0000010, 0x90000018, 0x60000000, 0x90000010, 0x90000010, 0x90000000,
+            0x80000000, 0x00000010, 0xa0000000, 0x20000000, 0xa0000000, 0x20000010,
+            0x00000000, 0x20000010, 0x20000000, 0x00000010, 0x20000000, 0x00000010,
+            0xa0000000, 0x00000000, 0x20000000, 0x20000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000001, 0x00000020, 0x00000001, 0x40000002,
+            0x40000040, 0x40000002, 0x80000004, 0x80000080, 0x80000006, 0x00000049,
+            0x00000103, 0x80000009, 0x80000012, 0x80000202, 0x00000018, 0x00000164,
+            0x00000408, 0x800000e6,
+        ],
+    },
+    // I(47,2)
+    DisturbanceVector {
+        testt: 58,
+        dm: [
+            0x20000043, 0xb0000040, 0xd0000053, 0xd0000022, 0x20000000, 0x60000032,
+            0x60000043, 0x20000040, 0xe0000042, 0x60000002, 0x80000001, 0x00000020,
+            0x00000003, 0x40000052, 0x40000040, 0xe0000052, 0xa0000000, 0x80000040,
+            0x20000001, 0x20000060, 0x80000001, 0x40000042, 0xc0000043, 0x40000022,
+            0x00000003, 0x40000042, 0xc0000043, 0xc0000022, 0x00000001, 0x40000002,
+            0xc0000043, 0x40000062, 0x80000001, 0x40000042, 0x40000042, 0x40000002,
+            0x00000002, 0x00000040, 0x80000002, 0x80000000, 0x80000002, 0x80000040,
+            0x00000000, 0x80000040, 0x80000000, 0x00000040, 0x80000000, 0x00000040,
+            0x80000002, 0x00000000, 0x80000000, 0x80000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000004, 0x00000080, 0x00000004, 0x00000009,
+            0x00000101, 0x00000009, 0x00000012, 0x00000202, 0x0000001a, 0x00000124,
+            0x0000040c, 0x00000026, 0x0000004a, 0x0000080a, 0x00000060, 0x00000590,
+            0x00001020, 0x0000039a,
+        ],
+    },
+    // I(48,0)
+    DisturbanceVector {
+        testt: 58,
+        dm: [
+            0xb800000a, 0xc8000010, 0x2c000010, 0xf4000014, 0xb4000008, 0x08000000,
+            0x9800000c, 0xd8000010, 0x08000010, 0xb8000010, 0x98000000, 0x60000000,
+            0x00000008, 0xc0000000, 0x90000014, 0x10000010, 0xb8000014, 0x28000000,
+            0x20000010, 0x48000000, 0x08000018, 0x60000000, 0x90000010, 0xf0000010,
+            0x90000008, 0xc0000000, 0x90000010, 0xf0000010, 0xb0000008, 0x40000000,
+            0x90000000, 0xf0000010, 0x90000018, 0x60000000, 0x90000010, 0x90000010,
+            0x90000000, 0x80000000, 0x00000010, 0xa0000000, 0x20000000, 0xa0000000,
+            0x20000010, 0x00000000, 0x20000010, 0x20000000, 0x00000010, 0x20000000,
+            0x00000010, 0xa0000000, 0x00000000, 0x20000000, 0x20000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000001, 0x00000020, 0x00000001,
+            0x40000002, 0x40000040, 0x40000002, 0x80000004, 0x80000080, 0x80000006,
+            0x00000049, 0x00000103, 0x80000009, 0x80000012, 0x80000202, 0x00000018,
+            0x00000164, 0x00000408,
+        ],
+    },
+    // I(48,2)
+    DisturbanceVector {
+        testt: 58,
+        dm: [
+            0xe000002a, 0x20000043, 0xb0000040, 0xd0000053, 0xd0000022, 0x20000000,
+            0x60000032, 0x60000043, 0x20000040, 0xe0000042, 0x60000002, 0x80000001,
+            0x00000020, 0x00000003, 0x40000052, 0x40000040, 0xe0000052, 0xa0000000,
+            0x80000040, 0x20000001, 0x20000060, 0x80000001, 0x40000042, 0xc0000043,
+            0x40000022, 0x00000003, 0x40000042, 0xc0000043, 0xc0000022, 0x00000001,
+            0x40000002, 0xc0000043, 0x40000062, 0x80000001, 0x40000042, 0x40000042,
+            0x40000002, 0x00000002, 0x00000040, 0x80000002, 0x80000000, 0x80000002,
+            0x80000040, 0x00000000, 0x80000040, 0x80000000, 0x00000040, 0x80000000,
+            0x00000040, 0x80000002, 0x00000000, 0x80000000, 0x80000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000004, 0x00000080, 0x00000004,
+            0x00000009, 0x00000101, 0x00000009, 0x00000012, 0x00000202, 0x0000001a,
+            0x00000124, 0x0000040c, 0x00000026, 0x0000004a, 0x0000080a, 0x00000060,
+            0x00000590, 0x00001020,
+        ],
+    },
+    // I(49,0)
+    DisturbanceVector {
+        testt: 58,
+        dm: [
+            0x18000000, 0xb800000a, 0xc8000010, 0x2c000010, 0xf4000014, 0xb4000008,
+            0x08000000, 0x9800000c, 0xd8000010, 0x08000010, 0xb8000010, 0x98000000,
+            0x60000000, 0x00000008, 0xc0000000, 0x90000014, 0x10000010, 0xb8000014,
+            0x28000000, 0x20000010, 0x48000000, 0x08000018, 0x60000000, 0x90000010,
+            0xf0000010, 0x90000008, 0xc0000000, 0x90000010, 0xf0000010, 0xb0000008,
+            0x40000000, 0x90000000, 0xf0000010, 0x90000018, 0x60000000, 0x90000010,
+            0x90000010, 0x90000000, 0x80000000, 0x00000010, 0xa0000000, 0x20000000,
+            0xa0000000, 0x20000010, 0x00000000, 0x20000010, 0x20000000, 0x00000010,
+            0x20000000, 0x00000010, 0xa0000000, 0x00000000, 0x20000000, 0x20000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000001, 0x00000020,
+            0x00000001, 0x40000002, 0x40000040, 0x40000002, 0x80000004, 0x80000080,
+            0x80000006, 0x00000049, 0x00000103, 0x80000009, 0x80000012, 0x80000202,
+            0x00000018, 0x00000164,
+        ],
+    },
+    // I(49,2)
+    DisturbanceVector {
+        testt: 58,
+        dm: [
+            0x60000000, 0xe000002a, 0x20000043, 0xb0000040, 0xd0000053, 0xd0000022,
+            0x20000000, 0x60000032, 0x60000043, 0x20000040, 0xe0000042, 0x60000002,
+            0x80000001, 0x00000020, 0x00000003, 0x40000052, 0x40000040, 0xe0000052,
+            0xa0000000, 0x80000040, 0x20000001, 0x20000060, 0x80000001, 0x40000042,
+            0xc0000043, 0x40000022, 0x00000003, 0x40000042, 0xc0000043, 0xc0000022,
+            0x00000001, 0x40000002, 0xc0000043, 0x40000062, 0x80000001, 0x40000042,
+            0x40000042, 0x40000002, 0x00000002, 0x00000040, 0x80000002, 0x80000000,
+            0x80000002, 0x80000040, 0x00000000, 0x80000040, 0x80000000, 0x00000040,
+            0x80000000, 0x00000040, 0x80000002, 0x00000000, 0x80000000, 0x80000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000004, 0x00000080,
+            0x00000004, 0x00000009, 0x00000101, 0x00000009, 0x00000012, 0x00000202,
+            0x0000001a, 0x00000124, 0x0000040c, 0x00000026, 0x0000004a, 0x0000080a,
+            0x00000060, 0x00000590,
+        ],
+    },
+    // I(50,0)
+    DisturbanceVector {
+        testt: 65,
+        dm: [
+            0x0800000c, 0x18000000, 0xb800000a, 0xc8000010, 0x2c000010, 0xf4000014,
+            0xb4000008, 0x08000000, 0x9800000c, 0xd8000010, 0x08000010, 0xb8000010,
+            0x98000000, 0x60000000, 0x00000008, 0xc0000000, 0x90000014, 0x10000010,
+            0xb8000014, 0x28000000, 0x20000010, 0x48000000, 0x08000018, 0x60000000,
+            0x90000010, 0xf0000010, 0x90000008, 0xc0000000, 0x90000010, 0xf0000010,
+            0xb0000008, 0x40000000, 0x90000000, 0xf0000010, 0x90000018, 0x60000000,
+            0x90000010, 0x90000010, 0x90000000, 0x80000000, 0x00000010, 0xa0000000,
+            0x20000000, 0xa0000000, 0x20000010, 0x00000000, 0x20000010, 0x20000000,
+            0x00000010, 0x20000000, 0x00000010, 0xa0000000, 0x00000000, 0x20000000,
+            0x20000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000001,
+            0x00000020, 0x00000001, 0x40000002, 0x40000040, 0x40000002, 0x80000004,
+            0x80000080, 0x80000006, 0x00000049, 0x00000103, 0x80000009, 0x80000012,
+            0x80000202, 0x00000018,
+        ],
+    },
+    // I(50,2)
+    DisturbanceVector {
+        testt: 65,
+        dm: [
+            0x20000030, 0x60000000, 0xe000002a, 0x20000043, 0xb0000040, 0xd0000053,
+            0xd0000022, 0x20000000, 0x60000032, 0x60000043, 0x20000040, 0xe0000042,
+            0x60000002, 0x80000001, 0x00000020, 0x00000003, 0x40000052, 0x40000040,
+            0xe0000052, 0xa0000000, 0x80000040, 0x20000001, 0x20000060, 0x80000001,
+            0x40000042, 0xc0000043, 0x40000022, 0x00000003, 0x40000042, 0xc0000043,
+            0xc0000022, 0x00000001, 0x40000002, 0xc0000043, 0x40000062, 0x80000001,
+            0x40000042, 0x40000042, 0x40000002, 0x00000002, 0x00000040, 0x80000002,
+            0x80000000, 0x80000002, 0x80000040, 0x00000000, 0x80000040, 0x80000000,
+            0x00000040, 0x80000000, 0x00000040, 0x80000002, 0x00000000, 0x80000000,
+            0x80000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000004,
+            0x00000080, 0x00000004, 0x00000009, 0x00000101, 0x00000009, 0x00000012,
+            0x00000202, 0x0000001a, 0x00000124, 0x0000040c, 0x00000026, 0x0000004a,
+            0x0000080a, 0x00000060,
+        ],
+    },
+    // I(51,0)
+    DisturbanceVector {
+        testt: 65,
+        dm: [
+            0xe8000000, 0x0800000c, 0x18000000, 0xb800000a, 0xc8000010, 0x2c000010,
+            0xf4000014, 0xb4000008, 0x08000000, 0x9800000c, 0xd8000010, 0x08000010,
+            0xb8000010, 0x98000000, 0x60000000, 0x00000008, 0xc0000000, 0x90000014,
+            0x10000010, 0xb8000014, 0x28000000, 0x20000010, 0x48000000, 0x08000018,
+            0x60000000, 0x90000010, 0xf0000010, 0x90000008, 0xc0000000, 0x90000010,
+            0xf0000010, 0xb0000008, 0x40000000, 0x90000000, 0xf0000010, 0x90000018,
+            0x60000000, 0x90000010, 0x90000010, 0x90000000, 0x80000000, 0x00000010,
+            0xa0000000, 0x20000000, 0xa0000000, 0x20000010, 0x00000000, 0x20000010,
+            0x20000000, 0x00000010, 0x20000000, 0x00000010, 0xa0000000, 0x00000000,
+            0x20000000, 0x20000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000001, 0x00000020, 0x00000001, 0x40000002, 0x40000040, 0x40000002,
+            0x80000004, 0x80000080, 0x80000006, 0x00000049, 0x00000103, 0x80000009,
+            0x80000012, 0x80000202,
+        ],
+    },
+    // I(51,2)
+    DisturbanceVector {
+        testt: 65,
+        dm: [
+            0xa0000003, 0x20000030, 0x60000000, 0xe000002a, 0x20000043, 0xb0000040,
+            0xd0000053, 0xd0000022, 0x20000000, 0x60000032, 0x60000043, 0x20000040,
+            0xe0000042, 0x60000002, 0x80000001, 0x00000020, 0x00000003, 0x40000052,
+            0x40000040, 0xe0000052, 0xa0000000, 0x80000040, 0x20000001, 0x20000060,
+            0x80000001, 0x40000042, 0xc0000043, 0x40000022, 0x00000003, 0x40000042,
+            0xc0000043, 0xc0000022, 0x00000001, 0x40000002, 0xc0000043, 0x40000062,
+            0x80000001, 0x40000042, 0x40000042, 0x40000002, 0x00000002, 0x00000040,
+            0x80000002, 0x80000000, 0x80000002, 0x80000040, 0x00000000, 0x80000040,
+            0x80000000, 0x00000040, 0x80000000, 0x00000040, 0x80000002, 0x00000000,
+            0x80000000, 0x80000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000004, 0x00000080, 0x00000004, 0x00000009, 0x00000101, 0x00000009,
+            0x00000012, 0x00000202, 0x0000001a, 0x00000124, 0x0000040c, 0x00000026,
+            0x0000004a, 0x0000080a,
+        ],
+    },
+    // I(52,0)
+    DisturbanceVector {
+        testt: 65,
+        dm: [
+            0x04000010, 0xe8000000, 0x0800000c, 0x18000000, 0xb800000a, 0xc8000010,
+            0x2c000010, 0xf4000014, 0xb4000008, 0x08000000, 0x9800000c, 0xd8000010,
+            0x08000010, 0xb8000010, 0x98000000, 0x60000000, 0x00000008, 0xc0000000,
+            0x90000014, 0x10000010, 0xb8000014, 0x28000000, 0x20000010, 0x48000000,
+            0x08000018, 0x60000000, 0x90000010, 0xf0000010, 0x90000008, 0xc0000000,
+            0x90000010, 0xf0000010, 0xb0000008, 0x40000000, 0x90000000, 0xf0000010,
+            0x90000018, 0x60000000, 0x90000010, 0x90000010, 0x90000000, 0x80000000,
+            0x00000010, 0xa0000000, 0x20000000, 0xa0000000, 0x20000010, 0x00000000,
+            0x20000010, 0x20000000, 0x00000010, 0x20000000, 0x00000010, 0xa0000000,
+            0x00000000, 0x20000000, 0x20000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000001, 0x00000020, 0x00000001, 0x40000002, 0x40000040,
+            0x40000002, 0x80000004, 0x80000080, 0x80000006, 0x00000049, 0x00000103,
+            0x80000009, 0x80000012,
+        ],
+    },
+    // II(45,0)
+    DisturbanceVector {
+        testt: 58,
+        dm: [
+            0xec000014, 0x0c000002, 0xc0000010, 0xb400001c, 0x2c000004, 0xbc000018,
+            0xb0000010, 0x0000000c, 0xb8000010, 0x08000018, 0x78000010, 0x08000014,
+            0x70000010, 0xb800001c, 0xe8000000, 0xb0000004, 0x58000010, 0xb000000c,
+            0x48000000, 0xb0000000, 0xb8000010, 0x98000010, 0xa0000000, 0x00000000,
+            0x00000000, 0x20000000, 0x80000000, 0x00000010, 0x00000000, 0x20000010,
+            0x20000000, 0x00000010, 0x60000000, 0x00000018, 0xe0000000, 0x90000000,
+            0x30000010, 0xb0000000, 0x20000000, 0x20000000, 0xa0000000, 0x00000010,
+            0x80000000, 0x20000000, 0x20000000, 0x20000000, 0x80000000, 0x00000010,
+            0x00000000, 0x20000010, 0xa0000000, 0x00000000, 0x20000000, 0x20000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000001, 0x00000020, 0x00000001, 0x40000002, 0x40000041, 0x40000022,
+            0x80000005, 0xc0000082, 0xc0000046, 0x4000004b, 0x80000107, 0x00000089,
+            0x00000014, 0x8000024b, 0x0000011b, 0x8000016d, 0x8000041a, 0x000002e4,
+            0x80000054, 0x00000967,
+        ],
+    },
+    // II(46,0)
+    DisturbanceVector {
+        testt: 58,
+        dm: [
+            0x2400001c, 0xec000014, 0x0c000002, 0xc0000010, 0xb400001c, 0x2c000004,
+            0xbc000018, 0xb0000010, 0x0000000c, 0xb8000010, 0x08000018, 0x78000010,
+            0x08000014, 0x70000010, 0xb800001c, 0xe8000000, 0xb0000004, 0x58000010,
+            0xb000000c, 0x48000000, 0xb0000000, 0xb8000010, 0x98000010, 0xa0000000,
+            0x00000000, 0x00000000, 0x20000000, 0x80000000, 0x00000010, 0x00000000,
+            0x20000010, 0x20000000, 0x00000010, 0x60000000, 0x00000018, 0xe0000000,
+            0x90000000, 0x30000010, 0xb0000000, 0x20000000, 0x20000000, 0xa0000000,
+            0x00000010, 0x80000000, 0x20000000, 0x20000000, 0x20000000, 0x80000000,
+            0x00000010, 0x00000000, 0x20000010, 0xa0000000, 0x00000000, 0x20000000,
+            0x20000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000001, 0x00000020, 0x00000001, 0x40000002, 0x40000041,
+            0x40000022, 0x80000005, 0xc0000082, 0xc0000046, 0x4000004b, 0x80000107,
+            0x00000089, 0x00000014, 0x8000024b, 0x0000011b, 0x8000016d, 0x8000041a,
+            0x000002e4, 0x80000054,
+        ],
+    },
+    // II(46,2)
+    DisturbanceVector {
+        testt: 58,
+        dm: [
+            0x90000070, 0xb0000053, 0x30000008, 0x00000043, 0xd0000072, 0xb0000010,
+            0xf0000062, 0xc0000042, 0x00000030, 0xe0000042, 0x20000060, 0xe0000041,
+            0x20000050, 0xc0000041, 0xe0000072, 0xa0000003, 0xc0000012, 0x60000041,
+            0xc0000032, 0x20000001, 0xc0000002, 0xe0000042, 0x60000042, 0x80000002,
+            0x00000000, 0x00000000, 0x80000000, 0x00000002, 0x00000040, 0x00000000,
+            0x80000040, 0x80000000, 0x00000040, 0x80000001, 0x00000060, 0x80000003,
+            0x40000002, 0xc0000040, 0xc0000002, 0x80000000, 0x80000000, 0x80000002,
+            0x00000040, 0x00000002, 0x80000000, 0x80000000, 0x80000000, 0x00000002,
+            0x00000040, 0x00000000, 0x80000040, 0x80000002, 0x00000000, 0x80000000,
+            0x80000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000004, 0x00000080, 0x00000004, 0x00000009, 0x00000105,
+            0x00000089, 0x00000016, 0x0000020b, 0x0000011b, 0x0000012d, 0x0000041e,
+            0x00000224, 0x00000050, 0x0000092e, 0x0000046c, 0x000005b6, 0x0000106a,
+            0x00000b90, 0x00000152,
+        ],
+    },
+    // II(47,0)
+    DisturbanceVector {
+        testt: 58,
+        dm: [
+            0x20000010, 0x2400001c, 0xec000014, 0x0c000002, 0xc0000010, 0xb400001c,
+            0x2c000004, 0xbc000018, 0xb0000010, 0x0000000c, 0xb8000010, 0x08000018,
+            0x78000010, 0x08000014, 0x70000010, 0xb800001c, 0xe8000000, 0xb0000004,
+            0x58000010, 0xb000000c, 0x48000000, 0xb0000000, 0xb8000010, 0x98000010,
+            0xa0000000, 0x00000000, 0x00000000, 0x20000000, 0x80000000, 0x00000010,
+            0x00000000, 0x20000010, 0x20000000, 0x00000010, 0x60000000, 0x00000018,
+            0xe0000000, 0x90000000, 0x30000010, 0xb0000000, 0x20000000, 0x20000000,
+            0xa0000000, 0x00000010, 0x80000000, 0x20000000, 0x20000000, 0x20000000,
+            0x80000000, 0x00000010, 0x00000000, 0x20000010, 0xa0000000, 0x00000000,
+            0x20000000, 0x20000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000001, 0x00000020, 0x00000001, 0x40000002,
+            0x40000041, 0x40000022, 0x80000005, 0xc0000082, 0xc0000046, 0x4000004b,
+            0x80000107, 0x00000089, 0x00000014, 0x8000024b, 0x0000011b, 0x8000016d,
+            0x8000041a, 0x000002e4,
+        ],
+    },
+    // II(48,0)
+    DisturbanceVector {
+        testt: 58,
+        dm: [
+            0xbc00001a, 0x20000010, 0x2400001c, 0xec000014, 0x0c000002, 0xc0000010,
+            0xb400001c, 0x2c000004, 0xbc000018, 0xb0000010, 0x0000000c, 0xb8000010,
+            0x08000018, 0x78000010, 0x08000014, 0x70000010, 0xb800001c, 0xe8000000,
+            0xb0000004, 0x58000010, 0xb000000c, 0x48000000, 0xb0000000, 0xb8000010,
+            0x98000010, 0xa0000000, 0x00000000, 0x00000000, 0x20000000, 0x80000000,
+            0x00000010, 0x00000000, 0x20000010, 0x20000000, 0x00000010, 0x60000000,
+            0x00000018, 0xe0000000, 0x90000000, 0x30000010, 0xb0000000, 0x20000000,
+            0x20000000, 0xa0000000, 0x00000010, 0x80000000, 0x20000000, 0x20000000,
+            0x20000000, 0x80000000, 0x00000010, 0x00000000, 0x20000010, 0xa0000000,
+            0x00000000, 0x20000000, 0x20000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000001, 0x00000020, 0x00000001,
+            0x40000002, 0x40000041, 0x40000022, 0x80000005, 0xc0000082, 0xc0000046,
+            0x4000004b, 0x80000107, 0x00000089, 0x00000014, 0x8000024b, 0x0000011b,
+            0x8000016d, 0x8000041a,
+        ],
+    },
+    // II(49,0)
+    DisturbanceVector {
+        testt: 58,
+        dm: [
+            0x3c000004, 0xbc00001a, 0x20000010, 0x2400001c, 0xec000014, 0x0c000002,
+            0xc0000010, 0xb400001c, 0x2c000004, 0xbc000018, 0xb0000010, 0x0000000c,
+            0xb8000010, 0x08000018, 0x78000010, 0x08000014, 0x70000010, 0xb800001c,
+            0xe8000000, 0xb0000004, 0x58000010, 0xb000000c, 0x48000000, 0xb0000000,
+            0xb8000010, 0x98000010, 0xa0000000, 0x00000000, 0x00000000, 0x20000000,
+            0x80000000, 0x00000010, 0x00000000, 0x20000010, 0x20000000, 0x00000010,
+            0x60000000, 0x00000018, 0xe0000000, 0x90000000, 0x30000010, 0xb0000000,
+            0x20000000, 0x20000000, 0xa0000000, 0x00000010, 0x80000000, 0x20000000,
+            0x20000000, 0x20000000, 0x80000000, 0x00000010, 0x00000000, 0x20000010,
+            0xa0000000, 0x00000000, 0x20000000, 0x20000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000001, 0x00000020,
+            0x00000001, 0x40000002, 0x40000041, 0x40000022, 0x80000005, 0xc0000082,
+            0xc0000046, 0x4000004b, 0x80000107, 0x00000089, 0x00000014, 0x8000024b,
+            0x0000011b, 0x8000016d,
+        ],
+    },
+    // II(49,2)
+    DisturbanceVector {
+        testt: 58,
+        dm: [
+            0xf0000010, 0xf000006a, 0x80000040, 0x90000070, 0xb0000053, 0x30000008,
+            0x00000043, 0xd0000072, 0xb0000010, 0xf0000062, 0xc0000042, 0x00000030,
+            0xe0000042, 0x20000060, 0xe0000041, 0x20000050, 0xc0000041, 0xe0000072,
+            0xa0000003, 0xc0000012, 0x60000041, 0xc0000032, 0x20000001, 0xc0000002,
+            0xe0000042, 0x60000042, 0x80000002, 0x00000000, 0x00000000, 0x80000000,
+            0x00000002, 0x00000040, 0x00000000, 0x80000040, 0x80000000, 0x00000040,
+            0x80000001, 0x00000060, 0x80000003, 0x40000002, 0xc0000040, 0xc0000002,
+            0x80000000, 0x80000000, 0x80000002, 0x00000040, 0x00000002, 0x80000000,
+            0x80000000, 0x80000000, 0x00000002, 0x00000040, 0x00000000, 0x80000040,
+            0x80000002, 0x00000000, 0x80000000, 0x80000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000004, 0x00000080,
+            0x00000004, 0x00000009, 0x00000105, 0x00000089, 0x00000016, 0x0000020b,
+            0x0000011b, 0x0000012d, 0x0000041e, 0x00000224, 0x00000050, 0x0000092e,
+            0x0000046c, 0x000005b6,
+        ],
+    },
+    // II(50,0)
+    DisturbanceVector {
+        testt: 65,
+        dm: [
+            0xb400001c, 0x3c000004, 0xbc00001a, 0x20000010, 0x2400001c, 0xec000014,
+            0x0c000002, 0xc0000010, 0xb400001c, 0x2c000004, 0xbc000018, 0xb0000010,
+            0x0000000c, 0xb8000010, 0x08000018, 0x78000010, 0x08000014, 0x70000010,
+            0xb800001c, 0xe8000000, 0xb0000004, 0x58000010, 0xb000000c, 0x48000000,
+            0xb0000000, 0xb8000010, 0x98000010, 0xa0000000, 0x00000000, 0x00000000,
+            0x20000000, 0x80000000, 0x00000010, 0x00000000, 0x20000010, 0x20000000,
+            0x00000010, 0x60000000, 0x00000018, 0xe0000000, 0x90000000, 0x30000010,
+            0xb0000000, 0x20000000, 0x20000000, 0xa0000000, 0x00000010, 0x80000000,
+            0x20000000, 0x20000000, 0x20000000, 0x80000000, 0x00000010, 0x00000000,
+            0x20000010, 0xa0000000, 0x00000000, 0x20000000, 0x20000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000001,
+            0x00000020, 0x00000001, 0x40000002, 0x40000041, 0x40000022, 0x80000005,
+            0xc0000082, 0xc0000046, 0x4000004b, 0x80000107, 0x00000089, 0x00000014,
+            0x8000024b, 0x0000011b,
+        ],
+    },
+    // II(50,2)
+    DisturbanceVector {
+        testt: 65,
+        dm: [
+            0xd0000072, 0xf0000010, 0xf000006a, 0x80000040, 0x90000070, 0xb0000053,
+            0x30000008, 0x00000043, 0xd0000072, 0xb0000010, 0xf0000062, 0xc0000042,
+            0x00000030, 0xe0000042, 0x20000060, 0xe0000041, 0x20000050, 0xc0000041,
+            0xe0000072, 0xa0000003, 0xc0000012, 0x60000041, 0xc0000032, 0x20000001,
+            0xc0000002, 0xe0000042, 0x60000042, 0x80000002, 0x00000000, 0x00000000,
+            0x80000000, 0x00000002, 0x00000040, 0x00000000, 0x80000040, 0x80000000,
+            0x00000040, 0x80000001, 0x00000060, 0x80000003, 0x40000002, 0xc0000040,
+            0xc0000002, 0x80000000, 0x80000000, 0x80000002, 0x00000040, 0x00000002,
+            0x80000000, 0x80000000, 0x80000000, 0x00000002, 0x00000040, 0x00000000,
+            0x80000040, 0x80000002, 0x00000000, 0x80000000, 0x80000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000004,
+            0x00000080, 0x00000004, 0x00000009, 0x00000105, 0x00000089, 0x00000016,
+            0x0000020b, 0x0000011b, 0x0000012d, 0x0000041e, 0x00000224, 0x00000050,
+            0x0000092e, 0x0000046c,
+        ],
+    },
+    // II(51,0)
+    DisturbanceVector {
+        testt: 65,
+        dm: [
+            0xc0000010, 0xb400001c, 0x3c000004, 0xbc00001a, 0x20000010, 0x2400001c,
+            0xec000014, 0x0c000002, 0xc0000010, 0xb400001c, 0x2c000004, 0xbc000018,
+            0xb0000010, 0x0000000c, 0xb8000010, 0x08000018, 0x78000010, 0x08000014,
+            0x70000010, 0xb800001c, 0xe8000000, 0xb0000004, 0x58000010, 0xb000000c,
+            0x48000000, 0xb0000000, 0xb8000010, 0x98000010, 0xa0000000, 0x00000000,
+            0x00000000, 0x20000000, 0x80000000, 0x00000010, 0x00000000, 0x20000010,
+            0x20000000, 0x00000010, 0x60000000, 0x00000018, 0xe0000000, 0x90000000,
+            0x30000010, 0xb0000000, 0x20000000, 0x20000000, 0xa0000000, 0x00000010,
+            0x80000000, 0x20000000, 0x20000000, 0x20000000, 0x80000000, 0x00000010,
+            0x00000000, 0x20000010, 0xa0000000, 0x00000000, 0x20000000, 0x20000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000001, 0x00000020, 0x00000001, 0x40000002, 0x40000041, 0x40000022,
+            0x80000005, 0xc0000082, 0xc0000046, 0x4000004b, 0x80000107, 0x00000089,
+            0x00000014, 0x8000024b,
+        ],
+    },
+    // II(51,2)
+    DisturbanceVector {
+        testt: 65,
+        dm: [
+            0x00000043, 0xd0000072, 0xf0000010, 0xf000006a, 0x80000040, 0x90000070,
+            0xb0000053, 0x30000008, 0x00000043, 0xd0000072, 0xb0000010, 0xf0000062,
+            0xc0000042, 0x00000030, 0xe0000042, 0x20000060, 0xe0000041, 0x20000050,
+            0xc0000041, 0xe0000072, 0xa0000003, 0xc0000012, 0x60000041, 0xc0000032,
+            0x20000001, 0xc0000002, 0xe0000042, 0x60000042, 0x80000002, 0x00000000,
+            0x00000000, 0x80000000, 0x00000002, 0x00000040, 0x00000000, 0x80000040,
+            0x80000000, 0x00000040, 0x80000001, 0x00000060, 0x80000003, 0x40000002,
+            0xc0000040, 0xc0000002, 0x80000000, 0x80000000, 0x80000002, 0x00000040,
+            0x00000002, 0x80000000, 0x80000000, 0x80000000, 0x00000002, 0x00000040,
+            0x00000000, 0x80000040, 0x80000002, 0x00000000, 0x80000000, 0x80000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000004, 0x00000080, 0x00000004, 0x00000009, 0x00000105, 0x00000089,
+            0x00000016, 0x0000020b, 0x0000011b, 0x0000012d, 0x0000041e, 0x00000224,
+            0x00000050, 0x0000092e,
+        ],
+    },
+    // II(52,0)
+    DisturbanceVector {
+        testt: 65,
+        dm: [
+            0x0c000002, 0xc0000010, 0xb400001c, 0x3c000004, 0xbc00001a, 0x20000010,
+            0x2400001c, 0xec000014, 0x0c000002, 0xc0000010, 0xb400001c, 0x2c000004,
+            0xbc000018, 0xb0000010, 0x0000000c, 0xb8000010, 0x08000018, 0x78000010,
+            0x08000014, 0x70000010, 0xb800001c, 0xe8000000, 0xb0000004, 0x58000010,
+            0xb000000c, 0x48000000, 0xb0000000, 0xb8000010, 0x98000010, 0xa0000000,
+            0x00000000, 0x00000000, 0x20000000, 0x80000000, 0x00000010, 0x00000000,
+            0x20000010, 0x20000000, 0x00000010, 0x60000000, 0x00000018, 0xe0000000,
+            0x90000000, 0x30000010, 0xb0000000, 0x20000000, 0x20000000, 0xa0000000,
+            0x00000010, 0x80000000, 0x20000000, 0x20000000, 0x20000000, 0x80000000,
+            0x00000010, 0x00000000, 0x20000010, 0xa0000000, 0x00000000, 0x20000000,
+            0x20000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000001, 0x00000020, 0x00000001, 0x40000002, 0x40000041,
+            0x40000022, 0x80000005, 0xc0000082, 0xc0000046, 0x4000004b, 0x80000107,
+            0x00000089, 0x00000014,
+        ],
+    },
+    // II(53,0)
+    DisturbanceVector {
+        testt: 65,
+        dm: [
+            0xcc000014, 0x0c000002, 0xc0000010, 0xb400001c, 0x3c000004, 0xbc00001a,
+            0x20000010, 0x2400001c, 0xec000014, 0x0c000002, 0xc0000010, 0xb400001c,
+            0x2c000004, 0xbc000018, 0xb0000010, 0x0000000c, 0xb8000010, 0x08000018,
+            0x78000010, 0x08000014, 0x70000010, 0xb800001c, 0xe8000000, 0xb0000004,
+            0x58000010, 0xb000000c, 0x48000000, 0xb0000000, 0xb8000010, 0x98000010,
+            0xa0000000, 0x00000000, 0x00000000, 0x20000000, 0x80000000, 0x00000010,
+            0x00000000, 0x20000010, 0x20000000, 0x00000010, 0x60000000, 0x00000018,
+            0xe0000000, 0x90000000, 0x30000010, 0xb0000000, 0x20000000, 0x20000000,
+            0xa0000000, 0x00000010, 0x80000000, 0x20000000, 0x20000000, 0x20000000,
+            0x80000000, 0x00000010, 0x00000000, 0x20000010, 0xa0000000, 0x00000000,
+            0x20000000, 0x20000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000001, 0x00000020, 0x00000001, 0x40000002,
+            0x40000041, 0x40000022, 0x80000005, 0xc0000082, 0xc0000046, 0x4000004b,
+            0x80000107, 0x00000089,
+        ],
+    },
+    // II(54,0)
+    DisturbanceVector {
+        testt: 65,
+        dm: [
+            0x0400001c, 0xcc000014, 0x0c000002, 0xc0000010, 0xb400001c, 0x3c000004,
+            0xbc00001a, 0x20000010, 0x2400001c, 0xec000014, 0x0c000002, 0xc0000010,
+            0xb400001c, 0x2c000004, 0xbc000018, 0xb0000010, 0x0000000c, 0xb8000010,
+            0x08000018, 0x78000010, 0x08000014, 0x70000010, 0xb800001c, 0xe8000000,
+            0xb0000004, 0x58000010, 0xb000000c, 0x48000000, 0xb0000000, 0xb8000010,
+            0x98000010, 0xa0000000, 0x00000000, 0x00000000, 0x20000000, 0x80000000,
+            0x00000010, 0x00000000, 0x20000010, 0x20000000, 0x00000010, 0x60000000,
+            0x00000018, 0xe0000000, 0x90000000, 0x30000010, 0xb0000000, 0x20000000,
+            0x20000000, 0xa0000000, 0x00000010, 0x80000000, 0x20000000, 0x20000000,
+            0x20000000, 0x80000000, 0x00000010, 0x00000000, 0x20000010, 0xa0000000,
+            0x00000000, 0x20000000, 0x20000000, 0x00000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000001, 0x00000020, 0x00000001,
+            0x40000002, 0x40000041, 0x40000022, 0x80000005, 0xc0000082, 0xc0000046,
+            0x4000004b, 0x80000107,
+        ],
+    },
+    // II(55,0)
+    DisturbanceVector {
+        testt: 65,
+        dm: [
+            0x00000010, 0x0400001c, 0xcc000014, 0x0c000002, 0xc0000010, 0xb400001c,
+            0x3c000004, 0xbc00001a, 0x20000010, 0x2400001c, 0xec000014, 0x0c000002,
+            0xc0000010, 0xb400001c, 0x2c000004, 0xbc000018, 0xb0000010, 0x0000000c,
+            0xb8000010, 0x08000018, 0x78000010, 0x08000014, 0x70000010, 0xb800001c,
+            0xe8000000, 0xb0000004, 0x58000010, 0xb000000c, 0x48000000, 0xb0000000,
+            0xb8000010, 0x98000010, 0xa0000000, 0x00000000, 0x00000000, 0x20000000,
+            0x80000000, 0x00000010, 0x00000000, 0x20000010, 0x20000000, 0x00000010,
+            0x60000000, 0x00000018, 0xe0000000, 0x90000000, 0x30000010, 0xb0000000,
+            0x20000000, 0x20000000, 0xa0000000, 0x00000010, 0x80000000, 0x20000000,
+            0x20000000, 0x20000000, 0x80000000, 0x00000010, 0x00000000, 0x20000010,
+            0xa0000000, 0x00000000, 0x20000000, 0x20000000, 0x00000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000001, 0x00000020,
+            0x00000001, 0x40000002, 0x40000041, 0x40000022, 0x80000005, 0xc0000082,
+            0xc0000046, 0x4000004b,
+        ],
+    },
+    // II(56,0)
+    DisturbanceVector {
+        testt: 65,
+        dm: [
+            0x2600001a, 0x00000010, 0x0400001c, 0xcc000014, 0x0c000002, 0xc0000010,
+            0xb400001c, 0x3c000004, 0xbc00001a, 0x20000010, 0x2400001c, 0xec000014,
+            0x0c000002, 0xc0000010, 0xb400001c, 0x2c000004, 0xbc000018, 0xb0000010,
+            0x0000000c, 0xb8000010, 0x08000018, 0x78000010, 0x08000014, 0x70000010,
+            0xb800001c, 0xe8000000, 0xb0000004, 0x58000010, 0xb000000c, 0x48000000,
+            0xb0000000, 0xb8000010, 0x98000010, 0xa0000000, 0x00000000, 0x00000000,
+            0x20000000, 0x80000000, 0x00000010, 0x00000000, 0x20000010, 0x20000000,
+            0x00000010, 0x60000000, 0x00000018, 0xe0000000, 0x90000000, 0x30000010,
+            0xb0000000, 0x20000000, 0x20000000, 0xa0000000, 0x00000010, 0x80000000,
+            0x20000000, 0x20000000, 0x20000000, 0x80000000, 0x00000010, 0x00000000,
+            0x20000010, 0xa0000000, 0x00000000, 0x20000000, 0x20000000, 0x00000000,
+            0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000000, 0x00000001,
+            0x00000020, 0x00000001, 0x40000002, 0x40000041, 0x40000022, 0x80000005,
+            0xc0000082, 0xc0000046,
+        ],
+    },
+];
+
+#[inline]
+fn f_k(t: usize, b: u32, c: u32, d: u32) -> u32 {
+    match t / 20 {
+        0 => ((b & c) | (!b & d)).wrapping_add(0x5A827999),
+        1 => (b ^ c ^ d).wrapping_add(0x6ED9EBA1),
+        2 => ((b & c) | (b & d) | (c & d)).wrapping_add(0x8F1BBCDC),
+        _ => (b ^ c ^ d).wrapping_add(0xCA62C1D6),
+    }
+}
+
+// apply step t to the state [a, b, c, d, e]
+#[inline]
+fn step(s: &mut [u32; STATE_LEN], t: usize, w: u32) {
+    let [a, b, c, d, e] = *s;
+    let tmp = a
+        .rotate_left(5)
+        .wrapping_add(f_k(t, b, c, d))
+        .wrapping_add(e)
+        .wrapping_add(w);
+    *s = [tmp, a, b.rotate_left(30), c, d];
+}
+
+// revert step t of the state
+#[inline]
+fn unstep(s: &mut [u32; STATE_LEN], t: usize, w: u32) {
+    let [a1, b1, c1, d1, e1] = *s;
+    let (a, b, c, d) = (b1, c1.rotate_right(30), d1, e1);
+    let e = a1
+        .wrapping_sub(a.rotate_left(5))
+        .wrapping_sub(f_k(t, b, c, d))
+        .wrapping_sub(w);
+    *s = [a, b, c, d, e];
+}
+
+fn expand(block: &[u8]) -> [u32; STEPS] {
+    let mut w = [0u32; STEPS];
+    read_u32v_be(&mut w[..BLOCK_LEN], block);
+    for t in BLOCK_LEN..STEPS {
+        w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+    }
+    w
+}
+
+fn add_state(h: &mut [u32; STATE_LEN], s: &[u32; STATE_LEN]) {
+    for (h, s) in h.iter_mut().zip(s.iter()) {
+        *h = h.wrapping_add(*s);
+    }
+}
+
+// compress the expanded message, keeping the intermediate states before the steps of TESTT
+fn compress_states(h: &mut [u32; STATE_LEN], w: &[u32; STEPS]) -> [[u32; STATE_LEN]; 2] {
+    let mut states = [[0; STATE_LEN]; 2];
+    let mut s = *h;
+    for (t, wt) in w.iter().enumerate() {
+        if let Some(i) = TESTT.iter().position(|testt| *testt == t) {
+            states[i] = s;
+        }
+        step(&mut s, t, *wt);
+    }
+    add_state(h, &s);
+    states
+}
+
+// Recompute the chaining value input and output of the compression of the message `w`
+// going through `state` before the step `testt`
+fn recompress(
+    testt: usize,
+    state: &[u32; STATE_LEN],
+    w: &[u32; STEPS],
+) -> ([u32; STATE_LEN], [u32; STATE_LEN]) {
+    let mut ihvin = *state;
+    for t in (0..testt).rev() {
+        unstep(&mut ihvin, t, w[t]);
+    }
+    let mut s = *state;
+    for (t, wt) in w.iter().enumerate().skip(testt) {
+        step(&mut s, t, *wt);
+    }
+    let mut ihvout = ihvin;
+    add_state(&mut ihvout, &s);
+    (ihvin, ihvout)
+}
+
+// Compress a block, returning whether it is the second block of a near-collision attack
+fn compress_detect(h: &mut [u32; STATE_LEN], block: &[u8]) -> bool {
+    let w = expand(block);
+    let states = compress_states(h, &w);
+    check_dvs(h, &w, &states, &SHA1_DVS)
+}
+
+fn check_dvs(
+    ihv: &[u32; STATE_LEN],
+    w: &[u32; STEPS],
+    states: &[[u32; STATE_LEN]; 2],
+    dvs: &[DisturbanceVector],
+) -> bool {
+    let mut detected = false;
+    for dv in dvs.iter() {
+        let mut w2 = [0u32; STEPS];
+        for ((w2, w), dm) in w2.iter_mut().zip(w.iter()).zip(dv.dm.iter()) {
+            *w2 = w ^ dm;
+        }
+        let i = TESTT.iter().position(|testt| *testt == dv.testt).unwrap();
+        let (_, ihvout) = recompress(dv.testt, &states[i], &w2);
+        detected |= ihvout == *ihv;
+    }
+    detected
+}
+
+/// Structure representing the state of a SHA-1 computation with collision detection
+#[derive(Clone)]
+pub struct Sha1Cd {
+    h: [u32; STATE_LEN],
+    length_bits: u64,
+    buffer: FixedBuffer<64>,
+    computed: bool,
+    collision: bool,
+}
+
+impl Sha1Cd {
+    /// Construct a `Sha1Cd` context
+    pub const fn new() -> Self {
+        Sha1Cd {
+            h: super::H,
+            length_bits: 0,
+            buffer: FixedBuffer::new(),
+            computed: false,
+            collision: false,
+        }
+    }
+
+    /// Whether a collision attack has been detected in the blocks processed so far
+    ///
+    /// This should be checked after `result`, and the digest must not be trusted
+    /// if it returns true.
+    pub fn collision_detected(&self) -> bool {
+        self.collision
+    }
+}
+
+impl Digest for Sha1Cd {
+    fn reset(&mut self) {
+        self.h = super::H;
+        self.length_bits = 0;
+        self.buffer.reset();
+        self.computed = false;
+        self.collision = false;
+    }
+
+    fn input(&mut self, msg: &[u8]) {
+        assert!(!self.computed);
+        self.length_bits = add_bytes_to_bits(self.length_bits, msg.len() as u64);
+        let h = &mut self.h;
+        let collision = &mut self.collision;
+        self.buffer.input(msg, |d| {
+            for block in d.chunks(64) {
+                *collision |= compress_detect(h, block);
+            }
+        });
+    }
+
+    fn result(&mut self, out: &mut [u8]) {
+        assert_output_size(out, 20);
+        if !self.computed {
+            let h = &mut self.h;
+            let collision = &mut self.collision;
+            self.buffer
+                .standard_padding(8, |d| *collision |= compress_detect(h, d));
+            write_u32_be(self.buffer.next(4), (self.length_bits >> 32) as u32);
+            write_u32_be(self.buffer.next(4), self.length_bits as u32);
+            *collision |= compress_detect(h, self.buffer.full_buffer());
+            self.computed = true;
+        }
+        for (o, h) in out[..20].chunks_mut(4).zip(self.h.iter()) {
+            write_u32_be(o, *h);
+        }
+    }
+
+    fn output_bits(&self) -> usize {
+        160
+    }
+
+    fn block_size(&self) -> usize {
+        64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cryptoutil::test::result_hex;
+    use crate::sha1::Sha1;
+    use std::string::String;
+    use std::vec::Vec;
+
+    #[test]
+    fn same_digest_as_sha1() {
+        let inputs: [&[u8]; 4] = [
+            b"",
+            b"abc",
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+            &[0x5a; 1000],
+        ];
+        for input in inputs.iter() {
+            let mut sha1 = Sha1::new();
+            let mut sha1cd = Sha1Cd::new();
+            sha1.input(input);
+            sha1cd.input(input);
+            assert_eq!(result_hex(&mut sha1), result_hex(&mut sha1cd));
+            assert!(!sha1cd.collision_detected());
+        }
+    }
+
+    #[test]
+    fn recompression() {
+        let mut block = [0u8; 64];
+        for (i, b) in block.iter_mut().enumerate() {
+            *b = (i * 37 + 11) as u8;
+        }
+        let w = expand(&block);
+        let mut h = super::super::H;
+        let states = compress_states(&mut h, &w);
+
+        for dv in SHA1_DVS.iter() {
+            let i = TESTT.iter().position(|testt| *testt == dv.testt).unwrap();
+
+            // recompressing the message itself gives back the chaining values
+            let (ihvin, ihvout) = recompress(dv.testt, &states[i], &w);
+            assert_eq!(ihvin, super::super::H);
+            assert_eq!(ihvout, h);
+
+            // the sibling block compresses from the recomputed chaining value
+            let mut w2 = w;
+            for (w2, dm) in w2.iter_mut().zip(dv.dm.iter()) {
+                *w2 ^= dm;
+            }
+            let (ihvin2, ihvout2) = recompress(dv.testt, &states[i], &w2);
+            let mut h2 = ihvin2;
+            compress_states(&mut h2, &w2);
+            assert_eq!(h2, ihvout2);
+        }
+
+        assert!(!check_dvs(&h, &w, &states, &SHA1_DVS));
+
+        // a null difference is always reported as a collision
+        let null = DisturbanceVector {
+            testt: 58,
+            dm: [0; STEPS],
+        };
+        assert!(check_dvs(&h, &w, &states, &[null]));
+    }
+
+    fn unhex(parts: &[&str]) -> Vec<u8> {
+        let s: String = parts.concat();
+        (0..s.len() / 2)
+            .map(|i| u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap())
+            .collect()
+    }
+
+    // the first 320 bytes of shattered-1.pdf and shattered-2.pdf: a common
+    // prefix of 3 blocks, followed by the 2 blocks of the near-collision
+    // attack, which differ between the files and give the same SHA-1 state
+    const SHATTERED_PREFIX: [&str; 6] = [
+        "255044462d312e330a25e2e3cfd30a0a0a312030206f626a0a3c3c2f57696474",
+        "682032203020522f4865696768742033203020522f547970652034203020522f",
+        "537562747970652035203020522f46696c7465722036203020522f436f6c6f72",
+        "53706163652037203020522f4c656e6774682038203020522f42697473506572",
+        "436f6d706f6e656e7420383e3e0a73747265616d0affd8fffe00245348412d31",
+        "20697320646561642121212121852fec092339759c39b1a1c63c4c97e1fffe01",
+    ];
+    const SHATTERED_1: [&str; 4] = [
+        "7346dc9166b67e118f029ab621b2560ff9ca67cca8c7f85ba84c79030c2b3de2",
+        "18f86db3a90901d5df45c14f26fedfb3dc38e96ac22fe7bd728f0e45bce046d2",
+        "3c570feb141398bb552ef5a0a82be331fea48037b8b5d71f0e332edf93ac3500",
+        "eb4ddc0decc1a864790c782c76215660dd309791d06bd0af3f98cda4bc4629b1",
+    ];
+    const SHATTERED_2: [&str; 4] = [
+        "7f46dc93a6b67e013b029aaa1db2560b45ca67d688c7f84b8c4c791fe02b3df6",
+        "14f86db1690901c56b45c1530afedfb76038e972722fe7ad728f0e4904e046c2",
+        "30570fe9d41398abe12ef5bc942be33542a4802d98b5d70f2a332ec37fac3514",
+        "e74ddc0f2cc1a874cd0c78305a21566461309789606bd0bf3f98cda8044629a1",
+    ];
+
+    #[test]
+    fn shattered() {
+        let prefix = unhex(&SHATTERED_PREFIX);
+        let mut digests = Vec::new();
+        for blocks in [SHATTERED_1, SHATTERED_2].iter() {
+            let mut input = prefix.clone();
+            input.extend_from_slice(&unhex(blocks));
+
+            let mut sha1 = Sha1::new();
+            sha1.input(&input);
+            digests.push(result_hex(&mut sha1));
+
+            let mut sha1cd = Sha1Cd::new();
+            sha1cd.input(&prefix);
+            assert!(!sha1cd.collision_detected());
+            sha1cd.input(&input[prefix.len()..]);
+            assert!(sha1cd.collision_detected());
+        }
+        assert_eq!(digests[0], "f92d74e3874587aaf443d1db961d4e26dde13e9c");
+        assert_eq!(digests[0], digests[1]);
+    }
+}
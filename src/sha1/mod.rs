@@ -17,11 +17,28 @@
 //! context.input(b"hello world");
 //! context.result(&mut digest);
 //! ```
+//!
+//! When hashing possibly adversarial data, [`Sha1Cd`] additionally detects the known
+//! collision attacks, at the expense of performance:
+//!
+//! ```
+//! use cryptoxide::{sha1::Sha1Cd, digest::Digest};
+//!
+//! let mut digest = [0u8; 20];
+//! let mut context = Sha1Cd::new();
+//! context.input(b"hello world");
+//! context.result(&mut digest);
+//! assert!(!context.collision_detected());
+//! ```
 
 use crate::cryptoutil::{read_u32v_be, write_u32_be, FixedBuffer};
-use crate::digest::Digest;
+use crate::digest::{assert_output_size, Digest};
 use crate::simd::u32x4;
 
+mod collision;
+
+pub use collision::Sha1Cd;
+
 const STATE_LEN: usize = 5;
 const BLOCK_LEN: usize = 16;
 
@@ -359,7 +376,9 @@ fn add_input(st: &mut Sha1, msg: &[u8]) {
     st.length_bits = add_bytes_to_bits(st.length_bits, msg.len() as u64);
     let st_h = &mut st.h;
     st.buffer.input(msg, |d| {
-        sha1_digest_block(st_h, d);
+        for block in d.chunks(BLOCK_LEN * 4) {
+            sha1_digest_block(st_h, block);
+        }
     });
 }
 
@@ -421,6 +440,7 @@ impl Digest for Sha1 {
         add_input(self, msg);
     }
     fn result(&mut self, out: &mut [u8]) {
+        assert_output_size(out, 20);
         mk_result(self, out)
     }
     fn output_bits(&self) -> usize {
@@ -434,6 +454,7 @@ impl Digest for Sha1 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cryptoutil::test::result_hex;
     use alloc::vec::Vec;
 
     #[derive(Clone)]
@@ -492,9 +513,12 @@ mod tests {
             sh.result(&mut out);
             assert!(t.output[..] == out[..]);
 
-            let out_str = sh.result_str();
-            assert_eq!(out_str.len(), 40);
-            assert!(&out_str[..] == t.output_str);
+            #[cfg(feature = "alloc")]
+            {
+                let out_str = sh.result_str();
+                assert_eq!(out_str.len(), 40);
+                assert!(&out_str[..] == t.output_str);
+            }
 
             sh.reset();
         }
@@ -511,13 +535,28 @@ mod tests {
             sh.result(&mut out);
             assert!(t.output[..] == out[..]);
 
-            let out_str = sh.result_str();
-            assert_eq!(out_str.len(), 40);
-            assert!(&out_str[..] == t.output_str);
+            #[cfg(feature = "alloc")]
+            {
+                let out_str = sh.result_str();
+                assert_eq!(out_str.len(), 40);
+                assert!(&out_str[..] == t.output_str);
+            }
 
             sh.reset();
         }
     }
+
+    #[test]
+    fn test_multiple_blocks() {
+        // FIPS 180-1 one million repetitions of "a", given all at once
+        let input = vec![b'a'; 1_000_000];
+        let mut sh = Sha1::new();
+        sh.input(&input);
+        assert_eq!(
+            result_hex(&mut sh),
+            "34aa973cd4c4daa4f61eeb2bdbad27316534016f"
+        );
+    }
 }
 
 #[cfg(all(test, feature = "with-bench"))]
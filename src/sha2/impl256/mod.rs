@@ -7,37 +7,62 @@
 //! of creating the message schedule of 4 (SSE) or 8 (AVX) blocks
 //! at a time, then using the standard ALU to do the compression.
 //!
+use crate::cpu::Backend;
+
 mod reference;
 
 #[cfg(all(
+    not(feature = "force-portable"),
     any(target_arch = "x86", target_arch = "x86_64"),
     target_feature = "sse4.1"
 ))]
 mod sse41;
 
 #[cfg(all(
+    not(feature = "force-portable"),
     any(target_arch = "x86", target_arch = "x86_64"),
     all(target_feature = "sse4.1", target_feature = "avx"),
 ))]
 mod avx;
 
 #[cfg(not(all(
+    not(feature = "force-portable"),
     any(target_arch = "x86", target_arch = "x86_64"),
     any(target_feature = "sse4.1", target_feature = "avx")
 )))]
 pub(crate) use reference::*;
+#[cfg(not(all(
+    not(feature = "force-portable"),
+    any(target_arch = "x86", target_arch = "x86_64"),
+    any(target_feature = "sse4.1", target_feature = "avx")
+)))]
+pub(crate) const BACKEND: Backend = Backend::Portable;
 
 #[cfg(all(
+    not(feature = "force-portable"),
     target_arch = "x86_64",
     all(target_feature = "sse4.1", not(target_feature = "avx")),
 ))]
 pub(crate) use sse41::*;
+#[cfg(all(
+    not(feature = "force-portable"),
+    target_arch = "x86_64",
+    all(target_feature = "sse4.1", not(target_feature = "avx")),
+))]
+pub(crate) const BACKEND: Backend = Backend::Sse41;
 
 #[cfg(all(
+    not(feature = "force-portable"),
     target_arch = "x86_64",
     all(target_feature = "sse4.1", target_feature = "avx"),
 ))]
 pub(crate) use avx::*;
+#[cfg(all(
+    not(feature = "force-portable"),
+    target_arch = "x86_64",
+    all(target_feature = "sse4.1", target_feature = "avx"),
+))]
+pub(crate) const BACKEND: Backend = Backend::Avx;
 
 /*
 #[cfg(all(any(target_arch = "x86_64"), target_feature = "sha"))]
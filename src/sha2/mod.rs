@@ -56,15 +56,49 @@
 //!                    "989dd35bc5ff499670da34255b45b0cf",
 //!                    "d830e81f605dcf7dc5542e93ae9cd76f"));
 //! ```
+//!
+//! The one-shot functions `sha224`, `sha256`, `sha384`, `sha512`, `sha512_224`
+//! and `sha512_256` hash a single input:
+//!
+//! ```rust
+//! use self::cryptoxide::sha2::sha256;
+//!
+//! let digest: [u8; 32] = sha256(b"hello world");
+//! assert_eq!(digest[0], 0xb9);
+//! ```
+//!
+//! With the `std` feature, `sha256_reader` and the other `_reader` functions
+//! hash all the data of a `std::io::Read`, such as a file.
+//!
+//! All the contexts implement `Clone`, which forks the state of the hash in
+//! the middle of the stream: the clone and the original can then be fed and
+//! finalized independently, for example to hash many messages sharing a common
+//! prefix without processing the prefix again:
+//!
+//! ```rust
+//! use self::cryptoxide::digest::Digest;
+//! use self::cryptoxide::sha2::Sha512;
+//!
+//! let mut prefix = Sha512::new();
+//! prefix.input_str("hello ");
+//!
+//! let mut hasher = prefix.clone();
+//! hasher.input_str("world");
+//!
+//! let mut other = Sha512::new();
+//! other.input_str("hello world");
+//!
+//! assert_eq!(hasher.result_str(), other.result_str());
+//! ```
 
 mod eng256;
 mod eng512;
-mod impl256;
+pub(crate) mod impl256;
 mod impl512;
 mod initials;
 
 use crate::cryptoutil::{write_u128_be, write_u64_be, FixedBuffer};
-use crate::digest::Digest;
+use crate::digest::{assert_output_size, Digest};
 use initials::*;
 
 macro_rules! digest {
@@ -90,8 +124,9 @@ macro_rules! digest {
             }
 
             fn result(&mut self, out: &mut [u8]) {
+                assert_output_size(out, $output_bits / 8);
                 self.engine.finish();
-                self.engine.state.$output_fn(&mut out[0..$output_bits / 8]);
+                self.engine.state.$output_fn(out);
             }
 
             fn reset(&mut self) {
@@ -226,10 +261,95 @@ digest512!(Sha512Trunc224, output_224bits_at, 224, H512_TRUNC_224);
 digest256!(Sha256, output_256bits_at, 256, H256);
 digest256!(Sha224, output_224bits_at, 224, H224);
 
+macro_rules! oneshot {
+    ($fn_name: ident, $name: ident, $output_bytes: expr, $doc: expr) => {
+        #[doc = $doc]
+        pub fn $fn_name(input: &[u8]) -> [u8; $output_bytes] {
+            let mut hasher = $name::new();
+            hasher.input(input);
+            hasher.finalize_fixed()
+        }
+    };
+}
+
+oneshot!(sha224, Sha224, 28, "Compute the SHA-224 hash of `input`");
+oneshot!(sha256, Sha256, 32, "Compute the SHA-256 hash of `input`");
+oneshot!(sha384, Sha384, 48, "Compute the SHA-384 hash of `input`");
+oneshot!(sha512, Sha512, 64, "Compute the SHA-512 hash of `input`");
+oneshot!(
+    sha512_224,
+    Sha512Trunc224,
+    28,
+    "Compute the SHA-512/224 hash of `input`"
+);
+oneshot!(
+    sha512_256,
+    Sha512Trunc256,
+    32,
+    "Compute the SHA-512/256 hash of `input`"
+);
+
+macro_rules! reader {
+    ($fn_name: ident, $name: ident, $output_bytes: expr, $doc: expr) => {
+        #[doc = $doc]
+        ///
+        /// The data is read in chunks of 8 KiB until the end of the reader,
+        /// and the first I/O error is returned.
+        #[cfg(feature = "std")]
+        pub fn $fn_name<R: std::io::Read + ?Sized>(
+            r: &mut R,
+        ) -> std::io::Result<[u8; $output_bytes]> {
+            let mut hasher = $name::new();
+            hasher.input_reader(r)?;
+            Ok(hasher.finalize_fixed())
+        }
+    };
+}
+
+reader!(
+    sha224_reader,
+    Sha224,
+    28,
+    "Compute the SHA-224 hash of the data read from `r`"
+);
+reader!(
+    sha256_reader,
+    Sha256,
+    32,
+    "Compute the SHA-256 hash of the data read from `r`"
+);
+reader!(
+    sha384_reader,
+    Sha384,
+    48,
+    "Compute the SHA-384 hash of the data read from `r`"
+);
+reader!(
+    sha512_reader,
+    Sha512,
+    64,
+    "Compute the SHA-512 hash of the data read from `r`"
+);
+reader!(
+    sha512_224_reader,
+    Sha512Trunc224,
+    28,
+    "Compute the SHA-512/224 hash of the data read from `r`"
+);
+reader!(
+    sha512_256_reader,
+    Sha512Trunc256,
+    32,
+    "Compute the SHA-512/256 hash of the data read from `r`"
+);
+
 #[cfg(test)]
 mod tests {
-    use super::{Sha224, Sha256, Sha384, Sha512, Sha512Trunc224, Sha512Trunc256};
-    use crate::cryptoutil::test::test_digest_1million_random;
+    use super::{
+        sha224, sha256, sha384, sha512, sha512_224, sha512_256, Sha224, Sha256, Sha384, Sha512,
+        Sha512Trunc224, Sha512Trunc256,
+    };
+    use crate::cryptoutil::test::{result_hex, test_digest_1million_random};
     use crate::digest::Digest;
 
     struct Test {
@@ -242,7 +362,7 @@ mod tests {
         for t in tests.iter() {
             sh.input_str(t.input);
 
-            let out_str = sh.result_str();
+            let out_str = result_hex(&mut sh);
             assert_eq!(&out_str[..], t.output_str);
 
             sh.reset();
@@ -258,7 +378,7 @@ mod tests {
                 left -= take;
             }
 
-            let out_str = sh.result_str();
+            let out_str = result_hex(&mut sh);
             assert_eq!(&out_str[..], t.output_str);
 
             sh.reset();
@@ -270,13 +390,13 @@ mod tests {
             *vi = i as u8;
         }
         sh.input(&v[..]);
-        let out_str = sh.result_str();
+        let out_str = result_hex(&mut sh);
         sh.reset();
 
         for i in 0..v.len() / 16 {
             sh.input(&v[i * 16..i * 16 + 16]);
         }
-        let out_str2 = sh.result_str();
+        let out_str2 = result_hex(&mut sh);
 
         assert_eq!(&out_str, &out_str2);
         //assert_eq!(0, 1);
@@ -421,6 +541,177 @@ mod tests {
             "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0",
         );
     }
+
+    #[test]
+    fn test_output_size() {
+        use crate::digest::OutputSizeError;
+
+        let mut sh = Sha256::new();
+        sh.input(b"abc");
+        let mut short = [0u8; 31];
+        let mut long = [0u8; 33];
+        assert_eq!(
+            sh.try_result(&mut short),
+            Err(OutputSizeError {
+                expected: 32,
+                actual: 31
+            })
+        );
+        assert_eq!(
+            sh.try_result(&mut long),
+            Err(OutputSizeError {
+                expected: 32,
+                actual: 33
+            })
+        );
+        assert_eq!(long, [0u8; 33]);
+
+        let mut out = [0u8; 32];
+        assert_eq!(sh.try_result(&mut out), Ok(()));
+        let mut expected = [0u8; 32];
+        sh.result(&mut expected);
+        assert_eq!(out, expected);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_reader() {
+        use super::{
+            sha224_reader, sha256_reader, sha384_reader, sha512_224_reader, sha512_256_reader,
+            sha512_reader,
+        };
+        use std::io::{self, Cursor, Read};
+        use std::vec::Vec;
+
+        // more than the 8 KiB chunks, and not a multiple of them
+        let data: Vec<u8> = (0..20000u32).map(|i| (i * 7) as u8).collect();
+        assert_eq!(
+            sha256_reader(&mut Cursor::new(&data)).unwrap(),
+            sha256(&data)
+        );
+        assert_eq!(sha256_reader(&mut Cursor::new(&[])).unwrap(), sha256(&[]));
+        assert_eq!(
+            sha224_reader(&mut Cursor::new(&data)).unwrap(),
+            sha224(&data)
+        );
+        assert_eq!(
+            sha384_reader(&mut Cursor::new(&data)).unwrap(),
+            sha384(&data)
+        );
+        assert_eq!(
+            sha512_reader(&mut Cursor::new(&data)).unwrap(),
+            sha512(&data)
+        );
+        assert_eq!(
+            sha512_224_reader(&mut Cursor::new(&data)).unwrap(),
+            sha512_224(&data)
+        );
+        assert_eq!(
+            sha512_256_reader(&mut Cursor::new(&data)).unwrap(),
+            sha512_256(&data)
+        );
+
+        // the I/O error is returned after some data has been read
+        let mut failing = Cursor::new(&data[..100]).chain(FailingReader);
+        let err = sha256_reader(&mut failing).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+
+        struct FailingReader;
+        impl Read for FailingReader {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::from(io::ErrorKind::BrokenPipe))
+            }
+        }
+    }
+
+    // NIST "abc" examples
+    #[test]
+    fn test_oneshot() {
+        fn hex(bytes: &[u8]) -> alloc::string::String {
+            bytes.iter().map(|b| format!("{:02x}", b)).collect()
+        }
+
+        assert_eq!(
+            hex(&sha224(b"abc")),
+            "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7"
+        );
+        assert_eq!(
+            hex(&sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex(&sha384(b"abc")),
+            concat!(
+                "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded163",
+                "1a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7"
+            )
+        );
+        assert_eq!(
+            hex(&sha512(b"abc")),
+            concat!(
+                "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a",
+                "2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+            )
+        );
+        assert_eq!(
+            hex(&sha512_224(b"abc")),
+            "4634270f707b6a54daae7530460842e20e37ed265ceee9a43e8924aa"
+        );
+        assert_eq!(
+            hex(&sha512_256(b"abc")),
+            "53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23"
+        );
+    }
+
+    #[test]
+    fn test_fmt_hasher() {
+        use crate::digest::FmtHasher;
+        use core::fmt::Write;
+
+        let value = "abc";
+        let mut hasher = FmtHasher::new(Sha256::new());
+        write!(hasher, "{}", value).unwrap();
+        let mut out = [0u8; 32];
+        hasher.finalize(&mut out);
+        assert_eq!(out, sha256(b"abc"));
+
+        let mut hasher = FmtHasher::new(Sha256::new());
+        write!(hasher, "{}{}", &value[..1], &value[1..2]).unwrap();
+        hasher.write_char('c').unwrap();
+        assert_eq!(hasher.into_inner().finalize_fixed::<32>(), sha256(b"abc"));
+
+        let mut hasher = FmtHasher::new(Sha512::new());
+        write!(hasher, "{}-{:02x}", 42, 255).unwrap();
+        assert_eq!(hasher.into_inner().finalize_fixed::<64>(), sha512(b"42-ff"));
+    }
+
+    #[test]
+    fn test_finalize_fixed() {
+        let mut sh = Sha256::new();
+        sh.input(b"abc");
+        let mut expected = [0u8; 32];
+        sh.result(&mut expected);
+        assert_eq!(sh.finalize_fixed::<32>(), expected);
+
+        let mut sh = Sha512::new();
+        sh.input(b"abc");
+        let mut expected = [0u8; 64];
+        sh.result(&mut expected);
+        assert_eq!(sh.finalize_fixed::<64>(), expected);
+
+        let mut sh = Sha256::new();
+        let mut expected = [0u8; 32];
+        sh.result(&mut expected);
+        assert_eq!(Sha256::new().finalize_fixed::<32>(), expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid digest output size: expected 64 bytes, got 32")]
+    fn test_result_wrong_size() {
+        let mut sh = Sha512::new();
+        let mut out = [0u8; 32];
+        sh.result(&mut out);
+    }
 }
 
 #[cfg(all(test, feature = "with-bench"))]
@@ -8,6 +8,11 @@
 //!  * `SHA3-512`
 //!  * `Keccak224`, `Keccak256`, `Keccak384`, `Keccak512` (NIST submission without padding changes)
 //!
+//! and the 2 extendable output functions `SHAKE128` and `SHAKE256`.
+//!
+//! From the SHA-3 derived functions of [NIST SP 800-185][1], `cSHAKE128`,
+//! `cSHAKE256`, `TupleHash128` and `TupleHash256` are also provided.
+//!
 //! Based on an [implementation by Sébastien Martini](https://github.com/seb-m/crypto.rs/blob/master/src/sha3.rs)
 //!
 //! # Examples
@@ -27,12 +32,55 @@
 //! let mut out = [0u8; 32];
 //! context.result(&mut out);
 //! ```
+//!
+//! Getting an arbitrary length output from `SHAKE256`:
+//!
+//! ```rust
+//! use cryptoxide::sha3::Shake256;
+//!
+//! let mut context = Shake256::new();
+//! context.input(b"abc");
+//!
+//! let mut out = [0u8; 100];
+//! context.squeeze(&mut out);
+//! ```
+//!
+//! Hashing a tuple of byte strings with `TupleHash128`, where the boundaries
+//! between the elements are part of what is hashed:
+//!
+//! ```rust
+//! use cryptoxide::sha3::tuple_hash128;
+//!
+//! let mut out1 = [0u8; 32];
+//! let mut out2 = [0u8; 32];
+//! tuple_hash128(&[b"ab", b"c"], b"my application", &mut out1);
+//! tuple_hash128(&[b"a", b"bc"], b"my application", &mut out2);
+//! assert_ne!(out1, out2);
+//! ```
+//!
+//! Hashing a large input with `ParallelHash128`, split in blocks of 8 KiB:
+//!
+//! ```rust
+//! use cryptoxide::sha3::parallel_hash128;
+//!
+//! let data = [0u8; 100_000];
+//! let mut out = [0u8; 32];
+//! parallel_hash128(&data, 8192, b"my application", &mut out);
+//! ```
+//!
+//! With the `std` feature, the blocks of inputs of 64 KiB or more are hashed
+//! on up to `std::thread::available_parallelism()` threads; otherwise they
+//! are hashed one after the other, which gives the same output.
+//!
+//! The raw [`keccak_f1600`] permutation is also exported, to build other
+//! sponge or duplex constructions on top of it.
+//!
+//! [1]: <https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-185.pdf>
 
-use alloc::vec;
 use core::cmp;
 
 use crate::cryptoutil::{read_u64v_le, write_u64v_le, zero};
-use crate::digest::Digest;
+use crate::digest::{assert_output_size, Digest};
 
 const B: usize = 200;
 const NROUNDS: usize = 24;
@@ -75,17 +123,19 @@ fn rotl64(v: u64, n: usize) -> u64 {
     ((v << (n % 64)) & 0xffffffffffffffff) ^ (v >> (64 - (n % 64)))
 }
 
+/// The Keccak-f\[1600\] permutation, for building custom sponge or duplex constructions
+///
+/// The state is made of 25 lanes of 64 bits, the lane at position `(x, y)` of
+/// the Keccak specification being `state[x + 5 * y]`. The bit `z` of a lane is
+/// the bit of weight `2^z` of the `u64`, so that the state seen as 200 bytes
+/// is the lanes in order, each encoded in little endian, which is the byte
+/// order used to absorb and squeeze the SHA-3 functions.
 // Code based on Keccak-compact64.c from ref implementation.
 #[allow(clippy::needless_range_loop)]
-fn keccak_f(state: &mut [u8]) {
-    assert!(state.len() == B);
-
-    let mut s: [u64; 25] = [0; 25];
+pub fn keccak_f1600(s: &mut [u64; 25]) {
     let mut t: [u64; 1] = [0; 1];
     let mut c: [u64; 5] = [0; 5];
 
-    read_u64v_le(&mut s, state);
-
     for round in 0..NROUNDS {
         // Theta
         for x in 0..5 {
@@ -119,57 +169,63 @@ fn keccak_f(state: &mut [u8]) {
         // Iota
         s[0] ^= RC[round];
     }
+}
 
+fn keccak_f(state: &mut [u8]) {
+    assert!(state.len() == B);
+
+    let mut s: [u64; 25] = [0; 25];
+    read_u64v_le(&mut s, state);
+    keccak_f1600(&mut s);
     write_u64v_le(state, &s);
 }
 
 mod constants {
     pub trait Const {
         const DIGEST_LENGTH: usize;
-        const IS_KECCAK: bool;
+        // first byte of padding, which includes the domain separation bits
+        const DELIM: u8;
         const CAPACITY: usize;
         const BLOCK_SIZE: usize;
     }
 
     macro_rules! sha3_const {
-        ($C: ident, $DIGEST_LENGTH: expr, $IS_KECCAK: expr) => {
+        ($C: ident, $DIGEST_LENGTH: expr, $DELIM: expr) => {
             #[allow(non_camel_case_types)]
             pub(super) struct $C;
             impl Const for $C {
                 const DIGEST_LENGTH: usize = $DIGEST_LENGTH;
-                const IS_KECCAK: bool = $IS_KECCAK;
+                const DELIM: u8 = $DELIM;
                 const CAPACITY: usize = $DIGEST_LENGTH * 2;
                 const BLOCK_SIZE: usize = super::B - ($DIGEST_LENGTH * 2);
             }
         };
     }
 
-    /*
     macro_rules! shake_const {
         ($C: ident, $CAPACITY: expr) => {
             #[allow(non_camel_case_types)]
             pub(super) struct $C;
             impl Const for $C {
                 const DIGEST_LENGTH: usize = 0;
-                const IS_KECCAK: bool = false;
+                const DELIM: u8 = 0x1f;
                 const CAPACITY: usize = $CAPACITY;
-                const BLOCK_SIZE: usize = 0xfffff; // hum
+                const BLOCK_SIZE: usize = super::B - $CAPACITY;
             }
         };
     }
-    */
-
-    sha3_const!(Sha3_224, 28, false);
-    sha3_const!(Sha3_256, 32, false);
-    sha3_const!(Sha3_384, 48, false);
-    sha3_const!(Sha3_512, 64, false);
-    sha3_const!(Keccak224, 28, true);
-    sha3_const!(Keccak256, 32, true);
-    sha3_const!(Keccak384, 48, true);
-    sha3_const!(Keccak512, 64, true);
-
-    //shake_const!(Shake128, 32);
-    //shake_const!(Shake256, 64);
+
+    sha3_const!(Sha3_224, 28, 0x06);
+    sha3_const!(Sha3_256, 32, 0x06);
+    sha3_const!(Sha3_384, 48, 0x06);
+    sha3_const!(Sha3_512, 64, 0x06);
+    sha3_const!(Keccak224, 28, 0x01);
+    sha3_const!(Keccak256, 32, 0x01);
+    sha3_const!(Keccak384, 48, 0x01);
+    sha3_const!(Keccak512, 64, 0x01);
+
+    shake_const!(Shake128, 32);
+    shake_const!(Shake256, 64);
 }
 
 use core::marker::PhantomData;
@@ -180,7 +236,8 @@ struct Engine<E> {
     can_absorb: bool,  // Can absorb
     can_squeeze: bool, // Can squeeze
     offset: usize,     // Enqueued bytes in state for absorb phase
-                       // Squeeze offset for squeeze phase
+    // Squeeze offset for squeeze phase
+    delim: u8, // First byte of padding
 }
 
 impl<E> Clone for Engine<E> {
@@ -191,6 +248,7 @@ impl<E> Clone for Engine<E> {
             can_absorb: self.can_absorb,
             can_squeeze: self.can_squeeze,
             offset: self.offset,
+            delim: self.delim,
         }
     }
 }
@@ -208,70 +266,21 @@ impl<E: constants::Const> Engine<E> {
             can_absorb: true,
             can_squeeze: true,
             offset: 0,
+            delim: E::DELIM,
         }
     }
 
     fn finalize(&mut self) {
         assert!(self.can_absorb);
 
-        let output_bits = E::DIGEST_LENGTH * 8;
-
-        let ds_len = if E::IS_KECCAK {
-            0
-        } else if output_bits != 0 {
-            2
-        } else {
-            // TODO: for SHAKE
-            4
-        };
-
-        fn set_domain_sep(out_len: usize, buf: &mut [u8]) {
-            assert!(!buf.is_empty());
-            if out_len != 0 {
-                // 01...
-                buf[0] &= 0xfe;
-                buf[0] |= 0x2;
-            } else {
-                // 1111...
-                buf[0] |= 0xf;
-            }
-        }
-
-        // All parameters are expected to be in bits.
-        fn pad_len(ds_len: usize, offset: usize, rate: usize) -> usize {
-            assert!(rate % 8 == 0 && offset % 8 == 0);
-            let r: i64 = rate as i64;
-            let m: i64 = (offset + ds_len) as i64;
-            let zeros = (((-m - 2) + 2 * r) % r) as usize;
-            assert!((m as usize + zeros + 2) % 8 == 0);
-            (ds_len as usize + zeros + 2) / 8
-        }
-
-        fn set_pad(offset: usize, buf: &mut [u8]) {
-            //assert!(buf.len() as f32 >= ((offset + 2) as f32 / 8.0).ceil());
-            let s = offset / 8;
-            let buflen = buf.len();
-            buf[s] |= 1 << (offset % 8);
-            for i in (offset % 8) + 1..8 {
-                buf[s] &= !(1 << i);
-            }
-            for b in buf[s + 1..].iter_mut() {
-                *b = 0;
-            }
-            buf[buflen - 1] |= 0x80;
-        }
-
-        let p_len = pad_len(ds_len, self.offset * 8, self.rate() * 8);
-
-        let mut p = vec::from_elem(0, p_len);
-
-        if ds_len != 0 {
-            set_domain_sep(E::DIGEST_LENGTH * 8, &mut p);
-        }
-
-        set_pad(ds_len, &mut p);
+        // the domain separation bits are followed by the pad10*1 padding,
+        // which always fit in the current block since offset < rate
+        let r = self.rate();
+        self.state[self.offset] ^= self.delim;
+        self.state[r - 1] ^= 0x80;
+        keccak_f(&mut self.state);
 
-        self.process(&p);
+        self.offset = 0;
         self.can_absorb = false;
     }
 
@@ -364,17 +373,6 @@ impl<E: constants::Const> Engine<E> {
     }
 }
 
-/*
-/// New SHAKE-128 instance.
-pub fn shake_128() -> Sha3 {
-    Sha3::new(Sha3Mode::Shake128)
-}
-
-/// New SHAKE-256 instance.
-pub fn shake_256() -> Sha3 {
-    Sha3::new(Sha3Mode::Shake256)
-}
-*/
 use self::constants::Const;
 
 macro_rules! sha3_impl {
@@ -395,6 +393,7 @@ macro_rules! sha3_impl {
             }
 
             fn result(&mut self, out: &mut [u8]) {
+                assert_output_size(out, constants::$C::DIGEST_LENGTH);
                 self.0.output(out)
             }
 
@@ -423,9 +422,275 @@ sha3_impl!(Keccak256);
 sha3_impl!(Keccak384);
 sha3_impl!(Keccak512);
 
+macro_rules! shake_impl {
+    ($C: ident) => {
+        /// A $C extendable output function context
+        #[derive(Clone)]
+        pub struct $C(Engine<constants::$C>);
+
+        impl $C {
+            pub fn new() -> Self {
+                Self(Engine::new())
+            }
+
+            /// Absorb more data. This cannot be called once the output
+            /// has started to be squeezed, unless the context is reset.
+            pub fn input(&mut self, data: &[u8]) {
+                self.0.process(data)
+            }
+
+            /// Squeeze the next `out.len()` bytes of output. Successive calls
+            /// continue the output stream where the previous one stopped.
+            pub fn squeeze(&mut self, out: &mut [u8]) {
+                self.0.output(out)
+            }
+
+            /// Reset the context to its initial state
+            pub fn reset(&mut self) {
+                self.0.reset()
+            }
+        }
+    };
+}
+
+shake_impl!(Shake128);
+shake_impl!(Shake256);
+
+// SP 800-185 left_encode and right_encode of an integer, using `buf` as storage
+fn left_encode(x: u64, buf: &mut [u8; 9]) -> &[u8] {
+    let n = cmp::max(1, (71 - x.leading_zeros() as usize) / 8);
+    buf[0] = n as u8;
+    buf[1..n + 1].copy_from_slice(&x.to_be_bytes()[8 - n..]);
+    &buf[..n + 1]
+}
+
+fn right_encode(x: u64, buf: &mut [u8; 9]) -> &[u8] {
+    let n = cmp::max(1, (71 - x.leading_zeros() as usize) / 8);
+    buf[..n].copy_from_slice(&x.to_be_bytes()[8 - n..]);
+    buf[n] = n as u8;
+    &buf[..n + 1]
+}
+
+impl<E: Const> Engine<E> {
+    // absorb encode_string(s)
+    fn process_encoded_string(&mut self, s: &[u8]) {
+        let mut buf = [0u8; 9];
+        self.process(left_encode(s.len() as u64 * 8, &mut buf));
+        self.process(s);
+    }
+
+    // absorb bytepad(encode_string(strings[0]) || ... , rate)
+    fn process_bytepad(&mut self, strings: &[&[u8]]) {
+        let mut buf = [0u8; 9];
+        self.process(left_encode(self.rate() as u64, &mut buf));
+        for s in strings.iter() {
+            self.process_encoded_string(s);
+        }
+        if self.offset != 0 {
+            let zeros = [0u8; B];
+            self.process(&zeros[..self.rate() - self.offset]);
+        }
+    }
+}
+
+macro_rules! cshake_impl {
+    ($C: ident, $E: ident) => {
+        /// A $C customizable extendable output function context
+        #[derive(Clone)]
+        pub struct $C {
+            engine: Engine<constants::$E>,
+            initial: Engine<constants::$E>,
+        }
+
+        impl $C {
+            /// Create a new context from a function name, reserved for
+            /// functions defined by NIST and usually empty, and a
+            /// customization string used for domain separation.
+            pub fn new(function_name: &[u8], customization: &[u8]) -> Self {
+                let mut engine = Engine::new();
+                // without any name or customization, cSHAKE is plain SHAKE
+                if !function_name.is_empty() || !customization.is_empty() {
+                    engine.delim = 0x04;
+                    engine.process_bytepad(&[function_name, customization]);
+                }
+                Self {
+                    initial: engine.clone(),
+                    engine: engine,
+                }
+            }
+
+            /// Absorb more data. This cannot be called once the output
+            /// has started to be squeezed, unless the context is reset.
+            pub fn input(&mut self, data: &[u8]) {
+                self.engine.process(data)
+            }
+
+            /// Squeeze the next `out.len()` bytes of output. Successive calls
+            /// continue the output stream where the previous one stopped.
+            pub fn squeeze(&mut self, out: &mut [u8]) {
+                self.engine.output(out)
+            }
+
+            /// Reset the context to its initial state, keeping the same
+            /// function name and customization string
+            pub fn reset(&mut self) {
+                self.engine = self.initial.clone()
+            }
+        }
+    };
+}
+
+cshake_impl!(CShake128, Shake128);
+cshake_impl!(CShake256, Shake256);
+
+macro_rules! tuple_hash_impl {
+    ($name: ident, $C: ident, $doc: expr) => {
+        #[doc = $doc]
+        ///
+        /// Every item is hashed along with its length, so that no two different
+        /// tuples (e.g. `["ab", "c"]` and `["a", "bc"]`) hash to the same value
+        /// by construction. The output length is also part of the hash, so
+        /// different output lengths gives unrelated outputs.
+        pub fn $name(items: &[&[u8]], customization: &[u8], out: &mut [u8]) {
+            let mut ctx = $C::new(b"TupleHash", customization);
+            for item in items.iter() {
+                ctx.engine.process_encoded_string(item);
+            }
+            let mut buf = [0u8; 9];
+            ctx.input(right_encode(out.len() as u64 * 8, &mut buf));
+            ctx.squeeze(out);
+        }
+    };
+}
+
+tuple_hash_impl!(
+    tuple_hash128,
+    CShake128,
+    "Compute the TupleHash128 of a tuple of byte strings into `out`"
+);
+tuple_hash_impl!(
+    tuple_hash256,
+    CShake256,
+    "Compute the TupleHash256 of a tuple of byte strings into `out`"
+);
+
+// Below this input size, ParallelHash doesn't spread the blocks over threads
+#[cfg(feature = "std")]
+const PARALLEL_HASH_THREADED_INPUT: usize = 1 << 16;
+
+// Hash the blocks of `input` with `hash_block`, spread over up to
+// `std::thread::available_parallelism()` threads, or return `None` if the
+// input is too small to be worth it
+#[cfg(feature = "std")]
+fn threaded_block_hashes<const L: usize, F>(
+    input: &[u8],
+    block_size: usize,
+    hash_block: &F,
+) -> Option<std::vec::Vec<[u8; L]>>
+where
+    F: Fn(&[u8], &mut [u8; L]) + Sync,
+{
+    if input.len() < PARALLEL_HASH_THREADED_INPUT {
+        return None;
+    }
+    let blocks = input.len() / block_size + (input.len() % block_size != 0) as usize;
+    let workers = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(blocks);
+    if workers < 2 {
+        return None;
+    }
+    let blocks_per_worker = (blocks + workers - 1) / workers;
+    let mut hashes = std::vec![[0u8; L]; blocks];
+    let hash_group = |input: &[u8], hashes: &mut [[u8; L]]| {
+        for (block, block_hash) in input.chunks(block_size).zip(hashes.iter_mut()) {
+            hash_block(block, block_hash);
+        }
+    };
+    std::thread::scope(|scope| {
+        let mut groups = input
+            .chunks(blocks_per_worker * block_size)
+            .zip(hashes.chunks_mut(blocks_per_worker));
+        // the first group of blocks is hashed by the calling thread
+        let (first_input, first_hashes) = groups.next().unwrap();
+        for (input, hashes) in groups {
+            let hash_group = &hash_group;
+            scope.spawn(move || hash_group(input, hashes));
+        }
+        hash_group(first_input, first_hashes);
+    });
+    Some(hashes)
+}
+
+macro_rules! parallel_hash_impl {
+    ($name: ident, $C: ident, $S: ident, $chunk_len: expr, $doc: expr) => {
+        #[doc = $doc]
+        ///
+        /// The input is split in blocks of `block_size` bytes, which are hashed
+        /// independently, and the final hash is computed over the hashes of
+        /// the blocks. The block size and the output length are part of the hash.
+        ///
+        /// With the `std` feature, the blocks of a large input are hashed on
+        /// multiple threads.
+        pub fn $name(input: &[u8], block_size: usize, customization: &[u8], out: &mut [u8]) {
+            assert!(block_size > 0);
+            let mut ctx = $C::new(b"ParallelHash", customization);
+            let mut buf = [0u8; 9];
+            ctx.input(left_encode(block_size as u64, &mut buf));
+
+            let hash_block = |block: &[u8], block_hash: &mut [u8; $chunk_len]| {
+                let mut block_ctx = $S::new();
+                block_ctx.input(block);
+                block_ctx.squeeze(block_hash);
+            };
+            let finish = |mut ctx: $C, n: u64, out: &mut [u8]| {
+                let mut buf = [0u8; 9];
+                ctx.input(right_encode(n, &mut buf));
+                ctx.input(right_encode(out.len() as u64 * 8, &mut buf));
+                ctx.squeeze(out);
+            };
+
+            #[cfg(feature = "std")]
+            {
+                if let Some(hashes) = threaded_block_hashes(input, block_size, &hash_block) {
+                    for block_hash in hashes.iter() {
+                        ctx.input(block_hash);
+                    }
+                    return finish(ctx, hashes.len() as u64, out);
+                }
+            }
+
+            let mut n = 0;
+            for block in input.chunks(block_size) {
+                let mut block_hash = [0u8; $chunk_len];
+                hash_block(block, &mut block_hash);
+                ctx.input(&block_hash);
+                n += 1;
+            }
+            finish(ctx, n, out)
+        }
+    };
+}
+
+parallel_hash_impl!(
+    parallel_hash128,
+    CShake128,
+    Shake128,
+    32,
+    "Compute the ParallelHash128 of `input` into `out`"
+);
+parallel_hash_impl!(
+    parallel_hash256,
+    CShake256,
+    Shake256,
+    64,
+    "Compute the ParallelHash256 of `input` into `out`"
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cryptoutil::test::result_hex;
     use crate::digest::Digest;
 
     struct Test {
@@ -438,7 +703,7 @@ mod tests {
         for t in tests.iter() {
             sh.input_str(t.input);
 
-            let out_str = sh.result_str();
+            let out_str = result_hex(&mut sh);
             assert_eq!(&out_str[..], t.output_str);
 
             sh.reset();
@@ -454,7 +719,7 @@ mod tests {
                 left -= take;
             }
 
-            let out_str = sh.result_str();
+            let out_str = result_hex(&mut sh);
             assert_eq!(&out_str[..], t.output_str);
 
             sh.reset();
@@ -555,4 +820,296 @@ mod tests {
         ];
         test_hash(Keccak512::new(), &wikipedia_tests[..]);
     }
+
+    #[test]
+    fn test_shake() {
+        let mut out = [0u8; 32];
+        let mut sh = Shake128::new();
+        sh.squeeze(&mut out);
+        assert_eq!(
+            out,
+            [
+                0x7f, 0x9c, 0x2b, 0xa4, 0xe8, 0x8f, 0x82, 0x7d, 0x61, 0x60, 0x45, 0x50, 0x76, 0x05,
+                0x85, 0x3e, 0xd7, 0x3b, 0x80, 0x93, 0xf6, 0xef, 0xbc, 0x88, 0xeb, 0x1a, 0x6e, 0xac,
+                0xfa, 0x66, 0xef, 0x26
+            ]
+        );
+
+        let mut out = [0u8; 64];
+        let mut sh = Shake256::new();
+        sh.input(b"The quick brown fox jumps over the lazy dog");
+        sh.squeeze(&mut out);
+        assert_eq!(
+            &out[..],
+            &[
+                0x2f, 0x67, 0x13, 0x43, 0xd9, 0xb2, 0xe1, 0x60, 0x4d, 0xc9, 0xdc, 0xf0, 0x75, 0x3e,
+                0x5f, 0xe1, 0x5c, 0x7c, 0x64, 0xa0, 0xd2, 0x83, 0xcb, 0xbf, 0x72, 0x2d, 0x41, 0x1a,
+                0x0e, 0x36, 0xf6, 0xca, 0x1d, 0x01, 0xd1, 0x36, 0x9a, 0x23, 0x53, 0x9c, 0xd8, 0x0f,
+                0x7c, 0x05, 0x4b, 0x6e, 0x5d, 0xaf, 0x9c, 0x96, 0x2c, 0xad, 0x5b, 0x8e, 0xd5, 0xbd,
+                0x11, 0x99, 0x8b, 0x40, 0xd5, 0x73, 0x44, 0x42
+            ][..]
+        );
+    }
+
+    #[test]
+    fn test_shake_squeeze_incremental() {
+        let mut full = [0u8; 500];
+        let mut sh = Shake256::new();
+        sh.input(b"abc");
+        sh.squeeze(&mut full);
+
+        let mut pieces = [0u8; 500];
+        sh.reset();
+        sh.input(b"abc");
+        for chunk in pieces.chunks_mut(7) {
+            sh.squeeze(chunk);
+        }
+        assert_eq!(&full[..], &pieces[..]);
+    }
+
+    #[test]
+    fn test_cshake() {
+        // NIST SP 800-185 cSHAKE samples #1 and #3
+        let mut out = [0u8; 32];
+        let mut sh = CShake128::new(b"", b"Email Signature");
+        sh.input(&[0x00, 0x01, 0x02, 0x03]);
+        sh.squeeze(&mut out);
+        assert_eq!(
+            out,
+            [
+                0xc1, 0xc3, 0x69, 0x25, 0xb6, 0x40, 0x9a, 0x04, 0xf1, 0xb5, 0x04, 0xfc, 0xbc, 0xa9,
+                0xd8, 0x2b, 0x40, 0x17, 0x27, 0x7c, 0xb5, 0xed, 0x2b, 0x20, 0x65, 0xfc, 0x1d, 0x38,
+                0x14, 0xd5, 0xaa, 0xf5
+            ]
+        );
+
+        let mut out = [0u8; 64];
+        let mut sh = CShake256::new(b"", b"Email Signature");
+        sh.input(&[0x00, 0x01, 0x02, 0x03]);
+        sh.squeeze(&mut out);
+        assert_eq!(
+            &out[..],
+            &[
+                0xd0, 0x08, 0x82, 0x8e, 0x2b, 0x80, 0xac, 0x9d, 0x22, 0x18, 0xff, 0xee, 0x1d, 0x07,
+                0x0c, 0x48, 0xb8, 0xe4, 0xc8, 0x7b, 0xff, 0x32, 0xc9, 0x69, 0x9d, 0x5b, 0x68, 0x96,
+                0xee, 0xe0, 0xed, 0xd1, 0x64, 0x02, 0x0e, 0x2b, 0xe0, 0x56, 0x08, 0x58, 0xd9, 0xc0,
+                0x0c, 0x03, 0x7e, 0x34, 0xa9, 0x69, 0x37, 0xc5, 0x61, 0xa7, 0x4c, 0x41, 0x2b, 0xb4,
+                0xc7, 0x46, 0x46, 0x95, 0x27, 0x28, 0x1c, 0x8c
+            ][..]
+        );
+
+        // empty name and customization is SHAKE
+        let mut out1 = [0u8; 32];
+        let mut out2 = [0u8; 32];
+        let mut sh = CShake128::new(b"", b"");
+        sh.input(b"abc");
+        sh.squeeze(&mut out1);
+        let mut sh = Shake128::new();
+        sh.input(b"abc");
+        sh.squeeze(&mut out2);
+        assert_eq!(out1, out2);
+    }
+
+    #[test]
+    fn test_parallel_hash() {
+        // NIST SP 800-185 ParallelHash samples
+        let mut x = [0u8; 24];
+        for (i, b) in x.iter_mut().enumerate() {
+            *b = ((i / 8) * 0x10 + i % 8) as u8;
+        }
+
+        let mut out = [0u8; 32];
+        parallel_hash128(&x, 8, b"", &mut out);
+        assert_eq!(
+            out,
+            [
+                0xba, 0x8d, 0xc1, 0xd1, 0xd9, 0x79, 0x33, 0x1d, 0x3f, 0x81, 0x36, 0x03, 0xc6, 0x7f,
+                0x72, 0x60, 0x9a, 0xb5, 0xe4, 0x4b, 0x94, 0xa0, 0xb8, 0xf9, 0xaf, 0x46, 0x51, 0x44,
+                0x54, 0xa2, 0xb4, 0xf5
+            ]
+        );
+        parallel_hash128(&x, 8, b"Parallel Data", &mut out);
+        assert_eq!(
+            out,
+            [
+                0xfc, 0x48, 0x4d, 0xcb, 0x3f, 0x84, 0xdc, 0xee, 0xdc, 0x35, 0x34, 0x38, 0x15, 0x1b,
+                0xee, 0x58, 0x15, 0x7d, 0x6e, 0xfe, 0xd0, 0x44, 0x5a, 0x81, 0xf1, 0x65, 0xe4, 0x95,
+                0x79, 0x5b, 0x72, 0x06
+            ]
+        );
+
+        let mut out = [0u8; 64];
+        parallel_hash256(&x, 8, b"", &mut out);
+        assert_eq!(
+            out[..],
+            [
+                0xbc, 0x1e, 0xf1, 0x24, 0xda, 0x34, 0x49, 0x5e, 0x94, 0x8e, 0xad, 0x20, 0x7d, 0xd9,
+                0x84, 0x22, 0x35, 0xda, 0x43, 0x2d, 0x2b, 0xbc, 0x54, 0xb4, 0xc1, 0x10, 0xe6, 0x4c,
+                0x45, 0x11, 0x05, 0x53, 0x1b, 0x7f, 0x2a, 0x3e, 0x0c, 0xe0, 0x55, 0xc0, 0x28, 0x05,
+                0xe7, 0xc2, 0xde, 0x1f, 0xb7, 0x46, 0xaf, 0x97, 0xa1, 0xdd, 0x01, 0xf4, 0x3b, 0x82,
+                0x4e, 0x31, 0xb8, 0x76, 0x12, 0x41, 0x04, 0x29
+            ][..]
+        );
+        parallel_hash256(&x, 8, b"Parallel Data", &mut out);
+        assert_eq!(
+            out[..],
+            [
+                0xcd, 0xf1, 0x52, 0x89, 0xb5, 0x4f, 0x62, 0x12, 0xb4, 0xbc, 0x27, 0x05, 0x28, 0xb4,
+                0x95, 0x26, 0x00, 0x6d, 0xd9, 0xb5, 0x4e, 0x2b, 0x6a, 0xdd, 0x1e, 0xf6, 0x90, 0x0d,
+                0xda, 0x39, 0x63, 0xbb, 0x33, 0xa7, 0x24, 0x91, 0xf2, 0x36, 0x96, 0x9c, 0xa8, 0xaf,
+                0xae, 0xa2, 0x9c, 0x68, 0x2d, 0x47, 0xa3, 0x93, 0xc0, 0x65, 0xb3, 0x8e, 0x29, 0xfa,
+                0xe6, 0x51, 0xa2, 0x09, 0x1c, 0x83, 0x31, 0x10
+            ][..]
+        );
+    }
+
+    #[test]
+    fn test_parallel_hash_block_size() {
+        let x = [0x5au8; 100];
+        let mut out1 = [0u8; 32];
+        let mut out2 = [0u8; 32];
+        parallel_hash128(&x, 10, b"", &mut out1);
+        parallel_hash128(&x, 30, b"", &mut out2);
+        assert_ne!(out1, out2);
+        parallel_hash128(&x, 1000, b"", &mut out1);
+        parallel_hash128(&x, 100, b"", &mut out2);
+        assert_ne!(out1, out2);
+    }
+
+    #[test]
+    fn test_parallel_hash_large_input() {
+        // large enough to be hashed on multiple threads with the std feature,
+        // and checked against the definition of SP 800-185
+        let x: std::vec::Vec<u8> = (0..300_001u32).map(|i| (i * 7) as u8).collect();
+        let block_size = 4099;
+        let mut out = [0u8; 64];
+        parallel_hash256(&x, block_size, b"large", &mut out);
+
+        let mut ctx = CShake256::new(b"ParallelHash", b"large");
+        let mut buf = [0u8; 9];
+        ctx.input(left_encode(block_size as u64, &mut buf));
+        let mut n = 0;
+        for block in x.chunks(block_size) {
+            let mut block_ctx = Shake256::new();
+            block_ctx.input(block);
+            let mut block_hash = [0u8; 64];
+            block_ctx.squeeze(&mut block_hash);
+            ctx.input(&block_hash);
+            n += 1;
+        }
+        ctx.input(right_encode(n, &mut buf));
+        ctx.input(right_encode(512, &mut buf));
+        let mut expected = [0u8; 64];
+        ctx.squeeze(&mut expected);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_tuple_hash() {
+        // NIST SP 800-185 TupleHash samples
+        let x1: &[u8] = &[0x00, 0x01, 0x02];
+        let x2: &[u8] = &[0x10, 0x11, 0x12, 0x13, 0x14, 0x15];
+        let x3: &[u8] = &[0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27, 0x28];
+
+        let mut out = [0u8; 32];
+        tuple_hash128(&[x1, x2], b"", &mut out);
+        assert_eq!(
+            out,
+            [
+                0xc5, 0xd8, 0x78, 0x6c, 0x1a, 0xfb, 0x9b, 0x82, 0x11, 0x1a, 0xb3, 0x4b, 0x65, 0xb2,
+                0xc0, 0x04, 0x8f, 0xa6, 0x4e, 0x6d, 0x48, 0xe2, 0x63, 0x26, 0x4c, 0xe1, 0x70, 0x7d,
+                0x3f, 0xfc, 0x8e, 0xd1
+            ]
+        );
+        tuple_hash128(&[x1, x2], b"My Tuple App", &mut out);
+        assert_eq!(
+            out,
+            [
+                0x75, 0xcd, 0xb2, 0x0f, 0xf4, 0xdb, 0x11, 0x54, 0xe8, 0x41, 0xd7, 0x58, 0xe2, 0x41,
+                0x60, 0xc5, 0x4b, 0xae, 0x86, 0xeb, 0x8c, 0x13, 0xe7, 0xf5, 0xf4, 0x0e, 0xb3, 0x55,
+                0x88, 0xe9, 0x6d, 0xfb
+            ]
+        );
+        tuple_hash128(&[x1, x2, x3], b"My Tuple App", &mut out);
+        assert_eq!(
+            out,
+            [
+                0xe6, 0x0f, 0x20, 0x2c, 0x89, 0xa2, 0x63, 0x1e, 0xda, 0x8d, 0x4c, 0x58, 0x8c, 0xa5,
+                0xfd, 0x07, 0xf3, 0x9e, 0x51, 0x51, 0x99, 0x8d, 0xec, 0xcf, 0x97, 0x3a, 0xdb, 0x38,
+                0x04, 0xbb, 0x6e, 0x84
+            ]
+        );
+
+        let mut out = [0u8; 64];
+        tuple_hash256(&[x1, x2], b"", &mut out);
+        assert_eq!(
+            &out[..],
+            &[
+                0xcf, 0xb7, 0x05, 0x8c, 0xac, 0xa5, 0xe6, 0x68, 0xf8, 0x1a, 0x12, 0xa2, 0x0a, 0x21,
+                0x95, 0xce, 0x97, 0xa9, 0x25, 0xf1, 0xdb, 0xa3, 0xe7, 0x44, 0x9a, 0x56, 0xf8, 0x22,
+                0x01, 0xec, 0x60, 0x73, 0x11, 0xac, 0x26, 0x96, 0xb1, 0xab, 0x5e, 0xa2, 0x35, 0x2d,
+                0xf1, 0x42, 0x3b, 0xde, 0x7b, 0xd4, 0xbb, 0x78, 0xc9, 0xae, 0xd1, 0xa8, 0x53, 0xc7,
+                0x86, 0x72, 0xf9, 0xeb, 0x23, 0xbb, 0xe1, 0x94
+            ][..]
+        );
+        tuple_hash256(&[x1, x2, x3], b"My Tuple App", &mut out);
+        assert_eq!(
+            &out[..],
+            &[
+                0x45, 0x00, 0x0b, 0xe6, 0x3f, 0x9b, 0x6b, 0xfd, 0x89, 0xf5, 0x47, 0x17, 0x67, 0x0f,
+                0x69, 0xa9, 0xbc, 0x76, 0x35, 0x91, 0xa4, 0xf0, 0x5c, 0x50, 0xd6, 0x88, 0x91, 0xa7,
+                0x44, 0xbc, 0xc6, 0xe7, 0xd6, 0xd5, 0xb5, 0xe8, 0x2c, 0x01, 0x8d, 0xa9, 0x99, 0xed,
+                0x35, 0xb0, 0xbb, 0x49, 0xc9, 0x67, 0x8e, 0x52, 0x6a, 0xbd, 0x8e, 0x85, 0xc1, 0x3e,
+                0xd2, 0x54, 0x02, 0x1d, 0xb9, 0xe7, 0x90, 0xce
+            ][..]
+        );
+    }
+
+    #[test]
+    fn test_tuple_hash_unambiguous() {
+        let mut out1 = [0u8; 32];
+        let mut out2 = [0u8; 32];
+        tuple_hash128(&[b"ab", b"c"], b"", &mut out1);
+        tuple_hash128(&[b"a", b"bc"], b"", &mut out2);
+        assert_ne!(out1, out2);
+        tuple_hash128(&[b"abc"], b"", &mut out2);
+        assert_ne!(out1, out2);
+        tuple_hash128(&[b"abc", b""], b"", &mut out1);
+        assert_ne!(out1, out2);
+    }
+
+    // KeccakF-1600-IntermediateValues.txt of the Keccak team: the permutation of the zero state
+    #[test]
+    fn test_keccak_f1600_zero_state() {
+        let expected: [u64; 25] = [
+            0xF1258F7940E1DDE7,
+            0x84D5CCF933C0478A,
+            0xD598261EA65AA9EE,
+            0xBD1547306F80494D,
+            0x8B284E056253D057,
+            0xFF97A42D7F8E6FD4,
+            0x90FEE5A0A44647C4,
+            0x8C5BDA0CD6192E76,
+            0xAD30A6F71B19059C,
+            0x30935AB7D08FFC64,
+            0xEB5AA93F2317D635,
+            0xA9A6E6260D712103,
+            0x81A57C16DBCF555F,
+            0x43B831CD0347C826,
+            0x01F22F1A11A5569F,
+            0x05E5635A21D9AE61,
+            0x64BEFEF28CC970F2,
+            0x613670957BC46611,
+            0xB87C5A554FD00ECB,
+            0x8C3EE88A1CCF32C8,
+            0x940C7922AE3A2614,
+            0x1841F924A2C509E4,
+            0x16F53526E70465C2,
+            0x75F644E97F30A13B,
+            0xEAF1FF7B5CECA249,
+        ];
+        let mut state = [0u64; 25];
+        keccak_f1600(&mut state);
+        assert_eq!(state, expected);
+    }
 }
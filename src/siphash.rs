@@ -0,0 +1,238 @@
+//! SipHash-2-4 keyed hash function
+//!
+//! [SipHash][1] is a fast pseudorandom function with a 128 bits key and a 64 bits
+//! output, designed for hash tables keyed with a secret to resist hash
+//! flooding denial of service attacks. It is not a general purpose MAC:
+//! the 64 bits output is too short for message authentication.
+//!
+//! [`SipHasher24`] implements `core::hash::Hasher`, and [`SipHashBuilder`]
+//! implements `core::hash::BuildHasher`, to be used with the standard hash maps:
+//!
+//! ```
+//! use cryptoxide::siphash::SipHashBuilder;
+//! use std::collections::HashMap;
+//!
+//! // the key should be secret and random
+//! let key = [0x42u8; 16];
+//! let mut map = HashMap::with_hasher(SipHashBuilder::new(&key));
+//! map.insert("key", 1);
+//! assert_eq!(map.get("key"), Some(&1));
+//! ```
+//!
+//! [1]: <https://www.aumasson.jp/siphash/siphash.pdf>
+
+use core::hash::{BuildHasher, Hasher};
+
+#[derive(Clone)]
+struct State {
+    v0: u64,
+    v1: u64,
+    v2: u64,
+    v3: u64,
+}
+
+impl State {
+    fn new(k0: u64, k1: u64) -> Self {
+        State {
+            v0: k0 ^ 0x736f6d6570736575,
+            v1: k1 ^ 0x646f72616e646f6d,
+            v2: k0 ^ 0x6c7967656e657261,
+            v3: k1 ^ 0x7465646279746573,
+        }
+    }
+
+    #[inline]
+    fn round(&mut self) {
+        self.v0 = self.v0.wrapping_add(self.v1);
+        self.v1 = self.v1.rotate_left(13);
+        self.v1 ^= self.v0;
+        self.v0 = self.v0.rotate_left(32);
+        self.v2 = self.v2.wrapping_add(self.v3);
+        self.v3 = self.v3.rotate_left(16);
+        self.v3 ^= self.v2;
+        self.v0 = self.v0.wrapping_add(self.v3);
+        self.v3 = self.v3.rotate_left(21);
+        self.v3 ^= self.v0;
+        self.v2 = self.v2.wrapping_add(self.v1);
+        self.v1 = self.v1.rotate_left(17);
+        self.v1 ^= self.v2;
+        self.v2 = self.v2.rotate_left(32);
+    }
+
+    #[inline]
+    fn compress(&mut self, m: u64) {
+        self.v3 ^= m;
+        self.round();
+        self.round();
+        self.v0 ^= m;
+    }
+}
+
+/// SipHash-2-4 context
+#[derive(Clone)]
+pub struct SipHasher24 {
+    state: State,
+    tail: [u8; 8],
+    ntail: usize,
+    length: u64,
+}
+
+impl SipHasher24 {
+    /// Create a new context with a 16 bytes key
+    pub fn new(key: &[u8; 16]) -> Self {
+        let mut k0 = [0u8; 8];
+        let mut k1 = [0u8; 8];
+        k0.copy_from_slice(&key[0..8]);
+        k1.copy_from_slice(&key[8..16]);
+        Self::new_with_keys(u64::from_le_bytes(k0), u64::from_le_bytes(k1))
+    }
+
+    /// Create a new context with the key as two 64 bits words, the first
+    /// and second half of the key in little endian
+    pub fn new_with_keys(k0: u64, k1: u64) -> Self {
+        SipHasher24 {
+            state: State::new(k0, k1),
+            tail: [0; 8],
+            ntail: 0,
+            length: 0,
+        }
+    }
+
+    /// Process input data
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.length = self.length.wrapping_add(data.len() as u64);
+
+        if self.ntail > 0 {
+            let n = core::cmp::min(8 - self.ntail, data.len());
+            self.tail[self.ntail..self.ntail + n].copy_from_slice(&data[..n]);
+            self.ntail += n;
+            data = &data[n..];
+            if self.ntail < 8 {
+                return;
+            }
+            self.state.compress(u64::from_le_bytes(self.tail));
+            self.ntail = 0;
+        }
+
+        let mut blocks = data.chunks_exact(8);
+        for block in &mut blocks {
+            let mut m = [0u8; 8];
+            m.copy_from_slice(block);
+            self.state.compress(u64::from_le_bytes(m));
+        }
+        let rem = blocks.remainder();
+        self.tail[..rem.len()].copy_from_slice(rem);
+        self.ntail = rem.len();
+    }
+
+    /// Return the hash of the data processed so far, without changing the context
+    pub fn result(&self) -> u64 {
+        let mut state = self.state.clone();
+        let mut last = [0u8; 8];
+        last[..self.ntail].copy_from_slice(&self.tail[..self.ntail]);
+        last[7] = self.length as u8;
+        state.compress(u64::from_le_bytes(last));
+        state.v2 ^= 0xff;
+        for _ in 0..4 {
+            state.round();
+        }
+        state.v0 ^ state.v1 ^ state.v2 ^ state.v3
+    }
+}
+
+impl Hasher for SipHasher24 {
+    fn write(&mut self, bytes: &[u8]) {
+        self.update(bytes)
+    }
+
+    fn finish(&self) -> u64 {
+        self.result()
+    }
+}
+
+/// Builder of [`SipHasher24`] contexts sharing the same key, to be used as
+/// the `BuildHasher` of hash maps and sets
+///
+/// The key should be secret and chosen at random, e.g. from the operating
+/// system RNG, for the hash to be resistant to hash flooding.
+#[derive(Clone)]
+pub struct SipHashBuilder {
+    k0: u64,
+    k1: u64,
+}
+
+impl SipHashBuilder {
+    /// Create a new builder with a 16 bytes key
+    pub fn new(key: &[u8; 16]) -> Self {
+        let mut k0 = [0u8; 8];
+        let mut k1 = [0u8; 8];
+        k0.copy_from_slice(&key[0..8]);
+        k1.copy_from_slice(&key[8..16]);
+        Self::new_with_keys(u64::from_le_bytes(k0), u64::from_le_bytes(k1))
+    }
+
+    /// Create a new builder with the key as two 64 bits words
+    pub fn new_with_keys(k0: u64, k1: u64) -> Self {
+        SipHashBuilder { k0, k1 }
+    }
+}
+
+impl BuildHasher for SipHashBuilder {
+    type Hasher = SipHasher24;
+
+    fn build_hasher(&self) -> SipHasher24 {
+        SipHasher24::new_with_keys(self.k0, self.k1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SipHashBuilder, SipHasher24};
+    use std::collections::HashMap;
+    use std::vec::Vec;
+
+    // from the SipHash reference implementation, with the key 00 01 .. 0f and
+    // the message 00 01 .. (len - 1)
+    #[test]
+    fn test_vectors() {
+        let key: [u8; 16] = core::array::from_fn(|i| i as u8);
+        let tests = [
+            (0, 0x726fdb47dd0e0e31),
+            (1, 0x74f839c593dc67fd),
+            (7, 0xab0200f58b01d137),
+            (8, 0x93f5f5799a932462),
+            (15, 0xa129ca6149be45e5),
+            (63, 0x958a324ceb064572),
+        ];
+        for &(len, expected) in tests.iter() {
+            let msg: Vec<u8> = (0..len as u8).collect();
+            let mut h = SipHasher24::new(&key);
+            h.update(&msg);
+            assert_eq!(h.result(), expected, "length {}", len);
+
+            // incremental
+            for split in 0..len {
+                let mut h = SipHasher24::new(&key);
+                h.update(&msg[..split]);
+                h.update(&msg[split..]);
+                assert_eq!(h.result(), expected, "length {} split {}", len, split);
+            }
+        }
+    }
+
+    #[test]
+    fn hash_map() {
+        let key: [u8; 16] = core::array::from_fn(|i| i as u8);
+        let mut map: HashMap<u32, &str, SipHashBuilder> =
+            HashMap::with_hasher(SipHashBuilder::new(&key));
+        for i in 0..100 {
+            map.insert(i, if i % 2 == 0 { "even" } else { "odd" });
+        }
+        assert_eq!(map.len(), 100);
+        assert_eq!(map.get(&42), Some(&"even"));
+        assert_eq!(map.get(&17), Some(&"odd"));
+        assert_eq!(map.get(&100), None);
+        assert_eq!(map.remove(&42), Some("even"));
+        assert_eq!(map.get(&42), None);
+    }
+}
@@ -5,6 +5,10 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+pub use crate::cryptoutil::{conditional_copy, reverse_digest_endianness};
+use core::array::TryFromSliceError;
+use core::convert::TryFrom;
+
 pub fn secure_memset(dst: &mut [u8], val: u8) {
     for d in dst.iter_mut() {
         *d = val;
@@ -27,9 +31,69 @@ pub fn fixed_time_eq(lhs: &[u8], rhs: &[u8]) -> bool {
     }
 }
 
+/// Fixed size secret bytes, like a secret key or a shared secret
+///
+/// The bytes are not printed by `Debug`, are compared with [`fixed_time_eq`],
+/// and are wiped when the value is dropped.
+#[derive(Clone)]
+pub struct Secret<const N: usize>([u8; N]);
+
+impl<const N: usize> Secret<N> {
+    /// Wrap the secret bytes
+    pub const fn new(bytes: [u8; N]) -> Self {
+        Secret(bytes)
+    }
+
+    /// Access the secret bytes
+    pub fn expose(&self) -> &[u8; N] {
+        &self.0
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for Secret<N> {
+    fn from(bytes: [u8; N]) -> Self {
+        Secret(bytes)
+    }
+}
+
+impl<const N: usize> TryFrom<&[u8]> for Secret<N> {
+    type Error = TryFromSliceError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        <[u8; N]>::try_from(bytes).map(Secret)
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for Secret<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const N: usize> PartialEq for Secret<N> {
+    fn eq(&self, other: &Self) -> bool {
+        fixed_time_eq(&self.0, &other.0)
+    }
+}
+
+impl<const N: usize> Eq for Secret<N> {}
+
+impl<const N: usize> core::fmt::Debug for Secret<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Secret([redacted; {}])", N)
+    }
+}
+
+impl<const N: usize> Drop for Secret<N> {
+    fn drop(&mut self) {
+        secure_memset(&mut self.0, 0);
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::util::fixed_time_eq;
+    use crate::util::{fixed_time_eq, Secret};
+    use core::convert::TryFrom;
 
     #[test]
     pub fn test_fixed_time_eq() {
@@ -50,4 +114,28 @@ mod test {
         assert!(!fixed_time_eq(&a, &f));
         assert!(!fixed_time_eq(&a, &g));
     }
+
+    #[test]
+    pub fn test_secret() {
+        let a = Secret::new([0x42u8; 32]);
+        let b = Secret::from([0x42u8; 32]);
+        let mut c = [0x42u8; 32];
+        c[31] = 0;
+        let c = Secret::new(c);
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a.as_ref(), &[0x42u8; 32][..]);
+        assert_eq!(a.expose(), &[0x42u8; 32]);
+        assert_eq!(format!("{:?}", a), "Secret([redacted; 32])");
+        assert_eq!(
+            format!("{:?}", Secret::new([1u8; 64])),
+            "Secret([redacted; 64])"
+        );
+
+        let d = Secret::<32>::try_from(&[0x42u8; 32][..]).unwrap();
+        assert_eq!(a, d);
+        assert!(Secret::<32>::try_from(&[0x42u8; 31][..]).is_err());
+        assert!(Secret::<32>::try_from(&[0x42u8; 33][..]).is_err());
+    }
 }
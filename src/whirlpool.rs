@@ -0,0 +1,493 @@
+//! An implementation of the Whirlpool cryptographic hash algorithm.
+//!
+//! Whirlpool is a 512 bits hash function based on a dedicated block cipher similar to AES,
+//! standardized in ISO/IEC 10118-3. It is mostly provided for interoperability with legacy
+//! systems, such as TrueCrypt and VeraCrypt volumes.
+//!
+//! # Example
+//!
+//! ```
+//! use cryptoxide::{whirlpool::Whirlpool, digest::Digest};
+//!
+//! let mut digest = [0u8; 64];
+//! let mut context = Whirlpool::new();
+//! context.input(b"hello world");
+//! context.result(&mut digest);
+//! ```
+//!
+//! # References
+//! [1]: <https://web.archive.org/web/20171129084214/http://www.larc.usp.br/~pbarreto/WhirlpoolPage.html>
+
+use crate::cryptoutil::{read_u64v_be, write_u128_be, write_u64v_be, FixedBuffer};
+use crate::digest::{assert_output_size, Digest};
+
+const STATE_LEN: usize = 8;
+const BLOCK_BYTES: usize = 64;
+const ROUNDS: usize = 10;
+
+// The S-box multiplied by the rows of the circulant MDS matrix cir(1, 1, 4, 1, 8, 5, 2, 9),
+// the tables for the other columns being the byte rotations of this one
+const C0: [u64; 256] = [
+    0x18186018c07830d8,
+    0x23238c2305af4626,
+    0xc6c63fc67ef991b8,
+    0xe8e887e8136fcdfb,
+    0x878726874ca113cb,
+    0xb8b8dab8a9626d11,
+    0x0101040108050209,
+    0x4f4f214f426e9e0d,
+    0x3636d836adee6c9b,
+    0xa6a6a2a6590451ff,
+    0xd2d26fd2debdb90c,
+    0xf5f5f3f5fb06f70e,
+    0x7979f979ef80f296,
+    0x6f6fa16f5fcede30,
+    0x91917e91fcef3f6d,
+    0x52525552aa07a4f8,
+    0x60609d6027fdc047,
+    0xbcbccabc89766535,
+    0x9b9b569baccd2b37,
+    0x8e8e028e048c018a,
+    0xa3a3b6a371155bd2,
+    0x0c0c300c603c186c,
+    0x7b7bf17bff8af684,
+    0x3535d435b5e16a80,
+    0x1d1d741de8693af5,
+    0xe0e0a7e05347ddb3,
+    0xd7d77bd7f6acb321,
+    0xc2c22fc25eed999c,
+    0x2e2eb82e6d965c43,
+    0x4b4b314b627a9629,
+    0xfefedffea321e15d,
+    0x575741578216aed5,
+    0x15155415a8412abd,
+    0x7777c1779fb6eee8,
+    0x3737dc37a5eb6e92,
+    0xe5e5b3e57b56d79e,
+    0x9f9f469f8cd92313,
+    0xf0f0e7f0d317fd23,
+    0x4a4a354a6a7f9420,
+    0xdada4fda9e95a944,
+    0x58587d58fa25b0a2,
+    0xc9c903c906ca8fcf,
+    0x2929a429558d527c,
+    0x0a0a280a5022145a,
+    0xb1b1feb1e14f7f50,
+    0xa0a0baa0691a5dc9,
+    0x6b6bb16b7fdad614,
+    0x85852e855cab17d9,
+    0xbdbdcebd8173673c,
+    0x5d5d695dd234ba8f,
+    0x1010401080502090,
+    0xf4f4f7f4f303f507,
+    0xcbcb0bcb16c08bdd,
+    0x3e3ef83eedc67cd3,
+    0x0505140528110a2d,
+    0x676781671fe6ce78,
+    0xe4e4b7e47353d597,
+    0x27279c2725bb4e02,
+    0x4141194132588273,
+    0x8b8b168b2c9d0ba7,
+    0xa7a7a6a7510153f6,
+    0x7d7de97dcf94fab2,
+    0x95956e95dcfb3749,
+    0xd8d847d88e9fad56,
+    0xfbfbcbfb8b30eb70,
+    0xeeee9fee2371c1cd,
+    0x7c7ced7cc791f8bb,
+    0x6666856617e3cc71,
+    0xdddd53dda68ea77b,
+    0x17175c17b84b2eaf,
+    0x4747014702468e45,
+    0x9e9e429e84dc211a,
+    0xcaca0fca1ec589d4,
+    0x2d2db42d75995a58,
+    0xbfbfc6bf9179632e,
+    0x07071c07381b0e3f,
+    0xadad8ead012347ac,
+    0x5a5a755aea2fb4b0,
+    0x838336836cb51bef,
+    0x3333cc3385ff66b6,
+    0x636391633ff2c65c,
+    0x02020802100a0412,
+    0xaaaa92aa39384993,
+    0x7171d971afa8e2de,
+    0xc8c807c80ecf8dc6,
+    0x19196419c87d32d1,
+    0x494939497270923b,
+    0xd9d943d9869aaf5f,
+    0xf2f2eff2c31df931,
+    0xe3e3abe34b48dba8,
+    0x5b5b715be22ab6b9,
+    0x88881a8834920dbc,
+    0x9a9a529aa4c8293e,
+    0x262698262dbe4c0b,
+    0x3232c8328dfa64bf,
+    0xb0b0fab0e94a7d59,
+    0xe9e983e91b6acff2,
+    0x0f0f3c0f78331e77,
+    0xd5d573d5e6a6b733,
+    0x80803a8074ba1df4,
+    0xbebec2be997c6127,
+    0xcdcd13cd26de87eb,
+    0x3434d034bde46889,
+    0x48483d487a759032,
+    0xffffdbffab24e354,
+    0x7a7af57af78ff48d,
+    0x90907a90f4ea3d64,
+    0x5f5f615fc23ebe9d,
+    0x202080201da0403d,
+    0x6868bd6867d5d00f,
+    0x1a1a681ad07234ca,
+    0xaeae82ae192c41b7,
+    0xb4b4eab4c95e757d,
+    0x54544d549a19a8ce,
+    0x93937693ece53b7f,
+    0x222288220daa442f,
+    0x64648d6407e9c863,
+    0xf1f1e3f1db12ff2a,
+    0x7373d173bfa2e6cc,
+    0x12124812905a2482,
+    0x40401d403a5d807a,
+    0x0808200840281048,
+    0xc3c32bc356e89b95,
+    0xecec97ec337bc5df,
+    0xdbdb4bdb9690ab4d,
+    0xa1a1bea1611f5fc0,
+    0x8d8d0e8d1c830791,
+    0x3d3df43df5c97ac8,
+    0x97976697ccf1335b,
+    0x0000000000000000,
+    0xcfcf1bcf36d483f9,
+    0x2b2bac2b4587566e,
+    0x7676c57697b3ece1,
+    0x8282328264b019e6,
+    0xd6d67fd6fea9b128,
+    0x1b1b6c1bd87736c3,
+    0xb5b5eeb5c15b7774,
+    0xafaf86af112943be,
+    0x6a6ab56a77dfd41d,
+    0x50505d50ba0da0ea,
+    0x45450945124c8a57,
+    0xf3f3ebf3cb18fb38,
+    0x3030c0309df060ad,
+    0xefef9bef2b74c3c4,
+    0x3f3ffc3fe5c37eda,
+    0x55554955921caac7,
+    0xa2a2b2a2791059db,
+    0xeaea8fea0365c9e9,
+    0x656589650fecca6a,
+    0xbabad2bab9686903,
+    0x2f2fbc2f65935e4a,
+    0xc0c027c04ee79d8e,
+    0xdede5fdebe81a160,
+    0x1c1c701ce06c38fc,
+    0xfdfdd3fdbb2ee746,
+    0x4d4d294d52649a1f,
+    0x92927292e4e03976,
+    0x7575c9758fbceafa,
+    0x06061806301e0c36,
+    0x8a8a128a249809ae,
+    0xb2b2f2b2f940794b,
+    0xe6e6bfe66359d185,
+    0x0e0e380e70361c7e,
+    0x1f1f7c1ff8633ee7,
+    0x6262956237f7c455,
+    0xd4d477d4eea3b53a,
+    0xa8a89aa829324d81,
+    0x96966296c4f43152,
+    0xf9f9c3f99b3aef62,
+    0xc5c533c566f697a3,
+    0x2525942535b14a10,
+    0x59597959f220b2ab,
+    0x84842a8454ae15d0,
+    0x7272d572b7a7e4c5,
+    0x3939e439d5dd72ec,
+    0x4c4c2d4c5a619816,
+    0x5e5e655eca3bbc94,
+    0x7878fd78e785f09f,
+    0x3838e038ddd870e5,
+    0x8c8c0a8c14860598,
+    0xd1d163d1c6b2bf17,
+    0xa5a5aea5410b57e4,
+    0xe2e2afe2434dd9a1,
+    0x616199612ff8c24e,
+    0xb3b3f6b3f1457b42,
+    0x2121842115a54234,
+    0x9c9c4a9c94d62508,
+    0x1e1e781ef0663cee,
+    0x4343114322528661,
+    0xc7c73bc776fc93b1,
+    0xfcfcd7fcb32be54f,
+    0x0404100420140824,
+    0x51515951b208a2e3,
+    0x99995e99bcc72f25,
+    0x6d6da96d4fc4da22,
+    0x0d0d340d68391a65,
+    0xfafacffa8335e979,
+    0xdfdf5bdfb684a369,
+    0x7e7ee57ed79bfca9,
+    0x242490243db44819,
+    0x3b3bec3bc5d776fe,
+    0xabab96ab313d4b9a,
+    0xcece1fce3ed181f0,
+    0x1111441188552299,
+    0x8f8f068f0c890383,
+    0x4e4e254e4a6b9c04,
+    0xb7b7e6b7d1517366,
+    0xebeb8beb0b60cbe0,
+    0x3c3cf03cfdcc78c1,
+    0x81813e817cbf1ffd,
+    0x94946a94d4fe3540,
+    0xf7f7fbf7eb0cf31c,
+    0xb9b9deb9a1676f18,
+    0x13134c13985f268b,
+    0x2c2cb02c7d9c5851,
+    0xd3d36bd3d6b8bb05,
+    0xe7e7bbe76b5cd38c,
+    0x6e6ea56e57cbdc39,
+    0xc4c437c46ef395aa,
+    0x03030c03180f061b,
+    0x565645568a13acdc,
+    0x44440d441a49885e,
+    0x7f7fe17fdf9efea0,
+    0xa9a99ea921374f88,
+    0x2a2aa82a4d825467,
+    0xbbbbd6bbb16d6b0a,
+    0xc1c123c146e29f87,
+    0x53535153a202a6f1,
+    0xdcdc57dcae8ba572,
+    0x0b0b2c0b58271653,
+    0x9d9d4e9d9cd32701,
+    0x6c6cad6c47c1d82b,
+    0x3131c43195f562a4,
+    0x7474cd7487b9e8f3,
+    0xf6f6fff6e309f115,
+    0x464605460a438c4c,
+    0xacac8aac092645a5,
+    0x89891e893c970fb5,
+    0x14145014a04428b4,
+    0xe1e1a3e15b42dfba,
+    0x16165816b04e2ca6,
+    0x3a3ae83acdd274f7,
+    0x6969b9696fd0d206,
+    0x09092409482d1241,
+    0x7070dd70a7ade0d7,
+    0xb6b6e2b6d954716f,
+    0xd0d067d0ceb7bd1e,
+    0xeded93ed3b7ec7d6,
+    0xcccc17cc2edb85e2,
+    0x424215422a578468,
+    0x98985a98b4c22d2c,
+    0xa4a4aaa4490e55ed,
+    0x2828a0285d885075,
+    0x5c5c6d5cda31b886,
+    0xf8f8c7f8933fed6b,
+    0x8686228644a411c2,
+];
+const RC: [u64; ROUNDS] = [
+    0x1823c6e887b8014f,
+    0x36a6d2f5796f9152,
+    0x60bc9b8ea30c7b35,
+    0x1de0d7c22e4bfe57,
+    0x157737e59ff04ada,
+    0x58c9290ab1a06b85,
+    0xbd5d10f4cb3e0567,
+    0xe427418ba77d95d8,
+    0xfbee7c66dd17479e,
+    0xca2dbf07ad5a8333,
+];
+
+// one round of the W block cipher on the state, with the round key `k`
+fn round(state: &[u64; STATE_LEN], k: &[u64; STATE_LEN]) -> [u64; STATE_LEN] {
+    let mut out = *k;
+    for (i, o) in out.iter_mut().enumerate() {
+        for t in 0..8 {
+            let b = (state[(i + 8 - t) % 8] >> (56 - 8 * t)) as u8;
+            *o ^= C0[b as usize].rotate_right(8 * t as u32);
+        }
+    }
+    out
+}
+
+/// Process a block of 64 bytes with the Whirlpool compression function
+pub fn whirlpool_digest_block(h: &mut [u64; STATE_LEN], block: &[u8]) {
+    assert_eq!(block.len(), BLOCK_BYTES);
+    let mut m = [0u64; STATE_LEN];
+    read_u64v_be(&mut m, block);
+
+    let mut k = *h;
+    let mut state = [0u64; STATE_LEN];
+    for i in 0..STATE_LEN {
+        state[i] = m[i] ^ k[i];
+    }
+    for rc in RC.iter() {
+        let mut key = [0u64; STATE_LEN];
+        key[0] = *rc;
+        k = round(&k, &key);
+        state = round(&state, &k);
+    }
+    for i in 0..STATE_LEN {
+        h[i] ^= state[i] ^ m[i];
+    }
+}
+
+/// Structure representing the state of a Whirlpool computation
+#[derive(Clone)]
+pub struct Whirlpool {
+    h: [u64; STATE_LEN],
+    length_bits: u128,
+    buffer: FixedBuffer<BLOCK_BYTES>,
+    computed: bool,
+}
+
+impl Whirlpool {
+    /// Construct a `Whirlpool` context
+    pub const fn new() -> Self {
+        Whirlpool {
+            h: [0; STATE_LEN],
+            length_bits: 0,
+            buffer: FixedBuffer::new(),
+            computed: false,
+        }
+    }
+}
+
+impl Digest for Whirlpool {
+    fn reset(&mut self) {
+        self.h = [0; STATE_LEN];
+        self.length_bits = 0;
+        self.buffer.reset();
+        self.computed = false;
+    }
+
+    fn input(&mut self, msg: &[u8]) {
+        assert!(!self.computed);
+        self.length_bits = (msg.len() as u128)
+            .checked_mul(8)
+            .and_then(|bits| self.length_bits.checked_add(bits))
+            .expect("Numeric overflow occured.");
+        let h = &mut self.h;
+        self.buffer.input(msg, |d| {
+            for block in d.chunks(BLOCK_BYTES) {
+                whirlpool_digest_block(h, block);
+            }
+        });
+    }
+
+    fn result(&mut self, out: &mut [u8]) {
+        assert_output_size(out, 64);
+        if !self.computed {
+            let h = &mut self.h;
+            // the length is encoded on 256 bits, of which only the lower 128 bits are used
+            self.buffer
+                .standard_padding(32, |d| whirlpool_digest_block(h, d));
+            write_u128_be(self.buffer.next(16), 0);
+            write_u128_be(self.buffer.next(16), self.length_bits);
+            whirlpool_digest_block(h, self.buffer.full_buffer());
+            self.computed = true;
+        }
+        write_u64v_be(out, &self.h);
+    }
+
+    fn output_bits(&self) -> usize {
+        512
+    }
+
+    fn block_size(&self) -> usize {
+        BLOCK_BYTES
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Whirlpool;
+    use crate::cryptoutil::test::result_hex;
+    use crate::digest::Digest;
+    #[cfg(feature = "hmac")]
+    use crate::hmac::Hmac;
+    #[cfg(feature = "hmac")]
+    use crate::mac::Mac;
+
+    fn check(input: &[u8], expected: &str) {
+        let mut w = Whirlpool::new();
+        w.input(input);
+        assert_eq!(result_hex(&mut w), expected);
+
+        // same result when processing the input byte by byte
+        let mut w = Whirlpool::new();
+        for b in input.iter() {
+            w.input(&[*b]);
+        }
+        assert_eq!(result_hex(&mut w), expected);
+    }
+
+    #[test]
+    fn iso_vectors() {
+        check(
+            b"",
+            "19fa61d75522a4669b44e39c1d2e1726c530232130d407f89afee0964997f7a7\
+             3e83be698b288febcf88e3e03c4f0757ea8964e59b63d93708b138cc42a66eb3",
+        );
+        check(
+            b"abc",
+            "4e2448a4c6f486bb16b6562c73b4020bf3043e3a731bce721ae1b303d97e6d4c\
+             7181eebdb6c57e277d0e34957114cbd6c797fc9d95d8b582d225292076d4eef5",
+        );
+        check(
+            b"The quick brown fox jumps over the lazy dog",
+            "b97de512e91e3828b40d2b0fdce9ceb3c4a71f9bea8d88e75c4fa854df36725f\
+             d2b52eb6544edcacd6f8beddfea403cb55ae31f03ad62a5ef54e42ee82c3fb35",
+        );
+    }
+
+    #[test]
+    fn padding_boundaries() {
+        // the length doesn't fit in the block anymore
+        check(
+            &[b'a'; 32],
+            "661fe85e302a100bc85048438a734d219e0c006c8464f10eb2281194db21d3b2\
+             36fabb497818f63511a63be7e1c5ea4009a0f937040f4bc080a68a2fff589dab",
+        );
+        check(
+            &[b'a'; 64],
+            "3ab1400670b9c37bc24274578aac331eb7150167c598c6c247bcdd8ae54be548\
+             470fcdc3718f276cebc324d2c9b35b6b4748d9a26985d9b79563f7e2890da38a",
+        );
+    }
+
+    #[cfg(feature = "hmac")]
+    #[test]
+    fn hmac_whirlpool() {
+        let mut mac = Hmac::new(Whirlpool::new(), b"key");
+        mac.input(b"The quick brown fox jumps over the lazy dog");
+        let mut code = [0u8; 64];
+        mac.raw_result(&mut code);
+        assert_eq!(
+            code,
+            [
+                0x7f, 0x71, 0x92, 0xe3, 0xa1, 0x55, 0xcb, 0x6a, 0x81, 0x71, 0x58, 0x4b, 0xa1, 0x46,
+                0x88, 0x2f, 0x26, 0x82, 0x16, 0x58, 0x11, 0x2d, 0xfd, 0x26, 0x01, 0x27, 0x2d, 0xb0,
+                0x13, 0x51, 0x7a, 0x31, 0xe5, 0x73, 0x63, 0x7d, 0x14, 0x65, 0x84, 0x59, 0x6f, 0x86,
+                0xa8, 0x84, 0xeb, 0x0d, 0xec, 0xc9, 0x51, 0x4d, 0xde, 0x00, 0x0e, 0xcf, 0x24, 0x76,
+                0xdc, 0x5d, 0x43, 0x6a, 0x92, 0x19, 0x75, 0x27,
+            ]
+        );
+    }
+}
+
+#[cfg(all(test, feature = "with-bench"))]
+mod bench {
+    use super::*;
+    use test::Bencher;
+
+    #[bench]
+    pub fn whirlpool_1k(bh: &mut Bencher) {
+        let mut w = Whirlpool::new();
+        let bytes = [1u8; 1024];
+        bh.iter(|| {
+            w.input(&bytes);
+        });
+        bh.bytes = bytes.len() as u64;
+    }
+}
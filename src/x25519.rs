@@ -0,0 +1,317 @@
+//! X25519 scalar multiplication on arbitrary u-coordinates [RFC 7748][1]
+//!
+//! [`scalarmult`] is the Montgomery ladder of Curve25519 taking the scalar
+//! as is, for protocols (e.g. SPAKE2, CPace) that multiply arbitrary points,
+//! possibly on the twist, by scalars they have already prepared. [`x25519`]
+//! is the `X25519(k, u)` function of RFC 7748, which clamps the scalar
+//! first, and is the same as [`curve25519`](crate::curve25519::curve25519).
+//!
+//! In both cases, the most significant bit of the u-coordinate is ignored
+//! and non canonical u-coordinates are reduced modulo p, as specified by
+//! RFC 7748.
+//!
+//! # Examples
+//!
+//! ```
+//! use cryptoxide::x25519::{clamp_scalar, scalarmult, x25519, BASEPOINT};
+//!
+//! let mut scalar = [7u8; 32];
+//! let public = x25519(&scalar, &BASEPOINT);
+//!
+//! clamp_scalar(&mut scalar);
+//! assert_eq!(scalarmult(&scalar, &BASEPOINT), public);
+//! ```
+//!
+//! A key exchange with the [`SecretKey`] and [`PublicKey`] types:
+//!
+//! ```
+//! use cryptoxide::x25519::SecretKey;
+//!
+//! let alice = SecretKey::from([1u8; 32]); // use random keys !
+//! let bob = SecretKey::from([2u8; 32]);
+//! let shared = alice.diffie_hellman(&bob.public_key());
+//! assert_eq!(shared, bob.diffie_hellman(&alice.public_key()));
+//! ```
+//!
+//! [1]: <https://datatracker.ietf.org/doc/html/rfc7748>
+
+use crate::curve25519::{clamp_x25519_scalar, montgomery_ladder, x25519_public_key_is_weak};
+#[cfg(feature = "sha2")]
+use crate::digest::Digest;
+#[cfg(feature = "sha2")]
+use crate::sha2::Sha512;
+use crate::util::{secure_memset, Secret};
+use core::array::TryFromSliceError;
+use core::convert::TryFrom;
+
+/// u-coordinate of the base point of Curve25519
+pub const BASEPOINT: [u8; 32] = [
+    9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+];
+
+/// Clear the 3 least significant bits, clear the most significant bit and set
+/// the second most significant bit of the scalar, as done by [`x25519`]
+pub fn clamp_scalar(scalar: &mut [u8; 32]) {
+    clamp_x25519_scalar(scalar);
+}
+
+/// Multiply the point of u-coordinate `u` by `scalar`, without clamping
+///
+/// All the 256 bits of the scalar are used. The multiplication is done in
+/// constant time, whether `u` is on the curve or on its twist.
+pub fn scalarmult(scalar: &[u8; 32], u: &[u8; 32]) -> [u8; 32] {
+    montgomery_ladder(scalar, u)
+}
+
+/// The `X25519(k, u)` function of RFC 7748: clamp `k` and multiply the point
+/// of u-coordinate `u` by it
+pub fn x25519(k: &[u8; 32], u: &[u8; 32]) -> [u8; 32] {
+    let mut scalar = *k;
+    clamp_scalar(&mut scalar);
+    let r = scalarmult(&scalar, u);
+    secure_memset(&mut scalar, 0);
+    r
+}
+
+/// Derive a X25519 secret key from a 32 bytes seed, as SHA-512 of the seed
+/// truncated to 32 bytes and clamped
+///
+/// This is how an ed25519 seed is turned into the X25519 secret key used by
+/// [`ed25519::exchange`](crate::ed25519::exchange), so that custom
+/// Diffie-Hellman exchanges can be built compatible with it.
+#[cfg(feature = "sha2")]
+pub fn secret_from_hash(seed: &[u8; 32]) -> Secret<32> {
+    let mut hash = [0u8; 64];
+    let mut hasher = Sha512::new();
+    hasher.input(seed);
+    hasher.result(&mut hash);
+    let mut secret = [0u8; 32];
+    secret.copy_from_slice(&hash[0..32]);
+    clamp_scalar(&mut secret);
+    secure_memset(&mut hash, 0);
+    Secret::new(secret)
+}
+
+/// X25519 public key: the u-coordinate of a point
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicKey(pub [u8; 32]);
+
+impl PublicKey {
+    /// Check whether the public key is one of the points of small order, for
+    /// which the shared secret doesn't depend on the secret key
+    ///
+    /// See [`x25519_public_key_is_weak`].
+    pub fn is_weak(&self) -> bool {
+        x25519_public_key_is_weak(&self.0)
+    }
+}
+
+impl From<[u8; 32]> for PublicKey {
+    fn from(bytes: [u8; 32]) -> Self {
+        PublicKey(bytes)
+    }
+}
+
+impl TryFrom<&[u8]> for PublicKey {
+    type Error = TryFromSliceError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        <[u8; 32]>::try_from(bytes).map(PublicKey)
+    }
+}
+
+impl AsRef<[u8]> for PublicKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// X25519 secret key
+///
+/// Any 32 bytes are a valid secret key, as the scalar is clamped by [`x25519`]
+/// when used. The key is not printed by `Debug`, and is wiped when dropped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretKey(Secret<32>);
+
+impl SecretKey {
+    /// The public key of the secret key: `X25519(k, 9)`
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(x25519(self.0.expose(), &BASEPOINT))
+    }
+
+    /// The shared secret `X25519(k, u)` with the public key of the peer
+    ///
+    /// The shared secret is all zeros if the public key is of small order, see
+    /// [`PublicKey::is_weak`].
+    pub fn diffie_hellman(&self, public_key: &PublicKey) -> Secret<32> {
+        Secret::new(x25519(self.0.expose(), &public_key.0))
+    }
+}
+
+impl From<[u8; 32]> for SecretKey {
+    fn from(bytes: [u8; 32]) -> Self {
+        SecretKey(Secret::new(bytes))
+    }
+}
+
+impl TryFrom<&[u8]> for SecretKey {
+    type Error = TryFromSliceError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Secret::try_from(bytes).map(SecretKey)
+    }
+}
+
+impl AsRef<[u8]> for SecretKey {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex32(s: &str) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        for (i, o) in out.iter_mut().enumerate() {
+            *o = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        out
+    }
+
+    #[test]
+    fn rfc7748_vectors() {
+        // section 5.2
+        let k = hex32("a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4");
+        let u = hex32("e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c");
+        let r = hex32("c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552");
+        assert_eq!(x25519(&k, &u), r);
+
+        let k = hex32("4b66e9d4d1b4673c5ad22691957d6af5c11b6421e0ea01d42ca4169e7918ba0d");
+        let u = hex32("e5210f12786811d3f4b7959d0538ae2c31dbe7106fc03c3efc4cd549c715a493");
+        let r = hex32("95cbde9476e8907d7aade45cb4b873f88b595a68799fa152e6f8f7647aac7957");
+        assert_eq!(x25519(&k, &u), r);
+
+        let mut clamped = k;
+        clamp_scalar(&mut clamped);
+        assert_eq!(scalarmult(&clamped, &u), r);
+        assert_ne!(scalarmult(&k, &u), r);
+    }
+
+    #[test]
+    fn rfc7748_key_exchange() {
+        // section 6.1
+        let alice = SecretKey::from(hex32(
+            "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a",
+        ));
+        let bob = SecretKey::from(hex32(
+            "5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb",
+        ));
+        let alice_public = PublicKey::from(hex32(
+            "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a",
+        ));
+        let bob_public = PublicKey::from(hex32(
+            "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f",
+        ));
+        let shared = hex32("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742");
+        assert_eq!(alice.public_key(), alice_public);
+        assert_eq!(bob.public_key(), bob_public);
+        assert_eq!(alice.diffie_hellman(&bob_public).expose(), &shared);
+        assert_eq!(bob.diffie_hellman(&alice_public).expose(), &shared);
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    #[cfg_attr(feature = "no-panic", allow(deprecated))]
+    fn secret_from_hash_matches_ed25519_exchange() {
+        use crate::ed25519;
+
+        let seed = [3u8; 32];
+        let (ed_secret, _) = ed25519::keypair(&seed);
+        let (_, peer_ed_public) = ed25519::keypair(&[4u8; 32]);
+        let peer_public = x25519(secret_from_hash(&[4u8; 32]).expose(), &BASEPOINT);
+
+        assert_eq!(
+            x25519(secret_from_hash(&seed).expose(), &peer_public),
+            ed25519::exchange(&peer_ed_public, &ed_secret)
+        );
+    }
+
+    #[test]
+    fn weak_public_keys() {
+        // u-coordinates of small order, giving the all zeros shared secret
+        // that RFC 7748 section 6.1 asks to check, with non canonical encodings
+        let weak = [
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0100000000000000000000000000000000000000000000000000000000000000",
+            "e0eb7a7c3b41b8ae1656e3faf19fc46ada098deb9c32b1fd866205165f49b800",
+            "5f9c95bca3508c24b1d0b1559c83ef5b04445cc4581c8e86d8224eddd09f1157",
+            "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            "eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        ];
+        let secret = SecretKey::from([0x42u8; 32]);
+        for point in weak.iter() {
+            let public = PublicKey::from(hex32(point));
+            assert!(public.is_weak());
+            assert_eq!(secret.diffie_hellman(&public).expose(), &[0u8; 32]);
+        }
+
+        assert!(!PublicKey::from(BASEPOINT).is_weak());
+        assert!(!secret.public_key().is_weak());
+    }
+
+    #[test]
+    fn key_conversions() {
+        let bytes = [0x42u8; 32];
+        let public = PublicKey::try_from(&bytes[..]).unwrap();
+        assert_eq!(public, PublicKey::from(bytes));
+        assert_eq!(public.as_ref(), &bytes[..]);
+        assert!(PublicKey::try_from(&bytes[..31]).is_err());
+        assert!(PublicKey::try_from(&[0u8; 33][..]).is_err());
+
+        let secret = SecretKey::try_from(&bytes[..]).unwrap();
+        assert_eq!(secret, SecretKey::from(bytes));
+        assert_eq!(secret.as_ref(), &bytes[..]);
+        assert!(SecretKey::try_from(&bytes[..31]).is_err());
+        assert!(SecretKey::try_from(&[0u8; 33][..]).is_err());
+    }
+
+    #[test]
+    fn rfc7748_iterated() {
+        let mut k = BASEPOINT;
+        let mut u = BASEPOINT;
+        for i in 1..=1000 {
+            let r = x25519(&k, &u);
+            u = k;
+            k = r;
+            if i == 1 {
+                assert_eq!(
+                    k,
+                    hex32("422c8e7a6227d7bca1350b3e2bb7279f7897b87bb6854b783c60e80311ae3079")
+                );
+            }
+        }
+        assert_eq!(
+            k,
+            hex32("684cf59ba83309552800ef566f2f4d3c1c3887c49360e3875f2eb94d99532c51")
+        );
+    }
+
+    #[test]
+    fn scalarmult_unclamped() {
+        // the order of the base point is l, so (l + 1) * B = B while the
+        // clamped scalar gives a different point
+        let mut l_plus_one = crate::curve25519::L;
+        l_plus_one[0] += 1;
+        assert_eq!(scalarmult(&l_plus_one, &BASEPOINT), BASEPOINT);
+        assert_ne!(x25519(&l_plus_one, &BASEPOINT), BASEPOINT);
+
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        assert_eq!(scalarmult(&one, &BASEPOINT), BASEPOINT);
+        // the scalar 0 gives the point at infinity, encoded as 0
+        assert_eq!(scalarmult(&[0u8; 32], &BASEPOINT), [0u8; 32]);
+    }
+}
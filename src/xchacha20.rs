@@ -0,0 +1,36 @@
+//! XChaCha20, the ChaCha20 variant with a 24 bytes extended nonce
+//!
+//! The cipher itself is created with
+//! [`ChaCha20::new_xchacha20`](crate::chacha20::ChaCha20::new_xchacha20), and the
+//! XChaCha20-Poly1305 AEAD with `ChaCha20Poly1305::new_xchacha20` of the
+//! `chacha20poly1305` module. Unlike the 8 or 12 bytes nonces of ChaCha20,
+//! the extended nonce can be generated at random for each message, with
+//! [`random_nonce`].
+//!
+//! The same size of nonce is used by XSalsa20, so [`random_nonce`] can also
+//! be used with `Salsa20::new_xsalsa20`.
+
+/// Generate a random 24 bytes nonce for XChaCha20 or XSalsa20, filling it with `fill_random`
+///
+/// `fill_random` needs to fill the buffer from a cryptographically secure random
+/// number generator, such as the operating system RNG.
+///
+/// Random nonces are safe with the extended 192 bits nonce variants: after encrypting
+/// 2^64 messages with the same key, the probability of two messages having the same
+/// nonce is still below 2^-64. With the 96 bits nonce of ChaCha20, the same
+/// probability is reached after only 2^16 messages, and a reused nonce reveals the
+/// xor of the plaintexts.
+///
+/// ```
+/// use cryptoxide::{chacha20::ChaCha20, xchacha20::random_nonce};
+///
+/// let key = [1u8; 32];
+/// // use the operating system RNG here, this is only for example !
+/// let nonce = random_nonce(|buf| buf.fill(0x42));
+/// let mut cipher = ChaCha20::new_xchacha20(&key, &nonce);
+/// ```
+pub fn random_nonce<F: FnOnce(&mut [u8])>(fill_random: F) -> [u8; 24] {
+    let mut nonce = [0u8; 24];
+    fill_random(&mut nonce);
+    nonce
+}
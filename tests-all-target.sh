@@ -4,6 +4,13 @@ set -e
 
 cargo test
 
+# the optional modules, not enabled by default
+cargo test --features "kdf curve448 ed448 etm bcrypt ghash rfc6979 siphash whirlpool ripemd160 crc merkle argon2 aes keywrap aesgcmsiv ctrdrbg"
+
+# HMAC-SHA256 and PBKDF2 without the alloc feature
+cargo build --no-default-features --features hmac,pbkdf2,sha2
+cargo test --no-default-features --features hmac,pbkdf2,sha2
+
 for arch in core2 nehalem sandybridge broadwell
 do
     RUSTFLAGS="-C target_cpu=$arch" cargo test